@@ -1,9 +1,11 @@
 import fs from "node:fs";
 import path from "node:path";
-import os from "node:os";
 import { fileURLToPath } from "node:url";
+import type { ConfigSetupResult, HookRemovalResult } from "@felay/shared";
+import { lineDiff } from "./lineDiff.js";
+import { resolveConfigDir } from "./cliPaths.js";
 
-const CLAUDE_DIR = path.join(os.homedir(), ".claude");
+const CLAUDE_DIR = resolveConfigDir("CLAUDE_CONFIG_DIR", ".claude", "claude");
 const SETTINGS_PATH = path.join(CLAUDE_DIR, "settings.json");
 
 /** Resolve the absolute path to felay-claude-hook.js. */
@@ -104,16 +106,19 @@ export function checkClaudeConfig(): ClaudeConfigStatus {
   return { claudeInstalled: true, configExists: true, hookConfigured: false, currentHookCommand, felayScriptPath, configFilePath };
 }
 
-/** Configure the Claude Code Stop hook to point to felay-claude-hook.js. */
-export function setupClaudeConfig(): { ok: boolean; error?: string } {
+/**
+ * Configure the Claude Code Stop hook to point to felay-claude-hook.js.
+ * With `dryRun`, nothing is written and the proposed content is returned.
+ */
+export function setupClaudeConfig(dryRun = false): ConfigSetupResult {
   const status = checkClaudeConfig();
 
   if (!status.claudeInstalled) {
-    return { ok: false, error: "Claude Code 未安装（~/.claude/ 目录不存在）" };
+    return { ok: false, error: `Claude Code 未安装（${CLAUDE_DIR} 目录不存在）` };
   }
 
   if (status.hookConfigured) {
-    return { ok: true };
+    return dryRun ? { ok: true, dryRun, changed: false } : { ok: true };
   }
 
   const scriptPath = resolveHookScript();
@@ -123,11 +128,15 @@ export function setupClaudeConfig(): { ok: boolean; error?: string } {
 
   const hookCommand = `node ${toForwardSlash(scriptPath)}`;
 
+  let currentContent = "";
   let settings: Record<string, unknown>;
   try {
-    settings = fs.existsSync(SETTINGS_PATH)
-      ? JSON.parse(fs.readFileSync(SETTINGS_PATH, "utf8"))
-      : {};
+    if (fs.existsSync(SETTINGS_PATH)) {
+      currentContent = fs.readFileSync(SETTINGS_PATH, "utf8");
+      settings = JSON.parse(currentContent);
+    } else {
+      settings = {};
+    }
   } catch (err) {
     return { ok: false, error: `无法读取 settings.json: ${err}` };
   }
@@ -164,11 +173,86 @@ export function setupClaudeConfig(): { ok: boolean; error?: string } {
     hooks.Stop.push(felayHookGroup);
   }
 
+  const newContent = JSON.stringify(settings, null, 2);
+
+  if (dryRun) {
+    return {
+      ok: true,
+      dryRun,
+      changed: newContent !== currentContent,
+      currentContent,
+      newContent,
+      diff: lineDiff(currentContent, newContent),
+    };
+  }
+
   try {
-    fs.writeFileSync(SETTINGS_PATH, JSON.stringify(settings, null, 2), "utf8");
+    fs.writeFileSync(SETTINGS_PATH, newContent, "utf8");
   } catch (err) {
     return { ok: false, error: `无法写入 settings.json: ${err}` };
   }
 
   return { ok: true };
 }
+
+/**
+ * Remove hooks running felay-claude-hook.js from every hook event, and any
+ * group or event list left empty by that. Unrelated hooks are untouched;
+ * other commands mentioning felay are reported as leftover.
+ */
+export function removeClaudeHooks(): HookRemovalResult {
+  if (!fs.existsSync(SETTINGS_PATH)) {
+    return { ok: true, removed: [], leftover: [] };
+  }
+
+  let content: string;
+  let settings: Record<string, unknown>;
+  try {
+    content = fs.readFileSync(SETTINGS_PATH, "utf8");
+    settings = JSON.parse(content);
+  } catch (err) {
+    return { ok: false, error: `无法读取 settings.json: ${err}`, removed: [], leftover: [] };
+  }
+
+  const removed: string[] = [];
+  const leftover: string[] = [];
+  const hooks = settings.hooks as Record<string, unknown[]> | undefined;
+  if (hooks && typeof hooks === "object") {
+    for (const [event, groups] of Object.entries(hooks)) {
+      if (!Array.isArray(groups)) continue;
+      const keptGroups = groups.filter((group) => {
+        const g = group as { hooks?: Array<{ command?: string }> };
+        if (!Array.isArray(g.hooks)) return true;
+        const before = g.hooks.length;
+        g.hooks = g.hooks.filter((hook) => {
+          if (hook.command?.includes("felay-claude-hook")) {
+            removed.push(hook.command);
+            return false;
+          }
+          if (hook.command && /felay/i.test(hook.command)) leftover.push(hook.command);
+          return true;
+        });
+        return g.hooks.length > 0 || g.hooks.length === before;
+      });
+      if (keptGroups.length > 0 || keptGroups.length === groups.length) {
+        hooks[event] = keptGroups;
+      } else {
+        delete hooks[event];
+      }
+    }
+  }
+
+  if (removed.length === 0) {
+    return { ok: true, removed, leftover };
+  }
+
+  const tmpPath = `${SETTINGS_PATH}.${process.pid}.tmp`;
+  try {
+    const newContent = JSON.stringify(settings, null, 2) + (content.endsWith("\n") ? "\n" : "");
+    fs.writeFileSync(tmpPath, newContent, "utf8");
+    fs.renameSync(tmpPath, SETTINGS_PATH);
+  } catch (err) {
+    return { ok: false, error: `无法写入 settings.json: ${err}`, removed: [], leftover };
+  }
+  return { ok: true, removed, leftover };
+}
@@ -0,0 +1,62 @@
+import { after, test } from "node:test";
+import assert from "node:assert/strict";
+import fs from "node:fs";
+import os from "node:os";
+import path from "node:path";
+import { fileURLToPath } from "node:url";
+
+// Shared with the GUI's cli_config_local tests, so both checks agree on
+// the fields they have in common.
+const FIXTURES = path.resolve(
+  path.dirname(fileURLToPath(import.meta.url)),
+  "../../gui/src-tauri/fixtures/cli-config",
+);
+
+interface ParityCase {
+  file: string | null;
+  [field: string]: unknown;
+}
+
+const cases = JSON.parse(fs.readFileSync(path.join(FIXTURES, "cases.json"), "utf8")) as Record<
+  string,
+  ParityCase[]
+>;
+
+// The config paths are resolved on import, so relocate the dirs first
+const root = fs.mkdtempSync(path.join(os.tmpdir(), "felay-parity-"));
+const codexDir = path.join(root, "codex");
+const claudeDir = path.join(root, "claude");
+fs.mkdirSync(codexDir);
+fs.mkdirSync(claudeDir);
+process.env.CODEX_HOME = codexDir;
+process.env.CLAUDE_CONFIG_DIR = claudeDir;
+const { checkCodexConfig } = await import("./codexConfig.js");
+const { checkClaudeConfig } = await import("./claudeConfig.js");
+
+after(() => fs.rmSync(root, { recursive: true, force: true }));
+
+function assertParity(
+  cli: string,
+  fields: string[],
+  target: string,
+  check: () => object,
+): void {
+  for (const c of cases[cli]) {
+    fs.rmSync(target, { force: true });
+    if (c.file) fs.copyFileSync(path.join(FIXTURES, c.file), target);
+    const status = check() as Record<string, unknown>;
+    for (const field of fields) {
+      assert.equal(status[field] ?? null, c[field] ?? null, `${field} of ${c.file}`);
+    }
+  }
+}
+
+test("the Codex check matches the GUI's", () => {
+  const fields = ["configExists", "notifyConfigured"];
+  assertParity("codex", fields, path.join(codexDir, "config.toml"), () => checkCodexConfig());
+});
+
+test("the Claude Code check matches the GUI's", () => {
+  const fields = ["configExists", "hookConfigured", "currentHookCommand"];
+  assertParity("claude", fields, path.join(claudeDir, "settings.json"), () => checkClaudeConfig());
+});
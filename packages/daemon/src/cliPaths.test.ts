@@ -0,0 +1,72 @@
+import { afterEach, beforeEach, test } from "node:test";
+import assert from "node:assert/strict";
+import fs from "node:fs";
+import os from "node:os";
+import path from "node:path";
+import { resolveConfigDir } from "./cliPaths.js";
+
+const VARS = ["HOME", "USERPROFILE", "CODEX_HOME", "CLAUDE_CONFIG_DIR", "XDG_CONFIG_HOME"];
+const saved = new Map<string, string | undefined>();
+const linuxOnly = { skip: process.platform !== "linux" };
+let home: string;
+
+beforeEach(() => {
+  for (const name of VARS) {
+    saved.set(name, process.env[name]);
+    delete process.env[name];
+  }
+  home = fs.mkdtempSync(path.join(os.tmpdir(), "felay-home-"));
+  // os.homedir() reads these
+  process.env.HOME = home;
+  process.env.USERPROFILE = home;
+});
+
+afterEach(() => {
+  for (const [name, value] of saved) {
+    if (value === undefined) {
+      delete process.env[name];
+    } else {
+      process.env[name] = value;
+    }
+  }
+  fs.rmSync(home, { recursive: true, force: true });
+});
+
+test("CODEX_HOME and CLAUDE_CONFIG_DIR win", () => {
+  const elsewhere = fs.mkdtempSync(path.join(os.tmpdir(), "felay-cli-"));
+  try {
+    fs.mkdirSync(path.join(home, ".codex"));
+    process.env.CODEX_HOME = path.join(elsewhere, "codex");
+    process.env.CLAUDE_CONFIG_DIR = path.join(elsewhere, "claude");
+    assert.equal(resolveConfigDir("CODEX_HOME", ".codex", "codex"), path.join(elsewhere, "codex"));
+    assert.equal(
+      resolveConfigDir("CLAUDE_CONFIG_DIR", ".claude", "claude"),
+      path.join(elsewhere, "claude"),
+    );
+  } finally {
+    fs.rmSync(elsewhere, { recursive: true, force: true });
+  }
+});
+
+test("an empty env var is ignored", () => {
+  process.env.CODEX_HOME = "";
+  assert.equal(resolveConfigDir("CODEX_HOME", ".codex", "codex"), path.join(home, ".codex"));
+});
+
+test("the XDG dir is used on Linux when only it exists", linuxOnly, () => {
+  const xdg = path.join(home, "xdg");
+  fs.mkdirSync(path.join(xdg, "codex"), { recursive: true });
+  process.env.XDG_CONFIG_HOME = xdg;
+  assert.equal(resolveConfigDir("CODEX_HOME", ".codex", "codex"), path.join(xdg, "codex"));
+
+  fs.mkdirSync(path.join(home, ".codex"));
+  assert.equal(resolveConfigDir("CODEX_HOME", ".codex", "codex"), path.join(home, ".codex"));
+});
+
+test("~/.config is the XDG default", linuxOnly, () => {
+  fs.mkdirSync(path.join(home, ".config", "claude"), { recursive: true });
+  assert.equal(
+    resolveConfigDir("CLAUDE_CONFIG_DIR", ".claude", "claude"),
+    path.join(home, ".config", "claude"),
+  );
+});
@@ -0,0 +1,27 @@
+import fs from "node:fs";
+import path from "node:path";
+import os from "node:os";
+
+/**
+ * Resolve a CLI's config directory the way the CLI itself does.
+ *
+ * `envVar` (CODEX_HOME, CLAUDE_CONFIG_DIR) wins when set. Otherwise the
+ * directory is `~/<defaultDir>`, except on Linux when only the XDG location
+ * (`$XDG_CONFIG_HOME/<xdgName>`, default `~/.config/<xdgName>`) exists.
+ * The GUI's `cli_paths.rs` follows the same rules.
+ */
+export function resolveConfigDir(envVar: string, defaultDir: string, xdgName: string): string {
+  const fromEnv = process.env[envVar];
+  if (fromEnv) {
+    return fromEnv;
+  }
+  const dir = path.join(os.homedir(), defaultDir);
+  if (process.platform === "linux" && !fs.existsSync(dir)) {
+    const configHome = process.env.XDG_CONFIG_HOME || path.join(os.homedir(), ".config");
+    const xdg = path.join(configHome, xdgName);
+    if (fs.existsSync(xdg)) {
+      return xdg;
+    }
+  }
+  return dir;
+}
@@ -1,9 +1,11 @@
 import fs from "node:fs";
 import path from "node:path";
-import os from "node:os";
 import { fileURLToPath } from "node:url";
+import type { ConfigSetupResult, HookRemovalResult } from "@felay/shared";
+import { lineDiff } from "./lineDiff.js";
+import { resolveConfigDir } from "./cliPaths.js";
 
-const CODEX_DIR = path.join(os.homedir(), ".codex");
+const CODEX_DIR = resolveConfigDir("CODEX_HOME", ".codex", "codex");
 const CONFIG_PATH = path.join(CODEX_DIR, "config.toml");
 
 /** Resolve the absolute path to felay-notify.js. */
@@ -82,16 +84,19 @@ export function checkCodexConfig(): CodexConfigStatus {
   return { codexInstalled: true, configExists: true, notifyConfigured, currentNotify, felayScriptPath, configFilePath };
 }
 
-/** Configure the Codex notify hook to point to felay-notify.js. */
-export function setupCodexConfig(): { ok: boolean; error?: string } {
+/**
+ * Configure the Codex notify hook to point to felay-notify.js.
+ * With `dryRun`, nothing is written and the proposed content is returned.
+ */
+export function setupCodexConfig(dryRun = false): ConfigSetupResult {
   const status = checkCodexConfig();
 
   if (!status.codexInstalled) {
-    return { ok: false, error: "Codex 未安装（~/.codex/ 目录不存在）" };
+    return { ok: false, error: `Codex 未安装（${CODEX_DIR} 目录不存在）` };
   }
 
   if (status.notifyConfigured) {
-    return { ok: true };
+    return dryRun ? { ok: true, dryRun, changed: false } : { ok: true };
   }
 
   // Verify the script file actually exists
@@ -131,6 +136,17 @@ export function setupCodexConfig(): { ok: boolean; error?: string } {
     }
   }
 
+  if (dryRun) {
+    return {
+      ok: true,
+      dryRun,
+      changed: newContent !== content,
+      currentContent: content,
+      newContent,
+      diff: lineDiff(content, newContent),
+    };
+  }
+
   try {
     fs.writeFileSync(CONFIG_PATH, newContent, "utf8");
   } catch (err) {
@@ -139,3 +155,43 @@ export function setupCodexConfig(): { ok: boolean; error?: string } {
 
   return { ok: true };
 }
+
+/**
+ * Remove the notify line pointing at felay-notify.js, leaving every other
+ * line as it is. Other lines mentioning felay are reported as leftover.
+ */
+export function removeCodexHooks(): HookRemovalResult {
+  if (!fs.existsSync(CONFIG_PATH)) {
+    return { ok: true, removed: [], leftover: [] };
+  }
+
+  let content: string;
+  try {
+    content = fs.readFileSync(CONFIG_PATH, "utf8");
+  } catch (err) {
+    return { ok: false, error: `无法读取 config.toml: ${err}`, removed: [], leftover: [] };
+  }
+
+  const removed: string[] = [];
+  const kept = content.split("\n").filter((line) => {
+    if (/^notify\s*=/.test(line) && line.includes("felay-notify")) {
+      removed.push(line.trim());
+      return false;
+    }
+    return true;
+  });
+  const leftover = kept.filter((line) => /felay/i.test(line)).map((line) => line.trim());
+
+  if (removed.length === 0) {
+    return { ok: true, removed, leftover };
+  }
+
+  const tmpPath = `${CONFIG_PATH}.${process.pid}.tmp`;
+  try {
+    fs.writeFileSync(tmpPath, kept.join("\n"), "utf8");
+    fs.renameSync(tmpPath, CONFIG_PATH);
+  } catch (err) {
+    return { ok: false, error: `无法写入 config.toml: ${err}`, removed: [], leftover };
+  }
+  return { ok: true, removed, leftover };
+}
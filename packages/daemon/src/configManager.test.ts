@@ -0,0 +1,23 @@
+import { test } from "node:test";
+import assert from "node:assert/strict";
+import { splitLegacyBots } from "./configManager.js";
+
+const interactive = { id: "i1", name: "Lark", appId: "cli_a", appSecret: "s" };
+const push = { id: "p1", name: "Hook", webhook: "https://example.com/hook" };
+
+test("a v0 flat bot list is split by webhook", () => {
+  assert.deepEqual(splitLegacyBots([interactive, push]), {
+    interactive: [interactive],
+    push: [push],
+  });
+});
+
+test("split bots are kept as they are", () => {
+  const bots = { interactive: [interactive], push: [push] };
+  assert.deepEqual(splitLegacyBots(bots), bots);
+});
+
+test("missing lists default to empty", () => {
+  assert.deepEqual(splitLegacyBots(undefined), { interactive: [], push: [] });
+  assert.deepEqual(splitLegacyBots({ push: [push] }), { interactive: [], push: [push] });
+});
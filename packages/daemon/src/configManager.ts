@@ -5,6 +5,7 @@ import type {
   AppConfig,
   InteractiveBotConfig,
   PushBotConfig,
+  QuietHours,
   BotType,
   ReconnectSettings,
   PushSettings,
@@ -14,8 +15,48 @@ import type {
 import { defaultAppConfig } from "@felay/shared";
 import { encrypt, decrypt, isEncrypted } from "./secretStore.js";
 
+/**
+ * The directory holding `config.json`: `~/.felay`, or
+ * `~/.felay/profiles/<name>` when the GUI has switched to a named profile
+ * (`active_profile` in `gui-state.json`). Runtime files (lock, socket,
+ * logs, secret key) always stay in `~/.felay`. The GUI's `profiles.rs`
+ * follows the same rules.
+ */
+function getDataDir(): string {
+  const felayDir = path.join(os.homedir(), ".felay");
+  try {
+    const raw = fs.readFileSync(path.join(felayDir, "gui-state.json"), "utf8");
+    const profile = (JSON.parse(raw) as { active_profile?: unknown }).active_profile;
+    if (typeof profile === "string" && profile && !/[\\/]|^\.\.?$/.test(profile)) {
+      return path.join(felayDir, "profiles", profile);
+    }
+  } catch {
+    // No GUI state yet: the default profile
+  }
+  return felayDir;
+}
+
+/**
+ * The bots of a `config.json` `bots` value. Schema v0 kept them in one flat
+ * list, push bots told apart by their `webhook`; such a list is split here
+ * rather than dropped. The file keeps the old layout until the next save
+ * or the GUI's migration, which also records the version.
+ */
+export function splitLegacyBots(bots: unknown): AppConfig["bots"] {
+  if (Array.isArray(bots)) {
+    const isPush = (bot: unknown) =>
+      typeof bot === "object" && bot !== null && "webhook" in bot;
+    return {
+      interactive: bots.filter((bot) => !isPush(bot)) as InteractiveBotConfig[],
+      push: bots.filter(isPush) as PushBotConfig[],
+    };
+  }
+  const split = (bots ?? {}) as Partial<AppConfig["bots"]>;
+  return { interactive: split.interactive ?? [], push: split.push ?? [] };
+}
+
 function getConfigPath(): string {
-  return path.join(os.homedir(), ".felay", "config.json");
+  return path.join(getDataDir(), "config.json");
 }
 
 export class ConfigManager {
@@ -27,10 +68,9 @@ export class ConfigManager {
       const raw = await fs.promises.readFile(configPath, "utf8");
       const parsed = JSON.parse(raw) as Partial<AppConfig>;
       this.config = {
-        bots: this.decryptBots({
-          interactive: parsed.bots?.interactive ?? [],
-          push: parsed.bots?.push ?? [],
-        }),
+        // Left unset for old files so the GUI can offer a migration
+        schemaVersion: parsed.schemaVersion,
+        bots: this.decryptBots(splitLegacyBots(parsed.bots)),
         reconnect: { ...defaultAppConfig.reconnect, ...parsed.reconnect },
         push: { ...defaultAppConfig.push, ...parsed.push },
         defaults: { ...defaultAppConfig.defaults, ...parsed.defaults },
@@ -92,6 +132,15 @@ export class ConfigManager {
     return this.config;
   }
 
+  /** The config with every default the daemon applies filled in. */
+  getEffectiveConfig(): AppConfig {
+    return { ...this.config, input: this.getSettings().input };
+  }
+
+  getConfigPath(): string {
+    return getConfigPath();
+  }
+
   getBots(): { interactive: InteractiveBotConfig[]; push: PushBotConfig[] } {
     return this.config.bots;
   }
@@ -109,7 +158,11 @@ export class ConfigManager {
   async saveBotPush(bot: PushBotConfig): Promise<void> {
     const idx = this.config.bots.push.findIndex((b) => b.id === bot.id);
     if (idx >= 0) {
-      this.config.bots.push[idx] = bot;
+      // Quiet hours are managed separately; keep them across edits
+      this.config.bots.push[idx] = {
+        ...bot,
+        quietHours: bot.quietHours ?? this.config.bots.push[idx].quietHours,
+      };
     } else {
       this.config.bots.push.push(bot);
     }
@@ -147,14 +200,46 @@ export class ConfigManager {
   }
 
   async saveSettings(config: AppConfig): Promise<void> {
-    // Preserve existing defaults if not provided (backward compatibility)
+    // Preserve existing defaults, schema version and per-bot quiet hours if not provided (backward compatibility)
+    // Secrets may arrive still encrypted (e.g. a restored config backup)
+    const previousPush = this.config.bots.push;
+    const bots = this.decryptBots(config.bots);
     this.config = {
       ...config,
+      bots: {
+        ...bots,
+        push: bots.push.map((b) => ({
+          ...b,
+          quietHours: b.quietHours ?? previousPush.find((p) => p.id === b.id)?.quietHours,
+        })),
+      },
       defaults: config.defaults ?? this.config.defaults,
+      schemaVersion: config.schemaVersion ?? this.config.schemaVersion,
     };
     await this.save();
   }
 
+  /** Factory reset: drop all bots and settings. */
+  async resetToDefaults(): Promise<void> {
+    this.config = structuredClone(defaultAppConfig);
+    await this.save();
+  }
+
+  /* ── Quiet hours ── */
+
+  getQuietHours(botId: string): QuietHours | null | undefined {
+    const bot = this.config.bots.push.find((b) => b.id === botId);
+    return bot ? bot.quietHours ?? null : undefined;
+  }
+
+  async setQuietHours(botId: string, quietHours: QuietHours | null): Promise<boolean> {
+    const bot = this.config.bots.push.find((b) => b.id === botId);
+    if (!bot) return false;
+    bot.quietHours = quietHours ?? undefined;
+    await this.save();
+    return true;
+  }
+
   /* ── Default bot helpers ── */
 
   getDefaults(): DefaultBotSettings {
@@ -9,6 +9,8 @@ import {
   type FeishuInputEvent,
   type InteractiveBotConfig,
   type PushBotConfig,
+  type QuietHours,
+  type TestBotResult,
 } from "@felay/shared";
 import type { SessionRegistry } from "./sessionRegistry.js";
 import type { ConfigManager } from "./configManager.js";
@@ -23,6 +25,55 @@ function isCodexCli(cli: string): boolean {
   return name === "codex";
 }
 
+/* ── Quiet hours ── */
+
+/** Pushes held per bot during quiet hours; the oldest are dropped beyond this. */
+const MAX_QUIET_QUEUE = 200;
+
+function parseHhmm(value: string): number | null {
+  const m = /^([01]\d|2[0-3]):([0-5]\d)$/.exec(value);
+  return m ? Number(m[1]) * 60 + Number(m[2]) : null;
+}
+
+/** Whether `timezone` is an IANA zone this runtime understands. */
+export function isValidTimeZone(timezone: string): boolean {
+  try {
+    new Intl.DateTimeFormat("en-US", { timeZone: timezone });
+    return true;
+  } catch {
+    return false;
+  }
+}
+
+/** Validate a quiet-hours window; returns an error message or null. */
+export function validateQuietHours(q: QuietHours): string | null {
+  const start = parseHhmm(q.start);
+  const end = parseHhmm(q.end);
+  if (start === null || end === null) return "times must be HH:MM";
+  if (start === end) return "start and end must differ";
+  if (q.timezone && !isValidTimeZone(q.timezone)) return `unknown timezone ${q.timezone}`;
+  return null;
+}
+
+/** Whether `now` falls inside the window, handling windows that wrap midnight. */
+function isWithinQuietHours(q: QuietHours, now: Date = new Date()): boolean {
+  const start = parseHhmm(q.start);
+  const end = parseHhmm(q.end);
+  if (start === null || end === null || start === end) return false;
+
+  const parts = new Intl.DateTimeFormat("en-GB", {
+    hour: "2-digit",
+    minute: "2-digit",
+    hourCycle: "h23",
+    timeZone: q.timezone && isValidTimeZone(q.timezone) ? q.timezone : undefined,
+  }).formatToParts(now);
+  const hour = Number(parts.find((p) => p.type === "hour")?.value ?? 0);
+  const minute = Number(parts.find((p) => p.type === "minute")?.value ?? 0);
+  const current = hour * 60 + minute;
+
+  return start < end ? current >= start && current < end : current >= start || current < end;
+}
+
 /* ── Types ── */
 
 interface BotConnection {
@@ -102,6 +153,11 @@ export class FeishuManager {
   private readonly pendingReplies = new Map<string, PendingReply>();
   /** sessionId → chatId (persisted from first Feishu message for task summary) */
   private readonly sessionChatIds = new Map<string, string>();
+  /** botId → pushes held back during quiet hours */
+  private readonly quietQueue = new Map<string, { sessionId: string; text: string }[]>();
+  /** sessionId → window used instead of its push bot's (set per project by the GUI) */
+  private readonly sessionQuietHours = new Map<string, QuietHours>();
+  private quietFlushTimer: ReturnType<typeof setInterval> | null = null;
 
   constructor(
     registry: SessionRegistry,
@@ -740,6 +796,8 @@ export class FeishuManager {
       return;
     }
 
+    if (this.deferForQuietHours(botConfig, sessionId, cleanText)) return;
+
     try {
       const body: Record<string, unknown> = {
         msg_type: "post",
@@ -791,6 +849,8 @@ export class FeishuManager {
       return;
     }
 
+    if (this.deferForQuietHours(botConfig, sessionId, cleaned)) return;
+
     try {
       const body: Record<string, unknown> = {
         msg_type: "post",
@@ -827,6 +887,63 @@ export class FeishuManager {
     }
   }
 
+  /** Set or (with null) clear a session's own quiet hours. */
+  setSessionQuietHours(sessionId: string, quietHours: QuietHours | null): void {
+    if (quietHours) {
+      this.sessionQuietHours.set(sessionId, quietHours);
+    } else {
+      this.sessionQuietHours.delete(sessionId);
+    }
+  }
+
+  /** Whether pushes of `sessionId` through `bot` are held back right now. */
+  private isQuiet(bot: PushBotConfig, sessionId: string): boolean {
+    const window = this.sessionQuietHours.get(sessionId) ?? bot.quietHours;
+    return !!window && isWithinQuietHours(window);
+  }
+
+  /** Queue a push if its session or bot is in quiet hours; returns true if queued. */
+  private deferForQuietHours(botConfig: PushBotConfig, sessionId: string, text: string): boolean {
+    if (!this.isQuiet(botConfig, sessionId)) return false;
+
+    const queue = this.quietQueue.get(botConfig.id) ?? [];
+    queue.push({ sessionId, text });
+    if (queue.length > MAX_QUIET_QUEUE) queue.shift();
+    this.quietQueue.set(botConfig.id, queue);
+
+    if (!this.quietFlushTimer) {
+      this.quietFlushTimer = setInterval(() => void this.flushQuietQueue(), 60_000);
+      this.quietFlushTimer.unref();
+    }
+    return true;
+  }
+
+  /** Deliver queued pushes whose quiet hours have ended (or were removed). */
+  private async flushQuietQueue(): Promise<void> {
+    const bots = this.configManager.getBots();
+    for (const [botId, queue] of [...this.quietQueue]) {
+      const bot = bots.push.find((b) => b.id === botId);
+      // Sessions of one bot can have different windows; keep those still quiet
+      const due = bot ? queue.filter((entry) => !this.isQuiet(bot, entry.sessionId)) : queue;
+      const held = queue.filter((entry) => !due.includes(entry));
+      if (held.length > 0) {
+        this.quietQueue.set(botId, held);
+      } else {
+        this.quietQueue.delete(botId);
+      }
+      if (!bot || due.length === 0) continue;
+      console.log(`[felay] quiet hours ended for bot ${botId}, delivering ${due.length} queued push(es)`);
+      for (const entry of due) {
+        await this.sendPushCleanMessage(entry.sessionId, entry.text);
+      }
+    }
+
+    if (this.quietQueue.size === 0 && this.quietFlushTimer) {
+      clearInterval(this.quietFlushTimer);
+      this.quietFlushTimer = null;
+    }
+  }
+
   /** Validate that a webhook URL belongs to a trusted Feishu/Lark domain. */
   private static isAllowedWebhookUrl(url: string): boolean {
     try {
@@ -851,13 +968,16 @@ export class FeishuManager {
 
   /* ── Test bot connections ── */
 
-  async testInteractiveBot(
-    botId: string
-  ): Promise<{ ok: boolean; error?: string; botName?: string }> {
+  async testInteractiveBot(botId: string): Promise<TestBotResult> {
     const bots = this.configManager.getBots();
     const botConfig = bots.interactive.find((b) => b.id === botId);
     if (!botConfig) return { ok: false, error: "bot not found in config" };
+    return this.testInteractiveBotConfig(botConfig);
+  }
 
+  /** Verify interactive bot credentials without requiring a saved bot. */
+  async testInteractiveBotConfig(botConfig: InteractiveBotConfig): Promise<TestBotResult> {
+    const startedAt = Date.now();
     try {
       const client = new Lark.Client({
         appId: botConfig.appId,
@@ -870,25 +990,36 @@ export class FeishuManager {
       const resp = await client.auth.appAccessToken.internal({
         data: { app_id: botConfig.appId, app_secret: botConfig.appSecret },
       });
+      const latencyMs = Date.now() - startedAt;
       if (resp?.code === 0) {
-        return { ok: true, botName: botConfig.name };
+        return { ok: true, botName: botConfig.name, latencyMs, detail: resp?.msg };
       }
-      return { ok: false, error: resp?.msg ?? "failed to obtain access token" };
+      return {
+        ok: false,
+        error: resp?.msg ?? "failed to obtain access token",
+        latencyMs,
+        detail: resp?.msg,
+      };
     } catch (err: any) {
       const msg = err?.message ?? String(err);
-      return { ok: false, error: msg };
+      return { ok: false, error: msg, latencyMs: Date.now() - startedAt };
     }
   }
 
-  async testPushBot(botId: string): Promise<{ ok: boolean; error?: string }> {
+  async testPushBot(botId: string): Promise<TestBotResult> {
     const bots = this.configManager.getBots();
     const botConfig = bots.push.find((b) => b.id === botId);
     if (!botConfig) return { ok: false, error: "bot not found in config" };
+    return this.testPushBotConfig(botConfig);
+  }
 
+  /** Send a test message through a push bot config without requiring a saved bot. */
+  async testPushBotConfig(botConfig: PushBotConfig): Promise<TestBotResult> {
     if (!FeishuManager.isAllowedWebhookUrl(botConfig.webhook)) {
       return { ok: false, error: "webhook URL must be a feishu.cn or larksuite.com domain" };
     }
 
+    const startedAt = Date.now();
     try {
       const body: Record<string, unknown> = {
         msg_type: "text",
@@ -907,14 +1038,22 @@ export class FeishuManager {
         headers: { "Content-Type": "application/json" },
         body: JSON.stringify(body),
       });
+      const latencyMs = Date.now() - startedAt;
+      const statusCode = resp.status;
 
       const result = (await resp.json()) as { code?: number; msg?: string };
       if (result.code === 0) {
-        return { ok: true };
+        return { ok: true, statusCode, latencyMs, detail: result.msg };
       }
-      return { ok: false, error: result.msg ?? `code ${result.code}` };
+      return {
+        ok: false,
+        error: result.msg ?? `code ${result.code}`,
+        statusCode,
+        latencyMs,
+        detail: result.msg,
+      };
     } catch (err: any) {
-      return { ok: false, error: err?.message ?? String(err) };
+      return { ok: false, error: err?.message ?? String(err), latencyMs: Date.now() - startedAt };
     }
   }
 
@@ -998,6 +1137,7 @@ export class FeishuManager {
     this.pendingReplies.delete(sessionId);
     this.sessionChatIds.delete(sessionId);
     this.outputBuffer.cleanup(sessionId);
+    this.sessionQuietHours.delete(sessionId);
 
     // Clean up downloaded images for this session
     const sessionImagesDir = path.join(os.homedir(), ".felay", "images", sessionId);
@@ -1024,5 +1164,8 @@ export class FeishuManager {
     }
     this.pendingReplies.clear();
     this.sessionChatIds.clear();
+    if (this.quietFlushTimer) clearInterval(this.quietFlushTimer);
+    this.quietFlushTimer = null;
+    this.quietQueue.clear();
   }
 }
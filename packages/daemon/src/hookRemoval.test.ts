@@ -0,0 +1,121 @@
+import { after, beforeEach, test } from "node:test";
+import assert from "node:assert/strict";
+import fs from "node:fs";
+import os from "node:os";
+import path from "node:path";
+
+// The config paths are resolved on import, so relocate the dirs first
+const root = fs.mkdtempSync(path.join(os.tmpdir(), "felay-hooks-"));
+const codexConfig = path.join(root, "codex", "config.toml");
+const claudeSettings = path.join(root, "claude", "settings.json");
+fs.mkdirSync(path.dirname(codexConfig));
+fs.mkdirSync(path.dirname(claudeSettings));
+process.env.CODEX_HOME = path.dirname(codexConfig);
+process.env.CLAUDE_CONFIG_DIR = path.dirname(claudeSettings);
+const { removeCodexHooks } = await import("./codexConfig.js");
+const { removeClaudeHooks } = await import("./claudeConfig.js");
+
+beforeEach(() => {
+  fs.rmSync(codexConfig, { force: true });
+  fs.rmSync(claudeSettings, { force: true });
+});
+
+after(() => fs.rmSync(root, { recursive: true, force: true }));
+
+const hook = (command: string) => ({ type: "command", command });
+
+test("Codex: only the Felay notify line goes", () => {
+  fs.writeFileSync(
+    codexConfig,
+    [
+      "# my settings",
+      'model = "o3"',
+      'notify = ["node", "C:/Program Files/Felay/felay-notify.js"]',
+      "",
+      "# felay was here",
+      "[profiles.quiet]",
+      'notify   =   ["say", "done"]',
+      "",
+    ].join("\r\n"),
+  );
+  const result = removeCodexHooks();
+  assert.deepEqual(result, {
+    ok: true,
+    removed: ['notify = ["node", "C:/Program Files/Felay/felay-notify.js"]'],
+    leftover: ["# felay was here"],
+  });
+  const expected = [
+    "# my settings",
+    'model = "o3"',
+    "",
+    "# felay was here",
+    "[profiles.quiet]",
+    'notify   =   ["say", "done"]',
+    "",
+  ];
+  assert.equal(fs.readFileSync(codexConfig, "utf8"), expected.join("\r\n"));
+});
+
+test("Codex: a config without Felay is untouched", () => {
+  const content = 'model = "o3"\nnotify = ["notify-send", "done"]\n';
+  fs.writeFileSync(codexConfig, content);
+  assert.deepEqual(removeCodexHooks(), { ok: true, removed: [], leftover: [] });
+  assert.equal(fs.readFileSync(codexConfig, "utf8"), content);
+});
+
+test("Claude Code: other hooks, keys and layout survive", () => {
+  const felay = "node /opt/felay/felay-claude-hook.js";
+  const settings = {
+    model: "sonnet",
+    hooks: {
+      Stop: [
+        { matcher: "", hooks: [hook("afplay done.aiff"), hook(felay)] },
+        { matcher: "", hooks: [hook(felay)] },
+      ],
+      Notification: [{ hooks: [hook(felay)] }],
+      PreToolUse: [{ matcher: "Bash", hooks: [hook("~/bin/felay-audit.sh")] }],
+      SubagentStop: [],
+    },
+    permissions: { allow: ["Bash(git status)"] },
+  };
+  fs.writeFileSync(claudeSettings, JSON.stringify(settings, null, 2) + "\n");
+
+  const result = removeClaudeHooks();
+  assert.deepEqual(result, {
+    ok: true,
+    removed: [felay, felay, felay],
+    leftover: ["~/bin/felay-audit.sh"],
+  });
+
+  const expected = {
+    model: "sonnet",
+    hooks: {
+      Stop: [{ matcher: "", hooks: [hook("afplay done.aiff")] }],
+      PreToolUse: [{ matcher: "Bash", hooks: [hook("~/bin/felay-audit.sh")] }],
+      SubagentStop: [],
+    },
+    permissions: { allow: ["Bash(git status)"] },
+  };
+  assert.equal(fs.readFileSync(claudeSettings, "utf8"), JSON.stringify(expected, null, 2) + "\n");
+});
+
+test("Claude Code: settings without Felay are not rewritten", () => {
+  const content = '{"hooks":{"Stop":[{"hooks":[{"command":"say done"}]}]},"model":"opus"}';
+  fs.writeFileSync(claudeSettings, content);
+  assert.deepEqual(removeClaudeHooks(), { ok: true, removed: [], leftover: [] });
+  assert.equal(fs.readFileSync(claudeSettings, "utf8"), content);
+});
+
+test("Claude Code: unreadable settings are left alone", () => {
+  const content = '{ "hooks": { "Stop": [ node felay-claude-hook.js ] } }';
+  fs.writeFileSync(claudeSettings, content);
+  assert.equal(removeClaudeHooks().ok, false);
+  assert.equal(fs.readFileSync(claudeSettings, "utf8"), content);
+});
+
+test("missing files remove nothing", () => {
+  assert.deepEqual(removeCodexHooks(), { ok: true, removed: [], leftover: [] });
+  assert.deepEqual(removeClaudeHooks(), { ok: true, removed: [], leftover: [] });
+  assert.equal(fs.existsSync(codexConfig), false);
+  assert.equal(fs.existsSync(claudeSettings), false);
+});
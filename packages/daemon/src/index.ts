@@ -8,16 +8,30 @@ import os from "node:os";
 import { z } from "zod";
 import {
   toJsonLine,
+  IPC_PROTOCOL_VERSION,
+  type ErrorResponse,
+  type HelloResponse,
   type StatusResponse,
   type StopResponse,
   type ListBotsResponse,
   type SaveBotResponse,
   type DeleteBotResponse,
   type BindBotResponse,
+  type UnbindAllBotsResponse,
   type TestBotResponse,
+  type TestBotConfigResponse,
+  type TestBotResult,
+  type GetBotSecretResponse,
+  type BotSchemaField,
+  type BotSchemaResponse,
+  type SetQuietHoursResponse,
+  type GetQuietHoursResponse,
+  type SetSessionQuietHoursResponse,
   type ActivateBotResponse,
   type GetConfigResponse,
+  type EffectiveConfigResponse,
   type SaveConfigResponse,
+  type ResetConfigResponse,
   type SetDefaultBotResponse,
   type GetDefaultsResponse,
   type CodexNotifyEvent,
@@ -26,15 +40,18 @@ import {
   type ClaudeNotifyEvent,
   type CheckClaudeConfigResponse,
   type SetupClaudeConfigResponse,
-  type DaemonLockFile,
+  type RemoveCodexHooksResponse,
+  type RemoveClaudeHooksResponse,
 } from "@felay/shared";
 import { getIpcPath } from "./ipc.js";
 import { SessionRegistry } from "./sessionRegistry.js";
 import { ConfigManager } from "./configManager.js";
 import { OutputBuffer } from "./outputBuffer.js";
-import { FeishuManager } from "./feishuManager.js";
-import { checkCodexConfig, setupCodexConfig } from "./codexConfig.js";
-import { checkClaudeConfig, setupClaudeConfig } from "./claudeConfig.js";
+import { FeishuManager, validateQuietHours } from "./feishuManager.js";
+import { checkCodexConfig, removeCodexHooks, setupCodexConfig } from "./codexConfig.js";
+import { checkClaudeConfig, removeClaudeHooks, setupClaudeConfig } from "./claudeConfig.js";
+import { removeLockFile, writeLockFile } from "./lockFile.js";
+import { unbindAllBots, unbindSessionBot } from "./sessionBots.js";
 
 /* ── Zod schemas ── */
 
@@ -74,14 +91,83 @@ const stopSchema = z.object({ type: z.literal("stop_request") });
 
 const listBotsSchema = z.object({ type: z.literal("list_bots_request") });
 
+const quietHoursSchema = z.object({
+  start: z.string(),
+  end: z.string(),
+  timezone: z.string().optional(),
+});
+
+const interactiveBotSchema = z.object({
+  id: z.string(),
+  name: z.string(),
+  appId: z.string(),
+  appSecret: z.string(),
+  encryptKey: z.string().optional(),
+});
+
+const pushBotSchema = z.object({
+  id: z.string(),
+  name: z.string(),
+  webhook: z.string(),
+  secret: z.string().optional(),
+  quietHours: quietHoursSchema.optional(),
+});
+
 const saveBotSchema = z.object({
   type: z.literal("save_bot_request"),
+  payload: z.object({
+    botType: z.enum(["interactive", "push"]),
+    interactive: interactiveBotSchema.optional(),
+    push: pushBotSchema.optional(),
+  }),
+});
+
+const deleteBotSchema = z.object({
+  type: z.literal("delete_bot_request"),
+  payload: z.object({
+    botType: z.enum(["interactive", "push"]),
+    botId: z.string(),
+  }),
+});
+
+const bindBotSchema = z.object({
+  type: z.literal("bind_bot_request"),
+  payload: z.object({
+    sessionId: z.string(),
+    botType: z.enum(["interactive", "push"]),
+    botId: z.string(),
+  }),
+});
+
+const unbindBotSchema = z.object({
+  type: z.literal("unbind_bot_request"),
+  payload: z.object({
+    sessionId: z.string(),
+    botType: z.enum(["interactive", "push"]),
+  }),
+});
+
+const unbindAllBotsSchema = z.object({
+  type: z.literal("unbind_all_bots_request"),
+  payload: z.object({ sessionId: z.string() }),
+});
+
+const testBotSchema = z.object({
+  type: z.literal("test_bot_request"),
+  payload: z.object({
+    botType: z.enum(["interactive", "push"]),
+    botId: z.string(),
+  }),
+});
+
+const testBotConfigSchema = z.object({
+  type: z.literal("test_bot_config_request"),
   payload: z.object({
     botType: z.enum(["interactive", "push"]),
     interactive: z
       .object({
-        id: z.string(),
-        name: z.string(),
+        id: z.string().default(""),
+        name: z.string().default(""),
         appId: z.string(),
         appSecret: z.string(),
         encryptKey: z.string().optional(),
@@ -89,8 +175,8 @@ const saveBotSchema = z.object({
       .optional(),
     push: z
       .object({
-        id: z.string(),
-        name: z.string(),
+        id: z.string().default(""),
+        name: z.string().default(""),
         webhook: z.string(),
         secret: z.string().optional(),
       })
@@ -98,36 +184,42 @@ const saveBotSchema = z.object({
   }),
 });
 
-const deleteBotSchema = z.object({
-  type: z.literal("delete_bot_request"),
+const getBotSecretSchema = z.object({
+  type: z.literal("get_bot_secret_request"),
   payload: z.object({
     botType: z.enum(["interactive", "push"]),
     botId: z.string(),
+    field: z.enum(["appSecret", "encryptKey", "webhook", "secret"]),
   }),
 });
 
-const bindBotSchema = z.object({
-  type: z.literal("bind_bot_request"),
+const botSchemaSchema = z.object({
+  type: z.literal("bot_schema_request"),
+  payload: z.object({ botType: z.enum(["interactive", "push"]) }),
+});
+
+const setQuietHoursSchema = z.object({
+  type: z.literal("set_quiet_hours_request"),
   payload: z.object({
-    sessionId: z.string(),
     botType: z.enum(["interactive", "push"]),
     botId: z.string(),
+    quietHours: quietHoursSchema.nullable(),
   }),
 });
 
-const unbindBotSchema = z.object({
-  type: z.literal("unbind_bot_request"),
+const getQuietHoursSchema = z.object({
+  type: z.literal("get_quiet_hours_request"),
   payload: z.object({
-    sessionId: z.string(),
     botType: z.enum(["interactive", "push"]),
+    botId: z.string(),
   }),
 });
 
-const testBotSchema = z.object({
-  type: z.literal("test_bot_request"),
+const setSessionQuietHoursSchema = z.object({
+  type: z.literal("set_session_quiet_hours_request"),
   payload: z.object({
-    botType: z.enum(["interactive", "push"]),
-    botId: z.string(),
+    sessionId: z.string(),
+    quietHours: quietHoursSchema.nullable(),
   }),
 });
 
@@ -138,9 +230,12 @@ const activateBotSchema = z.object({
 
 const getConfigSchema = z.object({ type: z.literal("get_config_request") });
 
+const effectiveConfigSchema = z.object({ type: z.literal("effective_config_request") });
+
 const saveConfigSchema = z.object({
   type: z.literal("save_config_request"),
   payload: z.object({
+    schemaVersion: z.number().int().optional(),
     bots: z.object({
       interactive: z.array(
         z.object({
@@ -157,6 +252,7 @@ const saveConfigSchema = z.object({
           name: z.string(),
           webhook: z.string(),
           secret: z.string().optional(),
+          quietHours: quietHoursSchema.optional(),
         })
       ),
     }),
@@ -180,6 +276,8 @@ const saveConfigSchema = z.object({
   }),
 });
 
+const resetConfigSchema = z.object({ type: z.literal("reset_config_request") });
+
 const setDefaultBotSchema = z.object({
   type: z.literal("set_default_bot_request"),
   payload: z.object({
@@ -201,7 +299,12 @@ const codexNotifySchema = z.object({
 });
 
 const checkCodexConfigSchema = z.object({ type: z.literal("check_codex_config_request") });
-const setupCodexConfigSchema = z.object({ type: z.literal("setup_codex_config_request") });
+const setupCodexConfigSchema = z.object({
+  type: z.literal("setup_codex_config_request"),
+  payload: z.object({ dryRun: z.boolean().optional() }).optional(),
+});
+
+const removeCodexHooksSchema = z.object({ type: z.literal("remove_codex_hooks_request") });
 
 const claudeNotifySchema = z.object({
   type: z.literal("claude_notify"),
@@ -213,7 +316,12 @@ const claudeNotifySchema = z.object({
 });
 
 const checkClaudeConfigSchema = z.object({ type: z.literal("check_claude_config_request") });
-const setupClaudeConfigSchema = z.object({ type: z.literal("setup_claude_config_request") });
+const setupClaudeConfigSchema = z.object({
+  type: z.literal("setup_claude_config_request"),
+  payload: z.object({ dryRun: z.boolean().optional() }).optional(),
+});
+
+const removeClaudeHooksSchema = z.object({ type: z.literal("remove_claude_hooks_request") });
 
 const apiProxyEventSchema = z.object({
   type: z.literal("api_proxy_event"),
@@ -229,8 +337,72 @@ const apiProxyEventSchema = z.object({
   }),
 });
 
+const helloSchema = z.object({ type: z.literal("hello") });
+
+/** Advertised in the hello handshake so clients can feature-detect. */
+const SUPPORTED_REQUESTS = [
+  helloSchema,
+  registerSchema,
+  ptyOutputSchema,
+  endedSchema,
+  statusSchema,
+  stopSchema,
+  listBotsSchema,
+  saveBotSchema,
+  deleteBotSchema,
+  bindBotSchema,
+  unbindBotSchema,
+  unbindAllBotsSchema,
+  testBotSchema,
+  testBotConfigSchema,
+  getBotSecretSchema,
+  botSchemaSchema,
+  setQuietHoursSchema,
+  getQuietHoursSchema,
+  setSessionQuietHoursSchema,
+  activateBotSchema,
+  getConfigSchema,
+  effectiveConfigSchema,
+  saveConfigSchema,
+  resetConfigSchema,
+  setDefaultBotSchema,
+  getDefaultsSchema,
+  codexNotifySchema,
+  checkCodexConfigSchema,
+  setupCodexConfigSchema,
+  removeCodexHooksSchema,
+  claudeNotifySchema,
+  checkClaudeConfigSchema,
+  setupClaudeConfigSchema,
+  removeClaudeHooksSchema,
+  apiProxyEventSchema,
+].map((schema) => schema.shape.type.value);
+
+/** Advertised next to the requests: behaviors an older daemon would ignore. */
+const SUPPORTED_FEATURES = ["setup_dry_run"];
+
 /* ── Helpers ── */
 
+/** Bot fields holding secrets: the ones get_bot_secret_request reads. */
+const SENSITIVE_BOT_FIELDS: readonly string[] = getBotSecretSchema.shape.payload.shape.field.options;
+
+/** Describe the fields of a bot object schema for bot_schema_request. */
+function describeBotFields(schema: z.AnyZodObject): BotSchemaField[] {
+  return Object.entries(schema.shape as z.ZodRawShape).map(([name, field]) => {
+    const inner = field instanceof z.ZodOptional ? field.unwrap() : field;
+    let type: BotSchemaField["type"] = "object";
+    if (inner instanceof z.ZodString) type = "string";
+    else if (inner instanceof z.ZodNumber) type = "number";
+    else if (inner instanceof z.ZodBoolean) type = "boolean";
+    return {
+      name,
+      type,
+      required: !field.isOptional(),
+      sensitive: SENSITIVE_BOT_FIELDS.includes(name),
+    };
+  });
+}
+
 function isCodexSession(cli: string): boolean {
   const base = cli.replace(/\\/g, "/").split("/").pop() || "";
   const name = base.replace(/\.(exe|cmd|bat)$/i, "").toLowerCase();
@@ -272,23 +444,6 @@ async function ensureSocketDir(ipcPath: string): Promise<void> {
   }
 }
 
-async function writeLockFile(ipcPath: string): Promise<void> {
-  await fs.promises.mkdir(getStateDir(), { recursive: true });
-  const lock: DaemonLockFile = {
-    pid: process.pid,
-    ipc: ipcPath,
-    started_at: new Date().toISOString(),
-  };
-  await fs.promises.writeFile(getLockFilePath(), JSON.stringify(lock, null, 2), "utf8");
-}
-
-async function removeLockFile(): Promise<void> {
-  const lockPath = getLockFilePath();
-  if (fs.existsSync(lockPath)) {
-    await fs.promises.unlink(lockPath);
-  }
-}
-
 async function cleanup(server: net.Server, ipcPath: string): Promise<void> {
   await new Promise<void>((resolve) => {
     server.close(() => resolve());
@@ -297,7 +452,7 @@ async function cleanup(server: net.Server, ipcPath: string): Promise<void> {
   if (process.platform !== "win32" && fs.existsSync(ipcPath)) {
     await fs.promises.unlink(ipcPath);
   }
-  await removeLockFile();
+  await removeLockFile(getLockFilePath());
 }
 
 /* ── Main ── */
@@ -361,7 +516,7 @@ async function main(): Promise<void> {
           continue;
         }
 
-        void handleMessage(
+        handleMessage(
           parsed,
           socket,
           registry,
@@ -381,7 +536,19 @@ async function main(): Promise<void> {
               }, 50);
             }
           }
-        );
+        ).catch((err) => {
+          // Answer instead of leaving the client to time out
+          console.error("[felay:daemon] request failed", err);
+          const reply: ErrorResponse = {
+            type: "error",
+            payload: {
+              code: "internal_error",
+              message: err instanceof Error ? err.message : String(err),
+              requestType: (parsed as { type?: unknown })?.type as string | undefined,
+            },
+          };
+          if (!socket.destroyed) socket.write(toJsonLine(reply));
+        });
       }
     });
 
@@ -419,7 +586,7 @@ async function main(): Promise<void> {
   });
 
   server.listen(ipcPath, async () => {
-    await writeLockFile(ipcPath);
+    await writeLockFile(getLockFilePath(), ipcPath);
     console.log(`[felay:daemon] listening on ${ipcPath}`);
   });
 
@@ -552,6 +719,20 @@ async function handleMessage(
     return;
   }
 
+  const hello = helloSchema.safeParse(parsed);
+  if (hello.success) {
+    const payload: HelloResponse = {
+      type: "hello_response",
+      payload: {
+        protocolVersion: IPC_PROTOCOL_VERSION,
+        requests: SUPPORTED_REQUESTS,
+        features: SUPPORTED_FEATURES,
+      },
+    };
+    socket.write(toJsonLine(payload));
+    return;
+  }
+
   const status = statusSchema.safeParse(parsed);
   if (status.success) {
     const payload: StatusResponse = {
@@ -682,26 +863,7 @@ async function handleMessage(
   const unbindBot = unbindBotSchema.safeParse(parsed);
   if (unbindBot.success) {
     const { sessionId, botType } = unbindBot.data.payload;
-    let ok: boolean;
-    if (botType === "interactive") {
-      const session = registry.get(sessionId);
-      const oldBotId = session?.interactiveBotId;
-      ok = registry.unbindInteractiveBot(sessionId);
-      if (ok && oldBotId) {
-        // M3: stop WSClient if no other session uses this bot
-        const stillUsed = registry
-          .list()
-          .some((s) => s.interactiveBotId === oldBotId && s.status !== "ended");
-        if (!stillUsed) {
-          feishuManager.stopInteractiveBot(oldBotId);
-        }
-      }
-    } else {
-      ok = registry.unbindPushBot(sessionId);
-      if (ok) {
-        outputBuffer.cleanup(sessionId);
-      }
-    }
+    const ok = unbindSessionBot(botType, sessionId, registry, feishuManager, outputBuffer);
     const payload: BindBotResponse = {
       type: "bind_bot_response",
       payload: { ok, error: ok ? undefined : "session not found" },
@@ -710,12 +872,24 @@ async function handleMessage(
     return;
   }
 
+  // Both types in one step, so pollers never see a half-unbound session
+  const unbindAll = unbindAllBotsSchema.safeParse(parsed);
+  if (unbindAll.success) {
+    const { sessionId } = unbindAll.data.payload;
+    const payload: UnbindAllBotsResponse = {
+      type: "unbind_all_bots_response",
+      payload: unbindAllBots(sessionId, registry, feishuManager, outputBuffer),
+    };
+    socket.write(toJsonLine(payload));
+    return;
+  }
+
   /* ── M3: Test bot connection ── */
 
   const testBot = testBotSchema.safeParse(parsed);
   if (testBot.success) {
     const { botType, botId } = testBot.data.payload;
-    let result: { ok: boolean; error?: string; botName?: string };
+    let result: TestBotResult;
     if (botType === "interactive") {
       result = await feishuManager.testInteractiveBot(botId);
     } else {
@@ -729,11 +903,130 @@ async function handleMessage(
     return;
   }
 
+  const testBotConfig = testBotConfigSchema.safeParse(parsed);
+  if (testBotConfig.success) {
+    const { botType, interactive, push } = testBotConfig.data.payload;
+    let result: TestBotResult;
+    if (botType === "interactive" && interactive) {
+      result = await feishuManager.testInteractiveBotConfig(interactive);
+    } else if (botType === "push" && push) {
+      result = await feishuManager.testPushBotConfig(push);
+    } else {
+      result = { ok: false, error: "missing bot config for given type" };
+    }
+    const payload: TestBotConfigResponse = {
+      type: "test_bot_config_response",
+      payload: result,
+    };
+    socket.write(toJsonLine(payload));
+    return;
+  }
+
+  /* ── Bot secret read-back (single field, never the whole config) ── */
+
+  const getBotSecret = getBotSecretSchema.safeParse(parsed);
+  if (getBotSecret.success) {
+    const { botType, botId, field } = getBotSecret.data.payload;
+    const bots = configManager.getBots();
+    const bot =
+      botType === "interactive"
+        ? bots.interactive.find((b) => b.id === botId)
+        : bots.push.find((b) => b.id === botId);
+    const value = bot ? (bot as unknown as Record<string, unknown>)[field] : undefined;
+    const payload: GetBotSecretResponse = {
+      type: "get_bot_secret_response",
+      payload: !bot
+        ? { ok: false, error: "bot not found" }
+        : typeof value === "string"
+          ? { ok: true, value }
+          : { ok: false, error: `field ${field} is not set on this bot` },
+    };
+    socket.write(toJsonLine(payload));
+    return;
+  }
+
+  /* ── Bot field schema ── */
+
+  const botSchema = botSchemaSchema.safeParse(parsed);
+  if (botSchema.success) {
+    const { botType } = botSchema.data.payload;
+    const payload: BotSchemaResponse = {
+      type: "bot_schema_response",
+      payload: {
+        ok: true,
+        botType,
+        fields: describeBotFields(botType === "push" ? pushBotSchema : interactiveBotSchema),
+      },
+    };
+    socket.write(toJsonLine(payload));
+    return;
+  }
+
+  /* ── Quiet hours (push bots only) ── */
+
+  const setQuietHours = setQuietHoursSchema.safeParse(parsed);
+  if (setQuietHours.success) {
+    const { botType, botId, quietHours } = setQuietHours.data.payload;
+    const error =
+      botType !== "push"
+        ? "quiet hours only apply to push bots"
+        : quietHours
+          ? validateQuietHours(quietHours)
+          : null;
+    let payload: SetQuietHoursResponse;
+    if (error) {
+      payload = { type: "set_quiet_hours_response", payload: { ok: false, error } };
+    } else {
+      const ok = await configManager.setQuietHours(botId, quietHours);
+      payload = {
+        type: "set_quiet_hours_response",
+        payload: ok ? { ok: true } : { ok: false, error: "bot not found" },
+      };
+    }
+    socket.write(toJsonLine(payload));
+    return;
+  }
+
+  const getQuietHours = getQuietHoursSchema.safeParse(parsed);
+  if (getQuietHours.success) {
+    const { botType, botId } = getQuietHours.data.payload;
+    const quietHours = botType === "push" ? configManager.getQuietHours(botId) : null;
+    const payload: GetQuietHoursResponse = {
+      type: "get_quiet_hours_response",
+      payload:
+        quietHours === undefined
+          ? { ok: false, error: "bot not found" }
+          : { ok: true, quietHours },
+    };
+    socket.write(toJsonLine(payload));
+    return;
+  }
+
+  // Per-project windows from the GUI; they end with the session
+  const setSessionQuietHours = setSessionQuietHoursSchema.safeParse(parsed);
+  if (setSessionQuietHours.success) {
+    const { sessionId, quietHours } = setSessionQuietHours.data.payload;
+    const error = !registry.get(sessionId)
+      ? "session not found"
+      : quietHours
+        ? validateQuietHours(quietHours)
+        : null;
+    if (!error) feishuManager.setSessionQuietHours(sessionId, quietHours);
+    const payload: SetSessionQuietHoursResponse = {
+      type: "set_session_quiet_hours_response",
+      payload: error ? { ok: false, error } : { ok: true },
+    };
+    socket.write(toJsonLine(payload));
+    return;
+  }
+
   /* ── Activate bot (temporary long connection) ── */
 
   const activateBot = activateBotSchema.safeParse(parsed);
   if (activateBot.success) {
     const { botId } = activateBot.data.payload;
+    // Most recently activated bot (other than this one) whose temp activation is still live
+    const previousBotId = [...activationTimers.keys()].filter((id) => id !== botId).pop() ?? null;
     try {
       await feishuManager.startInteractiveBot(botId);
 
@@ -751,6 +1044,8 @@ async function handleMessage(
       const existingTimer = activationTimers.get(botId);
       if (existingTimer) {
         clearTimeout(existingTimer);
+        // Re-insert below so insertion order tracks the most recent activation
+        activationTimers.delete(botId);
       }
 
       // Auto-disconnect after 30 seconds if no session is using this bot
@@ -768,7 +1063,7 @@ async function handleMessage(
 
       const payload: ActivateBotResponse = {
         type: "activate_bot_response",
-        payload: { ok: true },
+        payload: { ok: true, previousBotId },
       };
       socket.write(toJsonLine(payload));
     } catch (e: any) {
@@ -793,6 +1088,19 @@ async function handleMessage(
     return;
   }
 
+  const effectiveConfig = effectiveConfigSchema.safeParse(parsed);
+  if (effectiveConfig.success) {
+    const payload: EffectiveConfigResponse = {
+      type: "effective_config_response",
+      payload: {
+        config: configManager.getEffectiveConfig(),
+        configPath: configManager.getConfigPath(),
+      },
+    };
+    socket.write(toJsonLine(payload));
+    return;
+  }
+
   const saveConfig = saveConfigSchema.safeParse(parsed);
   if (saveConfig.success) {
     try {
@@ -817,6 +1125,19 @@ async function handleMessage(
     return;
   }
 
+  const resetConfig = resetConfigSchema.safeParse(parsed);
+  if (resetConfig.success) {
+    let payload: ResetConfigResponse;
+    try {
+      await configManager.resetToDefaults();
+      payload = { type: "reset_config_response", payload: { ok: true } };
+    } catch (err) {
+      payload = { type: "reset_config_response", payload: { ok: false, error: String(err) } };
+    }
+    socket.write(toJsonLine(payload));
+    return;
+  }
+
   /* ── Default bot settings ── */
 
   const setDefaultBot = setDefaultBotSchema.safeParse(parsed);
@@ -863,7 +1184,7 @@ async function handleMessage(
 
   const setupCodex = setupCodexConfigSchema.safeParse(parsed);
   if (setupCodex.success) {
-    const result = setupCodexConfig();
+    const result = setupCodexConfig(setupCodex.data.payload?.dryRun ?? false);
     const payload: SetupCodexConfigResponse = {
       type: "setup_codex_config_response",
       payload: result,
@@ -872,6 +1193,16 @@ async function handleMessage(
     return;
   }
 
+  const removeCodex = removeCodexHooksSchema.safeParse(parsed);
+  if (removeCodex.success) {
+    const payload: RemoveCodexHooksResponse = {
+      type: "remove_codex_hooks_response",
+      payload: removeCodexHooks(),
+    };
+    socket.write(toJsonLine(payload));
+    return;
+  }
+
   /* ── Codex notify hook ── */
 
   const codexNotify = codexNotifySchema.safeParse(parsed);
@@ -937,7 +1268,7 @@ async function handleMessage(
 
   const setupClaude = setupClaudeConfigSchema.safeParse(parsed);
   if (setupClaude.success) {
-    const result = setupClaudeConfig();
+    const result = setupClaudeConfig(setupClaude.data.payload?.dryRun ?? false);
     const payload: SetupClaudeConfigResponse = {
       type: "setup_claude_config_response",
       payload: result,
@@ -946,6 +1277,16 @@ async function handleMessage(
     return;
   }
 
+  const removeClaude = removeClaudeHooksSchema.safeParse(parsed);
+  if (removeClaude.success) {
+    const payload: RemoveClaudeHooksResponse = {
+      type: "remove_claude_hooks_response",
+      payload: removeClaudeHooks(),
+    };
+    socket.write(toJsonLine(payload));
+    return;
+  }
+
   /* ── API proxy event ── */
 
   const apiProxyEvent = apiProxyEventSchema.safeParse(parsed);
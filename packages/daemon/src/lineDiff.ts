@@ -0,0 +1,36 @@
+/**
+ * Minimal line diff for previewing config file edits.
+ *
+ * Produces unified-style lines ("  " unchanged, "- " removed, "+ " added)
+ * from an LCS table. Config files are small, so the O(n·m) table is fine.
+ */
+export function lineDiff(before: string, after: string): string {
+  const a = before === "" ? [] : before.split("\n");
+  const b = after === "" ? [] : after.split("\n");
+
+  // lcs[i][j] = length of the LCS of a[i..] and b[j..]
+  const lcs: number[][] = Array.from({ length: a.length + 1 }, () => new Array<number>(b.length + 1).fill(0));
+  for (let i = a.length - 1; i >= 0; i--) {
+    for (let j = b.length - 1; j >= 0; j--) {
+      lcs[i][j] = a[i] === b[j] ? lcs[i + 1][j + 1] + 1 : Math.max(lcs[i + 1][j], lcs[i][j + 1]);
+    }
+  }
+
+  const out: string[] = [];
+  let i = 0;
+  let j = 0;
+  while (i < a.length && j < b.length) {
+    if (a[i] === b[j]) {
+      out.push("  " + a[i]);
+      i++;
+      j++;
+    } else if (lcs[i + 1][j] >= lcs[i][j + 1]) {
+      out.push("- " + a[i++]);
+    } else {
+      out.push("+ " + b[j++]);
+    }
+  }
+  while (i < a.length) out.push("- " + a[i++]);
+  while (j < b.length) out.push("+ " + b[j++]);
+  return out.join("\n");
+}
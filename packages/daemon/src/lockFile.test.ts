@@ -0,0 +1,63 @@
+import { afterEach, beforeEach, test } from "node:test";
+import assert from "node:assert/strict";
+import fs from "node:fs";
+import os from "node:os";
+import path from "node:path";
+import { removeLockFile, writeLockFile } from "./lockFile.js";
+
+let dir: string;
+let lockPath: string;
+
+beforeEach(() => {
+  dir = fs.mkdtempSync(path.join(os.tmpdir(), "felay-lock-"));
+  lockPath = path.join(dir, ".felay", "daemon.json");
+});
+
+afterEach(() => {
+  fs.rmSync(dir, { recursive: true, force: true });
+});
+
+test("writes a complete lock file and leaves no temp file", async () => {
+  await writeLockFile(lockPath, "/tmp/felay.sock");
+  const lock = JSON.parse(fs.readFileSync(lockPath, "utf8"));
+  assert.equal(lock.pid, process.pid);
+  assert.equal(lock.ipc, "/tmp/felay.sock");
+  assert.ok(!Number.isNaN(Date.parse(lock.started_at)));
+  assert.deepEqual(fs.readdirSync(path.dirname(lockPath)), ["daemon.json"]);
+});
+
+test("replaces a truncated lock file", async () => {
+  fs.mkdirSync(path.dirname(lockPath), { recursive: true });
+  fs.writeFileSync(lockPath, '{ "pid": 12');
+  await writeLockFile(lockPath, "/tmp/felay.sock");
+  assert.equal(JSON.parse(fs.readFileSync(lockPath, "utf8")).ipc, "/tmp/felay.sock");
+});
+
+test("readers never see a partial file while it is rewritten", async () => {
+  await writeLockFile(lockPath, "/tmp/first.sock");
+  let reads = 0;
+  let writing = true;
+  const reader = (async () => {
+    while (writing) {
+      // Each read is a whole file: the old one or the new one
+      const lock = JSON.parse(fs.readFileSync(lockPath, "utf8"));
+      assert.match(lock.ipc, /^\/tmp\/(first|\d+)\.sock$/);
+      reads++;
+      await new Promise((resolve) => setImmediate(resolve));
+    }
+  })();
+  for (let i = 0; i < 50; i++) {
+    await writeLockFile(lockPath, `/tmp/${i}.sock`);
+  }
+  writing = false;
+  await reader;
+  assert.ok(reads > 0);
+  assert.equal(JSON.parse(fs.readFileSync(lockPath, "utf8")).ipc, "/tmp/49.sock");
+});
+
+test("removing a missing lock file is fine", async () => {
+  await removeLockFile(lockPath);
+  await writeLockFile(lockPath, "/tmp/felay.sock");
+  await removeLockFile(lockPath);
+  assert.ok(!fs.existsSync(lockPath));
+});
@@ -0,0 +1,25 @@
+import fs from "node:fs";
+import path from "node:path";
+import type { DaemonLockFile } from "@felay/shared";
+
+/**
+ * Write daemon.json atomically (temp file + rename). The GUI and CLI read it
+ * at any moment, and a reader must never see a half-written file.
+ */
+export async function writeLockFile(lockPath: string, ipcPath: string): Promise<void> {
+  await fs.promises.mkdir(path.dirname(lockPath), { recursive: true });
+  const lock: DaemonLockFile = {
+    pid: process.pid,
+    ipc: ipcPath,
+    started_at: new Date().toISOString(),
+  };
+  const tmpPath = `${lockPath}.${process.pid}.tmp`;
+  await fs.promises.writeFile(tmpPath, JSON.stringify(lock, null, 2), "utf8");
+  await fs.promises.rename(tmpPath, lockPath);
+}
+
+export async function removeLockFile(lockPath: string): Promise<void> {
+  if (fs.existsSync(lockPath)) {
+    await fs.promises.unlink(lockPath);
+  }
+}
@@ -0,0 +1,70 @@
+import { beforeEach, test } from "node:test";
+import assert from "node:assert/strict";
+import { SessionRegistry } from "./sessionRegistry.js";
+import { unbindAllBots } from "./sessionBots.js";
+
+let registry: SessionRegistry;
+let stopped: string[];
+let cleaned: string[];
+const feishuManager = { stopInteractiveBot: (botId: string) => void stopped.push(botId) };
+const outputBuffer = { cleanup: (sessionId: string) => void cleaned.push(sessionId) };
+
+function addSession(sessionId: string, interactiveBotId?: string, pushBotId?: string): void {
+  registry.register({
+    sessionId,
+    cli: "codex",
+    cwd: "/work",
+    status: "listening",
+    startedAt: new Date().toISOString(),
+  });
+  if (interactiveBotId) registry.bindInteractiveBot(sessionId, interactiveBotId);
+  if (pushBotId) registry.bindPushBot(sessionId, pushBotId);
+}
+
+beforeEach(() => {
+  registry = new SessionRegistry();
+  stopped = [];
+  cleaned = [];
+});
+
+test("detaches both bot types at once", () => {
+  addSession("s1", "i1", "p1");
+  const result = unbindAllBots("s1", registry, feishuManager, outputBuffer);
+  assert.deepEqual(result, { ok: true, unbound: { interactive: true, push: true } });
+  const session = registry.get("s1");
+  assert.equal(session?.interactiveBotId, undefined);
+  assert.equal(session?.pushBotId, undefined);
+  assert.deepEqual(stopped, ["i1"]);
+  assert.deepEqual(cleaned, ["s1"]);
+});
+
+test("an unbound type is reported as false, not an error", () => {
+  addSession("s1", undefined, "p1");
+  const result = unbindAllBots("s1", registry, feishuManager, outputBuffer);
+  assert.deepEqual(result, { ok: true, unbound: { interactive: false, push: true } });
+  assert.deepEqual(stopped, []);
+
+  const again = unbindAllBots("s1", registry, feishuManager, outputBuffer);
+  assert.deepEqual(again, { ok: true, unbound: { interactive: false, push: false } });
+  assert.deepEqual(cleaned, ["s1"]);
+});
+
+test("keeps an interactive bot running while another live session uses it", () => {
+  addSession("s1", "i1");
+  addSession("s2", "i1");
+  addSession("s3", "i1");
+  registry.end("s3");
+  unbindAllBots("s1", registry, feishuManager, outputBuffer);
+  assert.deepEqual(stopped, []);
+  assert.equal(registry.get("s2")?.interactiveBotId, "i1");
+
+  unbindAllBots("s2", registry, feishuManager, outputBuffer);
+  assert.deepEqual(stopped, ["i1"]);
+});
+
+test("an unknown session is an error", () => {
+  const result = unbindAllBots("missing", registry, feishuManager, outputBuffer);
+  assert.deepEqual(result, { ok: false, error: "session not found" });
+  assert.deepEqual(stopped, []);
+  assert.deepEqual(cleaned, []);
+});
@@ -0,0 +1,65 @@
+import type { UnbindAllBotsResponse } from "@felay/shared";
+import type { FeishuManager } from "./feishuManager.js";
+import type { OutputBuffer } from "./outputBuffer.js";
+import type { SessionRegistry } from "./sessionRegistry.js";
+
+type BotStopper = Pick<FeishuManager, "stopInteractiveBot">;
+type BufferCleaner = Pick<OutputBuffer, "cleanup">;
+
+/** Detach one bot type from a session, releasing resources it no longer needs. */
+export function unbindSessionBot(
+  botType: "interactive" | "push",
+  sessionId: string,
+  registry: SessionRegistry,
+  feishuManager: BotStopper,
+  outputBuffer: BufferCleaner
+): boolean {
+  if (botType === "interactive") {
+    const session = registry.get(sessionId);
+    const oldBotId = session?.interactiveBotId;
+    const ok = registry.unbindInteractiveBot(sessionId);
+    if (ok && oldBotId) {
+      // M3: stop WSClient if no other session uses this bot
+      const stillUsed = registry
+        .list()
+        .some((s) => s.interactiveBotId === oldBotId && s.status !== "ended");
+      if (!stillUsed) {
+        feishuManager.stopInteractiveBot(oldBotId);
+      }
+    }
+    return ok;
+  }
+
+  const ok = registry.unbindPushBot(sessionId);
+  if (ok) {
+    outputBuffer.cleanup(sessionId);
+  }
+  return ok;
+}
+
+/**
+ * Detach both bot types in one step, so pollers never see a half-unbound
+ * session. A type that was not bound is reported as `false`, not an error.
+ */
+export function unbindAllBots(
+  sessionId: string,
+  registry: SessionRegistry,
+  feishuManager: BotStopper,
+  outputBuffer: BufferCleaner
+): UnbindAllBotsResponse["payload"] {
+  const session = registry.get(sessionId);
+  if (!session) {
+    return { ok: false, error: "session not found" };
+  }
+  const unbound = {
+    interactive: Boolean(session.interactiveBotId),
+    push: Boolean(session.pushBotId),
+  };
+  if (unbound.interactive) {
+    unbindSessionBot("interactive", sessionId, registry, feishuManager, outputBuffer);
+  }
+  if (unbound.push) {
+    unbindSessionBot("push", sessionId, registry, feishuManager, outputBuffer);
+  }
+  return { ok: true, unbound };
+}
@@ -0,0 +1,116 @@
+//! Local history of bot configuration changes.
+//!
+//! Every save/delete issued from the GUI appends one JSON line to
+//! `~/.felay/bot-audit.jsonl`, including attempts the daemon rejected.
+//! Entries list which top-level keys changed; values of secret keys are
+//! never written, only the fact that they changed.
+
+use crate::{get_home_dir, is_sensitive_key, unix_now};
+use serde_json::{Map, Value};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// The log is rotated to `bot-audit.jsonl.1` once it grows past this size.
+const MAX_AUDIT_BYTES: u64 = 1024 * 1024;
+
+pub(crate) const AUDIT_FILE: &str = "bot-audit.jsonl";
+
+fn audit_path() -> Option<PathBuf> {
+  let home = get_home_dir()?;
+  Some(PathBuf::from(home).join(".felay").join(AUDIT_FILE))
+}
+
+fn rotated_path(path: &std::path::Path) -> PathBuf {
+  path.with_extension("jsonl.1")
+}
+
+/// Top-level keys that differ between `before` and `after`. Secret keys are
+/// reported without their values.
+pub(crate) fn diff_keys(before: Option<&Value>, after: Option<&Value>) -> Vec<Value> {
+  let empty = Map::new();
+  let before = before.and_then(|v| v.as_object()).unwrap_or(&empty);
+  let after = after.and_then(|v| v.as_object()).unwrap_or(&empty);
+
+  let mut keys: Vec<&String> = before.keys().chain(after.keys()).collect();
+  keys.sort();
+  keys.dedup();
+
+  keys
+    .into_iter()
+    .filter(|k| before.get(*k) != after.get(*k))
+    .map(|k| {
+      if is_sensitive_key(k) {
+        serde_json::json!({ "key": k, "redacted": true })
+      } else {
+        serde_json::json!({ "key": k, "before": before.get(k), "after": after.get(k) })
+      }
+    })
+    .collect()
+}
+
+/// Append one audit entry. Failures to write are logged and otherwise
+/// ignored so auditing never blocks the actual operation.
+pub(crate) fn record(
+  action: &str,
+  bot_type: &str,
+  bot_id: &str,
+  bot_name: Option<&str>,
+  changes: Vec<Value>,
+  result: &Value,
+) {
+  let Some(path) = audit_path() else {
+    return;
+  };
+  let entry = serde_json::json!({
+    "ts": unix_now(),
+    "action": action,
+    "bot_type": bot_type,
+    "bot_id": bot_id,
+    "bot_name": bot_name,
+    "ok": result["ok"].as_bool().unwrap_or(false),
+    "error": result.get("error").filter(|e| !e.is_null()),
+    "changes": changes,
+  });
+
+  if let Some(dir) = path.parent() {
+    let _ = fs::create_dir_all(dir);
+  }
+  if fs::metadata(&path).is_ok_and(|m| m.len() > MAX_AUDIT_BYTES) {
+    let _ = fs::rename(&path, rotated_path(&path));
+  }
+
+  let written = OpenOptions::new()
+    .create(true)
+    .append(true)
+    .open(&path)
+    .and_then(|mut f| writeln!(f, "{}", entry));
+  if let Err(e) = written {
+    gui_println!("[gui] failed to write bot audit log: {}", e);
+  }
+}
+
+/// Read audit entries, newest first, optionally for one bot only.
+#[tauri::command]
+pub(crate) fn get_bot_audit(bot_id: Option<String>, limit: Option<usize>) -> Vec<Value> {
+  let Some(path) = audit_path() else {
+    return vec![];
+  };
+
+  // Rotated file first so entries stay in chronological order
+  let mut entries: Vec<Value> = [rotated_path(&path), path]
+    .iter()
+    .filter_map(|p| fs::read_to_string(p).ok())
+    .flat_map(|text| {
+      text
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .collect::<Vec<_>>()
+    })
+    .filter(|e| bot_id.as_deref().is_none_or(|id| e["bot_id"].as_str() == Some(id)))
+    .collect();
+
+  entries.reverse();
+  entries.truncate(limit.unwrap_or(100));
+  entries
+}
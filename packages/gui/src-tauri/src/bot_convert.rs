@@ -0,0 +1,291 @@
+//! Copy a bot's credentials across bot types.
+//!
+//! The same app credentials often back both an interactive and a push bot,
+//! so instead of typing them twice the user can clone one into the other.
+//! Only fields listed in `FIELD_MAP` carry over; every other field the
+//! target type needs is saved empty and reported as `unmapped_fields`.
+
+use crate::{
+  audit, config_cache, fetch_bots, file_watcher, get_ipc_path, ipc_request_typed,
+  GenericOkResponse,
+};
+use serde_json::{Map, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Per-provider field mapping: (provider, interactive field, push field).
+/// The mapping is symmetric and applies in both directions. `appId` and
+/// `encryptKey` identify an app and `webhook` a single robot, so neither
+/// side has a counterpart for them and they always end up unmapped.
+const FIELD_MAP: &[(&str, &str, &str)] = &[
+  ("feishu", "appSecret", "secret"),
+  ("lark", "appSecret", "secret"),
+  ("dingtalk", "appSecret", "secret"),
+];
+
+/// Fields each bot type stores besides `id`/`name`.
+const TYPE_FIELDS: &[(&str, &[&str])] = &[
+  ("interactive", &["appId", "appSecret", "encryptKey"]),
+  ("push", &["webhook", "secret"]),
+];
+
+/// Fields the daemon requires to be present (possibly empty) on save.
+const REQUIRED_FIELDS: &[(&str, &[&str])] = &[
+  ("interactive", &["appId", "appSecret"]),
+  ("push", &["webhook"]),
+];
+
+fn fields_of(table: &[(&str, &'static [&'static str])], bot_type: &str) -> &'static [&'static str] {
+  table
+    .iter()
+    .find(|(t, _)| *t == bot_type)
+    .map(|(_, f)| *f)
+    .unwrap_or(&[])
+}
+
+/// Interactive bots are always Feishu/Lark apps; push bots are identified
+/// by their webhook host.
+fn provider_of(bot_type: &str, bot: &Value) -> &'static str {
+  if bot_type != "push" {
+    return "feishu";
+  }
+  bot
+    .get("webhook")
+    .and_then(|w| w.as_str())
+    .and_then(|w| reqwest::Url::parse(w).ok())
+    .and_then(|u| u.host_str().and_then(crate::webhook::detect_provider))
+    .unwrap_or("feishu")
+}
+
+/// Target-side name of `field` for a conversion, if the provider maps it.
+fn map_field(provider: &str, from_type: &str, field: &str) -> Option<&'static str> {
+  FIELD_MAP
+    .iter()
+    .filter(|(p, _, _)| *p == provider)
+    .find_map(|(_, interactive, push)| match from_type {
+      "interactive" if *interactive == field => Some(*push),
+      "push" if *push == field => Some(*interactive),
+      _ => None,
+    })
+}
+
+/// Build the target config from `source`. Err if a mapped secret is masked.
+fn convert_config(
+  source: &Value,
+  from_type: &str,
+  to_type: &str,
+  id: &str,
+  name: &str,
+) -> Result<(Value, Vec<String>), String> {
+  let provider = provider_of(from_type, source);
+  let mut target = Map::new();
+  target.insert("id".into(), Value::String(id.to_string()));
+  target.insert("name".into(), Value::String(name.to_string()));
+
+  for field in fields_of(TYPE_FIELDS, from_type) {
+    let value = source.get(*field).and_then(|v| v.as_str()).unwrap_or("");
+    if value.is_empty() {
+      continue;
+    }
+    let Some(to_field) = map_field(provider, from_type, field) else {
+      continue;
+    };
+    // Values as shown in the bot list (`abc***xyz`) are not real secrets
+    if crate::is_masked_secret(value) {
+      return Err(format!("{} 是掩码后的值，无法复制，请重新输入原始密钥", field));
+    }
+    target.insert(to_field.into(), Value::String(value.to_string()));
+  }
+
+  let unmapped: Vec<String> = fields_of(TYPE_FIELDS, to_type)
+    .iter()
+    .filter(|f| !target.contains_key(**f))
+    .map(|f| f.to_string())
+    .collect();
+  for field in fields_of(REQUIRED_FIELDS, to_type) {
+    target
+      .entry(field.to_string())
+      .or_insert_with(|| Value::String(String::new()));
+  }
+
+  Ok((Value::Object(target), unmapped))
+}
+
+#[tauri::command]
+pub(crate) fn convert_bot(
+  bot_type_from: String,
+  bot_id: String,
+  bot_type_to: String,
+  new_name: String,
+) -> Value {
+  let valid = |t: &str| t == "interactive" || t == "push";
+  if !valid(&bot_type_from) || !valid(&bot_type_to) || bot_type_from == bot_type_to {
+    return serde_json::json!({ "ok": false, "error": "invalid bot type conversion" });
+  }
+  let Some(ipc_path) = get_ipc_path() else {
+    return serde_json::json!({ "ok": false, "error": "daemon not running" });
+  };
+
+  let bots = fetch_bots();
+  let source = bots
+    .get(&bot_type_from)
+    .and_then(|list| list.as_array())
+    .and_then(|list| list.iter().find(|b| b["id"].as_str() == Some(bot_id.as_str())));
+  let Some(source) = source else {
+    return serde_json::json!({ "ok": false, "error": "bot not found" });
+  };
+
+  let name = if new_name.trim().is_empty() {
+    source["name"].as_str().unwrap_or("").to_string()
+  } else {
+    new_name.trim().to_string()
+  };
+  let nanos = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_nanos())
+    .unwrap_or_default();
+  let new_id = format!("{}-{:x}", bot_type_to, nanos);
+
+  let (config, unmapped) =
+    match convert_config(source, &bot_type_from, &bot_type_to, &new_id, &name) {
+      Ok(c) => c,
+      Err(e) => return serde_json::json!({ "ok": false, "error": e }),
+    };
+
+  let changes = audit::diff_keys(None, Some(&config));
+  let mut payload = serde_json::json!({ "botType": bot_type_to });
+  payload[bot_type_to.as_str()] = config;
+  let req = serde_json::json!({ "type": "save_bot_request", "payload": payload });
+  let req_str = serde_json::to_string(&req).unwrap_or_default();
+
+  let _own_write = file_watcher::own_config_write();
+  let result = match ipc_request_typed::<GenericOkResponse>(&ipc_path, &req_str) {
+    Ok(resp) => serde_json::json!({
+      "ok": resp.payload.ok,
+      "error": resp.payload.error,
+      "bot_id": new_id,
+      "unmapped_fields": unmapped,
+    }),
+    Err(e) => e.to_json(),
+  };
+  config_cache::invalidate();
+  audit::record("create", &bot_type_to, &new_id, Some(&name), changes, &result);
+  result
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn lark_push() -> Value {
+    json!({
+      "id": "p1",
+      "name": "构建通知",
+      "webhook": "https://open.larksuite.com/open-apis/bot/v2/hook/abc-def",
+      "secret": "lark-sign-secret",
+    })
+  }
+
+  fn dingtalk_push() -> Value {
+    json!({
+      "id": "p2",
+      "name": "钉钉告警",
+      "webhook": "https://oapi.dingtalk.com/robot/send?access_token=abc",
+      "secret": "SECdingtalk-sign-secret",
+    })
+  }
+
+  fn lark_app() -> Value {
+    json!({
+      "id": "i1",
+      "name": "团队助手",
+      "appId": "cli_a1b2c3",
+      "appSecret": "app-secret-value",
+      "encryptKey": "encrypt-key",
+    })
+  }
+
+  #[test]
+  fn lark_push_round_trip_keeps_the_secret() {
+    let source = lark_push();
+    let (app, unmapped) = convert_config(&source, "push", "interactive", "i", "n").unwrap();
+    assert_eq!(
+      app,
+      json!({ "id": "i", "name": "n", "appId": "", "appSecret": "lark-sign-secret" })
+    );
+    assert_eq!(unmapped, vec!["appId", "encryptKey"]);
+
+    let (back, unmapped) = convert_config(&app, "interactive", "push", "p", "n").unwrap();
+    assert_eq!(back["secret"], source["secret"]);
+    assert_eq!(back["webhook"], "");
+    assert_eq!(unmapped, vec!["webhook"]);
+  }
+
+  #[test]
+  fn dingtalk_push_round_trip_keeps_the_secret() {
+    let source = dingtalk_push();
+    let (app, unmapped) = convert_config(&source, "push", "interactive", "i", "n").unwrap();
+    assert_eq!(app["appSecret"], "SECdingtalk-sign-secret");
+    assert_eq!(app["appId"], "");
+    assert_eq!(unmapped, vec!["appId", "encryptKey"]);
+
+    let (back, unmapped) = convert_config(&app, "interactive", "push", "p", "n").unwrap();
+    assert_eq!(back["secret"], source["secret"]);
+    assert_eq!(unmapped, vec!["webhook"]);
+  }
+
+  #[test]
+  fn lark_app_round_trip_reports_what_did_not_map() {
+    let source = lark_app();
+    let (push, unmapped) = convert_config(&source, "interactive", "push", "p", "n").unwrap();
+    assert_eq!(
+      push,
+      json!({ "id": "p", "name": "n", "secret": "app-secret-value", "webhook": "" })
+    );
+    assert_eq!(unmapped, vec!["webhook"]);
+
+    let (back, unmapped) = convert_config(&push, "push", "interactive", "i", "n").unwrap();
+    assert_eq!(back["appSecret"], source["appSecret"]);
+    assert_eq!(unmapped, vec!["appId", "encryptKey"]);
+  }
+
+  #[test]
+  fn masked_secret_aborts_with_the_field_name() {
+    let mut lark = lark_push();
+    lark["secret"] = json!("lar***ret");
+    let err = convert_config(&lark, "push", "interactive", "i", "n").unwrap_err();
+    assert!(err.starts_with("secret "), "{}", err);
+
+    let mut dingtalk = dingtalk_push();
+    dingtalk["secret"] = json!("SEC…ret");
+    assert!(convert_config(&dingtalk, "push", "interactive", "i", "n").is_err());
+
+    let mut app = lark_app();
+    app["appSecret"] = json!("app***lue");
+    let err = convert_config(&app, "interactive", "push", "p", "n").unwrap_err();
+    assert!(err.starts_with("appSecret "), "{}", err);
+  }
+
+  #[test]
+  fn unmapped_fields_are_not_checked_for_masks() {
+    let mut lark = lark_push();
+    lark["webhook"] = json!("https://open.larksuite.com/open-apis/bot/v2/hook/***");
+    lark["secret"] = json!("");
+    let (app, unmapped) = convert_config(&lark, "push", "interactive", "i", "n").unwrap();
+    assert_eq!(app["appSecret"], "");
+    assert_eq!(unmapped, vec!["appId", "appSecret", "encryptKey"]);
+  }
+
+  #[test]
+  fn wecom_has_no_mapped_fields() {
+    let wecom = json!({
+      "id": "p3",
+      "name": "企微",
+      "webhook": "https://qyapi.weixin.qq.com/cgi-bin/webhook/send?key=abc",
+      "secret": "unused",
+    });
+    let (app, unmapped) = convert_config(&wecom, "push", "interactive", "i", "n").unwrap();
+    assert_eq!(app, json!({ "id": "i", "name": "n", "appId": "", "appSecret": "" }));
+    assert_eq!(unmapped, vec!["appId", "appSecret", "encryptKey"]);
+  }
+}
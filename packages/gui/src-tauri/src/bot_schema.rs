@@ -0,0 +1,77 @@
+//! Field schema of interactive / push bots, as the daemon defines it.
+//!
+//! The settings form renders bot fields from `get_bot_schema` instead of
+//! hard-coding them, and masks the ones marked `sensitive`; the same marks
+//! feed `is_sensitive_key`, so log and export masking follow the daemon too.
+//! Answers are cached per bot type for the daemon process that gave them
+//! (lock-file pid). Without a daemon that answers `bot_schema_request`, the
+//! GUI's mirror of the save schema (`config_schema`) is returned with
+//! `source: "local"`.
+
+use crate::{capabilities, config_schema, get_ipc_path, ipc_payload, read_lock_file};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, MutexGuard};
+
+const BOT_TYPES: &[&str] = &["interactive", "push"];
+
+/// Daemon answers by bot type, with the pid of the daemon that gave them.
+static CACHE: Mutex<BTreeMap<String, (i64, Value)>> = Mutex::new(BTreeMap::new());
+
+fn cache() -> MutexGuard<'static, BTreeMap<String, (i64, Value)>> {
+  CACHE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn from_daemon(bot_type: &str) -> Option<Value> {
+  let pid = read_lock_file().map(|lock| lock.pid).unwrap_or_default();
+  if let Some((cached_pid, schema)) = cache().get(bot_type) {
+    if *cached_pid == pid {
+      return Some(schema.clone());
+    }
+  }
+
+  let ipc_path = get_ipc_path()?;
+  if capabilities::supports("bot_schema_request") != Some(true) {
+    return None;
+  }
+  let req = serde_json::json!({ "type": "bot_schema_request", "payload": { "botType": bot_type } });
+  let payload = ipc_payload(&ipc_path, &req.to_string()).ok()?;
+  if payload["ok"] != true || !payload["fields"].is_array() {
+    return None;
+  }
+  let schema = serde_json::json!({
+    "ok": true,
+    "botType": bot_type,
+    "fields": payload["fields"],
+    "source": "daemon",
+  });
+  cache().insert(bot_type.to_string(), (pid, schema.clone()));
+  Some(schema)
+}
+
+/// Fields of `bot_type` bots: `name`, `type`, `required`, `sensitive`.
+#[tauri::command]
+pub(crate) fn get_bot_schema(bot_type: String) -> Value {
+  if !BOT_TYPES.contains(&bot_type.as_str()) {
+    return serde_json::json!({ "ok": false, "error": format!("unknown bot type: {}", bot_type) });
+  }
+  if let Some(schema) = from_daemon(&bot_type) {
+    return schema;
+  }
+  serde_json::json!({
+    "ok": true,
+    "botType": bot_type,
+    "fields": config_schema::bot_fields(&bot_type),
+    "source": "local",
+  })
+}
+
+/// Whether a schema fetched from the daemon marks `key` as sensitive.
+pub(crate) fn is_sensitive_field(key: &str) -> bool {
+  cache().values().any(|(_, schema)| {
+    let fields = schema["fields"].as_array().into_iter().flatten();
+    fields.filter(|field| field["sensitive"] == true).any(|field| {
+      field["name"].as_str().is_some_and(|name| name.eq_ignore_ascii_case(key))
+    })
+  })
+}
@@ -0,0 +1,179 @@
+//! IPC capability negotiation with the daemon.
+//!
+//! Daemons answer `{"type":"hello"}` with their protocol version and the
+//! request types they understand. Older daemons ignore unknown requests, so
+//! a request they don't support just times out; checking capabilities first
+//! lets commands fail fast or fall back instead. Daemons without the
+//! handshake are assumed to support `LEGACY_REQUESTS`.
+//!
+//! A hello reply is cached per daemon process (lock-file pid), so a daemon
+//! restarted after an upgrade is asked again. The legacy fallback is never
+//! cached: a silent hello may just be a busy daemon, and caching it would
+//! turn off every newer feature until the daemon restarts.
+
+use crate::{daemon_reachable, get_ipc_path, ipc_request_timeout, read_lock_file};
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Request types every daemon handled before the hello handshake existed.
+const LEGACY_REQUESTS: &[&str] = &[
+  "register_session",
+  "pty_output",
+  "session_ended",
+  "status_request",
+  "stop_request",
+  "list_bots_request",
+  "save_bot_request",
+  "delete_bot_request",
+  "bind_bot_request",
+  "unbind_bot_request",
+  "test_bot_request",
+  "activate_bot_request",
+  "get_config_request",
+  "save_config_request",
+  "set_default_bot_request",
+  "get_defaults_request",
+  "codex_notify",
+  "check_codex_config_request",
+  "setup_codex_config_request",
+  "claude_notify",
+  "check_claude_config_request",
+  "setup_claude_config_request",
+  "api_proxy_event",
+];
+
+/// Advertised by daemons that honor `dryRun` on setup requests; others
+/// would apply the change.
+pub(crate) const SETUP_DRY_RUN: &str = "setup_dry_run";
+
+/// A daemon that knows `hello` answers at once; don't wait the full IPC timeout.
+const HELLO_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct Capabilities {
+  /// None for daemons that predate the handshake.
+  protocol_version: Option<u64>,
+  requests: Vec<String>,
+  features: Vec<String>,
+  pub(crate) legacy: bool,
+}
+
+impl Capabilities {
+  pub(crate) fn supports(&self, request_type: &str) -> bool {
+    self.requests.iter().any(|r| r == request_type)
+  }
+
+  pub(crate) fn has_feature(&self, feature: &str) -> bool {
+    self.features.iter().any(|f| f == feature)
+  }
+}
+
+static CACHE: Mutex<Option<(String, Capabilities)>> = Mutex::new(None);
+
+fn daemon_identity(ipc_path: &str) -> String {
+  let pid = read_lock_file().map(|lock| lock.pid).unwrap_or_default();
+  format!("{}#{}", ipc_path, pid)
+}
+
+fn strings(list: &Value) -> Vec<String> {
+  list
+    .as_array()
+    .map(|r| r.iter().filter_map(|t| t.as_str().map(String::from)).collect())
+    .unwrap_or_default()
+}
+
+fn negotiate(ipc_path: &str) -> Option<Capabilities> {
+  let reply = ipc_request_timeout(ipc_path, r#"{"type":"hello"}"#, HELLO_TIMEOUT);
+  match reply.filter(|v| v["type"] == "hello_response") {
+    Some(v) => Some(Capabilities {
+      protocol_version: v["payload"]["protocolVersion"].as_u64(),
+      requests: strings(&v["payload"]["requests"]),
+      features: strings(&v["payload"]["features"]),
+      legacy: false,
+    }),
+    // Reachable but silent: a daemon from before the handshake
+    None if daemon_reachable(ipc_path) => Some(Capabilities {
+      protocol_version: None,
+      requests: LEGACY_REQUESTS.iter().map(|r| r.to_string()).collect(),
+      features: Vec::new(),
+      legacy: true,
+    }),
+    None => None,
+  }
+}
+
+/// Capabilities of the running daemon, or None if it is not running.
+pub(crate) fn get() -> Option<Capabilities> {
+  let ipc_path = get_ipc_path()?;
+  let identity = daemon_identity(&ipc_path);
+
+  let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+  if let Some((id, caps)) = cache.as_ref() {
+    if *id == identity {
+      return Some(caps.clone());
+    }
+  }
+  let caps = negotiate(&ipc_path)?;
+  remember(&mut cache, identity, &caps);
+  Some(caps)
+}
+
+/// Cache `caps` for `identity` if they came from a hello reply.
+fn remember(cache: &mut Option<(String, Capabilities)>, identity: String, caps: &Capabilities) {
+  if !caps.legacy {
+    *cache = Some((identity, caps.clone()));
+  }
+}
+
+/// Whether the daemon handles `request_type`; None if it is not running.
+pub(crate) fn supports(request_type: &str) -> Option<bool> {
+  get().map(|caps| caps.supports(request_type))
+}
+
+/// Whether the daemon advertises `feature`; false if it is not running.
+pub(crate) fn has_feature(feature: &str) -> bool {
+  get().is_some_and(|caps| caps.has_feature(feature))
+}
+
+/// Negotiated capabilities, for diagnostics.
+#[tauri::command]
+pub(crate) fn get_daemon_capabilities() -> Option<Capabilities> {
+  get()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn caps(legacy: bool) -> Capabilities {
+    Capabilities {
+      protocol_version: (!legacy).then_some(2),
+      requests: vec!["status_request".to_string()],
+      features: Vec::new(),
+      legacy,
+    }
+  }
+
+  #[test]
+  fn hello_replies_are_cached() {
+    let mut cache = None;
+    remember(&mut cache, "ipc#1".to_string(), &caps(false));
+    let (id, cached) = cache.unwrap();
+    assert_eq!(id, "ipc#1");
+    assert!(!cached.legacy);
+  }
+
+  #[test]
+  fn legacy_fallback_is_not_cached() {
+    let mut cache = None;
+    remember(&mut cache, "ipc#1".to_string(), &caps(true));
+    assert!(cache.is_none());
+
+    // Nor does it replace an earlier reply
+    let mut cache = Some(("ipc#1".to_string(), caps(false)));
+    remember(&mut cache, "ipc#1".to_string(), &caps(true));
+    assert!(!cache.unwrap().1.legacy);
+  }
+}
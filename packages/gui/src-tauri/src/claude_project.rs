@@ -0,0 +1,183 @@
+//! Project-local Claude Code settings, `<project>/.claude/settings.json`.
+//!
+//! Claude Code lets a repo's own settings override `~/.claude/settings.json`,
+//! so a project file without Felay's Stop hook shadows the global one and
+//! sessions in that repo never notify. These commands check and set up the
+//! project file the way `check_claude_config` / `setup_claude_config` do the
+//! global one. Setup only appends the hook group, snapshots the file first
+//! (see `cli_config_backup`), and never creates a project file: without one
+//! the global hook already applies.
+
+use crate::cli_setup_preview::{claude_add_hook, claude_hook_command, line_diff};
+use crate::{cli_config_backup, cli_config_local, cli_group_name, get_home_dir};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+/// How long `shadows_hook_cached` trusts an earlier look at a project.
+const SHADOWS_TTL: Duration = Duration::from_secs(30);
+
+/// `shadows_hook` results by cwd, and when each was taken.
+static SHADOWS: Mutex<BTreeMap<PathBuf, (Instant, bool)>> = Mutex::new(BTreeMap::new());
+
+fn settings_path(project: &Path) -> PathBuf {
+  project.join(".claude").join("settings.json")
+}
+
+fn project_dir(project_path: &str) -> Result<PathBuf, String> {
+  let path = PathBuf::from(project_path);
+  if !path.is_dir() {
+    return Err(format!("项目目录不存在: {}", project_path));
+  }
+  path.canonicalize().map_err(|e| e.to_string())
+}
+
+/// Snapshot name for a project: its directory name plus a short hash of the
+/// full path, so same-named repos don't share snapshots.
+fn backup_name(project: &Path) -> String {
+  let dir_name: String = project
+    .file_name()
+    .map(|n| n.to_string_lossy().to_string())
+    .unwrap_or_default()
+    .chars()
+    .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+    .collect();
+  let digest = Sha256::digest(project.to_string_lossy().as_bytes());
+  let hash: String = digest.iter().take(4).map(|b| format!("{:02x}", b)).collect();
+  format!("claude-project-{}-{}", dir_name, hash)
+}
+
+pub(crate) fn is_claude_session(cli: &str) -> bool {
+  cli_group_name(cli) == "claude"
+}
+
+/// True when `cwd` has project settings without Felay's hook, which
+/// shadow the global hook.
+pub(crate) fn shadows_hook(cwd: &Path) -> bool {
+  let path = settings_path(cwd);
+  path.is_file() && cli_config_local::check_claude_settings(&path)["hookConfigured"] != true
+}
+
+/// `shadows_hook`, reusing a result younger than `SHADOWS_TTL`, so status
+/// polls don't read every session's project settings each time.
+pub(crate) fn shadows_hook_cached(cwd: &Path) -> bool {
+  let fresh = |at: &Instant| at.elapsed() < SHADOWS_TTL;
+  if let Some(&(at, shadows)) = SHADOWS.lock().unwrap_or_else(|e| e.into_inner()).get(cwd) {
+    if fresh(&at) {
+      return shadows;
+    }
+  }
+  let shadows = shadows_hook(cwd);
+  let mut cache = SHADOWS.lock().unwrap_or_else(|e| e.into_inner());
+  cache.retain(|_, (at, _)| fresh(at));
+  cache.insert(cwd.to_path_buf(), (Instant::now(), shadows));
+  shadows
+}
+
+/// Drop cached results, e.g. once a project file has been set up.
+fn forget_shadows() {
+  SHADOWS.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+fn check(project: &Path) -> Value {
+  let mut status = cli_config_local::check_claude_settings(&settings_path(project));
+  status["projectPath"] = Value::String(project.to_string_lossy().to_string());
+  status["shadowsGlobalHook"] = Value::Bool(shadows_hook(project));
+  status
+}
+
+/// `check_claude_config` for `<project_path>/.claude/settings.json`, plus
+/// `shadowsGlobalHook`.
+#[tauri::command]
+pub(crate) fn check_claude_project_config(project_path: String) -> Value {
+  match project_dir(&project_path) {
+    Ok(project) => check(&project),
+    Err(e) => serde_json::json!({ "ok": false, "error": e }),
+  }
+}
+
+/// Add Felay's Stop hook to `<project_path>/.claude/settings.json` if the
+/// file exists and lacks it. Returns `changed`, the `diff` and the
+/// `backup` taken first.
+#[tauri::command]
+pub(crate) fn setup_claude_project_config(app: AppHandle, project_path: String) -> Value {
+  let project = match project_dir(&project_path) {
+    Ok(p) => p,
+    Err(e) => return serde_json::json!({ "ok": false, "error": e }),
+  };
+  let path = settings_path(&project);
+  let status = check(&project);
+  if status["configExists"] != true || status["hookConfigured"] == true {
+    return serde_json::json!({ "ok": true, "changed": false, "status": status });
+  }
+  if status["issues"].as_array().is_some_and(|i| !i.is_empty()) {
+    return serde_json::json!({ "ok": false, "error": status["issues"][0], "status": status });
+  }
+
+  let Some(home) = get_home_dir().map(PathBuf::from) else {
+    return serde_json::json!({ "ok": false, "error": "cannot determine home directory" });
+  };
+  let command = match claude_hook_command(&app, &home) {
+    Ok(c) => c,
+    Err(e) => return serde_json::json!({ "ok": false, "error": e }),
+  };
+  let current = fs::read_to_string(&path).unwrap_or_default();
+  let new_content = match claude_add_hook(&current, &command) {
+    Ok(c) => c,
+    Err(e) => return serde_json::json!({ "ok": false, "error": e }),
+  };
+
+  let backup = match cli_config_backup::snapshot_file(&backup_name(&project), &path) {
+    Ok(backup) => backup,
+    Err(e) => {
+      return serde_json::json!({ "ok": false, "error": format!("配置备份失败: {}", e) })
+    }
+  };
+  let tmp = path.with_extension("felay-tmp");
+  if let Err(e) = fs::write(&tmp, &new_content).and_then(|_| fs::rename(&tmp, &path)) {
+    let _ = fs::remove_file(&tmp);
+    return serde_json::json!({ "ok": false, "error": format!("无法写入 settings.json: {}", e) });
+  }
+  forget_shadows();
+  serde_json::json!({
+    "ok": true,
+    "changed": true,
+    "diff": line_diff(&current, &new_content),
+    "backup": backup,
+    "status": check(&project),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/cli-config").join(name)
+  }
+
+  #[test]
+  fn shadowing_is_cached_per_cwd_until_forgotten() {
+    let project = tempfile::tempdir().unwrap();
+    let other = tempfile::tempdir().unwrap();
+    let settings = settings_path(project.path());
+    fs::create_dir_all(settings.parent().unwrap()).unwrap();
+    fs::copy(fixture("claude-other-hook.json"), &settings).unwrap();
+
+    assert!(shadows_hook_cached(project.path()));
+    assert!(!shadows_hook_cached(other.path()), "no project file shadows nothing");
+
+    // Fixed behind the cache's back: the cached answer stands
+    fs::copy(fixture("claude-felay.json"), &settings).unwrap();
+    assert!(!shadows_hook(project.path()));
+    assert!(shadows_hook_cached(project.path()));
+
+    forget_shadows();
+    assert!(!shadows_hook_cached(project.path()));
+  }
+}
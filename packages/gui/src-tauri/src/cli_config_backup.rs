@@ -0,0 +1,157 @@
+//! Snapshots of CLI config files taken before the daemon's one-click setup.
+//!
+//! `setup_codex_config` / `setup_claude_config` let the daemon rewrite
+//! `~/.codex/config.toml` and `~/.claude/settings.json`. The file as it was
+//! is copied next to the config backups first, as
+//! `~/.felay/backups/<integration>-<file stem>-<timestamp>.<ext>` (e.g.
+//! `codex-config-20250101-120000.toml`), keeping the newest
+//! `SNAPSHOT_LIMIT` per file. `revert_cli_config` puts the newest copy
+//! back; the rollback commands do the same after asking the user. A
+//! restored snapshot is removed, so reverting again steps further back.
+
+use crate::config_backup::format_timestamp;
+use crate::{check_claude_config, check_codex_config, cli_hook_watch, get_home_dir};
+use crate::{integrations, unix_now};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+const SNAPSHOT_LIMIT: usize = 10;
+
+fn backups_dir() -> Option<PathBuf> {
+  Some(PathBuf::from(get_home_dir()?).join(".felay").join("backups"))
+}
+
+/// `<integration>-<file stem>-`, e.g. `codex-config-`.
+fn snapshot_prefix(name: &str, config_path: &Path) -> String {
+  let stem = config_path.file_stem().map(|s| s.to_string_lossy().to_string());
+  format!("{}-{}-", name, stem.unwrap_or_default())
+}
+
+/// Snapshot file names for `prefix`, oldest first.
+fn snapshot_names(prefix: &str) -> Vec<String> {
+  let Some(dir) = backups_dir() else {
+    return vec![];
+  };
+  let mut names: Vec<String> = fs::read_dir(dir)
+    .map(|entries| {
+      entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.file_name().into_string().ok())
+        // A timestamp must follow, so "codex" doesn't match "codex-beta-…"
+        .filter(|n| {
+          n.strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+        })
+        .collect()
+    })
+    .unwrap_or_default();
+  names.sort();
+  names
+}
+
+/// Copy the config file of integration `name` aside and prune old copies.
+/// Returns the snapshot's path, or None if there is no file yet.
+pub(crate) fn snapshot(name: &str) -> Result<Option<String>, String> {
+  snapshot_file(name, &integrations::config_path(name)?)
+}
+
+/// `snapshot` for a file that isn't an integration's, under the name
+/// `name`.
+pub(crate) fn snapshot_file(name: &str, config_path: &Path) -> Result<Option<String>, String> {
+  if !config_path.exists() {
+    return Ok(None);
+  }
+  let dir = backups_dir().ok_or("cannot determine home directory")?;
+  fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+  let prefix = snapshot_prefix(name, config_path);
+  let ext = config_path.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default();
+  let stamp = format_timestamp(unix_now());
+  let mut file = format!("{}{}.{}", prefix, stamp, ext);
+  let mut n = 1;
+  while dir.join(&file).exists() {
+    file = format!("{}{}_{}.{}", prefix, stamp, n, ext);
+    n += 1;
+  }
+  fs::copy(config_path, dir.join(&file)).map_err(|e| e.to_string())?;
+
+  let names = snapshot_names(&prefix);
+  let excess = names.len().saturating_sub(SNAPSHOT_LIMIT);
+  for old in &names[..excess] {
+    let _ = fs::remove_file(dir.join(old));
+  }
+
+  Ok(Some(dir.join(file).to_string_lossy().to_string()))
+}
+
+/// Restore the newest snapshot of integration `name`'s config file.
+#[tauri::command]
+pub(crate) fn revert_cli_config(name: String) -> Value {
+  let config_path = match integrations::config_path(&name) {
+    Ok(p) => p,
+    Err(e) => return serde_json::json!({ "ok": false, "error": e }),
+  };
+  let prefix = snapshot_prefix(&name, &config_path);
+  let (Some(dir), Some(latest)) = (backups_dir(), snapshot_names(&prefix).pop()) else {
+    return serde_json::json!({
+      "ok": false,
+      "error": "没有可恢复的备份",
+      "nothing_to_revert": true,
+    });
+  };
+
+  let backup = dir.join(&latest);
+  let _own_write = cli_hook_watch::own_write(&name);
+  let tmp = config_path.with_extension("felay-revert.tmp");
+  let restored = fs::copy(&backup, &tmp).and_then(|_| fs::rename(&tmp, &config_path));
+  if let Err(e) = restored {
+    let _ = fs::remove_file(&tmp);
+    return serde_json::json!({ "ok": false, "error": format!("恢复失败: {}", e) });
+  }
+  let _ = fs::remove_file(&backup);
+  serde_json::json!({ "ok": true, "restored": latest, "config_path": config_path })
+}
+
+/// Ask before restoring the newest snapshot of `name`, then report the
+/// resulting state from `check` as `status`.
+fn rollback(app: &AppHandle, name: &str, label: &str, check: fn() -> Value) -> Value {
+  let latest = integrations::config_path(name)
+    .ok()
+    .and_then(|path| snapshot_names(&snapshot_prefix(name, &path)).pop());
+  let Some(latest) = latest else {
+    return revert_cli_config(name.to_string());
+  };
+
+  let confirmed = app
+    .dialog()
+    .message(format!(
+      "将用备份 {} 覆盖当前的 {} 配置。\n之后对该文件的修改会丢失。",
+      latest, label
+    ))
+    .title("回滚配置?")
+    .kind(MessageDialogKind::Warning)
+    .buttons(MessageDialogButtons::OkCancel)
+    .blocking_show();
+  if !confirmed {
+    return serde_json::json!({ "ok": false, "error": "cancelled" });
+  }
+
+  let mut result = revert_cli_config(name.to_string());
+  if result["ok"] == true {
+    result["status"] = check();
+  }
+  result
+}
+
+#[tauri::command]
+pub(crate) fn rollback_codex_config(app: AppHandle) -> Value {
+  rollback(&app, "codex", "Codex", check_codex_config)
+}
+
+#[tauri::command]
+pub(crate) fn rollback_claude_config(app: AppHandle) -> Value {
+  rollback(&app, "claude", "Claude Code", check_claude_config)
+}
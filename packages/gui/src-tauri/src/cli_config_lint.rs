@@ -0,0 +1,367 @@
+//! Syntax checks for the Codex and Claude Code config files.
+//!
+//! A hand-edited `config.toml` that no longer parses is ignored by Codex as
+//! a whole, while the `notify` line in it can still look configured. These
+//! parse the files strictly and report the first error with its position
+//! (see `config_text::TextError`), plus a suggested fix for the mistakes
+//! people actually make: Windows paths with backslashes in TOML basic
+//! strings or unquoted, trailing commas in JSON, and duplicate keys.
+//! Duplicate keys in JSON are legal for serde_json and Claude Code (the last
+//! one wins) but usually mean an edit went into the wrong copy, so they are
+//! looked for separately and reported as warnings: the file still works,
+//! and callers that treat errors as "can't tell" must not stop at them.
+//! `read_codex_config` returns the parsed Codex config for display, or the
+//! same error.
+
+use crate::config_text::{text_error, TextError};
+use crate::integrations;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Escapes a TOML basic string may contain after a backslash.
+const TOML_ESCAPES: &str = "btnfr\"\\uUe";
+
+/// The line containing byte offset `at`.
+fn line_at(text: &str, at: usize) -> &str {
+  let start = text[..at].rfind('\n').map_or(0, |i| i + 1);
+  let end = text[at..].find('\n').map_or(text.len(), |i| at + i);
+  text[start..end].trim_end_matches('\r')
+}
+
+/// `line` with each double-quoted string holding an invalid backslash
+/// escape turned into a single-quoted literal string, if that changes it.
+fn literal_strings(line: &str) -> Option<String> {
+  let parts: Vec<&str> = line.split('"').collect();
+  // An odd number of parts means the quotes pair up
+  if parts.len().is_multiple_of(2) {
+    return None;
+  }
+  let invalid_escape = |s: &str| {
+    s.split('\\').skip(1).any(|rest| !rest.starts_with(|c| TOML_ESCAPES.contains(c)))
+  };
+  let mut changed = false;
+  let mut fixed = String::new();
+  for (i, part) in parts.iter().enumerate() {
+    let quote = if i % 2 == 1 && invalid_escape(part) && !part.contains('\'') {
+      changed = true;
+      "'"
+    } else {
+      "\""
+    };
+    if i % 2 == 1 {
+      fixed.push_str(quote);
+      fixed.push_str(part);
+      fixed.push_str(quote);
+    } else {
+      fixed.push_str(part);
+    }
+  }
+  changed.then_some(fixed)
+}
+
+/// `key = C:\path` with the value put in a literal string.
+fn quoted_path(line: &str) -> Option<String> {
+  let (key, value) = line.split_once('=')?;
+  let value = value.trim();
+  let quoted = value.starts_with(['"', '\'', '[', '{']);
+  (!quoted && value.contains('\\') && !value.contains('\''))
+    .then(|| format!("{} = '{}'", key.trim_end(), value))
+}
+
+/// Line number of the first `key = ...` before byte offset `before`.
+fn first_definition(text: &str, key: &str, before: usize) -> Option<usize> {
+  text[..before]
+    .lines()
+    .position(|line| {
+      let rest = line.trim_start().strip_prefix(key);
+      rest.is_some_and(|rest| rest.trim_start().starts_with('='))
+    })
+    .map(|i| i + 1)
+}
+
+/// Check Codex's `config.toml` text.
+pub(crate) fn lint_codex_text(text: &str) -> Result<(), TextError> {
+  let Err(e) = text.parse::<toml::Table>() else {
+    return Ok(());
+  };
+  let at = e.span().map_or(0, |span| span.start).min(text.len());
+  let line = line_at(text, at);
+  let error = TextError::at(text, at, e.message().trim());
+
+  if e.message().contains("duplicate key") {
+    let span = e.span().unwrap_or(at..at);
+    let key = text.get(span).unwrap_or_default().trim();
+    let suggestion = match first_definition(text, key, at) {
+      Some(n) => format!("`{}` 已在第 {} 行定义，删除或合并其中一处", key, n),
+      None => format!("`{}` 重复定义，删除或合并其中一处", key),
+    };
+    return Err(error.with_suggestion(suggestion));
+  }
+  if let Some(fixed) = literal_strings(line) {
+    let suggestion = format!("Windows 路径请用单引号字面量字符串或正斜杠: {}", fixed.trim());
+    return Err(error.with_suggestion(suggestion));
+  }
+  if let Some(fixed) = quoted_path(line) {
+    return Err(error.with_suggestion(format!("路径需要加引号: {}", fixed.trim())));
+  }
+  Err(error)
+}
+
+/// First key repeated within one object, as (offset of the repeat, key).
+/// `text` must already be valid JSON.
+fn duplicate_json_key(text: &str) -> Option<(usize, String)> {
+  // One entry per open object/array; arrays have no key set
+  let mut stack: Vec<Option<HashSet<String>>> = Vec::new();
+  let bytes = text.as_bytes();
+  let mut i = 0;
+  while i < bytes.len() {
+    match bytes[i] {
+      b'{' => stack.push(Some(HashSet::new())),
+      b'[' => stack.push(None),
+      b'}' | b']' => {
+        stack.pop();
+      }
+      b'"' => {
+        let start = i;
+        i += 1;
+        while i < bytes.len() && bytes[i] != b'"' {
+          i += if bytes[i] == b'\\' { 2 } else { 1 };
+        }
+        let is_key = text[i + 1..].trim_start().starts_with(':');
+        if let (true, Some(Some(keys))) = (is_key, stack.last_mut()) {
+          let key: String = serde_json::from_str(&text[start..=i]).unwrap_or_default();
+          if !keys.insert(key.clone()) {
+            return Some((start, key));
+          }
+        }
+      }
+      _ => {}
+    }
+    i += 1;
+  }
+  None
+}
+
+/// Check Claude Code's `settings.json` text for syntax errors; see
+/// `claude_text_warnings` for what parses but looks wrong.
+pub(crate) fn lint_claude_text(text: &str) -> Result<(), TextError> {
+  if let Err(e) = serde_json::from_str::<Value>(text) {
+    if !e.to_string().starts_with("trailing comma") {
+      return Err(text_error(text, &e));
+    }
+    // serde_json points after the comma; report the comma itself
+    let line_start: usize = text.split_inclusive('\n').take(e.line() - 1).map(str::len).sum();
+    let at = (line_start + e.column().saturating_sub(1)).min(text.len());
+    let comma = text[..at].trim_end().strip_suffix(',').map(str::len);
+    return Err(match comma {
+      Some(comma) => TextError::at(text, comma, "多余的逗号")
+        .with_suggestion("JSON 不允许在 } 或 ] 前加逗号，删除这个逗号"),
+      None => text_error(text, &e).with_suggestion("删除 } 或 ] 前多余的逗号"),
+    });
+  }
+  Ok(())
+}
+
+/// Likely mistakes in `settings.json` text that still parses: a key
+/// repeated within one object. Empty for text that doesn't parse.
+pub(crate) fn claude_text_warnings(text: &str) -> Vec<TextError> {
+  if serde_json::from_str::<Value>(text).is_err() {
+    return Vec::new();
+  }
+  let Some((at, key)) = duplicate_json_key(text) else {
+    return Vec::new();
+  };
+  let message = format!("重复的键 \"{}\"", key);
+  let suggestion = "同一对象中后出现的值会覆盖前面的，删除或合并其中一处";
+  vec![TextError::at(text, at, message).with_suggestion(suggestion)]
+}
+
+/// Warnings as returned to the frontend: the error fields without `ok`.
+pub(crate) fn warnings_json(warnings: &[TextError]) -> Value {
+  let list = warnings.iter().map(|w| {
+    let mut json = w.to_json();
+    if let Some(fields) = json.as_object_mut() {
+      fields.remove("ok");
+    }
+    json
+  });
+  Value::Array(list.collect())
+}
+
+/// The integration's config path and text, None if the file doesn't exist.
+/// Errs with the result to return as is.
+fn read_file(name: &str) -> Result<(PathBuf, Option<String>), Value> {
+  let path = integrations::config_path(name)
+    .map_err(|e| serde_json::json!({ "ok": false, "error": e }))?;
+  match fs::read_to_string(&path) {
+    Ok(text) => Ok((path, Some(text))),
+    Err(e) if e.kind() == ErrorKind::NotFound => Ok((path, None)),
+    Err(e) => Err(serde_json::json!({ "ok": false, "error": e.to_string(), "path": path })),
+  }
+}
+
+fn error_result(e: TextError, path: &Path) -> Value {
+  let mut result = e.to_json();
+  result["exists"] = Value::Bool(true);
+  result["path"] = serde_json::json!(path);
+  result
+}
+
+fn lint_file(
+  name: &str,
+  lint: fn(&str) -> Result<(), TextError>,
+  warnings: fn(&str) -> Vec<TextError>,
+) -> Value {
+  let (path, text) = match read_file(name) {
+    Ok(file) => file,
+    Err(result) => return result,
+  };
+  let Some(text) = text else {
+    return serde_json::json!({ "ok": true, "exists": false, "path": path });
+  };
+  match lint(&text) {
+    Ok(()) => serde_json::json!({
+      "ok": true,
+      "exists": true,
+      "path": path,
+      "warnings": warnings_json(&warnings(&text)),
+    }),
+    Err(e) => error_result(e, &path),
+  }
+}
+
+/// TOML as JSON; datetimes become their RFC 3339 text.
+fn toml_to_json(value: toml::Value) -> Value {
+  match value {
+    toml::Value::String(s) => Value::String(s),
+    toml::Value::Integer(i) => Value::from(i),
+    // NaN and infinities have no JSON form and become null
+    toml::Value::Float(f) => Value::from(f),
+    toml::Value::Boolean(b) => Value::Bool(b),
+    toml::Value::Datetime(d) => Value::String(d.to_string()),
+    toml::Value::Array(items) => Value::Array(items.into_iter().map(toml_to_json).collect()),
+    toml::Value::Table(table) => {
+      Value::Object(table.into_iter().map(|(k, v)| (k, toml_to_json(v))).collect())
+    }
+  }
+}
+
+/// Parse `~/.codex/config.toml`: `ok`, or the first error with `line`,
+/// `column`, `snippet` and, for common mistakes, `suggestion`.
+#[tauri::command]
+pub(crate) fn lint_codex_config() -> Value {
+  // TOML itself rejects duplicate keys, so there is nothing to warn about
+  lint_file("codex", lint_codex_text, |_| Vec::new())
+}
+
+/// `~/.codex/config.toml` parsed, as JSON under `config` (null when the
+/// file doesn't exist). A file that doesn't parse gives
+/// `lint_codex_config`'s error instead.
+#[tauri::command]
+pub(crate) fn read_codex_config() -> Value {
+  let (path, text) = match read_file("codex") {
+    Ok(file) => file,
+    Err(result) => return result,
+  };
+  let Some(text) = text else {
+    return serde_json::json!({ "ok": true, "exists": false, "path": path, "config": null });
+  };
+  if let Err(e) = lint_codex_text(&text) {
+    return error_result(e, &path);
+  }
+  let table = text.parse::<toml::Table>().unwrap_or_default();
+  serde_json::json!({
+    "ok": true,
+    "exists": true,
+    "path": path,
+    "config": toml_to_json(toml::Value::Table(table)),
+  })
+}
+
+/// `lint_codex_config` for `~/.claude/settings.json`, plus `warnings` for
+/// duplicate keys, which don't stop the file from loading.
+#[tauri::command]
+pub(crate) fn lint_claude_config() -> Value {
+  lint_file("claude", lint_claude_text, claude_text_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const CLAUDE_OK: &str = r#"{
+  "hooks": {
+    "Stop": [{ "hooks": [{ "type": "command", "command": "node felay-claude-hook.js" }] }]
+  }
+}
+"#;
+
+  #[test]
+  fn valid_claude_settings_have_no_errors_or_warnings() {
+    assert!(lint_claude_text(CLAUDE_OK).is_ok());
+    assert!(claude_text_warnings(CLAUDE_OK).is_empty());
+  }
+
+  #[test]
+  fn duplicate_json_key_is_a_warning_not_an_error() {
+    let text = "{\n  \"model\": \"opus\",\n  \"model\": \"sonnet\"\n}\n";
+    assert!(lint_claude_text(text).is_ok());
+    let warnings = claude_text_warnings(text);
+    assert_eq!(warnings.len(), 1);
+    let json = warnings_json(&warnings);
+    assert_eq!(json[0]["line"], 3);
+    assert!(json[0]["error"].as_str().unwrap().contains("\"model\""));
+    assert!(json[0].get("ok").is_none());
+  }
+
+  #[test]
+  fn same_key_in_different_objects_is_fine() {
+    let text = r#"{ "a": { "cmd": "x" }, "b": { "cmd": "y" }, "c": [{ "k": 1 }, { "k": 2 }] }"#;
+    assert!(claude_text_warnings(text).is_empty());
+  }
+
+  #[test]
+  fn escaped_quotes_in_keys_are_handled() {
+    assert!(claude_text_warnings(r#"{ "a\"b": 1, "a": 2 }"#).is_empty());
+    assert_eq!(claude_text_warnings(r#"{ "a\"b": 1, "a\"b": 2 }"#).len(), 1);
+  }
+
+  #[test]
+  fn trailing_comma_points_at_the_comma() {
+    let text = "{\n  \"a\": 1,\n}\n";
+    let json = lint_claude_text(text).unwrap_err().to_json();
+    assert_eq!(json["line"], 2);
+    assert_eq!(json["column"], 9);
+    assert!(json["suggestion"].is_string());
+    assert!(claude_text_warnings(text).is_empty());
+  }
+
+  #[test]
+  fn codex_windows_path_in_basic_string() {
+    let text = "notify = [\"node\", \"C:\\Users\\me\\felay-notify.js\"]\n";
+    let e = lint_codex_text(text).unwrap_err();
+    let suggestion = e.suggestion().unwrap();
+    assert!(suggestion.contains("'C:\\Users\\me\\felay-notify.js'"), "{}", suggestion);
+  }
+
+  #[test]
+  fn codex_unquoted_path() {
+    let e = lint_codex_text("model_instructions_file = C:\\notes.md\n").unwrap_err();
+    assert_eq!(e.suggestion(), Some("路径需要加引号: model_instructions_file = 'C:\\notes.md'"));
+  }
+
+  #[test]
+  fn codex_duplicate_key_names_the_first_line() {
+    let text = "model = \"o3\"\napproval_policy = \"never\"\nmodel = \"o4\"\n";
+    let e = lint_codex_text(text).unwrap_err();
+    assert!(e.suggestion().unwrap().contains("第 1 行"), "{:?}", e.suggestion());
+  }
+
+  #[test]
+  fn valid_codex_config_passes() {
+    assert!(lint_codex_text("notify = [\"node\", \"C:/felay/felay-notify.js\"]\n").is_ok());
+  }
+}
@@ -0,0 +1,265 @@
+//! Codex / Claude Code / Gemini CLI config checks done by the GUI itself.
+//!
+//! The daemon answers `check_codex_config_request` and
+//! `check_claude_config_request`, but on first run it isn't started yet
+//! while onboarding already wants to show whether the hooks are in place.
+//! These read the same files and return the daemon's payload shape, marked
+//! with `source: "local"`. They recognize the hooks by script name
+//! (`felay-notify`, `felay-claude-hook`, `felay-gemini-hook`), as the daemon does;
+//! `felayScriptPath` is left out since only the daemon knows where its
+//! scripts are installed. Problems reading a file are listed in `issues`;
+//! syntax errors also come back as `syntaxError`, the `cli_config_lint`
+//! result with position and suggested fix. Duplicate JSON keys go to
+//! `warnings` instead, since the file still loads. Where the hook's script is and
+//! whether it is current is added by `hook_paths`.
+
+use crate::cli_config_lint::{
+  claude_text_warnings, lint_claude_text, lint_codex_text, warnings_json,
+};
+use crate::cli_setup_preview::{CLAUDE_SCRIPT, CODEX_SCRIPT};
+use crate::{cli_paths, hook_paths};
+use crate::config_text::TextError;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+const CODEX_HOOK: &str = "felay-notify";
+const CLAUDE_HOOK: &str = "felay-claude-hook";
+const GEMINI_HOOK: &str = "felay-gemini-hook";
+
+fn forward_slash(path: &Path) -> String {
+  path.to_string_lossy().replace('\\', "/")
+}
+
+/// Record a lint error in `status`.
+fn add_syntax_error(status: &mut Value, file: &str, e: &TextError) {
+  let issue = match e.suggestion() {
+    Some(suggestion) => format!("{} 语法错误: {}（{}）", file, e, suggestion),
+    None => format!("{} 语法错误: {}", file, e),
+  };
+  status["issues"] = serde_json::json!([issue]);
+  status["syntaxError"] = e.to_json();
+}
+
+/// The Codex check for the config of the user with home `home`.
+pub(crate) fn check_codex(home: &Path) -> Value {
+  let dir = cli_paths::codex_dir(home);
+  let path = cli_paths::codex_config_path(home);
+  let mut status = serde_json::json!({
+    "codexInstalled": dir.is_dir(),
+    "configExists": path.is_file(),
+    "notifyConfigured": false,
+    "configFilePath": forward_slash(&path),
+    "source": "local",
+    "issues": [],
+  });
+  if status["configExists"] != true {
+    return status;
+  }
+
+  let text = match fs::read_to_string(&path) {
+    Ok(text) => text,
+    Err(e) => {
+      status["issues"] = serde_json::json!([format!("无法读取 config.toml: {}", e)]);
+      return status;
+    }
+  };
+  let table = match text.parse::<toml::Table>() {
+    Ok(table) => table,
+    Err(_) => {
+      if let Err(e) = lint_codex_text(&text) {
+        add_syntax_error(&mut status, "config.toml", &e);
+      }
+      return status;
+    }
+  };
+
+  // `notify` is the argv Codex runs, e.g. ["node", ".../felay-notify.js"]
+  let Some(notify) = table.get("notify") else {
+    return status;
+  };
+  let args: Vec<&str> = match notify {
+    toml::Value::Array(items) => items.iter().filter_map(|v| v.as_str()).collect(),
+    toml::Value::String(s) => vec![s.as_str()],
+    _ => Vec::new(),
+  };
+  status["notifyConfigured"] = Value::Bool(args.iter().any(|a| a.contains(CODEX_HOOK)));
+  if let Some(script) = args.iter().find(|a| a.contains(CODEX_HOOK)) {
+    hook_paths::annotate(&mut status, script, CODEX_SCRIPT);
+  }
+  status["currentNotify"] =
+    Value::String(serde_json::to_string(notify).unwrap_or_else(|_| notify.to_string()));
+  status
+}
+
+/// The Claude Code check for the settings of the user with home `home`.
+pub(crate) fn check_claude(home: &Path) -> Value {
+  let dir = cli_paths::claude_dir(home);
+  let mut status = check_claude_settings(&cli_paths::claude_settings_path(home));
+  status["claudeInstalled"] = Value::Bool(dir.is_dir());
+  status
+}
+
+/// The Claude Code check for one settings file, global or project-local.
+pub(crate) fn check_claude_settings(path: &Path) -> Value {
+  // settings.hooks.Stop[*].hooks[*].command
+  let mut status =
+    check_json_settings(path, CLAUDE_HOOK, |settings| vec![&settings["hooks"]["Stop"]]);
+  let command = status["currentHookCommand"].as_str().filter(|_| status["hookConfigured"] == true);
+  if let Some(script) = command.and_then(|c| hook_paths::script_arg(c, CLAUDE_HOOK)) {
+    hook_paths::annotate(&mut status, &script, CLAUDE_SCRIPT);
+  }
+  status
+}
+
+/// The Gemini CLI check for the settings of the user with home `home`.
+pub(crate) fn check_gemini(home: &Path) -> Value {
+  let dir = cli_paths::gemini_dir(home);
+  let mut status = check_json_settings(&cli_paths::gemini_settings_path(home), GEMINI_HOOK, |s| {
+    // Grouped per event like Claude Code's; Felay's hook may be under any
+    s["hooks"].as_object().into_iter().flat_map(|events| events.values()).collect()
+  });
+  status["geminiInstalled"] = Value::Bool(dir.is_dir());
+  status
+}
+
+/// The check for a JSON settings file; `events` picks out the arrays of
+/// `{ "hooks": [{ "command" }] }` groups to look through.
+fn check_json_settings(path: &Path, hook: &str, events: fn(&Value) -> Vec<&Value>) -> Value {
+  let mut status = serde_json::json!({
+    "configExists": path.is_file(),
+    "hookConfigured": false,
+    "configFilePath": forward_slash(path),
+    "source": "local",
+    "issues": [],
+  });
+  if status["configExists"] != true {
+    return status;
+  }
+
+  let text = match fs::read_to_string(path) {
+    Ok(text) => text,
+    Err(e) => {
+      status["issues"] = serde_json::json!([format!("无法读取 settings.json: {}", e)]);
+      return status;
+    }
+  };
+  if let Err(e) = lint_claude_text(&text) {
+    add_syntax_error(&mut status, "settings.json", &e);
+  }
+  let Ok(settings) = serde_json::from_str::<Value>(&text) else {
+    return status;
+  };
+  let warnings = claude_text_warnings(&text);
+  if !warnings.is_empty() {
+    status["warnings"] = warnings_json(&warnings);
+  }
+
+  let commands: Vec<&str> = events(&settings)
+    .into_iter()
+    .flat_map(|event| event.as_array().into_iter().flatten())
+    .filter_map(|group| group["hooks"].as_array())
+    .flatten()
+    .filter_map(|hook| hook["command"].as_str())
+    .collect();
+  // The felay hook if present, else the first hook for display
+  let current = commands.iter().find(|c| c.contains(hook)).or(commands.first());
+  if let Some(command) = current {
+    status["hookConfigured"] = Value::Bool(command.contains(hook));
+    status["currentHookCommand"] = Value::String(command.to_string());
+  }
+  status
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn settings_file(text: &str) -> (tempfile::TempDir, std::path::PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("settings.json");
+    fs::write(&path, text).unwrap();
+    (dir, path)
+  }
+
+  #[test]
+  fn duplicate_keys_do_not_hide_the_hook() {
+    let text = r#"{
+  "model": "opus",
+  "model": "sonnet",
+  "hooks": { "Stop": [{ "hooks": [{ "command": "node /x/felay-claude-hook.js" }] }] }
+}"#;
+    let (_dir, path) = settings_file(text);
+    let status = check_claude_settings(&path);
+    assert_eq!(status["hookConfigured"], true);
+    assert_eq!(status["issues"], serde_json::json!([]));
+    assert!(status.get("syntaxError").is_none());
+    assert_eq!(status["warnings"][0]["line"], 3);
+  }
+
+  #[test]
+  fn syntax_errors_are_issues() {
+    let (_dir, path) = settings_file("{ \"hooks\": {}, }");
+    let status = check_claude_settings(&path);
+    assert_eq!(status["hookConfigured"], false);
+    assert_eq!(status["issues"].as_array().map(Vec::len), Some(1));
+    assert!(status["syntaxError"].is_object());
+  }
+
+  #[test]
+  fn checks_follow_relocated_dirs() {
+    cli_paths::with_relocated_dirs(|home, codex, claude| {
+      let notify = "notify = [\"node\", \"/opt/felay/felay-notify.js\"]\n";
+      fs::write(codex.join("config.toml"), notify).unwrap();
+      fs::write(home.join(".codex").join("config.toml"), "model = \"o3\"\n").unwrap();
+      let status = check_codex(home);
+      assert_eq!(status["notifyConfigured"], true);
+      assert_eq!(status["configFilePath"], forward_slash(&codex.join("config.toml")));
+
+      let status = check_claude(home);
+      assert_eq!(status["claudeInstalled"], true);
+      assert_eq!(status["configExists"], false);
+      assert_eq!(status["configFilePath"], forward_slash(&claude.join("settings.json")));
+    });
+  }
+
+  /// Cases shared with the daemon's cliConfigParity.test.ts, so both checks
+  /// agree on the fields they have in common.
+  fn parity_cases(cli: &str) -> Vec<Value> {
+    let cases = include_str!("../fixtures/cli-config/cases.json");
+    let cases: Value = serde_json::from_str(cases).unwrap();
+    cases[cli].as_array().unwrap().clone()
+  }
+
+  fn assert_parity(cli: &str, fields: &[&str], target: &Path, status: impl Fn() -> Value) {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/cli-config");
+    for case in parity_cases(cli) {
+      let _ = fs::remove_file(target);
+      if let Some(file) = case["file"].as_str() {
+        fs::copy(fixtures.join(file), target).unwrap();
+      }
+      let status = status();
+      for field in fields {
+        let expected = case.get(*field).unwrap_or(&Value::Null);
+        let actual = status.get(*field).unwrap_or(&Value::Null);
+        assert_eq!(actual, expected, "{} of {}", field, case["file"]);
+      }
+    }
+  }
+
+  #[test]
+  fn codex_check_matches_the_daemon() {
+    cli_paths::with_relocated_dirs(|home, codex, _| {
+      let fields = ["configExists", "notifyConfigured"];
+      assert_parity("codex", &fields, &codex.join("config.toml"), || check_codex(home));
+    });
+  }
+
+  #[test]
+  fn claude_check_matches_the_daemon() {
+    cli_paths::with_relocated_dirs(|home, _, claude| {
+      let fields = ["configExists", "hookConfigured", "currentHookCommand"];
+      assert_parity("claude", &fields, &claude.join("settings.json"), || check_claude(home));
+    });
+  }
+}
@@ -0,0 +1,239 @@
+//! Detection of the AI CLI tools Felay can proxy.
+//!
+//! Onboarding wants to know which of codex / claude / gemini are installed
+//! before offering setup. Each is looked up on PATH and in the usual npm /
+//! user install directories, then run with `--version`. A binary that
+//! doesn't answer within `VERSION_TIMEOUT` is killed. PATH scans are slow on
+//! some Windows setups, so results are cached for `CACHE_TTL`.
+//! `detect_installed_clis` adds what the CLIs' config dirs (see `cli_paths`)
+//! say, for the integrations Felay sets up.
+
+use crate::{cli_paths, get_home_dir};
+use serde::Serialize;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+const VERSION_TIMEOUT: Duration = Duration::from_secs(3);
+const CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Binary name and whether Felay has an integration for it.
+const TOOLS: &[(&str, bool)] = &[("codex", true), ("claude", true), ("gemini", false)];
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct CliTool {
+  name: &'static str,
+  found: bool,
+  path: Option<String>,
+  /// First version-looking token of `--version`; None if it didn't run.
+  version: Option<String>,
+  supported_by_felay: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct InstalledCli {
+  name: &'static str,
+  /// The binary was found, or the CLI's config dir exists (it may run
+  /// through npx, or from a dir we don't search).
+  installed: bool,
+  /// The binary; None when only the config dir was found.
+  path: Option<String>,
+  config_exists: bool,
+}
+
+type PathFn = fn(&Path) -> PathBuf;
+
+/// Config dir and file of each integration.
+const CONFIGS: &[(&str, PathFn, PathFn)] = &[
+  ("codex", cli_paths::codex_dir, cli_paths::codex_config_path),
+  ("claude", cli_paths::claude_dir, cli_paths::claude_settings_path),
+  ("gemini", cli_paths::gemini_dir, cli_paths::gemini_settings_path),
+];
+
+static CACHE: Mutex<Option<(Instant, Vec<CliTool>)>> = Mutex::new(None);
+
+/// PATH entries followed by common install locations not always on PATH
+/// (a GUI started from the desktop may not see the shell's PATH).
+fn search_dirs() -> Vec<PathBuf> {
+  let mut dirs: Vec<PathBuf> = std::env::var_os("PATH")
+    .map(|p| std::env::split_paths(&p).collect())
+    .unwrap_or_default();
+  if let Some(home) = get_home_dir().map(PathBuf::from) {
+    dirs.push(home.join(".local").join("bin"));
+    dirs.push(home.join(".npm-global").join("bin"));
+    dirs.push(home.join(".claude").join("local"));
+  }
+  if cfg!(target_os = "windows") {
+    if let Some(appdata) = std::env::var_os("APPDATA") {
+      dirs.push(PathBuf::from(appdata).join("npm"));
+    }
+  } else {
+    dirs.push(PathBuf::from("/usr/local/bin"));
+    dirs.push(PathBuf::from("/opt/homebrew/bin"));
+  }
+  dirs
+}
+
+/// File names `name` can have on this platform.
+fn candidates(name: &str) -> Vec<String> {
+  if !cfg!(target_os = "windows") {
+    return vec![name.to_string()];
+  }
+  let pathext = std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+  pathext
+    .split(';')
+    .filter(|ext| !ext.is_empty())
+    .map(|ext| format!("{}{}", name, ext.to_lowercase()))
+    .collect()
+}
+
+/// `name` looked up the way detection does: PATH, then the extra dirs.
+pub(crate) fn which(name: &str) -> Option<PathBuf> {
+  find(name, &search_dirs())
+}
+
+fn find(name: &str, dirs: &[PathBuf]) -> Option<PathBuf> {
+  let names = candidates(name);
+  dirs
+    .iter()
+    .flat_map(|dir| names.iter().map(move |n| dir.join(n)))
+    .find(|path| path.is_file())
+}
+
+/// The first token that looks like a version ("1.2.3", "v0.4.0-beta"),
+/// whatever surrounds it: "codex-cli 0.20.0", "1.0.3 (Claude Code)", ...
+fn parse_version(output: &str) -> Option<String> {
+  output
+    .split(|c: char| c.is_whitespace() || matches!(c, '(' | ')' | ',' | '/'))
+    .map(|token| token.trim_start_matches(['v', 'V']))
+    .find(|token| {
+      let mut parts = token.split('.');
+      let numeric =
+        |p: Option<&str>| p.is_some_and(|p| p.starts_with(|c: char| c.is_ascii_digit()));
+      numeric(parts.next()) && numeric(parts.next())
+    })
+    .map(|token| token.trim_end_matches(['.', ':', ';']).to_string())
+}
+
+fn read_all<R: Read + Send + 'static>(pipe: Option<R>) -> JoinHandle<String> {
+  thread::spawn(move || {
+    let mut text = String::new();
+    if let Some(mut pipe) = pipe {
+      let _ = pipe.read_to_string(&mut text);
+    }
+    text
+  })
+}
+
+/// Run `path --version`, killing it after `VERSION_TIMEOUT`.
+fn run_version(path: &Path) -> Option<String> {
+  let mut cmd = Command::new(path);
+  cmd.arg("--version").stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+  #[cfg(target_os = "windows")]
+  {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+    cmd.creation_flags(CREATE_NO_WINDOW);
+  }
+  let mut child = cmd.spawn().ok()?;
+
+  // Read on threads so a chatty binary can't fill the pipe and stall
+  let stdout = read_all(child.stdout.take());
+  let stderr = read_all(child.stderr.take());
+
+  let deadline = Instant::now() + VERSION_TIMEOUT;
+  loop {
+    match child.try_wait() {
+      Ok(Some(_)) => break,
+      Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(50)),
+      _ => {
+        gui_println!("[gui] {} --version timed out, killing it", path.display());
+        let _ = child.kill();
+        let _ = child.wait();
+        return None;
+      }
+    }
+  }
+  let stdout = stdout.join().unwrap_or_default();
+  let stderr = stderr.join().unwrap_or_default();
+  parse_version(&format!("{}\n{}", stdout, stderr))
+}
+
+fn detect() -> Vec<CliTool> {
+  let dirs = search_dirs();
+  TOOLS
+    .iter()
+    .map(|&(name, supported_by_felay)| {
+      let path = find(name, &dirs);
+      CliTool {
+        name,
+        found: path.is_some(),
+        version: path.as_deref().and_then(run_version),
+        path: path.map(|p| p.to_string_lossy().to_string()),
+        supported_by_felay,
+      }
+    })
+    .collect()
+}
+
+/// Installed AI CLIs, from the cache while it is fresh.
+fn tools() -> Vec<CliTool> {
+  if let Some((at, tools)) = CACHE.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+    if at.elapsed() < CACHE_TTL {
+      return tools.clone();
+    }
+  }
+  // Not under the lock: detection can take a few seconds
+  let tools = detect();
+  *CACHE.lock().unwrap_or_else(|e| e.into_inner()) = Some((Instant::now(), tools.clone()));
+  tools
+}
+
+/// `tools` off the async runtime: detection runs each CLI's `--version`,
+/// waiting up to `VERSION_TIMEOUT` per binary.
+async fn tools_blocking() -> Vec<CliTool> {
+  tokio::task::spawn_blocking(tools).await.unwrap_or_default()
+}
+
+#[tauri::command]
+pub(crate) async fn detect_cli_tools() -> Vec<CliTool> {
+  tools_blocking().await
+}
+
+/// Per integration, whether its CLI is installed and configured at all, for
+/// onboarding to point at what is missing.
+#[tauri::command]
+pub(crate) async fn detect_installed_clis() -> Vec<InstalledCli> {
+  let tools = tools_blocking().await;
+  let home = get_home_dir().map(PathBuf::from);
+  CONFIGS
+    .iter()
+    .map(|&(name, dir, config)| {
+      let path = tools.iter().find(|t| t.name == name).and_then(|t| t.path.clone());
+      let dir_exists = home.as_deref().is_some_and(|home| dir(home).is_dir());
+      InstalledCli {
+        name,
+        installed: path.is_some() || dir_exists,
+        path,
+        config_exists: home.as_deref().is_some_and(|home| config(home).is_file()),
+      }
+    })
+    .collect()
+}
+
+/// One line per tool, for the diagnostics bundle.
+pub(crate) fn summary() -> String {
+  tools()
+    .iter()
+    .map(|t| match (&t.path, &t.version) {
+      (Some(path), version) => {
+        format!("{}: {} ({})", t.name, version.as_deref().unwrap_or("version unknown"), path)
+      }
+      (None, _) => format!("{}: not found", t.name),
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
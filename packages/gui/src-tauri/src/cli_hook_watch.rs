@@ -0,0 +1,237 @@
+//! Warn when Felay's hooks disappear from the Codex / Claude Code configs.
+//!
+//! Codex updates sometimes rewrite `config.toml` without the `notify` line,
+//! and nobody notices until notifications stop. `file_watcher` reports
+//! settled changes to the watched files here; the local hook check is re-run
+//! and a hook that was present and is now gone is announced with a
+//! `cli-hooks-lost` event, a warning in `GuiStatus.warnings` until it comes
+//! back, and (unless `cli_hooks_lost_prompt` is off) a native prompt
+//! offering to run the setup again.
+//!
+//! Felay's own setup / rollback / removal commands hold an `OwnWrite` while
+//! they touch a file, and changes seen during it or shortly after only move
+//! the baseline.
+
+use crate::{cli_config_local, cli_paths, gui_settings, setup_claude_config, setup_codex_config};
+use crate::{spawn_background, warnings, BotWarning};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+pub(crate) const CLI_HOOKS_LOST_EVENT: &str = "cli-hooks-lost";
+
+/// How long after an own write changes are still attributed to it; covers
+/// the watcher's settle delay.
+const QUIET_AFTER: Duration = Duration::from_secs(3);
+
+struct Watched {
+  name: &'static str,
+  label: &'static str,
+  path: fn(&Path) -> PathBuf,
+  check: fn(&Path) -> Value,
+  /// Key of `check`'s result that is true while the hook is installed.
+  key: &'static str,
+  setup: fn(Option<bool>, Option<String>) -> Value,
+}
+
+const WATCHED: &[Watched] = &[
+  Watched {
+    name: "codex",
+    label: "Codex",
+    path: cli_paths::codex_config_path,
+    check: cli_config_local::check_codex,
+    key: "notifyConfigured",
+    setup: setup_codex_config,
+  },
+  Watched {
+    name: "claude",
+    label: "Claude Code",
+    path: cli_paths::claude_settings_path,
+    check: cli_config_local::check_claude,
+    key: "hookConfigured",
+    setup: setup_claude_config,
+  },
+];
+
+struct State {
+  /// Hook presence as of the last check.
+  present: BTreeMap<&'static str, bool>,
+  /// Hooks lost and not back yet.
+  lost: BTreeSet<&'static str>,
+  /// Own writes in progress.
+  busy: BTreeMap<&'static str, usize>,
+  quiet_until: BTreeMap<&'static str, Instant>,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+  present: BTreeMap::new(),
+  lost: BTreeSet::new(),
+  busy: BTreeMap::new(),
+  quiet_until: BTreeMap::new(),
+});
+
+fn state() -> std::sync::MutexGuard<'static, State> {
+  STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn watched(name: &str) -> Option<&'static Watched> {
+  WATCHED.iter().find(|w| w.name == name)
+}
+
+/// Whether the hook is installed; None while the file has syntax issues
+/// (e.g. is half-written), which says nothing either way.
+fn hook_present(home: &Path, w: &Watched) -> Option<bool> {
+  let status = (w.check)(home);
+  if status["issues"].as_array().is_some_and(|i| !i.is_empty()) {
+    return None;
+  }
+  Some(status[w.key] == true)
+}
+
+/// Marks a Felay command writing integration `name`'s config.
+pub(crate) struct OwnWrite(Option<&'static str>);
+
+pub(crate) fn own_write(name: &str) -> OwnWrite {
+  let name = watched(name).map(|w| w.name);
+  if let Some(name) = name {
+    *state().busy.entry(name).or_default() += 1;
+  }
+  OwnWrite(name)
+}
+
+impl Drop for OwnWrite {
+  fn drop(&mut self) {
+    let Some(name) = self.0 else {
+      return;
+    };
+    let mut state = state();
+    if let Some(count) = state.busy.get_mut(name) {
+      *count = count.saturating_sub(1);
+    }
+    state.quiet_until.insert(name, Instant::now() + QUIET_AFTER);
+  }
+}
+
+/// The watched files by integration name.
+pub(crate) fn paths(home: &Path) -> Vec<(&'static str, PathBuf)> {
+  WATCHED.iter().map(|w| (w.name, (w.path)(home))).collect()
+}
+
+/// Record the current hook presence without warning.
+pub(crate) fn baseline(home: &Path) {
+  for w in WATCHED {
+    if let Some(present) = hook_present(home, w) {
+      state().present.insert(w.name, present);
+    }
+  }
+}
+
+/// Re-check integration `name` after its config file changed.
+pub(crate) fn changed(app: &AppHandle, home: &Path, name: &str) {
+  let Some(w) = watched(name) else {
+    return;
+  };
+  let Some(present) = hook_present(home, w) else {
+    return;
+  };
+
+  let mut state = state();
+  let was_present = state.present.insert(w.name, present) == Some(true);
+  let own = state.busy.get(w.name).is_some_and(|&n| n > 0)
+    || state.quiet_until.get(w.name).is_some_and(|&t| Instant::now() < t);
+  if present || own {
+    state.lost.remove(w.name);
+    return;
+  }
+  if !was_present {
+    return;
+  }
+  state.lost.insert(w.name);
+  drop(state);
+
+  gui_println!("[gui] Felay hook missing from {} config", w.label);
+  let path = (w.path)(home);
+  let payload = serde_json::json!({ "name": w.name, "label": w.label, "config_path": path });
+  if let Err(e) = app.emit(CLI_HOOKS_LOST_EVENT, payload) {
+    gui_println!("[gui] failed to emit {}: {}", CLI_HOOKS_LOST_EVENT, e);
+  }
+  if gui_settings::with(|s| s.cli_hooks_lost_prompt) {
+    let app = app.clone();
+    spawn_background(move || offer_repair(&app, w));
+  }
+}
+
+fn offer_repair(app: &AppHandle, w: &'static Watched) {
+  let repair = app
+    .dialog()
+    .message(format!(
+      "{} 的配置文件被修改，Felay 的 hook 已丢失，将收不到通知。\n是否重新配置？",
+      w.label
+    ))
+    .title("Felay hook 已丢失")
+    .kind(MessageDialogKind::Warning)
+    .buttons(MessageDialogButtons::OkCancelCustom("修复".into(), "忽略".into()))
+    .blocking_show();
+  if !repair {
+    return;
+  }
+
+  let result = (w.setup)(None, None);
+  if result["ok"] != true {
+    let error = result["error"].as_str().unwrap_or("unknown error").to_string();
+    gui_println!("[gui] repairing {} hook failed: {}", w.label, error);
+    app
+      .dialog()
+      .message(format!("修复失败: {}", error))
+      .title("Felay hook 已丢失")
+      .kind(MessageDialogKind::Error)
+      .blocking_show();
+  }
+}
+
+/// A warning per lost hook, for `GuiStatus.warnings`; dismissed ones are
+/// left out as for daemon warnings.
+pub(crate) fn lost_warnings() -> Vec<BotWarning> {
+  let lost: Vec<&Watched> = state().lost.iter().filter_map(|name| watched(name)).collect();
+  let mut list: Vec<BotWarning> = lost
+    .into_iter()
+    .map(|w| BotWarning {
+      bot_id: format!("cli:{}", w.name),
+      message: format!("{} 配置中的 Felay hook 已丢失，请重新配置", w.label),
+      hash: String::new(),
+      dismissed: false,
+    })
+    .collect();
+  warnings::annotate(&mut list);
+  list.retain(|w| !w.dismissed);
+  list
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::fs;
+
+  #[test]
+  fn watches_relocated_files() {
+    cli_paths::with_relocated_dirs(|home, codex, claude| {
+      let paths = paths(home);
+      assert_eq!(paths[0], ("codex", codex.join("config.toml")));
+      assert_eq!(paths[1], ("claude", claude.join("settings.json")));
+
+      let claude_watch = watched("claude").unwrap();
+      assert_eq!(hook_present(home, claude_watch), Some(false));
+      let hook = serde_json::json!({ "command": "node felay-claude-hook.js" });
+      let settings = serde_json::json!({ "hooks": { "Stop": [{ "hooks": [hook] }] } }).to_string();
+      fs::write(claude.join("settings.json"), settings).unwrap();
+      assert_eq!(hook_present(home, claude_watch), Some(true));
+      // Half-written: can't tell
+      fs::write(claude.join("settings.json"), "{ \"hooks\": ").unwrap();
+      assert_eq!(hook_present(home, claude_watch), None);
+    });
+  }
+}
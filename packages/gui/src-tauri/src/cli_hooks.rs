@@ -0,0 +1,371 @@
+//! Removing Felay's hooks from the Codex / Claude Code configs.
+//!
+//! Setup installs a `notify` line in `~/.codex/config.toml` and a Stop hook
+//! in `~/.claude/settings.json`; once Felay is gone they point at a script
+//! that no longer exists. Removal asks the daemon when it supports
+//! `remove_*_hooks_request`, and otherwise edits the file here the same
+//! way: only entries running Felay's hook scripts are removed, anything
+//! else mentioning felay is reported as `leftover`. Either way the file is
+//! snapshotted first (see `cli_config_backup`).
+
+use crate::cli_setup_preview::is_notify_line;
+use crate::{capabilities, cli_config_backup, cli_hook_watch, cli_paths, get_home_dir};
+use crate::{get_ipc_path, ipc_payload};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const CODEX_HOOK: &str = "felay-notify";
+const CLAUDE_HOOK: &str = "felay-claude-hook";
+
+fn mentions_felay(text: &str) -> bool {
+  text.to_lowercase().contains("felay")
+}
+
+fn write_atomic(path: &Path, content: &str) -> Result<(), String> {
+  let tmp = path.with_extension("felay-tmp");
+  fs::write(&tmp, content).and_then(|_| fs::rename(&tmp, path)).map_err(|e| {
+    let _ = fs::remove_file(&tmp);
+    e.to_string()
+  })
+}
+
+fn removal(removed: Vec<String>, leftover: Vec<String>) -> Value {
+  serde_json::json!({ "ok": true, "removed": removed, "leftover": leftover })
+}
+
+/// Drop the `notify` line running felay-notify.js.
+fn remove_codex_local(home: &Path) -> Value {
+  let path = cli_paths::codex_config_path(home);
+  let Ok(content) = fs::read_to_string(&path) else {
+    return removal(vec![], vec![]);
+  };
+
+  let (removed, kept): (Vec<&str>, Vec<&str>) =
+    content.split('\n').partition(|l| is_notify_line(l) && l.contains(CODEX_HOOK));
+  let leftover =
+    kept.iter().filter(|l| mentions_felay(l)).map(|l| l.trim().to_string()).collect();
+  let removed: Vec<String> = removed.iter().map(|l| l.trim().to_string()).collect();
+  if removed.is_empty() {
+    return removal(removed, leftover);
+  }
+
+  let new_content = kept.join("\n");
+  // Only a line was dropped, but never leave Codex a file it can't read
+  if content.parse::<toml::Table>().is_ok() && new_content.parse::<toml::Table>().is_err() {
+    let error = "removing the notify line would break config.toml";
+    return serde_json::json!({ "ok": false, "error": error });
+  }
+  match write_atomic(&path, &new_content) {
+    Ok(()) => removal(removed, leftover),
+    Err(e) => serde_json::json!({ "ok": false, "error": format!("无法写入 config.toml: {}", e) }),
+  }
+}
+
+/// Drop hooks running felay-claude-hook.js from every event, and the
+/// groups and event lists that leaves empty.
+fn remove_claude_local(home: &Path) -> Value {
+  let path = cli_paths::claude_settings_path(home);
+  let Ok(content) = fs::read_to_string(&path) else {
+    return removal(vec![], vec![]);
+  };
+  let mut settings = match serde_json::from_str::<Value>(&content) {
+    Ok(s) => s,
+    Err(e) => {
+      return serde_json::json!({ "ok": false, "error": format!("无法读取 settings.json: {}", e) })
+    }
+  };
+
+  let mut removed = Vec::new();
+  let mut leftover = Vec::new();
+  if let Some(events) = settings.get_mut("hooks").and_then(|h| h.as_object_mut()) {
+    events.retain(|_, groups| {
+      let Some(groups) = groups.as_array_mut() else {
+        return true;
+      };
+      let before = groups.len();
+      groups.retain_mut(|group| {
+        let Some(hooks) = group.get_mut("hooks").and_then(|h| h.as_array_mut()) else {
+          return true;
+        };
+        let before = hooks.len();
+        hooks.retain(|hook| {
+          let command = hook["command"].as_str().unwrap_or_default();
+          if command.contains(CLAUDE_HOOK) {
+            removed.push(command.to_string());
+            return false;
+          }
+          if mentions_felay(command) {
+            leftover.push(command.to_string());
+          }
+          true
+        });
+        !hooks.is_empty() || hooks.len() == before
+      });
+      !groups.is_empty() || groups.len() == before
+    });
+  }
+  if removed.is_empty() {
+    return removal(removed, leftover);
+  }
+
+  let mut new_content = serde_json::to_string_pretty(&settings).unwrap_or_default();
+  if content.ends_with('\n') {
+    new_content.push('\n');
+  }
+  match write_atomic(&path, &new_content) {
+    Ok(()) => removal(removed, leftover),
+    Err(e) => serde_json::json!({ "ok": false, "error": format!("无法写入 settings.json: {}", e) }),
+  }
+}
+
+fn remove(name: &str, request_type: &str, local: fn(&Path) -> Value) -> Value {
+  let Some(home) = get_home_dir().map(PathBuf::from) else {
+    return serde_json::json!({ "ok": false, "error": "cannot determine home directory" });
+  };
+  let _own_write = cli_hook_watch::own_write(name);
+  let backup = match cli_config_backup::snapshot(name) {
+    Ok(backup) => backup,
+    Err(e) => {
+      return serde_json::json!({ "ok": false, "error": format!("配置备份失败: {}", e) })
+    }
+  };
+
+  let daemon = get_ipc_path().filter(|_| capabilities::supports(request_type) == Some(true));
+  let (mut result, source) = match daemon {
+    Some(ipc_path) => {
+      let req = serde_json::json!({ "type": request_type }).to_string();
+      (ipc_payload(&ipc_path, &req).unwrap_or_else(|e| e.to_json()), "daemon")
+    }
+    None => (local(&home), "local"),
+  };
+  result["source"] = Value::String(source.into());
+  result["backup"] = serde_json::json!(backup);
+  result
+}
+
+/// Remove Felay's `notify` hook from the Codex config. Returns `removed`
+/// and `leftover` lines, and the `backup` taken first.
+#[tauri::command]
+pub(crate) fn remove_codex_hooks() -> Value {
+  remove("codex", "remove_codex_hooks_request", remove_codex_local)
+}
+
+/// Remove Felay's hooks from the Claude Code settings, as
+/// `remove_codex_hooks` does for Codex.
+#[tauri::command]
+pub(crate) fn remove_claude_hooks() -> Value {
+  remove("claude", "remove_claude_hooks_request", remove_claude_local)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn removal_edits_the_relocated_files() {
+    cli_paths::with_relocated_dirs(|home, codex, claude| {
+      let notify = "notify = [\"node\", \"/opt/felay/felay-notify.js\"]\n";
+      fs::write(codex.join("config.toml"), notify).unwrap();
+      fs::write(home.join(".codex").join("config.toml"), notify).unwrap();
+      let hook = serde_json::json!({ "command": "node felay-claude-hook.js" });
+      let settings = serde_json::json!({ "hooks": { "Stop": [{ "hooks": [hook] }] } }).to_string();
+      fs::write(claude.join("settings.json"), settings).unwrap();
+
+      assert_eq!(remove_codex_local(home)["removed"].as_array().map(Vec::len), Some(1));
+      assert_eq!(fs::read_to_string(codex.join("config.toml")).unwrap(), "");
+      // The decoy under ~/.codex is not Codex's config here
+      let decoy = fs::read_to_string(home.join(".codex").join("config.toml")).unwrap();
+      assert_eq!(decoy, notify);
+
+      assert_eq!(remove_claude_local(home)["removed"].as_array().map(Vec::len), Some(1));
+      let settings = fs::read_to_string(claude.join("settings.json")).unwrap();
+      assert!(!settings.contains("felay-claude-hook"));
+    });
+  }
+
+  fn codex_removal(content: &str) -> (Value, String) {
+    let mut result = (Value::Null, String::new());
+    cli_paths::with_relocated_dirs(|home, codex, _| {
+      fs::write(codex.join("config.toml"), content).unwrap();
+      let removal = remove_codex_local(home);
+      result = (removal, fs::read_to_string(codex.join("config.toml")).unwrap());
+    });
+    result
+  }
+
+  fn claude_removal(content: &str) -> (Value, String) {
+    let mut result = (Value::Null, String::new());
+    cli_paths::with_relocated_dirs(|home, _, claude| {
+      fs::write(claude.join("settings.json"), content).unwrap();
+      let removal = remove_claude_local(home);
+      result = (removal, fs::read_to_string(claude.join("settings.json")).unwrap());
+    });
+    result
+  }
+
+  #[test]
+  fn codex_removal_drops_only_the_felay_notify_line() {
+    let content = concat!(
+      "# my settings\r\n",
+      "model = \"o3\"\r\n",
+      "notify = [\"node\", \"C:/Program Files/Felay/felay-notify.js\"]\r\n",
+      "\r\n",
+      "# felay was here\r\n",
+      "[profiles.quiet]\r\n",
+      "notify   =   [\"say\", \"done\"]\r\n",
+    );
+    let (removal, after) = codex_removal(content);
+    assert_eq!(removal["ok"], true);
+    assert_eq!(
+      removal["removed"],
+      serde_json::json!(["notify = [\"node\", \"C:/Program Files/Felay/felay-notify.js\"]"])
+    );
+    assert_eq!(removal["leftover"], serde_json::json!(["# felay was here"]));
+    let expected = concat!(
+      "# my settings\r\n",
+      "model = \"o3\"\r\n",
+      "\r\n",
+      "# felay was here\r\n",
+      "[profiles.quiet]\r\n",
+      "notify   =   [\"say\", \"done\"]\r\n",
+    );
+    assert_eq!(after, expected);
+  }
+
+  #[test]
+  fn codex_config_without_felay_is_untouched() {
+    let content = "model = \"o3\"\nnotify = [\"notify-send\", \"done\"]\n";
+    let (removal, after) = codex_removal(content);
+    assert_eq!(removal["removed"], serde_json::json!([]));
+    assert_eq!(after, content);
+  }
+
+  #[test]
+  fn claude_removal_keeps_other_hooks_and_layout() {
+    let content = r#"{
+  "model": "sonnet",
+  "hooks": {
+    "Stop": [
+      {
+        "matcher": "",
+        "hooks": [
+          {
+            "type": "command",
+            "command": "afplay done.aiff"
+          },
+          {
+            "type": "command",
+            "command": "node /opt/felay/felay-claude-hook.js"
+          }
+        ]
+      },
+      {
+        "matcher": "",
+        "hooks": [
+          {
+            "type": "command",
+            "command": "node /opt/felay/felay-claude-hook.js"
+          }
+        ]
+      }
+    ],
+    "Notification": [
+      {
+        "hooks": [
+          {
+            "type": "command",
+            "command": "node /opt/felay/felay-claude-hook.js"
+          }
+        ]
+      }
+    ],
+    "PreToolUse": [
+      {
+        "matcher": "Bash",
+        "hooks": [
+          {
+            "type": "command",
+            "command": "~/bin/felay-audit.sh"
+          }
+        ]
+      }
+    ],
+    "SubagentStop": []
+  },
+  "permissions": {
+    "allow": [
+      "Bash(git status)"
+    ]
+  }
+}
+"#;
+    let expected = r#"{
+  "model": "sonnet",
+  "hooks": {
+    "Stop": [
+      {
+        "matcher": "",
+        "hooks": [
+          {
+            "type": "command",
+            "command": "afplay done.aiff"
+          }
+        ]
+      }
+    ],
+    "PreToolUse": [
+      {
+        "matcher": "Bash",
+        "hooks": [
+          {
+            "type": "command",
+            "command": "~/bin/felay-audit.sh"
+          }
+        ]
+      }
+    ],
+    "SubagentStop": []
+  },
+  "permissions": {
+    "allow": [
+      "Bash(git status)"
+    ]
+  }
+}
+"#;
+    let (removal, after) = claude_removal(content);
+    assert_eq!(removal["ok"], true);
+    assert_eq!(removal["removed"].as_array().map(Vec::len), Some(3));
+    assert_eq!(removal["leftover"], serde_json::json!(["~/bin/felay-audit.sh"]));
+    assert_eq!(after, expected);
+  }
+
+  #[test]
+  fn claude_settings_without_felay_are_untouched() {
+    // Compact and without a trailing newline; not rewritten at all
+    let content = r#"{"hooks":{"Stop":[{"hooks":[{"command":"say done"}]}]},"model":"opus"}"#;
+    let (removal, after) = claude_removal(content);
+    assert_eq!(removal["removed"], serde_json::json!([]));
+    assert_eq!(after, content);
+  }
+
+  #[test]
+  fn unreadable_claude_settings_are_left_alone() {
+    let content = "{ \"hooks\": { \"Stop\": [ node felay-claude-hook.js ] } }";
+    let (removal, after) = claude_removal(content);
+    assert_eq!(removal["ok"], false);
+    assert_eq!(after, content);
+  }
+
+  #[test]
+  fn missing_files_remove_nothing() {
+    cli_paths::with_relocated_dirs(|home, codex, claude| {
+      for result in [remove_codex_local(home), remove_claude_local(home)] {
+        assert_eq!(result, removal(vec![], vec![]));
+      }
+      assert!(!codex.join("config.toml").exists());
+      assert!(!claude.join("settings.json").exists());
+    });
+  }
+}
@@ -0,0 +1,163 @@
+//! Where Codex and Claude Code keep their config.
+//!
+//! Both CLIs can be relocated: Codex reads `$CODEX_HOME` and Claude Code
+//! `$CLAUDE_CONFIG_DIR`. Without them the dirs are `~/.codex` and
+//! `~/.claude`, except on Linux when only the XDG location
+//! (`$XDG_CONFIG_HOME/codex`, default `~/.config/codex`; likewise `claude`)
+//! exists. The daemon's `codexConfig.ts` / `claudeConfig.ts` resolve the
+//! same way, so the GUI checks the file the daemon sets up. Gemini CLI has
+//! no override and always uses `~/.gemini`.
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+fn resolve(home: &Path, env_var: &str, default_dir: &str, xdg_name: &str) -> PathBuf {
+  if let Some(dir) = env::var_os(env_var).filter(|v| !v.is_empty()) {
+    return PathBuf::from(dir);
+  }
+  let dir = home.join(default_dir);
+  if cfg!(target_os = "linux") && !dir.is_dir() {
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+      .filter(|v| !v.is_empty())
+      .map(PathBuf::from)
+      .unwrap_or_else(|| home.join(".config"));
+    let xdg = config_home.join(xdg_name);
+    if xdg.is_dir() {
+      return xdg;
+    }
+  }
+  dir
+}
+
+pub(crate) fn codex_dir(home: &Path) -> PathBuf {
+  resolve(home, "CODEX_HOME", ".codex", "codex")
+}
+
+pub(crate) fn claude_dir(home: &Path) -> PathBuf {
+  resolve(home, "CLAUDE_CONFIG_DIR", ".claude", "claude")
+}
+
+pub(crate) fn gemini_dir(home: &Path) -> PathBuf {
+  home.join(".gemini")
+}
+
+pub(crate) fn codex_config_path(home: &Path) -> PathBuf {
+  codex_dir(home).join("config.toml")
+}
+
+pub(crate) fn claude_settings_path(home: &Path) -> PathBuf {
+  claude_dir(home).join("settings.json")
+}
+
+pub(crate) fn gemini_settings_path(home: &Path) -> PathBuf {
+  gemini_dir(home).join("settings.json")
+}
+
+/// The resolved config files, for the diagnostics bundle.
+pub(crate) fn summary(home: &Path) -> String {
+  format!(
+    "codex: {}\nclaude: {}\ngemini: {}",
+    codex_config_path(home).display(),
+    claude_settings_path(home).display(),
+    gemini_settings_path(home).display()
+  )
+}
+
+/// Serializes tests that set `TEST_ENV_VARS`.
+#[cfg(test)]
+static TEST_ENV: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[cfg(test)]
+const TEST_ENV_VARS: [&str; 3] = ["CODEX_HOME", "CLAUDE_CONFIG_DIR", "XDG_CONFIG_HOME"];
+
+/// Unsets `TEST_ENV_VARS` when dropped, also when a test panics.
+#[cfg(test)]
+struct ResetEnv;
+
+#[cfg(test)]
+impl Drop for ResetEnv {
+  fn drop(&mut self) {
+    for name in TEST_ENV_VARS {
+      env::remove_var(name);
+    }
+  }
+}
+
+/// Run `f` with `vars` as the only `TEST_ENV_VARS` set, holding `TEST_ENV`.
+#[cfg(test)]
+pub(crate) fn with_env(vars: &[(&str, &Path)], f: impl FnOnce()) {
+  let _guard = TEST_ENV.lock().unwrap_or_else(|e| e.into_inner());
+  for name in TEST_ENV_VARS {
+    env::remove_var(name);
+  }
+  let _reset = ResetEnv;
+  for (name, value) in vars {
+    env::set_var(name, value);
+  }
+  f();
+}
+
+/// Run `f(home, codex_dir, claude_dir)` with a fresh home and the Codex and
+/// Claude Code dirs relocated outside it through their env vars. Both dirs
+/// exist; the home has empty `~/.codex` and `~/.claude` as decoys.
+#[cfg(test)]
+pub(crate) fn with_relocated_dirs(f: impl FnOnce(&Path, &Path, &Path)) {
+  let home = tempfile::tempdir().unwrap();
+  let elsewhere = tempfile::tempdir().unwrap();
+  let codex = elsewhere.path().join("codex-home");
+  let claude = elsewhere.path().join("claude-config");
+  for dir in [&codex, &claude, &home.path().join(".codex"), &home.path().join(".claude")] {
+    std::fs::create_dir_all(dir).unwrap();
+  }
+  let vars = [("CODEX_HOME", codex.as_path()), ("CLAUDE_CONFIG_DIR", claude.as_path())];
+  with_env(&vars, || f(home.path(), &codex, &claude));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::fs;
+
+  #[test]
+  fn env_vars_win() {
+    with_relocated_dirs(|home, codex, claude| {
+      assert_eq!(codex_dir(home), codex);
+      assert_eq!(claude_dir(home), claude);
+      assert_eq!(codex_config_path(home), codex.join("config.toml"));
+      assert_eq!(claude_settings_path(home), claude.join("settings.json"));
+      assert_eq!(gemini_settings_path(home), home.join(".gemini").join("settings.json"));
+      let summary = summary(home);
+      assert!(summary.contains(&codex.join("config.toml").display().to_string()));
+      assert!(summary.contains(&claude.join("settings.json").display().to_string()));
+    });
+  }
+
+  #[test]
+  fn defaults_without_env_vars() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+    with_env(&[("CODEX_HOME", Path::new(""))], || {
+      assert_eq!(codex_dir(home), home.join(".codex"));
+      assert_eq!(claude_dir(home), home.join(".claude"));
+    });
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn xdg_dir_when_only_it_exists() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+    let xdg = home.join("xdg");
+    fs::create_dir_all(xdg.join("codex")).unwrap();
+    fs::create_dir_all(home.join(".config").join("claude")).unwrap();
+    with_env(&[("XDG_CONFIG_HOME", &xdg)], || {
+      assert_eq!(codex_dir(home), xdg.join("codex"));
+      // Not under $XDG_CONFIG_HOME, so the default stays
+      assert_eq!(claude_dir(home), home.join(".claude"));
+    });
+    with_env(&[], || assert_eq!(claude_dir(home), home.join(".config").join("claude")));
+    // The default dir wins once it exists
+    fs::create_dir_all(home.join(".codex")).unwrap();
+    with_env(&[("XDG_CONFIG_HOME", &xdg)], || assert_eq!(codex_dir(home), home.join(".codex")));
+  }
+}
@@ -0,0 +1,303 @@
+//! Previews of the Codex / Claude Code one-click setup.
+//!
+//! The daemon answers a dry-run `setup_*_config_request` with the content
+//! it would write. Daemons from before dry-run support (and a daemon that
+//! isn't running) get the same edit computed here instead, mirroring
+//! `codexConfig.ts` / `claudeConfig.ts`, with the hook scripts assumed next
+//! to the daemon executable as in an installed build.
+//!
+//! Every preview carries `content_hash`, the SHA-256 of the file as it was
+//! previewed. Passing it back to `setup_codex_config` / `setup_claude_config`
+//! makes them refuse to apply once the file has changed since.
+
+use crate::cli_paths::{claude_settings_path, codex_config_path};
+use crate::{capabilities, cli_config_local, find_daemon_exe, get_home_dir, get_ipc_path};
+use crate::{integrations, setup_config_preview};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+pub(crate) const CODEX_SCRIPT: &str = "felay-notify.js";
+pub(crate) const CLAUDE_SCRIPT: &str = "felay-claude-hook.js";
+
+fn forward_slash(path: &Path) -> String {
+  path.to_string_lossy().replace('\\', "/")
+}
+
+/// SHA-256 of the file; a missing file hashes like an empty one.
+fn file_hash(path: &Path) -> String {
+  let bytes = fs::read(path).unwrap_or_default();
+  Sha256::digest(&bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Line diff in the daemon's `lineDiff` format: "  " kept, "- " removed,
+/// "+ " added.
+pub(crate) fn line_diff(before: &str, after: &str) -> String {
+  let split = |s: &str| -> Vec<String> {
+    if s.is_empty() {
+      vec![]
+    } else {
+      s.split('\n').map(String::from).collect()
+    }
+  };
+  let (a, b) = (split(before), split(after));
+
+  // lcs[i][j] = length of the LCS of a[i..] and b[j..]
+  let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+  for i in (0..a.len()).rev() {
+    for j in (0..b.len()).rev() {
+      lcs[i][j] =
+        if a[i] == b[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+    }
+  }
+
+  let mut out = Vec::new();
+  let (mut i, mut j) = (0, 0);
+  while i < a.len() && j < b.len() {
+    if a[i] == b[j] {
+      out.push(format!("  {}", a[i]));
+      i += 1;
+      j += 1;
+    } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+      out.push(format!("- {}", a[i]));
+      i += 1;
+    } else {
+      out.push(format!("+ {}", b[j]));
+      j += 1;
+    }
+  }
+  out.extend(a[i..].iter().map(|l| format!("- {}", l)));
+  out.extend(b[j..].iter().map(|l| format!("+ {}", l)));
+  out.join("\n")
+}
+
+/// A top-level `notify = ...` line, as the daemon's `/^notify\s*=\s*.+$/m`.
+pub(crate) fn is_notify_line(line: &str) -> bool {
+  line
+    .strip_prefix("notify")
+    .map(str::trim_start)
+    .and_then(|rest| rest.strip_prefix('='))
+    .is_some_and(|rest| !rest.is_empty())
+}
+
+/// Codex: point `notify` at the script, replacing an existing line or
+/// inserting before the first table.
+fn codex_edit(content: &str, script: &str) -> String {
+  let notify_line = format!(r#"notify = ["node", "{}"]"#, script);
+  let mut lines: Vec<&str> = content.split('\n').collect();
+
+  if let Some(pos) = lines.iter().position(|l| is_notify_line(l)) {
+    lines[pos] = &notify_line;
+    return lines.join("\n");
+  }
+  match lines.iter().position(|l| l.trim_start().starts_with('[')) {
+    Some(table) => {
+      lines.splice(table..table, [notify_line.as_str(), ""]);
+      lines.join("\n")
+    }
+    None => {
+      let sep = if content.trim().is_empty() { "" } else { "\n" };
+      format!("{}{}{}\n", content.trim_end(), sep, notify_line)
+    }
+  }
+}
+
+/// Claude Code: append a Stop hook group running the script.
+fn claude_edit(content: &str, script: &str) -> Result<String, String> {
+  claude_add_hook(content, &format!("node {}", script))
+}
+
+/// Append a Stop hook group running `command` to the settings in `content`,
+/// keeping everything else.
+pub(crate) fn claude_add_hook(content: &str, command: &str) -> Result<String, String> {
+  let mut settings: Value = if content.is_empty() {
+    serde_json::json!({})
+  } else {
+    serde_json::from_str(content).map_err(|e| format!("无法读取 settings.json: {}", e))?
+  };
+  if !settings.is_object() {
+    return Err("settings.json is not an object".to_string());
+  }
+  if !settings["hooks"].is_object() {
+    settings["hooks"] = serde_json::json!({});
+  }
+  if !settings["hooks"]["Stop"].is_array() {
+    settings["hooks"]["Stop"] = serde_json::json!([]);
+  }
+  let group = serde_json::json!({
+    "matcher": "",
+    "hooks": [{ "type": "command", "command": command, "timeout": 30 }],
+  });
+  if let Some(stop) = settings["hooks"]["Stop"].as_array_mut() {
+    stop.push(group);
+  }
+  serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())
+}
+
+/// The command of the Claude Code hook: the one in the global settings if
+/// it is set up there, else the script next to the daemon executable.
+pub(crate) fn claude_hook_command(app: &AppHandle, home: &Path) -> Result<String, String> {
+  let global = cli_config_local::check_claude(home);
+  if global["hookConfigured"] == true {
+    if let Some(command) = global["currentHookCommand"].as_str() {
+      return Ok(command.to_string());
+    }
+  }
+  let script = find_daemon_exe(app)?.with_file_name(CLAUDE_SCRIPT);
+  Ok(format!("node {}", forward_slash(&script)))
+}
+
+/// The edit the daemon would make, computed here.
+fn local_preview(app: &AppHandle, name: &str, home: &Path) -> Value {
+  let (path, script) = match name {
+    "codex" => (codex_config_path(home), CODEX_SCRIPT),
+    _ => (claude_settings_path(home), CLAUDE_SCRIPT),
+  };
+  if !path.parent().is_some_and(|d| d.is_dir()) {
+    let dir = path.parent().unwrap_or(&path).display();
+    let error = match name {
+      "codex" => format!("Codex 未安装（{} 目录不存在）", dir),
+      _ => format!("Claude Code 未安装（{} 目录不存在）", dir),
+    };
+    return serde_json::json!({ "ok": false, "error": error });
+  }
+  let script = match find_daemon_exe(app) {
+    Ok(exe) => exe.with_file_name(script),
+    Err(e) => return serde_json::json!({ "ok": false, "error": e }),
+  };
+  let current = fs::read_to_string(&path).unwrap_or_default();
+
+  // Same test as the daemon's check: nothing to do if the hook is there
+  let (configured, edit) = match name {
+    "codex" => (
+      cli_config_local::check_codex(home)["notifyConfigured"] == true,
+      Ok(codex_edit(&current, &forward_slash(&script))),
+    ),
+    _ => (
+      cli_config_local::check_claude(home)["hookConfigured"] == true,
+      claude_edit(&current, &forward_slash(&script)),
+    ),
+  };
+  let edit = match edit {
+    _ if configured => None,
+    Ok(edit) => Some(edit),
+    Err(e) => return serde_json::json!({ "ok": false, "error": e }),
+  };
+
+  let Some(new_content) = edit else {
+    return serde_json::json!({ "ok": true, "dryRun": true, "changed": false, "changes": [] });
+  };
+  serde_json::json!({
+    "ok": true,
+    "dryRun": true,
+    "changed": new_content != current,
+    "diff": line_diff(&current, &new_content),
+    "currentContent": current,
+    "newContent": new_content,
+    "changes": key_changes(name, &current),
+  })
+}
+
+/// The keys the setup adds or modifies, judged from the current file.
+fn key_changes(name: &str, current: &str) -> Value {
+  let change = match name {
+    "codex" => {
+      let action = if current.lines().any(is_notify_line) { "modified" } else { "added" };
+      serde_json::json!({ "key": "notify", "action": action })
+    }
+    _ => {
+      let settings: Value = serde_json::from_str(current).unwrap_or_default();
+      let action = if settings["hooks"]["Stop"].is_array() { "modified" } else { "added" };
+      serde_json::json!({ "key": "hooks.Stop", "action": action })
+    }
+  };
+  serde_json::json!([change])
+}
+
+fn preview(app: &AppHandle, name: &str, request_type: &str) -> Value {
+  let Some(home) = get_home_dir().map(PathBuf::from) else {
+    return serde_json::json!({ "ok": false, "error": "cannot determine home directory" });
+  };
+  let path = match name {
+    "codex" => codex_config_path(&home),
+    _ => claude_settings_path(&home),
+  };
+  // Hash first: a change after this point must fail the apply
+  let content_hash = file_hash(&path);
+
+  let dry_run_capable = capabilities::has_feature(capabilities::SETUP_DRY_RUN);
+  let mut result = match get_ipc_path().filter(|_| dry_run_capable) {
+    Some(ipc_path) => {
+      let mut payload = setup_config_preview(&ipc_path, request_type);
+      if payload["changed"] == true {
+        let current = payload["currentContent"].as_str().unwrap_or_default().to_string();
+        payload["changes"] = key_changes(name, &current);
+      } else if payload["ok"] == true {
+        payload["changes"] = serde_json::json!([]);
+      }
+      payload["source"] = Value::String("daemon".into());
+      payload
+    }
+    None => {
+      let mut payload = local_preview(app, name, &home);
+      payload["source"] = Value::String("local".into());
+      payload
+    }
+  };
+  if result["ok"] == true {
+    result["content_hash"] = Value::String(content_hash);
+  }
+  result
+}
+
+/// What `setup_codex_config` would change: unified `diff`, `changes` as
+/// `{ key, action }`, and the `content_hash` to pass back when applying.
+#[tauri::command]
+pub(crate) fn preview_codex_setup(app: AppHandle) -> Value {
+  preview(&app, "codex", "setup_codex_config_request")
+}
+
+/// Like `preview_codex_setup`, for Claude Code's Stop hook.
+#[tauri::command]
+pub(crate) fn preview_claude_setup(app: AppHandle) -> Value {
+  preview(&app, "claude", "setup_claude_config_request")
+}
+
+/// Err with a conflict response if `name`'s config no longer hashes to
+/// `expected` (the `content_hash` of a preview).
+pub(crate) fn ensure_unchanged(name: &str, expected: Option<&str>) -> Result<(), Value> {
+  let Some(expected) = expected else {
+    return Ok(());
+  };
+  let path = integrations::config_path(name)
+    .map_err(|e| serde_json::json!({ "ok": false, "error": e }))?;
+  if file_hash(&path) == expected {
+    return Ok(());
+  }
+  Err(serde_json::json!({
+    "ok": false,
+    "error": "配置文件在预览后已被修改，请重新预览",
+    "conflict": true,
+  }))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::cli_paths;
+
+  #[test]
+  fn preview_hash_is_of_the_relocated_file() {
+    cli_paths::with_relocated_dirs(|_, codex, _| {
+      let path = codex.join("config.toml");
+      fs::write(&path, "model = \"o3\"\n").unwrap();
+      let hash = file_hash(&path);
+      assert!(ensure_unchanged("codex", Some(&hash)).is_ok());
+      fs::write(&path, "model = \"o4\"\n").unwrap();
+      let conflict = ensure_unchanged("codex", Some(&hash)).unwrap_err();
+      assert_eq!(conflict["conflict"], true);
+    });
+  }
+}
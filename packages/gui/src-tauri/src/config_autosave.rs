@@ -0,0 +1,168 @@
+//! Debounced `save_config` for autosaving forms.
+//!
+//! Every `save_config` is a full IPC write plus a backup on disk, so a form
+//! saving on each keystroke would flood both. `save_config_debounced` only
+//! records the latest config; a single worker thread writes it once no new
+//! value has arrived for `DEBOUNCE_WINDOW`, then emits `CONFIG_SAVED_EVENT`
+//! with the `save_config` result.
+
+use crate::{config_schema, save_config, shutdown_requested, spawn_background};
+use serde_json::Value;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+const DEBOUNCE_WINDOW: Duration = Duration::from_millis(800);
+/// Longest single sleep, so shutdown is noticed promptly.
+const MAX_WAIT: Duration = Duration::from_millis(200);
+
+pub(crate) const CONFIG_SAVED_EVENT: &str = "config-saved";
+
+struct Autosave {
+  /// Latest config not yet written (last write wins).
+  pending: Option<Value>,
+  /// When `pending` may be written, pushed back on every new value.
+  due: Instant,
+  /// Whether a worker thread is currently waiting to write.
+  worker: bool,
+}
+
+/// What the worker does next.
+#[derive(Debug, PartialEq)]
+enum Step {
+  Wait(Duration),
+  Write(Value),
+  /// Nothing pending; the worker exits.
+  Exit,
+}
+
+impl Autosave {
+  fn new(now: Instant) -> Self {
+    Autosave { pending: None, due: now, worker: false }
+  }
+
+  /// Record `config` as the latest, due `DEBOUNCE_WINDOW` after `now`.
+  /// True when no worker is running and one must be started.
+  fn queue(&mut self, config: Value, now: Instant) -> bool {
+    self.pending = Some(config);
+    self.due = now + DEBOUNCE_WINDOW;
+    !std::mem::replace(&mut self.worker, true)
+  }
+
+  fn next(&mut self, now: Instant, shutting_down: bool) -> Step {
+    // On shutdown, write immediately rather than dropping the last edit
+    if self.pending.is_some() && now < self.due && !shutting_down {
+      return Step::Wait((self.due - now).min(MAX_WAIT));
+    }
+    match self.pending.take() {
+      Some(config) => Step::Write(config),
+      None => {
+        // Cleared under the lock, so a new value always finds a worker
+        self.worker = false;
+        Step::Exit
+      }
+    }
+  }
+}
+
+static AUTOSAVE: Mutex<Option<Autosave>> = Mutex::new(None);
+
+fn run_worker(app: AppHandle) {
+  loop {
+    let step = {
+      let mut guard = AUTOSAVE.lock().unwrap_or_else(|e| e.into_inner());
+      let Some(state) = guard.as_mut() else {
+        return;
+      };
+      state.next(Instant::now(), shutdown_requested())
+    };
+    let config = match step {
+      Step::Wait(wait) => {
+        thread::sleep(wait);
+        continue;
+      }
+      Step::Write(config) => config,
+      Step::Exit => return,
+    };
+
+    let result = save_config(Some(config), None);
+    if let Err(e) = app.emit(CONFIG_SAVED_EVENT, result) {
+      gui_println!("[gui] failed to emit {}: {}", CONFIG_SAVED_EVENT, e);
+    }
+  }
+}
+
+/// Queue a config write. Invalid configs are rejected immediately; valid
+/// ones return `{ ok: true, queued: true }` and the outcome arrives later as
+/// a `config-saved` event.
+#[tauri::command]
+pub(crate) fn save_config_debounced(app: AppHandle, config: Value) -> Value {
+  let validation = config_schema::validate(&config);
+  if !validation.is_valid() {
+    return serde_json::to_value(validation).unwrap_or_default();
+  }
+
+  let mut guard = AUTOSAVE.lock().unwrap_or_else(|e| e.into_inner());
+  let now = Instant::now();
+  let state = guard.get_or_insert_with(|| Autosave::new(now));
+  if state.queue(config, now) {
+    spawn_background(move || run_worker(app));
+  }
+
+  serde_json::json!({ "ok": true, "queued": true })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  const MS: Duration = Duration::from_millis(1);
+
+  #[test]
+  fn burst_of_edits_is_written_once_with_the_last_value() {
+    let t0 = Instant::now();
+    let mut state = Autosave::new(t0);
+    assert!(state.queue(json!({ "v": 1 }), t0), "the first value starts a worker");
+    assert!(!state.queue(json!({ "v": 2 }), t0 + 300 * MS));
+    assert!(!state.queue(json!({ "v": 3 }), t0 + 600 * MS));
+
+    // Each value pushes the write back a full window
+    assert_eq!(state.next(t0 + 900 * MS, false), Step::Wait(MAX_WAIT));
+    assert_eq!(state.next(t0 + 1300 * MS, false), Step::Wait(100 * MS));
+    assert_eq!(state.next(t0 + 1400 * MS, false), Step::Write(json!({ "v": 3 })));
+    assert_eq!(state.next(t0 + 1400 * MS, false), Step::Exit);
+  }
+
+  #[test]
+  fn value_arriving_during_a_write_gets_its_own_write() {
+    let t0 = Instant::now();
+    let mut state = Autosave::new(t0);
+    state.queue(json!(1), t0);
+    assert_eq!(state.next(t0 + DEBOUNCE_WINDOW, false), Step::Write(json!(1)));
+    // Queued while the worker is writing: it is still running, so no new one
+    assert!(!state.queue(json!(2), t0 + 900 * MS));
+    assert!(matches!(state.next(t0 + 900 * MS, false), Step::Wait(_)));
+    assert_eq!(state.next(t0 + 1700 * MS, false), Step::Write(json!(2)));
+  }
+
+  #[test]
+  fn exited_worker_is_restarted_by_the_next_value() {
+    let t0 = Instant::now();
+    let mut state = Autosave::new(t0);
+    state.queue(json!(1), t0);
+    state.next(t0 + DEBOUNCE_WINDOW, false);
+    assert_eq!(state.next(t0 + DEBOUNCE_WINDOW, false), Step::Exit);
+    assert!(state.queue(json!(2), t0 + 2000 * MS));
+  }
+
+  #[test]
+  fn shutdown_writes_at_once() {
+    let t0 = Instant::now();
+    let mut state = Autosave::new(t0);
+    state.queue(json!({ "v": 1 }), t0);
+    assert_eq!(state.next(t0 + MS, true), Step::Write(json!({ "v": 1 })));
+    assert_eq!(state.next(t0 + MS, true), Step::Exit);
+  }
+}
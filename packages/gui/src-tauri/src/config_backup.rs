@@ -0,0 +1,262 @@
+//! Timestamped backups of the daemon's `config.json`.
+//!
+//! `save_config` replaces the whole daemon config, so the on-disk file is
+//! copied to `backups/` in the active profile's data dir first (for the
+//! default profile, `~/.felay/backups/`). Each profile only sees, prunes
+//! and restores its own backups. Backups keep the daemon's encrypted
+//! secrets as-is; restoring goes through `save_config_request`, and the
+//! daemon decrypts them on load.
+
+use crate::{get_ipc_path, gui_settings, profiles, save_config_at, unix_now};
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ConfigBackup {
+  name: String,
+  size: u64,
+  modified: u64,
+}
+
+/// The active profile's backups.
+fn backups_dir() -> Option<PathBuf> {
+  Some(profiles::felay_data_dir()?.join("backups"))
+}
+
+fn backup_limit() -> usize {
+  gui_settings::with(|s| s.config_backup_limit())
+}
+
+/// `YYYYMMDD-HHMMSS` in UTC for a unix timestamp.
+pub(crate) fn format_timestamp(secs: u64) -> String {
+  let days = (secs / 86_400) as i64;
+  let rem = secs % 86_400;
+
+  // Civil-from-days (Howard Hinnant's algorithm)
+  let z = days + 719_468;
+  let era = z.div_euclid(146_097);
+  let doe = z.rem_euclid(146_097);
+  let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let day = doy - (153 * mp + 2) / 5 + 1;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 };
+  let year = yoe + era * 400 + i64::from(month <= 2);
+
+  format!(
+    "{:04}{:02}{:02}-{:02}{:02}{:02}",
+    year,
+    month,
+    day,
+    rem / 3600,
+    rem % 3600 / 60,
+    rem % 60
+  )
+}
+
+/// Backup file names only: no directories, no traversal.
+fn is_backup_name(name: &str) -> bool {
+  name.starts_with("config-")
+    && name.ends_with(".json")
+    && !name.contains(['/', '\\'])
+    && !name.contains("..")
+}
+
+/// Backups in `dir`, sorted oldest first (names embed the timestamp).
+fn backup_names(dir: &Path) -> Vec<String> {
+  let mut names: Vec<String> = fs::read_dir(dir)
+    .map(|entries| {
+      entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.file_name().into_string().ok())
+        .filter(|n| is_backup_name(n))
+        .collect()
+    })
+    .unwrap_or_default();
+  names.sort();
+  names
+}
+
+/// Copy the current config.json into the backups directory and prune old
+/// backups. Returns the backup file name, or None if there was no config.
+pub(crate) fn backup_current_config() -> Result<Option<String>, String> {
+  let config_path = profiles::config_path().ok_or("cannot determine home directory")?;
+  let dir = backups_dir().ok_or("cannot determine home directory")?;
+  backup_into(&config_path, &dir, backup_limit())
+}
+
+/// Copy `config_path` into `dir`, keeping at most `limit` backups there.
+fn backup_into(config_path: &Path, dir: &Path, limit: usize) -> Result<Option<String>, String> {
+  if !config_path.exists() {
+    return Ok(None);
+  }
+  fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+
+  let stamp = format_timestamp(unix_now());
+  let mut name = format!("config-{}.json", stamp);
+  let mut n = 1;
+  while dir.join(&name).exists() {
+    // "_" sorts after ".", keeping same-second backups in creation order
+    name = format!("config-{}_{}.json", stamp, n);
+    n += 1;
+  }
+  fs::copy(config_path, dir.join(&name)).map_err(|e| e.to_string())?;
+
+  let names = backup_names(dir);
+  let excess = names.len().saturating_sub(limit);
+  for old in &names[..excess] {
+    let _ = fs::remove_file(dir.join(old));
+  }
+
+  Ok(Some(name))
+}
+
+#[tauri::command]
+pub(crate) fn list_config_backups() -> Vec<ConfigBackup> {
+  let Some(dir) = backups_dir() else {
+    return vec![];
+  };
+  backup_names(&dir)
+    .into_iter()
+    .rev()
+    .filter_map(|name| {
+      let meta = fs::metadata(dir.join(&name)).ok()?;
+      let modified = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+      Some(ConfigBackup {
+        name,
+        size: meta.len(),
+        modified,
+      })
+    })
+    .collect()
+}
+
+/// Parse the active profile's backup `name`.
+pub(crate) fn read_backup(name: &str) -> Result<Value, String> {
+  let dir = backups_dir().ok_or("cannot determine home directory")?;
+  read_backup_in(&dir, name)
+}
+
+fn read_backup_in(dir: &Path, name: &str) -> Result<Value, String> {
+  if !is_backup_name(name) {
+    return Err("invalid backup name".to_string());
+  }
+  fs::read_to_string(dir.join(name))
+    .map_err(|e| e.to_string())
+    .and_then(|text| serde_json::from_str::<Value>(&text).map_err(|e| e.to_string()))
+    .map_err(|e| format!("无法读取备份: {}", e))
+}
+
+/// Restore a backup by sending it through `save_config`, so the daemon
+/// reloads it (and the current config is itself backed up first).
+#[tauri::command]
+pub(crate) fn restore_config_backup(name: String) -> Value {
+  let Some(dir) = backups_dir() else {
+    return serde_json::json!({ "ok": false, "error": "cannot determine home directory" });
+  };
+  restore_backup_at(get_ipc_path().as_deref(), &dir, &name, backup_current_config)
+}
+
+/// `restore_config_backup` of `name` in `dir`, with `crate::save_config_at`'s
+/// `ipc_path` and `backup`.
+fn restore_backup_at(
+  ipc_path: Option<&str>,
+  dir: &Path,
+  name: &str,
+  backup: impl FnOnce() -> Result<Option<String>, String>,
+) -> Value {
+  let config = match read_backup_in(dir, name) {
+    Ok(c) => c,
+    Err(e) => return serde_json::json!({ "ok": false, "error": e }),
+  };
+
+  let mut result = save_config_at(ipc_path, config, backup);
+  result["restored"] = Value::String(name.to_string());
+  result
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// A profile data dir holding a `config.json` with `marker`.
+  fn profile(marker: &str) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("config.json"), format!(r#"{{"marker":"{}"}}"#, marker)).unwrap();
+    dir
+  }
+
+  #[test]
+  fn backups_and_pruning_stay_within_a_profile() {
+    let work = profile("work");
+    let home = profile("home");
+    let work_backups = work.path().join("backups");
+    let home_backups = home.path().join("backups");
+
+    let kept = backup_into(&home.path().join("config.json"), &home_backups, 2).unwrap();
+    for _ in 0..3 {
+      backup_into(&work.path().join("config.json"), &work_backups, 2).unwrap();
+    }
+
+    assert_eq!(backup_names(&work_backups).len(), 2);
+    assert_eq!(backup_names(&home_backups), vec![kept.unwrap()]);
+  }
+
+  #[test]
+  fn a_backup_is_only_read_from_its_own_profile() {
+    let work = profile("work");
+    let home = profile("home");
+    let name = backup_into(&work.path().join("config.json"), &work.path().join("backups"), 5)
+      .unwrap()
+      .unwrap();
+
+    let restored = read_backup_in(&work.path().join("backups"), &name).unwrap();
+    assert_eq!(restored["marker"], "work");
+    assert!(read_backup_in(&home.path().join("backups"), &name).is_err());
+  }
+
+  #[test]
+  fn missing_config_makes_no_backup() {
+    let dir = tempfile::tempdir().unwrap();
+    let backups = dir.path().join("backups");
+    assert_eq!(backup_into(&dir.path().join("config.json"), &backups, 5), Ok(None));
+    assert!(!backups.exists());
+  }
+
+  #[test]
+  fn backup_names_reject_paths() {
+    assert!(is_backup_name("config-20260101-000000.json"));
+    assert!(!is_backup_name("../config-20260101-000000.json"));
+    assert!(!is_backup_name("codex-20260101-000000.toml"));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn restoring_a_backup_invalidates_the_cache() {
+    let work = profile("work");
+    let config: Value = serde_json::from_str(crate::DEFAULT_CONFIG).unwrap();
+    fs::write(work.path().join("config.json"), config.to_string()).unwrap();
+    let backups = work.path().join("backups");
+    let name = backup_into(&work.path().join("config.json"), &backups, 5).unwrap().unwrap();
+
+    let daemon = crate::tests::MockDaemon::start(|_| crate::tests::ok_reply(true));
+    let mut result = Value::Null;
+    let cached = crate::config_cache::cached_after(&config, || {
+      result = restore_backup_at(Some(&daemon.path), &backups, &name, || Ok(None));
+    });
+    assert_eq!(result["ok"], true);
+    assert_eq!(result["restored"], name.as_str());
+    assert!(!cached);
+    let sent = daemon.requests.lock().unwrap();
+    assert_eq!(sent[0]["type"], "save_config_request");
+    assert_eq!(sent[0]["payload"]["bots"], config["bots"]);
+  }
+}
+
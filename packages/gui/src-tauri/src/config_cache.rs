@@ -0,0 +1,138 @@
+//! Short-lived cache of the daemon config.
+//!
+//! The settings page asks for the whole config on every tab switch. The last
+//! answer is kept with its SHA-256 for `config_cache_ttl_secs`, and a caller
+//! that passes the hash it already has gets `{ not_modified: true }` instead
+//! of the config. Every command that changes the config calls `invalidate`,
+//! as does the file watcher when `config.json` or `daemon.json` changes.
+
+use crate::{gui_settings, load_config};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::sync::Mutex;
+use std::time::Instant;
+
+struct Entry {
+  config: Value,
+  hash: String,
+  fetched_at: Instant,
+}
+
+static CACHE: Mutex<Option<Entry>> = Mutex::new(None);
+
+fn hash_of(config: &Value) -> String {
+  let bytes = serde_json::to_vec(config).unwrap_or_default();
+  Sha256::digest(&bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Drop the cached config; the next read asks the daemon.
+pub(crate) fn invalidate() {
+  *CACHE.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Serializes tests that seed the cache.
+#[cfg(test)]
+static SEEDED: Mutex<()> = Mutex::new(());
+
+/// Run `f` with `config` cached as if just read from the daemon, and tell
+/// whether it is still cached afterwards.
+#[cfg(test)]
+pub(crate) fn cached_after(config: &Value, f: impl FnOnce()) -> bool {
+  let _seeded = SEEDED.lock().unwrap_or_else(|e| e.into_inner());
+  *CACHE.lock().unwrap_or_else(|e| e.into_inner()) = Some(Entry {
+    config: config.clone(),
+    hash: hash_of(config),
+    fetched_at: Instant::now(),
+  });
+  f();
+  let cached = CACHE.lock().unwrap_or_else(|e| e.into_inner()).is_some();
+  cached
+}
+
+/// The config and its hash, from the cache while it is fresh. None when the
+/// daemon is not running.
+fn cached() -> Option<(Value, String)> {
+  let (enabled, ttl) = gui_settings::with(|s| (s.config_cache_enabled, s.config_cache_ttl()));
+  let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+  if let Some(entry) = cache.as_ref().filter(|e| enabled && e.fetched_at.elapsed() < ttl) {
+    return Some((entry.config.clone(), entry.hash.clone()));
+  }
+
+  let config = load_config();
+  if config.is_null() {
+    *cache = None;
+    return None;
+  }
+  let hash = hash_of(&config);
+  *cache = enabled.then(|| Entry {
+    config: config.clone(),
+    hash: hash.clone(),
+    fetched_at: Instant::now(),
+  });
+  Some((config, hash))
+}
+
+/// The daemon config in the current layout, or null if it is not running.
+pub(crate) fn config() -> Value {
+  cached().map(|(config, _)| config).unwrap_or(Value::Null)
+}
+
+/// The daemon config, tagged with `config_hash`. With `known_hash` equal to
+/// the current hash, only `{ not_modified: true, config_hash }` is returned.
+#[tauri::command]
+pub(crate) fn get_config(known_hash: Option<String>) -> Value {
+  match cached() {
+    Some((config, hash)) => respond(config, hash, known_hash.as_deref()),
+    None => Value::Null,
+  }
+}
+
+fn respond(mut config: Value, hash: String, known_hash: Option<&str>) -> Value {
+  if known_hash == Some(hash.as_str()) {
+    return serde_json::json!({ "not_modified": true, "config_hash": hash });
+  }
+  config["config_hash"] = Value::String(hash);
+  config
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn hash_is_the_sha256_of_the_compact_json() {
+    let config = json!({ "bots": { "push": [] }, "schemaVersion": 2 });
+    assert_eq!(
+      hash_of(&config),
+      "a147200e22c777177cf1ac68f813094f38d346ab3525d95b45357889a79c069e"
+    );
+  }
+
+  #[test]
+  fn hash_follows_content() {
+    let config = json!({ "push": { "mergeWindow": 2000 }, "bots": { "push": [{ "id": "p1" }] } });
+    assert_eq!(hash_of(&config), hash_of(&config.clone()));
+    let mut changed = config.clone();
+    changed["push"]["mergeWindow"] = json!(2001);
+    assert_ne!(hash_of(&config), hash_of(&changed));
+    // Same value, different type
+    changed["push"]["mergeWindow"] = json!("2000");
+    assert_ne!(hash_of(&config), hash_of(&changed));
+  }
+
+  #[test]
+  fn known_hash_gets_not_modified() {
+    let config = json!({ "schemaVersion": 2 });
+    let hash = hash_of(&config);
+    let response = respond(config.clone(), hash.clone(), Some(&hash));
+    assert_eq!(response, json!({ "not_modified": true, "config_hash": hash }));
+
+    for known in [None, Some("stale"), Some("")] {
+      let response = respond(config.clone(), hash.clone(), known);
+      assert_eq!(response["schemaVersion"], 2);
+      assert_eq!(response["config_hash"], hash.as_str());
+      assert!(response.get("not_modified").is_none());
+    }
+  }
+}
@@ -0,0 +1,196 @@
+//! Structural diff between the daemon's current config and a proposed one.
+//!
+//! Lets the raw JSON editor show a "review changes" step before
+//! `save_config`. Paths use the same `$.a.b[0]` form as `config_schema`, and
+//! values under sensitive keys are masked so secrets never reach the view.
+
+use crate::{config_cache, is_sensitive_key};
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum DiffKind {
+  Added,
+  Removed,
+  Changed,
+  /// An array changed length; `old`/`new` hold the two lengths.
+  Length,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct DiffEntry {
+  path: String,
+  kind: DiffKind,
+  old: Option<Value>,
+  new: Option<Value>,
+}
+
+/// Replace a secret with a fixed marker, keeping empty values visible so
+/// "secret cleared" still reads as a change.
+fn mask(value: &Value) -> Value {
+  match value {
+    Value::String(s) if s.is_empty() => value.clone(),
+    Value::Null => Value::Null,
+    _ => Value::String("***".to_string()),
+  }
+}
+
+fn push(
+  out: &mut Vec<DiffEntry>,
+  path: String,
+  kind: DiffKind,
+  old: Option<&Value>,
+  new: Option<&Value>,
+  sensitive: bool,
+) {
+  let show = |v: &Value| if sensitive { mask(v) } else { v.clone() };
+  out.push(DiffEntry {
+    path,
+    kind,
+    old: old.map(show),
+    new: new.map(show),
+  });
+}
+
+fn diff_value(old: &Value, new: &Value, path: &str, sensitive: bool, out: &mut Vec<DiffEntry>) {
+  match (old, new) {
+    (Value::Object(a), Value::Object(b)) => {
+      let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+      keys.sort();
+      keys.dedup();
+      for key in keys {
+        let child = format!("{}.{}", path, key);
+        let secret = sensitive || is_sensitive_key(key);
+        match (a.get(key), b.get(key)) {
+          (Some(x), Some(y)) => diff_value(x, y, &child, secret, out),
+          (Some(x), None) => push(out, child, DiffKind::Removed, Some(x), None, secret),
+          (None, Some(y)) => push(out, child, DiffKind::Added, None, Some(y), secret),
+          (None, None) => {}
+        }
+      }
+    }
+    (Value::Array(a), Value::Array(b)) => {
+      if a.len() != b.len() {
+        out.push(DiffEntry {
+          path: path.to_string(),
+          kind: DiffKind::Length,
+          old: Some(a.len().into()),
+          new: Some(b.len().into()),
+        });
+      }
+      for i in 0..a.len().max(b.len()) {
+        let child = format!("{}[{}]", path, i);
+        match (a.get(i), b.get(i)) {
+          (Some(x), Some(y)) => diff_value(x, y, &child, sensitive, out),
+          (Some(x), None) => push(out, child, DiffKind::Removed, Some(x), None, sensitive),
+          (None, Some(y)) => push(out, child, DiffKind::Added, None, Some(y), sensitive),
+          (None, None) => {}
+        }
+      }
+    }
+    // Scalars, and any type change (object -> array, string -> number, ...)
+    _ if old != new => {
+      push(out, path.to_string(), DiffKind::Changed, Some(old), Some(new), sensitive)
+    }
+    _ => {}
+  }
+}
+
+/// Diff two configs. Paths are rooted at `$`.
+pub(crate) fn diff(old: &Value, new: &Value) -> Vec<DiffEntry> {
+  let mut out = Vec::new();
+  diff_value(old, new, "$", false, &mut out);
+  out
+}
+
+/// Diff the daemon's current config against `proposed`.
+#[tauri::command]
+pub(crate) fn diff_config(proposed: Value) -> Result<Vec<DiffEntry>, String> {
+  let current = config_cache::config();
+  if current.is_null() {
+    return Err("无法读取当前配置（daemon 未运行？）".to_string());
+  }
+  Ok(diff(&current, &proposed))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn entries(old: &Value, new: &Value) -> Vec<(String, DiffKind, Option<Value>, Option<Value>)> {
+    diff(old, new).into_iter().map(|e| (e.path, e.kind, e.old, e.new)).collect()
+  }
+
+  #[test]
+  fn identical_configs_have_no_diff() {
+    let config = json!({ "push": { "mergeWindow": 2000 }, "bots": { "push": [{ "id": "p1" }] } });
+    assert!(diff(&config, &config).is_empty());
+  }
+
+  #[test]
+  fn scalar_changes_additions_and_removals() {
+    let old = json!({ "push": { "mergeWindow": 2000, "maxMessageBytes": 30000 } });
+    let new = json!({ "push": { "mergeWindow": 500 }, "input": { "enterRetryCount": 2 } });
+    assert_eq!(
+      entries(&old, &new),
+      [
+        ("$.input".to_string(), DiffKind::Added, None, Some(json!({ "enterRetryCount": 2 }))),
+        ("$.push.maxMessageBytes".to_string(), DiffKind::Removed, Some(json!(30000)), None),
+        ("$.push.mergeWindow".to_string(), DiffKind::Changed, Some(json!(2000)), Some(json!(500))),
+      ]
+    );
+  }
+
+  #[test]
+  fn arrays_report_length_and_elements() {
+    let old = json!({ "bots": { "push": [{ "id": "p1", "name": "a" }] } });
+    let new = json!({ "bots": { "push": [{ "id": "p1", "name": "b" }, { "id": "p2" }] } });
+    assert_eq!(
+      entries(&old, &new),
+      [
+        ("$.bots.push".to_string(), DiffKind::Length, Some(json!(1)), Some(json!(2))),
+        ("$.bots.push[0].name".to_string(), DiffKind::Changed, Some(json!("a")), Some(json!("b"))),
+        ("$.bots.push[1]".to_string(), DiffKind::Added, None, Some(json!({ "id": "p2" }))),
+      ]
+    );
+  }
+
+  #[test]
+  fn type_change_is_one_change() {
+    let old = json!({ "defaults": { "pushBotId": "p1" } });
+    let new = json!({ "defaults": ["p1"] });
+    let (before, after) = (old["defaults"].clone(), new["defaults"].clone());
+    assert_eq!(
+      entries(&old, &new),
+      [("$.defaults".to_string(), DiffKind::Changed, Some(before), Some(after))]
+    );
+  }
+
+  #[test]
+  fn secrets_are_masked_at_any_depth() {
+    let old = json!({ "bots": { "interactive": [{ "id": "i1", "appSecret": "old-secret" }] } });
+    let new = json!({ "bots": { "interactive": [{ "id": "i1", "appSecret": "new-secret" }] } });
+    let (path, kind, before, after) = entries(&old, &new).remove(0);
+    assert_eq!((path.as_str(), kind), ("$.bots.interactive[0].appSecret", DiffKind::Changed));
+    assert_eq!((before, after), (Some(json!("***")), Some(json!("***"))));
+
+    // Everything under a sensitive key is masked, not just strings
+    let old = json!({ "secret": { "parts": ["a", "b"] } });
+    let new = json!({ "secret": { "parts": ["a", "c"] } });
+    assert_eq!(entries(&old, &new)[0].3, Some(json!("***")));
+    let text = serde_json::to_string(&diff(&old, &new)).unwrap();
+    assert!(!text.contains("\"c\""));
+  }
+
+  #[test]
+  fn clearing_a_secret_stays_visible() {
+    let old = json!({ "secret": "SEC123" });
+    let new = json!({ "secret": "" });
+    assert_eq!(entries(&old, &new)[0].2, Some(json!("***")));
+    assert_eq!(entries(&old, &new)[0].3, Some(json!("")));
+    let removed = entries(&json!({ "secret": null }), &json!({}));
+    assert_eq!(removed[0].2, Some(Value::Null));
+  }
+}
@@ -0,0 +1,290 @@
+//! Import a config file exported from another machine.
+//!
+//! `replace` sends the file as the whole config; `merge` deep-merges it into
+//! the daemon's current config, matching bots by `id`. Either way the result
+//! is schema-checked and goes through `save_config`, so the current config is
+//! backed up first. Sanitized exports are refused: their masked values
+//! (`***`, `ab…yz`) would overwrite the real secrets.
+//!
+//! `import_config_file` only reads, validates and diffs a picked file, for
+//! a UI that shows the changes before calling `save_config` itself.
+
+use crate::{config_cache, config_diff, config_schema, is_masked_secret, is_sensitive_key};
+use crate::config_backup::backup_current_config;
+use crate::{get_ipc_path, save_config_at};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use tauri_plugin_dialog::DialogExt;
+
+const BOT_TYPES: [&str; 2] = ["interactive", "push"];
+
+#[derive(Debug, Serialize, Default)]
+struct Counts {
+  added: usize,
+  changed: usize,
+  skipped: usize,
+}
+
+#[derive(Debug, Serialize, Default)]
+struct ImportSummary {
+  bots: Counts,
+  /// Bots dropped because `replace` mode did not include them.
+  bots_removed: usize,
+  settings: Counts,
+}
+
+/// Paths of secret fields still holding a masked placeholder.
+fn masked_paths(value: &Value, path: &str, out: &mut Vec<String>) {
+  match value {
+    Value::Object(map) => {
+      for (k, v) in map {
+        let child = format!("{}.{}", path, k);
+        match v.as_str() {
+          Some(s) if is_sensitive_key(k) && is_masked_secret(s) => out.push(child),
+          _ => masked_paths(v, &child, out),
+        }
+      }
+    }
+    Value::Array(items) => {
+      for (i, v) in items.iter().enumerate() {
+        masked_paths(v, &format!("{}[{}]", path, i), out);
+      }
+    }
+    _ => {}
+  }
+}
+
+fn bots_of<'a>(config: &'a Value, bot_type: &str) -> &'a [Value] {
+  config["bots"][bot_type].as_array().map(Vec::as_slice).unwrap_or(&[])
+}
+
+fn find_by_id<'a>(bots: &'a [Value], id: &Value) -> Option<&'a Value> {
+  bots.iter().find(|b| b.get("id") == Some(id))
+}
+
+/// Recursively merge objects; anything else in `incoming` replaces `base`.
+fn deep_merge(base: &mut Value, incoming: &Value) {
+  match (base, incoming) {
+    (Value::Object(b), Value::Object(i)) => {
+      for (k, v) in i {
+        match b.get_mut(k) {
+          Some(existing) => deep_merge(existing, v),
+          None => {
+            b.insert(k.clone(), v.clone());
+          }
+        }
+      }
+    }
+    (base, incoming) => *base = incoming.clone(),
+  }
+}
+
+/// Merge `imported` into `current`. Bots are matched by id: imported bots
+/// replace same-id bots and new ones are appended.
+fn merge_configs(current: &Value, imported: &Value) -> Value {
+  let mut merged = current.clone();
+  let mut settings = imported.clone();
+  if let Some(map) = settings.as_object_mut() {
+    map.remove("bots");
+  }
+  deep_merge(&mut merged, &settings);
+
+  for bot_type in BOT_TYPES {
+    let mut bots = bots_of(current, bot_type).to_vec();
+    for bot in bots_of(imported, bot_type) {
+      match bots.iter_mut().find(|b| b.get("id") == bot.get("id")) {
+        Some(existing) => *existing = bot.clone(),
+        None => bots.push(bot.clone()),
+      }
+    }
+    merged["bots"][bot_type] = Value::Array(bots);
+  }
+  merged
+}
+
+/// Count each leaf setting of `imported` as added, changed, or unchanged.
+fn tally_settings(imported: &Value, current: Option<&Value>, counts: &mut Counts) {
+  match (imported, current) {
+    (Value::Object(map), current) => {
+      for (k, v) in map {
+        tally_settings(v, current.and_then(|c| c.get(k)), counts);
+      }
+    }
+    (_, None) => counts.added += 1,
+    (v, Some(c)) if v == c => counts.skipped += 1,
+    _ => counts.changed += 1,
+  }
+}
+
+fn summarize(current: &Value, imported: &Value, replace: bool) -> ImportSummary {
+  let mut summary = ImportSummary::default();
+  for bot_type in BOT_TYPES {
+    let existing = bots_of(current, bot_type);
+    let incoming = bots_of(imported, bot_type);
+    for bot in incoming {
+      match find_by_id(existing, &bot["id"]) {
+        None => summary.bots.added += 1,
+        Some(old) if old == bot => summary.bots.skipped += 1,
+        Some(_) => summary.bots.changed += 1,
+      }
+    }
+    if replace {
+      summary.bots_removed += existing
+        .iter()
+        .filter(|b| find_by_id(incoming, &b["id"]).is_none())
+        .count();
+    }
+  }
+
+  let mut settings = imported.as_object().cloned().unwrap_or_else(Map::new);
+  settings.remove("bots");
+  tally_settings(&Value::Object(settings), Some(current), &mut summary.settings);
+  summary
+}
+
+fn pick_json_file(app: &AppHandle) -> Option<PathBuf> {
+  app
+    .dialog()
+    .file()
+    .add_filter("JSON", &["json"])
+    .blocking_pick_file()
+    .and_then(|p| p.into_path().ok())
+}
+
+/// Read and parse a config file, refusing sanitized exports. Err is the
+/// response to return.
+fn read_import(path: &Path) -> Result<Value, Value> {
+  let imported = fs::read_to_string(path)
+    .map_err(|e| e.to_string())
+    .and_then(|text| serde_json::from_str::<Value>(&text).map_err(|e| e.to_string()))
+    .map_err(|e| serde_json::json!({ "ok": false, "error": format!("无法读取配置文件: {}", e) }))?;
+
+  let mut masked = Vec::new();
+  masked_paths(&imported, "$", &mut masked);
+  if !masked.is_empty() {
+    return Err(serde_json::json!({
+      "ok": false,
+      "error": "该文件是脱敏导出，密钥已被掩码替换，无法导入",
+      "masked_fields": masked,
+    }));
+  }
+  Ok(imported)
+}
+
+/// Import a config file. With no `path`, a file picker is shown.
+#[tauri::command]
+pub(crate) fn import_config(app: AppHandle, path: Option<String>, mode: String) -> Value {
+  let replace = match mode.as_str() {
+    "replace" => true,
+    "merge" => false,
+    _ => return serde_json::json!({ "ok": false, "error": "mode must be replace or merge" }),
+  };
+
+  let path = match path.map(PathBuf::from).or_else(|| pick_json_file(&app)) {
+    Some(p) => p,
+    None => return serde_json::json!({ "ok": false, "error": "User cancelled" }),
+  };
+  let imported = match read_import(&path) {
+    Ok(v) => v,
+    Err(e) => return e,
+  };
+  let current = config_cache::config();
+  import_at(get_ipc_path().as_deref(), &current, imported, replace, backup_current_config)
+}
+
+/// `import_config` of `imported` over `current`, saved through
+/// `save_config_at` with its `ipc_path` and `backup`.
+fn import_at(
+  ipc_path: Option<&str>,
+  current: &Value,
+  imported: Value,
+  replace: bool,
+  backup: impl FnOnce() -> Result<Option<String>, String>,
+) -> Value {
+  if current.is_null() {
+    return serde_json::json!({ "ok": false, "error": "daemon not running" });
+  }
+
+  let proposed = if replace {
+    imported.clone()
+  } else {
+    merge_configs(current, &imported)
+  };
+  let validation = config_schema::validate(&proposed);
+  if !validation.is_valid() {
+    return serde_json::to_value(validation).unwrap_or_default();
+  }
+
+  let summary = summarize(current, &imported, replace);
+  let mut result = save_config_at(ipc_path, proposed, backup);
+  result["mode"] = Value::from(if replace { "replace" } else { "merge" });
+  result["summary"] = serde_json::to_value(summary).unwrap_or_default();
+  result
+}
+
+/// Pick a config file and check it without saving anything: returns the
+/// parsed `config` and its `diff` against the current config, for the UI
+/// to confirm before passing `config` to `save_config`. Files that don't
+/// pass the config schema are refused with its `validation`.
+#[tauri::command]
+pub(crate) fn import_config_file(app: AppHandle) -> Value {
+  let Some(path) = pick_json_file(&app) else {
+    return serde_json::json!({ "ok": false, "error": "User cancelled" });
+  };
+  let imported = match read_import(&path) {
+    Ok(v) => v,
+    Err(e) => return e,
+  };
+
+  let validation = config_schema::validate(&imported);
+  if !validation.is_valid() {
+    return serde_json::json!({
+      "ok": false,
+      "error": "该文件不是有效的 Felay 配置",
+      "validation": validation,
+    });
+  }
+
+  let current = config_cache::config();
+  if current.is_null() {
+    return serde_json::json!({ "ok": false, "error": "无法读取当前配置（daemon 未运行？）" });
+  }
+  serde_json::json!({
+    "ok": true,
+    "path": path.to_string_lossy(),
+    "config": imported,
+    "diff": config_diff::diff(&current, &imported),
+    "validation": validation,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[cfg(unix)]
+  #[test]
+  fn importing_invalidates_the_cache() {
+    use crate::tests::{ok_reply, MockDaemon};
+
+    let current: Value = serde_json::from_str(crate::DEFAULT_CONFIG).unwrap();
+    let mut imported = current.clone();
+    imported["bots"]["push"] = serde_json::json!([{ "id": "p1", "name": "构建通知", "webhook": "" }]);
+
+    for replace in [true, false] {
+      let daemon = MockDaemon::start(|_| ok_reply(true));
+      let mut result = Value::Null;
+      let cached = config_cache::cached_after(&current, || {
+        result = import_at(Some(&daemon.path), &current, imported.clone(), replace, || Ok(None));
+      });
+      assert_eq!(result["ok"], true, "{}", result);
+      assert!(!cached, "cache kept after an import with replace={}", replace);
+      let sent = daemon.requests.lock().unwrap();
+      assert_eq!(sent[0]["type"], "save_config_request");
+      assert_eq!(sent[0]["payload"]["bots"]["push"][0]["id"], "p1");
+    }
+  }
+}
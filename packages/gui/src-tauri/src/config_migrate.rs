@@ -0,0 +1,356 @@
+//! Upgrades of the daemon config layout.
+//!
+//! Version history (`schemaVersion` in `config.json`):
+//! - 0: `bots` is one flat list, push bots told apart by their `webhook`
+//! - 1: `bots` split into `interactive` / `push`; no version field yet
+//! - 2: `defaults` and `input` sections always present, version recorded
+//!
+//! Files without `schemaVersion` are classified by shape. Each entry in
+//! `MIGRATIONS` upgrades one version and describes what it changed.
+//! `get_config` runs the chain in memory so the editor always sees the
+//! current shape; `migrate_config` (daemon running) and
+//! `migrate_config_file` (daemon stopped) persist it after a backup.
+
+use crate::{config_backup, config_cache, config_schema, daemon_reachable, fetch_config};
+use crate::{file_watcher, get_ipc_path, profiles, save_config};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+
+/// Keep in sync with `CONFIG_SCHEMA_VERSION` in `@felay/shared`.
+pub(crate) const CURRENT_SCHEMA_VERSION: u64 = 2;
+
+/// The version `config` was written as.
+fn detect_version(config: &Value) -> u64 {
+  if let Some(version) = config["schemaVersion"].as_u64() {
+    return version;
+  }
+  if config["bots"].is_array() {
+    0
+  } else {
+    1
+  }
+}
+
+/// 0 → 1: split the flat bot list by type.
+fn v0_to_v1(config: &mut Value) -> Vec<String> {
+  let bots = match config["bots"].take() {
+    Value::Array(bots) => bots,
+    _ => Vec::new(),
+  };
+  let (push, interactive): (Vec<Value>, Vec<Value>) =
+    bots.into_iter().partition(|bot| bot.get("webhook").is_some());
+  let changes = vec![format!(
+    "split bots into {} interactive and {} push",
+    interactive.len(),
+    push.len()
+  )];
+  config["bots"] = serde_json::json!({ "interactive": interactive, "push": push });
+  changes
+}
+
+/// Fill `config[section]` with `defaults` for every missing key.
+fn fill_section(config: &mut Value, section: &str, defaults: Value, changes: &mut Vec<String>) {
+  if !config[section].is_object() {
+    config[section] = serde_json::json!({});
+    changes.push(format!("added {}", section));
+  }
+  let Some(defaults) = defaults.as_object() else {
+    return;
+  };
+  for (key, value) in defaults {
+    if config[section].get(key).is_none() {
+      config[section][key.as_str()] = value.clone();
+      changes.push(format!("added {}.{} = {}", section, key, value));
+    }
+  }
+}
+
+/// 1 → 2: configs written before `defaults`/`input` existed get them with
+/// their default values.
+fn v1_to_v2(config: &mut Value) -> Vec<String> {
+  let mut changes = Vec::new();
+  fill_section(config, "defaults", serde_json::json!({}), &mut changes);
+  fill_section(
+    config,
+    "input",
+    serde_json::json!({ "enterRetryCount": 2, "enterRetryInterval": 500 }),
+    &mut changes,
+  );
+  changes
+}
+
+/// `MIGRATIONS[n]` upgrades version `n` to `n + 1`.
+const MIGRATIONS: &[fn(&mut Value) -> Vec<String>] = &[v0_to_v1, v1_to_v2];
+
+pub(crate) struct Migration {
+  pub(crate) config: Value,
+  pub(crate) from: u64,
+  /// "v<n>: <what changed>" per change, in order.
+  pub(crate) changes: Vec<String>,
+}
+
+impl Migration {
+  pub(crate) fn migrated(&self) -> bool {
+    self.from < CURRENT_SCHEMA_VERSION
+  }
+}
+
+/// Bring `config` to the current version. Configs from a newer version are
+/// returned untouched.
+pub(crate) fn migrate(mut config: Value) -> Migration {
+  let from = detect_version(&config);
+  let mut changes = Vec::new();
+  if from < CURRENT_SCHEMA_VERSION {
+    for (version, step) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+      changes.extend(step(&mut config).into_iter().map(|c| format!("v{}: {}", version + 1, c)));
+    }
+    config["schemaVersion"] = Value::from(CURRENT_SCHEMA_VERSION);
+  }
+  Migration {
+    config,
+    from,
+    changes,
+  }
+}
+
+fn too_new(from: u64) -> Value {
+  serde_json::json!({
+    "ok": false,
+    "error": format!("配置版本 {} 比当前程序支持的版本更新，请升级 Felay", from),
+    "from_version": from,
+  })
+}
+
+fn already_current(from: u64) -> Value {
+  serde_json::json!({
+    "ok": true,
+    "already_current": true,
+    "from_version": from,
+    "to_version": from,
+  })
+}
+
+fn report(mut result: Value, migration: Migration) -> Value {
+  result["already_current"] = Value::Bool(false);
+  result["from_version"] = Value::from(migration.from);
+  result["to_version"] = Value::from(CURRENT_SCHEMA_VERSION);
+  result["changes"] = serde_json::json!(migration.changes);
+  result
+}
+
+/// Migrate the running daemon's config and save it.
+#[tauri::command]
+pub(crate) fn migrate_config() -> Value {
+  let config = fetch_config();
+  if config.is_null() {
+    return serde_json::json!({ "ok": false, "error": "daemon not running" });
+  }
+
+  let migration = migrate(config);
+  if migration.from > CURRENT_SCHEMA_VERSION {
+    return too_new(migration.from);
+  }
+  if !migration.migrated() {
+    return already_current(migration.from);
+  }
+
+  let validation = config_schema::validate(&migration.config);
+  if !validation.is_valid() {
+    return serde_json::to_value(validation).unwrap_or_default();
+  }
+  let result = save_config(Some(migration.config.clone()), None);
+  report(result, migration)
+}
+
+fn config_path() -> Option<PathBuf> {
+  profiles::config_path()
+}
+
+/// Migrate `~/.felay/config.json` on disk while the daemon is stopped (a
+/// running daemon would overwrite the file from memory). The original is
+/// backed up first.
+#[tauri::command]
+pub(crate) fn migrate_config_file() -> Value {
+  if get_ipc_path().is_some_and(|p| daemon_reachable(&p)) {
+    return serde_json::json!({
+      "ok": false,
+      "error": "daemon is running; use migrate_config instead",
+    });
+  }
+  let Some(path) = config_path() else {
+    return serde_json::json!({ "ok": false, "error": "cannot determine home directory" });
+  };
+
+  let config = match fs::read_to_string(&path)
+    .map_err(|e| e.to_string())
+    .and_then(|text| serde_json::from_str::<Value>(&text).map_err(|e| e.to_string()))
+  {
+    Ok(c) => c,
+    Err(e) => {
+      return serde_json::json!({ "ok": false, "error": format!("无法读取配置文件: {}", e) })
+    }
+  };
+
+  let migration = migrate(config);
+  if migration.from > CURRENT_SCHEMA_VERSION {
+    return too_new(migration.from);
+  }
+  if !migration.migrated() {
+    return already_current(migration.from);
+  }
+
+  let validation = config_schema::validate(&migration.config);
+  if !validation.is_valid() {
+    return serde_json::to_value(validation).unwrap_or_default();
+  }
+
+  let backup = match config_backup::backup_current_config() {
+    Ok(name) => name,
+    Err(e) => {
+      return serde_json::json!({ "ok": false, "error": format!("配置备份失败: {}", e) })
+    }
+  };
+  let text = serde_json::to_string_pretty(&migration.config).unwrap_or_default();
+  let tmp = path.with_extension("json.tmp");
+  let _own_write = file_watcher::own_config_write();
+  let written = fs::write(&tmp, text).and_then(|_| fs::rename(&tmp, &path));
+  config_cache::invalidate();
+  let result = match written {
+    Ok(()) => serde_json::json!({ "ok": true, "error": null, "backup": backup }),
+    Err(e) => serde_json::json!({ "ok": false, "error": e.to_string(), "backup": backup }),
+  };
+  report(result, migration)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn v0() -> Value {
+    json!({
+      "bots": [
+        { "id": "i1", "name": "Lark", "appId": "cli_a", "appSecret": "s" },
+        { "id": "p1", "name": "Hook", "webhook": "https://example.com/hook" },
+      ],
+      "reconnect": { "maxRetries": 3 },
+    })
+  }
+
+  #[test]
+  fn detects_versions_by_shape() {
+    assert_eq!(detect_version(&v0()), 0);
+    assert_eq!(detect_version(&json!({ "bots": { "interactive": [], "push": [] } })), 1);
+    assert_eq!(detect_version(&json!({ "schemaVersion": 2, "bots": [] })), 2);
+  }
+
+  #[test]
+  fn v0_to_v1_splits_bots_by_webhook() {
+    let mut config = v0();
+    let changes = v0_to_v1(&mut config);
+    assert_eq!(config["bots"]["interactive"][0]["id"], "i1");
+    assert_eq!(config["bots"]["push"][0]["id"], "p1");
+    assert_eq!(config["bots"]["interactive"].as_array().map(Vec::len), Some(1));
+    assert_eq!(config["bots"]["push"].as_array().map(Vec::len), Some(1));
+    assert_eq!(config["reconnect"]["maxRetries"], 3);
+    assert_eq!(changes, vec!["split bots into 1 interactive and 1 push"]);
+  }
+
+  #[test]
+  fn v1_to_v2_fills_missing_sections_only() {
+    let mut config = json!({
+      "bots": { "interactive": [], "push": [] },
+      "input": { "enterRetryCount": 5 },
+    });
+    let changes = v1_to_v2(&mut config);
+    assert_eq!(config["defaults"], json!({}));
+    assert_eq!(config["input"], json!({ "enterRetryCount": 5, "enterRetryInterval": 500 }));
+    assert_eq!(changes, vec!["added defaults", "added input.enterRetryInterval = 500"]);
+  }
+
+  #[test]
+  fn v1_to_v2_replaces_a_non_object_section() {
+    let mut config = json!({ "bots": { "interactive": [], "push": [] }, "defaults": null });
+    v1_to_v2(&mut config);
+    assert_eq!(config["defaults"], json!({}));
+  }
+
+  #[test]
+  fn migrate_runs_the_whole_chain() {
+    let migration = migrate(v0());
+    assert_eq!(migration.from, 0);
+    assert!(migration.migrated());
+    let config = &migration.config;
+    assert_eq!(config["schemaVersion"], CURRENT_SCHEMA_VERSION);
+    assert_eq!(config["bots"]["push"][0]["webhook"], "https://example.com/hook");
+    assert_eq!(config["input"]["enterRetryCount"], 2);
+    assert!(migration.changes[0].starts_with("v1: split bots"));
+    assert!(migration.changes[1..].iter().all(|c| c.starts_with("v2: ")));
+  }
+
+  #[test]
+  fn migrate_leaves_current_and_newer_configs_alone() {
+    let current = json!({ "schemaVersion": CURRENT_SCHEMA_VERSION, "bots": {} });
+    let migration = migrate(current.clone());
+    assert!(!migration.migrated());
+    assert_eq!(migration.config, current);
+    assert!(migration.changes.is_empty());
+
+    let newer = json!({ "schemaVersion": CURRENT_SCHEMA_VERSION + 1, "bots": [] });
+    let migration = migrate(newer.clone());
+    assert_eq!(migration.from, CURRENT_SCHEMA_VERSION + 1);
+    assert_eq!(migration.config, newer);
+  }
+
+  fn full_v0() -> Value {
+    json!({
+      "bots": [
+        { "id": "i1", "name": "Lark", "appId": "cli_a", "appSecret": "app-secret" },
+        { "id": "p1", "name": "Hook", "webhook": "https://open.feishu.cn/open-apis/bot/v2/hook/x" },
+      ],
+      "reconnect": { "maxRetries": 3, "initialInterval": 5, "backoffMultiplier": 2 },
+      "push": { "mergeWindow": 2000, "maxMessageBytes": 30000 },
+    })
+  }
+
+  #[test]
+  fn migrated_configs_pass_the_schema() {
+    let mut v1 = full_v0();
+    v0_to_v1(&mut v1);
+    for (config, from) in [(full_v0(), 0), (v1, 1)] {
+      let migration = migrate(config);
+      assert_eq!(migration.from, from);
+      assert!(config_schema::validate(&migration.config).is_valid(), "from v{}", from);
+    }
+  }
+
+  #[test]
+  fn migrating_twice_changes_nothing() {
+    let once = migrate(full_v0()).config;
+    let twice = migrate(once.clone());
+    assert!(!twice.migrated());
+    assert!(twice.changes.is_empty());
+    assert_eq!(twice.config, once);
+  }
+
+  #[test]
+  fn results_report_the_versions() {
+    let newer = too_new(CURRENT_SCHEMA_VERSION + 1);
+    assert_eq!(newer["ok"], false);
+    assert_eq!(newer["from_version"], CURRENT_SCHEMA_VERSION + 1);
+
+    let current = already_current(CURRENT_SCHEMA_VERSION);
+    assert_eq!(current["ok"], true);
+    assert_eq!(current["already_current"], true);
+
+    let migration = migrate(full_v0());
+    let changes = migration.changes.clone();
+    let result = report(json!({ "ok": true, "backup": "config-1.json" }), migration);
+    assert_eq!(result["backup"], "config-1.json");
+    assert_eq!(result["already_current"], false);
+    assert_eq!(result["from_version"], 0);
+    assert_eq!(result["to_version"], CURRENT_SCHEMA_VERSION);
+    assert_eq!(result["changes"], json!(changes));
+  }
+}
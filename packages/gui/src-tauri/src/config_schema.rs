@@ -0,0 +1,210 @@
+//! Shape check for the config object sent with `save_config`.
+//!
+//! Mirrors the daemon's `save_config_request` schema so malformed configs are
+//! rejected locally with a path to the problem instead of an opaque daemon
+//! error. Unknown keys only produce warnings, so configs written by a newer
+//! daemon still pass.
+
+use crate::is_sensitive_key;
+use serde::Serialize;
+use serde_json::Value;
+
+enum Schema {
+  Str,
+  Num,
+  Array(&'static Schema),
+  Object(&'static [Field]),
+}
+
+struct Field {
+  name: &'static str,
+  schema: Schema,
+  required: bool,
+}
+
+const fn req(name: &'static str, schema: Schema) -> Field {
+  Field { name, schema, required: true }
+}
+
+const fn opt(name: &'static str, schema: Schema) -> Field {
+  Field { name, schema, required: false }
+}
+
+const QUIET_HOURS: Schema = Schema::Object(&[
+  req("start", Schema::Str),
+  req("end", Schema::Str),
+  opt("timezone", Schema::Str),
+]);
+
+const INTERACTIVE_BOT: Schema = Schema::Object(&[
+  req("id", Schema::Str),
+  req("name", Schema::Str),
+  req("appId", Schema::Str),
+  req("appSecret", Schema::Str),
+  opt("encryptKey", Schema::Str),
+]);
+
+const PUSH_BOT: Schema = Schema::Object(&[
+  req("id", Schema::Str),
+  req("name", Schema::Str),
+  req("webhook", Schema::Str),
+  opt("secret", Schema::Str),
+  opt("quietHours", QUIET_HOURS),
+]);
+
+const CONFIG: Schema = Schema::Object(&[
+  opt("schemaVersion", Schema::Num),
+  req(
+    "bots",
+    Schema::Object(&[
+      req("interactive", Schema::Array(&INTERACTIVE_BOT)),
+      req("push", Schema::Array(&PUSH_BOT)),
+    ]),
+  ),
+  req(
+    "reconnect",
+    Schema::Object(&[
+      req("maxRetries", Schema::Num),
+      req("initialInterval", Schema::Num),
+      req("backoffMultiplier", Schema::Num),
+    ]),
+  ),
+  req(
+    "push",
+    Schema::Object(&[
+      req("mergeWindow", Schema::Num),
+      req("maxMessageBytes", Schema::Num),
+    ]),
+  ),
+  opt(
+    "defaults",
+    Schema::Object(&[
+      opt("defaultInteractiveBotId", Schema::Str),
+      opt("defaultPushBotId", Schema::Str),
+    ]),
+  ),
+  opt(
+    "input",
+    Schema::Object(&[
+      req("enterRetryCount", Schema::Num),
+      req("enterRetryInterval", Schema::Num),
+    ]),
+  ),
+]);
+
+fn schema_type(schema: &Schema) -> &'static str {
+  match schema {
+    Schema::Str => "string",
+    Schema::Num => "number",
+    Schema::Array(_) | Schema::Object(_) => "object",
+  }
+}
+
+/// The fields of an interactive or push bot in `bot_schema_request`'s
+/// shape, for when the daemon can't be asked.
+pub(crate) fn bot_fields(bot_type: &str) -> Value {
+  let schema = if bot_type == "push" { &PUSH_BOT } else { &INTERACTIVE_BOT };
+  let Schema::Object(fields) = schema else {
+    return Value::Array(Vec::new());
+  };
+  fields
+    .iter()
+    .map(|field| {
+      serde_json::json!({
+        "name": field.name,
+        "type": schema_type(&field.schema),
+        "required": field.required,
+        "sensitive": is_sensitive_key(field.name),
+      })
+    })
+    .collect()
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Issue {
+  path: String,
+  message: String,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub(crate) struct ConfigValidation {
+  ok: bool,
+  errors: Vec<Issue>,
+  warnings: Vec<Issue>,
+}
+
+impl ConfigValidation {
+  pub(crate) fn is_valid(&self) -> bool {
+    self.errors.is_empty()
+  }
+}
+
+fn type_name(value: &Value) -> &'static str {
+  match value {
+    Value::Null => "null",
+    Value::Bool(_) => "boolean",
+    Value::Number(_) => "number",
+    Value::String(_) => "string",
+    Value::Array(_) => "array",
+    Value::Object(_) => "object",
+  }
+}
+
+fn check(value: &Value, schema: &Schema, path: &str, out: &mut ConfigValidation) {
+  let mismatch = |expected: &str, out: &mut ConfigValidation| {
+    out.errors.push(Issue {
+      path: path.to_string(),
+      message: format!("expected {}, got {}", expected, type_name(value)),
+    })
+  };
+
+  match schema {
+    Schema::Str if !value.is_string() => mismatch("string", out),
+    Schema::Num if !value.is_number() => mismatch("number", out),
+    Schema::Str | Schema::Num => {}
+    Schema::Array(item) => match value.as_array() {
+      Some(items) => {
+        for (i, v) in items.iter().enumerate() {
+          check(v, item, &format!("{}[{}]", path, i), out);
+        }
+      }
+      None => mismatch("array", out),
+    },
+    Schema::Object(fields) => {
+      let Some(map) = value.as_object() else {
+        return mismatch("object", out);
+      };
+      for field in fields.iter() {
+        let child = format!("{}.{}", path, field.name);
+        match map.get(field.name) {
+          // The daemon's optional fields accept absence, not null
+          Some(v) => check(v, &field.schema, &child, out),
+          None if field.required => out.errors.push(Issue {
+            path: child,
+            message: "required field is missing".to_string(),
+          }),
+          None => {}
+        }
+      }
+      for key in map.keys().filter(|k| !fields.iter().any(|f| f.name == k.as_str())) {
+        out.warnings.push(Issue {
+          path: format!("{}.{}", path, key),
+          message: "unknown field".to_string(),
+        });
+      }
+    }
+  }
+}
+
+/// Validate a full config object. Paths are rooted at `$`.
+pub(crate) fn validate(config: &Value) -> ConfigValidation {
+  let mut result = ConfigValidation::default();
+  check(config, &CONFIG, "$", &mut result);
+  result.ok = result.is_valid();
+  result
+}
+
+#[tauri::command]
+pub(crate) fn validate_config(config: Value) -> ConfigValidation {
+  validate(&config)
+}
@@ -0,0 +1,328 @@
+//! Lenient parsing of config text typed into the raw JSON editor.
+//!
+//! People paste JSON with `//` and `/* */` comments, trailing commas, or a
+//! byte-order mark from a Windows editor. Rather than letting the daemon
+//! reject it with a generic error, the text is cleaned up first: comments
+//! and trailing commas are blanked out in place (so line/column positions
+//! still point into what the user typed), then parsed as strict JSON. Each
+//! cleanup is reported, and a real syntax error comes back with its
+//! position and a caret under the offending line.
+
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum RelaxationKind {
+  Bom,
+  LineComment,
+  BlockComment,
+  TrailingComma,
+}
+
+/// A non-standard construct that was accepted. Positions are 1-based.
+#[derive(Debug, Serialize)]
+struct Relaxation {
+  kind: RelaxationKind,
+  line: usize,
+  column: usize,
+}
+
+#[derive(Debug)]
+pub(crate) struct TextError {
+  message: String,
+  line: usize,
+  column: usize,
+  /// The offending line and a caret under the column.
+  snippet: String,
+  /// How to fix a common mistake, when it is recognized.
+  suggestion: Option<String>,
+}
+
+impl TextError {
+  /// An error at byte offset `at` of `text`.
+  pub(crate) fn at(text: &str, at: usize, message: impl Into<String>) -> Self {
+    let (line, column) = position(text, at.min(text.len()));
+    let line_text = text.lines().nth(line - 1).unwrap_or("");
+    TextError {
+      message: message.into(),
+      line,
+      column,
+      snippet: snippet(line_text, column - 1),
+      suggestion: None,
+    }
+  }
+
+  pub(crate) fn with_suggestion(mut self, suggestion: impl Into<String>) -> Self {
+    self.suggestion = Some(suggestion.into());
+    self
+  }
+
+  pub(crate) fn suggestion(&self) -> Option<&str> {
+    self.suggestion.as_deref()
+  }
+
+  pub(crate) fn to_json(&self) -> Value {
+    let mut json = serde_json::json!({
+      "ok": false,
+      "error": self.to_string(),
+      "line": self.line,
+      "column": self.column,
+      "snippet": self.snippet,
+    });
+    if let Some(suggestion) = &self.suggestion {
+      json["suggestion"] = Value::String(suggestion.clone());
+    }
+    json
+  }
+}
+
+impl std::fmt::Display for TextError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "第 {} 行第 {} 列: {}", self.line, self.column, self.message)
+  }
+}
+
+fn snippet(line_text: &str, caret: usize) -> String {
+  format!("{}\n{}^", line_text, " ".repeat(caret))
+}
+
+/// 1-based line and column (in chars) of byte offset `at`.
+fn position(text: &str, at: usize) -> (usize, usize) {
+  let before = &text[..at];
+  let line = before.matches('\n').count() + 1;
+  let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+  (line, before[line_start..].chars().count() + 1)
+}
+
+/// Blank out comments and trailing commas. Replaced bytes become spaces,
+/// newlines inside block comments are kept, so offsets don't move.
+fn relax(text: &str) -> (String, Vec<Relaxation>) {
+  let bytes = text.as_bytes();
+  let mut out = bytes.to_vec();
+  let mut found: Vec<(RelaxationKind, usize)> = Vec::new();
+  // Offset of a comma that may turn out to be trailing
+  let mut pending_comma: Option<usize> = None;
+  // Last byte outside strings, comments and whitespace
+  let mut last = 0u8;
+  let mut in_string = false;
+  let mut i = 0;
+
+  let blank = |out: &mut Vec<u8>, from: usize, to: usize| {
+    for b in &mut out[from..to] {
+      if *b != b'\n' && *b != b'\r' {
+        *b = b' ';
+      }
+    }
+  };
+
+  while i < bytes.len() {
+    let b = bytes[i];
+    if in_string {
+      match b {
+        b'\\' => i += 1,
+        b'"' => in_string = false,
+        _ => {}
+      }
+      i += 1;
+      continue;
+    }
+
+    match (b, bytes.get(i + 1)) {
+      (b'/', Some(b'/')) => {
+        let end = bytes[i..].iter().position(|c| *c == b'\n').map_or(bytes.len(), |n| i + n);
+        blank(&mut out, i, end);
+        found.push((RelaxationKind::LineComment, i));
+        i = end;
+        continue;
+      }
+      (b'/', Some(b'*')) => {
+        // An unterminated comment runs to the end; the parser reports that
+        let end = text[i + 2..].find("*/").map_or(bytes.len(), |n| i + 2 + n + 2);
+        blank(&mut out, i, end);
+        found.push((RelaxationKind::BlockComment, i));
+        i = end;
+        continue;
+      }
+      _ => {}
+    }
+
+    if !b.is_ascii_whitespace() {
+      if let Some(comma) = pending_comma.take() {
+        if b == b'}' || b == b']' {
+          out[comma] = b' ';
+          found.push((RelaxationKind::TrailingComma, comma));
+        }
+      }
+      match b {
+        b'"' => in_string = true,
+        // Only a comma after a value can be trailing; `[,]` stays an error
+        b',' if !matches!(last, b'[' | b'{' | b',') => pending_comma = Some(i),
+        _ => {}
+      }
+      last = b;
+    }
+    i += 1;
+  }
+
+  // Only ASCII bytes were replaced, so this stays valid UTF-8
+  let relaxed = String::from_utf8(out).unwrap_or_else(|_| text.to_string());
+  let relaxations = found
+    .into_iter()
+    .map(|(kind, at)| {
+      let (line, column) = position(text, at);
+      Relaxation { kind, line, column }
+    })
+    .collect();
+  (relaxed, relaxations)
+}
+
+/// A serde_json error as a `TextError` pointing into `text`.
+pub(crate) fn text_error(text: &str, e: &serde_json::Error) -> TextError {
+  let (line, column) = (e.line().max(1), e.column().max(1));
+  let line_text = text.lines().nth(line - 1).unwrap_or("");
+  // serde_json counts bytes; the caret goes under the character
+  let caret = line_text.char_indices().take_while(|(i, _)| *i < column - 1).count();
+  let message = e.to_string();
+  let message = message.split(" at line ").next().unwrap_or(&message).to_string();
+  TextError {
+    message,
+    line,
+    column: caret + 1,
+    snippet: snippet(line_text, caret),
+    suggestion: None,
+  }
+}
+
+/// Parse editor text leniently into a config value and the relaxations
+/// that were needed.
+fn parse(text: &str) -> Result<(Value, Vec<Relaxation>), TextError> {
+  let (text, bom) = match text.strip_prefix('\u{feff}') {
+    Some(rest) => (rest, true),
+    None => (text, false),
+  };
+  let (relaxed, mut relaxations) = relax(text);
+  if bom {
+    relaxations.insert(0, Relaxation { kind: RelaxationKind::Bom, line: 1, column: 1 });
+  }
+  let value = serde_json::from_str::<Value>(&relaxed).map_err(|e| text_error(text, &e))?;
+  Ok((value, relaxations))
+}
+
+/// `parse` for callers that only need the value.
+pub(crate) fn parse_config_text(text: &str) -> Result<Value, TextError> {
+  parse(text).map(|(value, _)| value)
+}
+
+/// Check editor text and return it as pretty-printed strict JSON, listing
+/// the comments/trailing commas/BOM that were dropped, or the syntax error.
+#[tauri::command]
+pub(crate) fn format_config_text(text: String) -> Value {
+  match parse(&text) {
+    Ok((value, relaxations)) => serde_json::json!({
+      "ok": true,
+      "text": serde_json::to_string_pretty(&value).unwrap_or_default(),
+      "relaxations": relaxations,
+    }),
+    Err(e) => e.to_json(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn kinds(relaxations: &[Relaxation]) -> Vec<(RelaxationKind, usize, usize)> {
+    relaxations.iter().map(|r| (r.kind, r.line, r.column)).collect()
+  }
+
+  #[test]
+  fn strict_json_needs_no_relaxation() {
+    let (value, relaxations) = parse(r#"{ "a": [1, 2], "b": { "c": null } }"#).unwrap();
+    assert_eq!(value, json!({ "a": [1, 2], "b": { "c": null } }));
+    assert!(relaxations.is_empty());
+  }
+
+  #[test]
+  fn comments_and_trailing_commas_are_reported_where_typed() {
+    let text = "{\n  // bots\n  \"a\": 1, /* one\n two */\n  \"b\": [1, 2,],\n}";
+    let (value, relaxations) = parse(text).unwrap();
+    assert_eq!(value, json!({ "a": 1, "b": [1, 2] }));
+    use RelaxationKind::*;
+    assert_eq!(
+      kinds(&relaxations),
+      [(LineComment, 2, 3), (BlockComment, 3, 11), (TrailingComma, 5, 13), (TrailingComma, 5, 15)]
+    );
+  }
+
+  #[test]
+  fn blanking_keeps_offsets() {
+    let text = "{ /* a\nb */ \"x\": 1, }";
+    let (relaxed, _) = relax(text);
+    assert_eq!(relaxed.len(), text.len());
+    assert_eq!(relaxed.lines().count(), text.lines().count());
+    assert_eq!(relaxed, "{     \n     \"x\": 1  }");
+  }
+
+  #[test]
+  fn bom_is_dropped_and_reported() {
+    let (value, relaxations) = parse("\u{feff}{\"a\": 1}").unwrap();
+    assert_eq!(value, json!({ "a": 1 }));
+    assert_eq!(kinds(&relaxations), [(RelaxationKind::Bom, 1, 1)]);
+  }
+
+  #[test]
+  fn strings_are_left_alone() {
+    let text = r#"{ "url": "https://example.com/a,}", "q": "say \"// hi\", ok", "c": "/* x */" }"#;
+    let (value, relaxations) = parse(text).unwrap();
+    assert_eq!(value["url"], "https://example.com/a,}");
+    assert_eq!(value["q"], "say \"// hi\", ok");
+    assert_eq!(value["c"], "/* x */");
+    assert!(relaxations.is_empty());
+  }
+
+  #[test]
+  fn syntax_error_points_at_the_character() {
+    let err = parse("{\n  \"名称\": \"机器人\" \"x\": 1\n}").unwrap_err();
+    assert_eq!((err.line, err.column), (2, 15));
+    assert_eq!(err.snippet, "  \"名称\": \"机器人\" \"x\": 1\n              ^");
+    assert!(err.to_string().starts_with("第 2 行第 15 列: "));
+  }
+
+  #[test]
+  fn unterminated_block_comment_is_an_error() {
+    let err = parse("{ \"a\": 1 /* never closed\n}").unwrap_err();
+    assert_eq!(err.line, 2);
+  }
+
+  #[test]
+  fn lone_comma_is_not_trailing() {
+    assert!(parse("[,]").is_err());
+    assert!(parse("{ , }").is_err());
+    assert!(parse("{ \"a\": 1,, }").is_err());
+    assert!(parse("[[1],]").is_ok());
+  }
+
+  #[test]
+  fn error_json_carries_position_and_suggestion() {
+    let err = TextError::at("{\n  oops\n}", 4, "unexpected").with_suggestion("quote the key");
+    assert_eq!(err.suggestion(), Some("quote the key"));
+    let json = err.to_json();
+    assert_eq!(json["ok"], false);
+    assert_eq!((json["line"].clone(), json["column"].clone()), (json!(2), json!(3)));
+    assert_eq!(json["snippet"], "  oops\n  ^");
+    assert_eq!(json["suggestion"], "quote the key");
+    assert!(TextError::at("", 99, "x").to_json().get("suggestion").is_none());
+  }
+
+  #[test]
+  fn format_returns_pretty_strict_json() {
+    let result = format_config_text("{ \"a\": [1,], // c\n}".to_string());
+    assert_eq!(result["ok"], true);
+    assert_eq!(result["text"], "{\n  \"a\": [\n    1\n  ]\n}");
+    assert_eq!(result["relaxations"][0]["kind"], "trailing_comma");
+    assert_eq!(result["relaxations"][1]["kind"], "line_comment");
+    assert_eq!(format_config_text("{".to_string())["ok"], false);
+  }
+}
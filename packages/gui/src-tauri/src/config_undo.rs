@@ -0,0 +1,103 @@
+//! One-step undo of the last `save_config`.
+//!
+//! Each successful save records the backup it made and the modification
+//! time `config.json` had right after the daemon wrote it, under
+//! `last_config_save` in gui-settings, so undo survives a GUI restart.
+//! Undo restores that backup only while `config.json` still has that
+//! modification time; any later write (a hand edit, a bot saved from the
+//! CLI) is a conflict, since undoing would silently discard it. Backups
+//! belong to a profile, so undo also refuses once another profile is
+//! active.
+
+use crate::config_backup::{self, format_timestamp};
+use crate::config_diff::{self, DiffEntry};
+use crate::{config_cache, gui_settings, launch_state, profiles, save_config, unix_now};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::time::UNIX_EPOCH;
+
+const LAST_SAVE_KEY: &str = "last_config_save";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LastSave {
+  /// Backup holding the config from before the save.
+  backup: String,
+  saved_at: u64,
+  /// `config.json` modification time (ms) after the save.
+  config_modified_ms: Option<u64>,
+  /// Profile that was active for the save; None for the default one.
+  #[serde(default)]
+  profile: Option<String>,
+}
+
+fn config_modified_ms() -> Option<u64> {
+  let path = profiles::config_path()?;
+  let modified = fs::metadata(path).ok()?.modified().ok()?;
+  Some(modified.duration_since(UNIX_EPOCH).ok()?.as_millis() as u64)
+}
+
+fn store(last: Option<&LastSave>) {
+  let mut settings = gui_settings::load();
+  settings[LAST_SAVE_KEY] = serde_json::to_value(last).unwrap_or_default();
+  if let Err(e) = gui_settings::save(&settings) {
+    gui_println!("[gui] failed to record last config save: {}", e);
+  }
+}
+
+/// Called by `save_config` after the daemon accepted a save.
+pub(crate) fn record_save(backup: &str) {
+  store(Some(&LastSave {
+    backup: backup.to_string(),
+    saved_at: unix_now(),
+    config_modified_ms: config_modified_ms(),
+    profile: launch_state::active_profile(),
+  }));
+}
+
+fn last_save() -> Option<LastSave> {
+  serde_json::from_value(gui_settings::load().get(LAST_SAVE_KEY)?.clone()).ok()
+}
+
+/// Restore the config from before the last `save_config`, reporting what
+/// was reverted as `reverted` (a `diff_config` of current → restored).
+#[tauri::command]
+pub(crate) fn undo_last_config_save() -> Value {
+  let Some(last) = last_save() else {
+    return serde_json::json!({ "ok": false, "error": "没有可撤销的保存" });
+  };
+
+  if last.profile != launch_state::active_profile() {
+    return serde_json::json!({ "ok": false, "error": "上次保存属于另一个配置档案，无法撤销" });
+  }
+
+  let modified_ms = config_modified_ms();
+  if modified_ms != last.config_modified_ms {
+    return serde_json::json!({
+      "ok": false,
+      "error": "配置在上次保存后已被修改，无法撤销",
+      "conflict": true,
+      "saved_at": format_timestamp(last.saved_at),
+      "modified_at": modified_ms.map(|ms| format_timestamp(ms / 1000)),
+    });
+  }
+
+  let restored = match config_backup::read_backup(&last.backup) {
+    Ok(config) => config,
+    Err(e) => return serde_json::json!({ "ok": false, "error": e }),
+  };
+  let current = config_cache::config();
+  if current.is_null() {
+    return serde_json::json!({ "ok": false, "error": "daemon not running" });
+  }
+  let reverted: Vec<DiffEntry> = config_diff::diff(&current, &restored);
+
+  let mut result = save_config(Some(restored), None);
+  if result["ok"] == true {
+    // The undo's own save must not become the next thing to undo
+    store(None);
+  }
+  result["restored"] = Value::String(last.backup);
+  result["reverted"] = serde_json::to_value(reverted).unwrap_or_default();
+  result
+}
@@ -0,0 +1,87 @@
+//! SHA-256 check of the daemon binary before it is spawned.
+//!
+//! Release builds ship `felay-daemon.exe.sha256` next to the daemon and are
+//! compiled with the `daemon-integrity` feature, which refuses to spawn a
+//! binary that does not match it. Without the feature (dev builds running a
+//! locally compiled daemon) the check is skipped.
+
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+fn checksum_path(daemon_path: &Path) -> PathBuf {
+  let mut name = daemon_path.as_os_str().to_owned();
+  name.push(".sha256");
+  PathBuf::from(name)
+}
+
+/// Expected hash from the checksum file (`sha256sum` format: the hex digest,
+/// optionally followed by the file name).
+fn expected_checksum(daemon_path: &Path) -> Result<String, String> {
+  let path = checksum_path(daemon_path);
+  let text = fs::read_to_string(&path)
+    .map_err(|e| format!("cannot read checksum file {}: {}", path.display(), e))?;
+  let hash = text.split_whitespace().next().unwrap_or("").to_ascii_lowercase();
+  if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+    return Err(format!("invalid checksum file: {}", path.display()));
+  }
+  Ok(hash)
+}
+
+#[cfg(feature = "daemon-integrity")]
+fn file_sha256(path: &Path) -> Result<String, String> {
+  use sha2::{Digest, Sha256};
+
+  let mut file = fs::File::open(path).map_err(|e| e.to_string())?;
+  let mut hasher = Sha256::new();
+  std::io::copy(&mut file, &mut hasher).map_err(|e| e.to_string())?;
+  Ok(
+    hasher
+      .finalize()
+      .iter()
+      .map(|b| format!("{:02x}", b))
+      .collect(),
+  )
+}
+
+#[cfg(not(feature = "daemon-integrity"))]
+fn file_sha256(_path: &Path) -> Result<String, String> {
+  Err("built without the daemon-integrity feature".to_string())
+}
+
+/// Refuse `daemon_path` if it does not match its bundled checksum. Always
+/// passes when the feature is off.
+pub(crate) fn check(daemon_path: &Path) -> Result<(), String> {
+  if !cfg!(feature = "daemon-integrity") {
+    return Ok(());
+  }
+  let expected = expected_checksum(daemon_path)?;
+  let actual = file_sha256(daemon_path)?;
+  if actual != expected {
+    return Err(format!(
+      "daemon integrity check failed: {} does not match the bundled checksum",
+      daemon_path.display()
+    ));
+  }
+  Ok(())
+}
+
+/// Report the daemon binary's checksum status without spawning it.
+#[tauri::command]
+pub(crate) fn verify_daemon_binary(app: AppHandle) -> Value {
+  let daemon_path = match crate::find_daemon_exe(&app) {
+    Ok(p) => p,
+    Err(e) => return serde_json::json!({ "ok": false, "error": e }),
+  };
+  let result = check(&daemon_path);
+
+  serde_json::json!({
+    "ok": result.is_ok(),
+    "enabled": cfg!(feature = "daemon-integrity"),
+    "path": daemon_path.display().to_string(),
+    "expected": expected_checksum(&daemon_path).ok(),
+    "actual": file_sha256(&daemon_path).ok(),
+    "error": result.err(),
+  })
+}
@@ -0,0 +1,149 @@
+//! Opening CLI config files in the user's editor.
+//!
+//! The OS default (see `os_open`) hands a `.toml` to whatever the OS
+//! associates with it, which is often a browser or a "choose an app" prompt.
+//! The `preferred_editor` GUI setting is a command template such as
+//! `code --wait {path}` or `"C:\Program Files\Notepad++\notepad++.exe" {path}`.
+//! It is split into arguments here, without a shell, and `{path}` is
+//! substituted per argument, so a path with spaces stays one argument; a
+//! template without `{path}` gets the path appended. If the editor can't be
+//! started, the OS default is used and the response says so.
+
+use crate::{cli_detect, gui_settings, os_open};
+use serde_json::Value;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::AppHandle;
+use tauri_plugin_dialog::DialogExt;
+
+const PATH_PLACEHOLDER: &str = "{path}";
+
+/// Split a command template into arguments. Single or double quotes group
+/// and are dropped; backslashes are literal so Windows paths need no
+/// escaping.
+pub(crate) fn split_template(template: &str) -> Result<Vec<String>, String> {
+  let mut args = Vec::new();
+  let mut current: Option<String> = None;
+  let mut quote: Option<char> = None;
+  for c in template.chars() {
+    match quote {
+      Some(q) if c == q => quote = None,
+      Some(_) => current.get_or_insert_with(String::new).push(c),
+      None if c == '"' || c == '\'' => {
+        quote = Some(c);
+        current.get_or_insert_with(String::new);
+      }
+      None if c.is_whitespace() => args.extend(current.take()),
+      None => current.get_or_insert_with(String::new).push(c),
+    }
+  }
+  if quote.is_some() {
+    return Err("unterminated quote in editor command".to_string());
+  }
+  args.extend(current);
+  if args.is_empty() {
+    return Err("editor command is empty".to_string());
+  }
+  Ok(args)
+}
+
+/// Program and arguments to open `path` with `template`.
+fn editor_argv(template: &str, path: &Path) -> Result<(PathBuf, Vec<OsString>), String> {
+  let mut parts = split_template(template)?;
+  let program = parts.remove(0);
+
+  let mut substituted = false;
+  let mut args: Vec<OsString> = parts
+    .into_iter()
+    .map(|arg| {
+      if arg == PATH_PLACEHOLDER {
+        substituted = true;
+        path.as_os_str().to_os_string()
+      } else if arg.contains(PATH_PLACEHOLDER) {
+        substituted = true;
+        arg.replace(PATH_PLACEHOLDER, &path.to_string_lossy()).into()
+      } else {
+        arg.into()
+      }
+    })
+    .collect();
+  if !substituted {
+    args.push(path.as_os_str().to_os_string());
+  }
+
+  // A bare name is looked up like the CLI tools are, so `code` finds
+  // code.cmd on Windows and editors outside the GUI's PATH
+  let program = match Path::new(&program).components().count() {
+    1 => cli_detect::which(&program).unwrap_or_else(|| PathBuf::from(&program)),
+    _ => PathBuf::from(&program),
+  };
+  Ok((program, args))
+}
+
+fn open_with_editor(template: &str, path: &Path) -> Result<(), String> {
+  let (program, args) = editor_argv(template, path)?;
+  Command::new(&program)
+    .args(&args)
+    .spawn()
+    .map(|_| ())
+    .map_err(|e| format!("{}: {}", program.display(), e))
+}
+
+/// Open `path` with the preferred editor if one is set, else (or if it
+/// fails to start) with the OS default. `opened_with` tells which; a
+/// failed editor is reported in `editor_error`.
+pub(crate) fn open(path: &Path) -> Value {
+  let editor = gui_settings::with(|s| s.preferred_editor.clone());
+  let editor_error = match editor.filter(|e| !e.trim().is_empty()) {
+    None => None,
+    Some(template) => match open_with_editor(&template, path) {
+      Ok(()) => return serde_json::json!({ "ok": true, "opened_with": "preferred_editor" }),
+      Err(e) => {
+        gui_println!("[gui] preferred editor failed ({}), using the OS default", e);
+        Some(e)
+      }
+    },
+  };
+
+  match os_open::open(path.as_os_str()) {
+    Ok(()) => {
+      let mut result = serde_json::json!({ "ok": true, "opened_with": "os_default" });
+      if let Some(e) = editor_error {
+        result["editor_error"] = Value::String(e);
+        result["message"] = Value::String("首选编辑器启动失败，已使用系统默认程序打开".into());
+      }
+      result
+    }
+    Err(e) => serde_json::json!({
+      "ok": false,
+      "error": format!("无法打开文件: {}", e),
+      "editor_error": editor_error,
+    }),
+  }
+}
+
+/// Set or clear (`editor` empty or null) the preferred editor. With
+/// `browse`, a file picker chooses the editor executable instead and the
+/// template becomes `"<exe>" {path}`.
+#[tauri::command]
+pub(crate) async fn set_preferred_editor(
+  app: AppHandle,
+  editor: Option<String>,
+  browse: Option<bool>,
+) -> Result<gui_settings::GuiSettings, String> {
+  let editor = if browse.unwrap_or(false) {
+    let picked = app.dialog().file().blocking_pick_file().and_then(|p| p.into_path().ok());
+    match picked {
+      Some(exe) => Some(format!("\"{}\" {}", exe.display(), PATH_PLACEHOLDER)),
+      None => return Err("User cancelled".to_string()),
+    }
+  } else {
+    editor.filter(|e| !e.trim().is_empty())
+  };
+
+  if let Some(template) = &editor {
+    split_template(template)?;
+  }
+  gui_settings::set_gui_setting("preferred_editor".to_string(), serde_json::json!(editor))
+}
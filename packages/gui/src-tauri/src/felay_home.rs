@@ -0,0 +1,127 @@
+//! Creating `~/.felay` on a machine that has never run Felay.
+//!
+//! Without the directory the lock file, log bundle and settings helpers
+//! quietly find nothing, and the daemon creates it later with the default
+//! umask. `setup()` calls `init_on_first_run`, which creates the tree
+//! (owner-only on unix) and the initial `gui-settings.json`, and records
+//! the run as the first one for `get_onboarding_state`. A failure is kept
+//! and reported there too, since the user has to fix the home directory.
+
+use crate::{get_home_dir, gui_settings};
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+const SUBDIRS: [&str; 2] = ["backups", "logs"];
+
+/// Set when this run found no `~/.felay`.
+static FIRST_RUN: AtomicBool = AtomicBool::new(false);
+/// Why initializing failed, until a later attempt succeeds.
+static INIT_ERROR: Mutex<Option<String>> = Mutex::new(None);
+
+#[derive(Debug, Serialize)]
+pub(crate) struct HomeInit {
+  /// Directories created by this call.
+  created: Vec<String>,
+  /// Whether the initial `gui-settings.json` was written.
+  settings_created: bool,
+}
+
+fn felay_dir() -> Option<PathBuf> {
+  Some(PathBuf::from(get_home_dir()?).join(".felay"))
+}
+
+/// An error saying what couldn't be created and what to check.
+fn describe(path: &Path, e: &io::Error) -> String {
+  let hint = match e.kind() {
+    io::ErrorKind::PermissionDenied => "没有写入权限，请检查主目录的权限",
+    io::ErrorKind::AlreadyExists => "同名文件已存在，请将其移走",
+    _ => "请检查主目录是否可写（只读文件系统或漫游配置文件可能导致此问题）",
+  };
+  format!("无法创建 {}: {}。{}", path.display(), e, hint)
+}
+
+#[cfg(unix)]
+fn restrict(path: &Path) -> io::Result<()> {
+  use std::os::unix::fs::PermissionsExt;
+  fs::set_permissions(path, fs::Permissions::from_mode(0o700))
+}
+
+#[cfg(not(unix))]
+fn restrict(_path: &Path) -> io::Result<()> {
+  Ok(())
+}
+
+/// Create `dir` owner-only unless it is already a directory. True if it
+/// was created.
+fn ensure_dir(dir: &Path) -> Result<bool, String> {
+  if dir.is_dir() {
+    return Ok(false);
+  }
+  if dir.exists() {
+    let e = io::Error::new(io::ErrorKind::AlreadyExists, "not a directory");
+    return Err(describe(dir, &e));
+  }
+  fs::create_dir(dir).and_then(|_| restrict(dir)).map_err(|e| describe(dir, &e))?;
+  Ok(true)
+}
+
+fn initialize() -> Result<HomeInit, String> {
+  let felay = felay_dir().ok_or("无法确定用户主目录，请检查 HOME / USERPROFILE 环境变量")?;
+  let mut created = Vec::new();
+  for dir in std::iter::once(felay.clone()).chain(SUBDIRS.iter().map(|d| felay.join(d))) {
+    if ensure_dir(&dir)? {
+      created.push(dir.to_string_lossy().to_string());
+    }
+  }
+
+  let settings_created = match gui_settings::settings_path() {
+    Some(path) if !path.exists() => {
+      gui_settings::write_initial().map_err(|e| format!("{}: {}", path.display(), e))?;
+      true
+    }
+    _ => false,
+  };
+  Ok(HomeInit { created, settings_created })
+}
+
+fn record(result: &Result<HomeInit, String>) {
+  let error = result.as_ref().err().cloned();
+  *INIT_ERROR.lock().unwrap_or_else(|e| e.into_inner()) = error;
+}
+
+/// Called from `setup()`: initialize `~/.felay` if it is missing, and mark
+/// this run as the first one.
+pub(crate) fn init_on_first_run() {
+  if felay_dir().is_some_and(|dir| dir.exists()) {
+    return;
+  }
+  FIRST_RUN.store(true, Ordering::Relaxed);
+  let result = initialize();
+  match &result {
+    Ok(init) => gui_println!("[gui] first run, created {:?}", init.created),
+    Err(e) => gui_println!("[gui] first run, initializing ~/.felay failed: {}", e),
+  }
+  record(&result);
+}
+
+pub(crate) fn first_run() -> bool {
+  FIRST_RUN.load(Ordering::Relaxed)
+}
+
+/// The error from the last initialization attempt, if it failed.
+pub(crate) fn init_error() -> Option<String> {
+  INIT_ERROR.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Create whatever is missing of `~/.felay`, `backups/`, `logs/` and
+/// `gui-settings.json`, and return what was created.
+#[tauri::command]
+pub(crate) fn initialize_felay_home() -> Result<HomeInit, String> {
+  let result = initialize();
+  record(&result);
+  result
+}
@@ -0,0 +1,334 @@
+//! Notice external edits to files in `~/.felay`, and to the CLI configs
+//! holding Felay's hooks.
+//!
+//! Each file's parent directory is watched through OS file notifications
+//! (`notify`), not the file itself: editors and the daemon save by writing
+//! a temporary file and renaming it over the old one, which a watch on the
+//! old file would not follow. A directory that doesn't exist yet is tried
+//! again every `REATTACH`. A burst of writes to `config.json` or a CLI
+//! config (an editor's save storm) is reported once, after the file has
+//! been quiet for `SETTLE`. CLI config changes go to `cli_hook_watch`.
+//!
+//! Commands that have the daemon write `config.json` hold an
+//! `OwnConfigWrite` meanwhile, like `cli_hook_watch`'s `OwnWrite`; changes
+//! seen during it or shortly after only move the baseline, so the GUI's own
+//! saves are not reported back to it as external edits.
+
+use crate::{cli_hook_watch, config_cache, get_home_dir, profiles, request_tray_refresh};
+use crate::{shutdown_requested, spawn_background};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// How often pending changes are checked for having settled.
+const TICK: Duration = Duration::from_millis(200);
+const SETTLE: Duration = Duration::from_millis(700);
+/// How often directories that couldn't be watched are tried again.
+const REATTACH: Duration = Duration::from_secs(5);
+
+/// How long after an own write changes are still attributed to it; covers
+/// `SETTLE` and a tick.
+const QUIET_AFTER: Duration = Duration::from_secs(3);
+
+pub(crate) const CONFIG_CHANGED_EVENT: &str = "config-changed";
+pub(crate) const DAEMON_LOCK_CHANGED_EVENT: &str = "daemon-lock-changed";
+
+/// Bumped on every (re)start; a watcher thread exits once it is outdated.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Own `config.json` writes in progress, and when the last one ended.
+struct OwnWrites {
+  busy: usize,
+  quiet_until: Option<Instant>,
+}
+
+impl OwnWrites {
+  fn active(&self, now: Instant) -> bool {
+    self.busy > 0 || self.quiet_until.is_some_and(|t| now < t)
+  }
+}
+
+static OWN_WRITES: Mutex<OwnWrites> = Mutex::new(OwnWrites { busy: 0, quiet_until: None });
+
+fn own_writes() -> std::sync::MutexGuard<'static, OwnWrites> {
+  OWN_WRITES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Marks a Felay command having the daemon write `config.json`.
+pub(crate) struct OwnConfigWrite(());
+
+pub(crate) fn own_config_write() -> OwnConfigWrite {
+  own_writes().busy += 1;
+  OwnConfigWrite(())
+}
+
+impl Drop for OwnConfigWrite {
+  fn drop(&mut self) {
+    let mut own = own_writes();
+    own.busy = own.busy.saturating_sub(1);
+    own.quiet_until = Some(Instant::now() + QUIET_AFTER);
+  }
+}
+
+/// Whether a change seen now is the GUI's own.
+fn own_config_change() -> bool {
+  own_writes().active(Instant::now())
+}
+
+fn emit(app: &AppHandle, event: &str, payload: serde_json::Value) {
+  if let Err(e) = app.emit(event, payload) {
+    gui_println!("[gui] failed to emit {}: {}", event, e);
+  }
+}
+
+/// Whether the `config.json` change pending since `dirty_at` has settled by
+/// `now`. The cached config is dropped then, before anyone is told.
+fn config_settled(dirty_at: &mut Option<Instant>, now: Instant) -> bool {
+  if !dirty_at.is_some_and(|t| now.duration_since(t) >= SETTLE) {
+    return false;
+  }
+  *dirty_at = None;
+  config_cache::invalidate();
+  true
+}
+
+/// A watched CLI config and its pending change.
+struct CliConfig {
+  name: &'static str,
+  path: PathBuf,
+  dirty_at: Option<Instant>,
+}
+
+/// Whether `event` may have changed `path`: it names the file and is more
+/// than a read. A rename over the file names it as the target.
+fn touches(event: &Event, path: &Path) -> bool {
+  !matches!(event.kind, EventKind::Access(_)) && event.paths.iter().any(|p| p == path)
+}
+
+/// The directories to watch, and which of them are watched so far.
+struct Dirs {
+  all: BTreeSet<PathBuf>,
+  attached: BTreeSet<PathBuf>,
+}
+
+impl Dirs {
+  fn of(files: &[PathBuf]) -> Self {
+    let all = files.iter().filter_map(|f| f.parent()).map(Path::to_path_buf).collect();
+    Dirs { all, attached: BTreeSet::new() }
+  }
+
+  /// Watch the directories not watched yet; returns those newly watched.
+  fn attach(&mut self, watcher: &mut impl Watcher) -> Vec<PathBuf> {
+    let mut added = Vec::new();
+    for dir in self.all.difference(&self.attached) {
+      if watcher.watch(dir, RecursiveMode::NonRecursive).is_ok() {
+        added.push(dir.clone());
+      }
+    }
+    self.attached.extend(added.iter().cloned());
+    added
+  }
+
+  /// Forget a watched directory that `event` removed, so it is watched
+  /// again once it is back.
+  fn detach_removed(&mut self, event: &Event) {
+    if matches!(event.kind, EventKind::Remove(_)) {
+      for path in &event.paths {
+        self.attached.remove(path);
+      }
+    }
+  }
+}
+
+fn watch(app: AppHandle, home: PathBuf, generation: u64) {
+  let dir = home.join(".felay");
+  let config_path = profiles::config_path().unwrap_or_else(|| dir.join("config.json"));
+  let lock_path = dir.join("daemon.json");
+
+  let mut lock_exists = lock_path.exists();
+  let mut config_dirty_at: Option<Instant> = None;
+
+  let mut cli_configs: Vec<CliConfig> = cli_hook_watch::paths(&home)
+    .into_iter()
+    .map(|(name, path)| CliConfig { name, path, dirty_at: None })
+    .collect();
+  cli_hook_watch::baseline(&home);
+
+  let (tx, rx) = mpsc::channel();
+  let mut watcher = match notify::recommended_watcher(tx) {
+    Ok(watcher) => watcher,
+    Err(e) => {
+      gui_println!("[gui] file watcher unavailable: {}", e);
+      return;
+    }
+  };
+  let files: Vec<PathBuf> = [config_path.clone(), lock_path.clone()]
+    .into_iter()
+    .chain(cli_configs.iter().map(|c| c.path.clone()))
+    .collect();
+  let mut dirs = Dirs::of(&files);
+  dirs.attach(&mut watcher);
+  let mut attached_at = Instant::now();
+
+  while GENERATION.load(Ordering::Relaxed) == generation && !shutdown_requested() {
+    let mut changed: Vec<PathBuf> = Vec::new();
+    match rx.recv_timeout(TICK) {
+      Ok(Ok(event)) => {
+        dirs.detach_removed(&event);
+        changed.extend(files.iter().filter(|f| touches(&event, f)).cloned());
+      }
+      Ok(Err(e)) => gui_println!("[gui] file watcher error: {}", e),
+      Err(RecvTimeoutError::Timeout) => {}
+      Err(RecvTimeoutError::Disconnected) => break,
+    }
+    if attached_at.elapsed() >= REATTACH {
+      attached_at = Instant::now();
+      // Files that appeared along with their directory went unseen
+      for dir in dirs.attach(&mut watcher) {
+        let appeared = files.iter().filter(|f| f.parent() == Some(dir.as_path()) && f.exists());
+        changed.extend(appeared.cloned());
+      }
+    }
+
+    let now = Instant::now();
+    for path in &changed {
+      if *path == config_path {
+        // The command that wrote it has already invalidated the cache
+        config_dirty_at = (!own_config_change()).then_some(now);
+      }
+      if let Some(cli) = cli_configs.iter_mut().find(|c| c.path == *path) {
+        cli.dirty_at = Some(now);
+      }
+    }
+    if config_settled(&mut config_dirty_at, now) {
+      emit(&app, CONFIG_CHANGED_EVENT, config_cache::config());
+    }
+
+    if changed.contains(&lock_path) && lock_path.exists() != lock_exists {
+      lock_exists = !lock_exists;
+      config_cache::invalidate();
+      request_tray_refresh();
+      emit(&app, DAEMON_LOCK_CHANGED_EVENT, serde_json::json!({ "exists": lock_exists }));
+    }
+
+    for cli in cli_configs.iter_mut() {
+      if cli.dirty_at.is_some_and(|t| now.duration_since(t) >= SETTLE) {
+        cli.dirty_at = None;
+        cli_hook_watch::changed(&app, &home, cli.name);
+      }
+    }
+  }
+}
+
+/// Start watching, replacing any watcher that is already running.
+pub(crate) fn start(app: AppHandle) -> bool {
+  let Some(home) = get_home_dir() else {
+    return false;
+  };
+  let home = PathBuf::from(home);
+  let generation = GENERATION.fetch_add(1, Ordering::Relaxed) + 1;
+  spawn_background(move || watch(app, home, generation));
+  true
+}
+
+#[tauri::command]
+pub(crate) fn restart_file_watcher(app: AppHandle) -> bool {
+  start(app)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn own_writes_are_attributed_until_quiet() {
+    let now = Instant::now();
+    assert!(!OwnWrites { busy: 0, quiet_until: None }.active(now));
+    assert!(OwnWrites { busy: 1, quiet_until: None }.active(now));
+    let ended = OwnWrites { busy: 0, quiet_until: Some(now + QUIET_AFTER) };
+    assert!(ended.active(now));
+    assert!(!ended.active(now + QUIET_AFTER));
+  }
+
+  #[test]
+  fn a_settled_config_change_invalidates_the_cache() {
+    let changed = Instant::now();
+    let mut dirty_at = Some(changed);
+    assert!(!config_settled(&mut dirty_at, changed + SETTLE / 2));
+    assert_eq!(dirty_at, Some(changed));
+
+    let config = serde_json::json!({ "schemaVersion": 2 });
+    let cached = config_cache::cached_after(&config, || {
+      assert!(config_settled(&mut dirty_at, changed + SETTLE));
+    });
+    assert!(!cached);
+    assert_eq!(dirty_at, None);
+    assert!(!config_settled(&mut dirty_at, changed + SETTLE * 2));
+  }
+
+  #[test]
+  fn reads_do_not_count_and_renames_count_for_their_target() {
+    use notify::event::{AccessKind, ModifyKind, RenameMode};
+    let home_file = |name: &str| Path::new("/home/u/.felay").join(name);
+    let (config, tmp) = (home_file("config.json"), home_file("config.json.tmp"));
+
+    let rename = Event::new(EventKind::Modify(ModifyKind::Name(RenameMode::Both)));
+    assert!(touches(&rename.clone().add_path(tmp.clone()).add_path(config.clone()), &config));
+    let other = home_file("daemon.json");
+    assert!(!touches(&rename.add_path(tmp).add_path(config.clone()), &other));
+    let read = Event::new(EventKind::Access(AccessKind::Any)).add_path(config.clone());
+    assert!(!touches(&read, &config));
+  }
+
+  /// Events from `rx` until one touches `path`, for up to five seconds.
+  fn wait_for(rx: &mpsc::Receiver<notify::Result<Event>>, path: &Path) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+      match rx.recv_timeout(left) {
+        Ok(Ok(event)) if touches(&event, path) => return true,
+        Ok(_) => {}
+        Err(_) => return false,
+      }
+    }
+    false
+  }
+
+  #[test]
+  fn an_atomic_rename_save_is_seen() {
+    let home = tempfile::tempdir().unwrap();
+    let config = home.path().join("config.json");
+    std::fs::write(&config, "{}").unwrap();
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).unwrap();
+    let mut dirs = Dirs::of(std::slice::from_ref(&config));
+    assert_eq!(dirs.attach(&mut watcher), vec![home.path().to_path_buf()]);
+    assert!(dirs.attach(&mut watcher).is_empty());
+
+    let tmp = home.path().join("config.json.tmp");
+    std::fs::write(&tmp, r#"{"schemaVersion":2}"#).unwrap();
+    std::fs::rename(&tmp, &config).unwrap();
+    assert!(wait_for(&rx, &config));
+  }
+
+  #[test]
+  fn a_missing_directory_is_watched_once_it_exists() {
+    let home = tempfile::tempdir().unwrap();
+    let codex = home.path().join(".codex");
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).unwrap();
+    let mut dirs = Dirs::of(&[codex.join("config.toml")]);
+    assert!(dirs.attach(&mut watcher).is_empty());
+
+    std::fs::create_dir(&codex).unwrap();
+    assert_eq!(dirs.attach(&mut watcher), vec![codex.clone()]);
+    std::fs::write(codex.join("config.toml"), "notify = []").unwrap();
+    assert!(wait_for(&rx, &codex.join("config.toml")));
+  }
+}
+
@@ -0,0 +1,343 @@
+//! GUI-local settings persisted in `~/.felay/gui-settings.json`.
+//!
+//! These are preferences that belong to the desktop app rather than the
+//! daemon's `config.json`, so they never travel over IPC. Known settings
+//! are typed fields of `GuiSettings`; any other key (data such as warning
+//! dismissals, or settings from a newer GUI) is kept in `extra` and written
+//! back untouched.
+//!
+//! The file is read once and cached; writes go through a temp file and a
+//! rename so a crash never leaves it half-written.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::Duration;
+
+fn default_tray_poll_secs() -> u64 {
+  5
+}
+fn default_ipc_timeout_secs() -> u64 {
+  10
+}
+fn default_update_check_timeout_secs() -> u64 {
+  15
+}
+fn default_config_backup_limit() -> usize {
+  20
+}
+fn default_warning_dismiss_hours() -> u64 {
+  24
+}
+fn default_config_cache_enabled() -> bool {
+  true
+}
+fn default_config_cache_ttl_secs() -> u64 {
+  30
+}
+fn default_cli_hooks_lost_prompt() -> bool {
+  true
+}
+fn default_update_check_interval_hours() -> u64 {
+  6
+}
+
+/// Which releases `check_update` offers.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum UpdateChannel {
+  #[default]
+  Stable,
+  /// Pre-releases too.
+  Beta,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GuiSettings {
+  /// Tray status poll interval while the daemon is up; backs off from here.
+  #[serde(default = "default_tray_poll_secs")]
+  pub(crate) tray_poll_secs: u64,
+  /// How long to wait for a daemon reply.
+  #[serde(default = "default_ipc_timeout_secs")]
+  pub(crate) ipc_timeout_secs: u64,
+  #[serde(default = "default_update_check_timeout_secs")]
+  pub(crate) update_check_timeout_secs: u64,
+  /// Number of `config.json` backups to keep.
+  #[serde(default = "default_config_backup_limit")]
+  pub(crate) config_backup_limit: usize,
+  /// How long a dismissed bot warning stays hidden.
+  #[serde(default = "default_warning_dismiss_hours")]
+  pub(crate) warning_dismiss_hours: u64,
+  /// Extra key names to treat as secrets when sanitizing, on top of the
+  /// built-in list.
+  #[serde(default)]
+  pub(crate) sensitive_keys: Vec<String>,
+  /// Serve `get_config` from memory between config changes.
+  #[serde(default = "default_config_cache_enabled")]
+  pub(crate) config_cache_enabled: bool,
+  #[serde(default = "default_config_cache_ttl_secs")]
+  pub(crate) config_cache_ttl_secs: u64,
+  /// Command used to open CLI config files, e.g. `code --wait {path}`;
+  /// None opens them with the OS default app. See `editor`.
+  #[serde(default)]
+  pub(crate) preferred_editor: Option<String>,
+  /// Offer to repair when Felay's hook disappears from a CLI config.
+  #[serde(default = "default_cli_hooks_lost_prompt")]
+  pub(crate) cli_hooks_lost_prompt: bool,
+  #[serde(default)]
+  pub(crate) update_channel: UpdateChannel,
+  /// How often the background check runs; also how long a result is
+  /// reused by `check_update`.
+  #[serde(default = "default_update_check_interval_hours")]
+  pub(crate) update_check_interval_hours: u64,
+  /// A release the user chose to skip; a newer one is offered again.
+  #[serde(default)]
+  pub(crate) skipped_update_version: Option<String>,
+  /// Unix time until which update reminders are snoozed.
+  #[serde(default)]
+  pub(crate) update_snoozed_until: Option<u64>,
+  /// Turns off update checks and downloads, e.g. where IT manages installs.
+  #[serde(default)]
+  pub(crate) disable_update_check: bool,
+  /// Proxy URLs for update checks, downloads and webhook probes; see `http`.
+  #[serde(default)]
+  pub(crate) http_proxy: Option<String>,
+  #[serde(default)]
+  pub(crate) https_proxy: Option<String>,
+  /// PEM file of extra trusted root certificates.
+  #[serde(default)]
+  pub(crate) extra_ca_cert_path: Option<String>,
+  /// GitHub token for update checks, for the higher authenticated rate
+  /// limit. Sent to api.github.com only; see `http::get`.
+  #[serde(default)]
+  pub(crate) github_token: Option<String>,
+  /// Base URL of a static mirror serving GitHub's release JSON at
+  /// `<base>/releases/latest` (and the list at `<base>/releases`); tried
+  /// before GitHub.
+  #[serde(default)]
+  pub(crate) update_mirror_url: Option<String>,
+  #[serde(flatten)]
+  extra: Map<String, Value>,
+}
+
+impl Default for GuiSettings {
+  fn default() -> Self {
+    serde_json::from_value(Value::Object(Map::new())).expect("all fields have defaults")
+  }
+}
+
+impl GuiSettings {
+  pub(crate) fn tray_poll(&self) -> Duration {
+    Duration::from_secs(self.tray_poll_secs.clamp(1, 300))
+  }
+
+  pub(crate) fn ipc_timeout(&self) -> Duration {
+    Duration::from_secs(self.ipc_timeout_secs.clamp(1, 120))
+  }
+
+  pub(crate) fn update_check_interval(&self) -> Duration {
+    Duration::from_secs(self.update_check_interval_hours.clamp(1, 7 * 24) * 3600)
+  }
+
+  pub(crate) fn update_check_timeout(&self) -> Duration {
+    Duration::from_secs(self.update_check_timeout_secs.clamp(3, 120))
+  }
+
+  pub(crate) fn config_cache_ttl(&self) -> Duration {
+    Duration::from_secs(self.config_cache_ttl_secs.min(3600))
+  }
+
+  pub(crate) fn config_backup_limit(&self) -> usize {
+    self.config_backup_limit.max(1)
+  }
+}
+
+/// None until first use.
+static SETTINGS: RwLock<Option<GuiSettings>> = RwLock::new(None);
+
+pub(crate) fn settings_path() -> Option<PathBuf> {
+  let home = crate::get_home_dir()?;
+  Some(PathBuf::from(home).join(".felay").join("gui-settings.json"))
+}
+
+/// Settings from the file's top-level object. A value that doesn't fit its
+/// setting is dropped and its key returned, so it costs that setting only.
+fn parse(map: Map<String, Value>) -> (GuiSettings, Vec<String>) {
+  if let Ok(settings) = serde_json::from_value(Value::Object(map.clone())) {
+    return (settings, Vec::new());
+  }
+  let mut dropped = Vec::new();
+  let mut kept = Map::new();
+  for (key, value) in map {
+    let alone = serde_json::json!({ key.as_str(): value });
+    if serde_json::from_value::<GuiSettings>(alone).is_ok() {
+      kept.insert(key, value);
+    } else {
+      dropped.push(key);
+    }
+  }
+  let settings = serde_json::from_value(Value::Object(kept)).unwrap_or_default();
+  (settings, dropped)
+}
+
+/// Read the file. A bad value falls back to its default alone (see
+/// `parse`); a file that isn't a JSON object at all is moved aside to
+/// `gui-settings.json.bak` so the next save doesn't destroy it.
+fn read_file() -> GuiSettings {
+  let Some(path) = settings_path() else {
+    return GuiSettings::default();
+  };
+  let Ok(text) = fs::read_to_string(&path) else {
+    return GuiSettings::default();
+  };
+  let map = match serde_json::from_str::<Value>(&text) {
+    Ok(Value::Object(map)) => map,
+    Ok(_) => {
+      gui_println!("[gui] gui-settings.json is not an object, starting fresh");
+      let _ = fs::rename(&path, path.with_extension("json.bak"));
+      return GuiSettings::default();
+    }
+    Err(e) => {
+      gui_println!("[gui] gui-settings.json is invalid ({}), starting fresh", e);
+      let _ = fs::rename(&path, path.with_extension("json.bak"));
+      return GuiSettings::default();
+    }
+  };
+  let (settings, dropped) = parse(map);
+  if !dropped.is_empty() {
+    gui_println!("[gui] gui-settings.json: ignoring invalid {}", dropped.join(", "));
+  }
+  settings
+}
+
+fn write_file(settings: &GuiSettings) -> Result<(), String> {
+  let path = settings_path().ok_or("cannot determine home directory")?;
+  if let Some(dir) = path.parent() {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+  }
+  let text = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+  let tmp = path.with_extension("json.tmp");
+  fs::write(&tmp, text).map_err(|e| e.to_string())?;
+  fs::rename(&tmp, &path).map_err(|e| e.to_string())
+}
+
+/// Write the settings in effect (the defaults, on a new machine) so the
+/// file exists to be edited.
+pub(crate) fn write_initial() -> Result<(), String> {
+  store(get()).map(|_| ())
+}
+
+/// Run `f` with the current settings, loading them on first use.
+pub(crate) fn with<T>(f: impl FnOnce(&GuiSettings) -> T) -> T {
+  if let Some(settings) = SETTINGS.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+    return f(settings);
+  }
+  let mut guard = SETTINGS.write().unwrap_or_else(|e| e.into_inner());
+  f(guard.get_or_insert_with(read_file))
+}
+
+pub(crate) fn get() -> GuiSettings {
+  with(GuiSettings::clone)
+}
+
+fn store(settings: GuiSettings) -> Result<GuiSettings, String> {
+  write_file(&settings)?;
+  *SETTINGS.write().unwrap_or_else(|e| e.into_inner()) = Some(settings.clone());
+  Ok(settings)
+}
+
+/// The settings as a JSON object, for code that keeps untyped data here.
+pub(crate) fn load() -> Value {
+  serde_json::to_value(get()).unwrap_or_else(|_| serde_json::json!({}))
+}
+
+pub(crate) fn save(settings: &Value) -> Result<(), String> {
+  let parsed =
+    serde_json::from_value::<GuiSettings>(settings.clone()).map_err(|e| e.to_string())?;
+  store(parsed).map(|_| ())
+}
+
+#[tauri::command]
+pub(crate) fn get_gui_settings() -> GuiSettings {
+  get()
+}
+
+/// Set one key. Known settings must have the right type; unknown keys are
+/// stored as-is.
+#[tauri::command]
+pub(crate) fn set_gui_setting(key: String, value: Value) -> Result<GuiSettings, String> {
+  let mut settings = load();
+  settings[key.as_str()] = value;
+  let parsed = serde_json::from_value::<GuiSettings>(settings)
+    .map_err(|e| format!("invalid value for {}: {}", key, e))?;
+  store(parsed)
+}
+
+/// Switch between stable releases only and pre-releases too.
+#[tauri::command]
+pub(crate) fn set_update_channel(channel: UpdateChannel) -> Result<GuiSettings, String> {
+  let mut settings = get();
+  settings.update_channel = channel;
+  store(settings)
+}
+
+/// Stop reminding about `version` (a tag or bare version); null un-skips.
+#[tauri::command]
+pub(crate) fn skip_update_version(version: Option<String>) -> Result<GuiSettings, String> {
+  let mut settings = get();
+  settings.skipped_update_version = version.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+  store(settings)
+}
+
+/// Hold update reminders for `hours` from now; 0 ends a snooze.
+#[tauri::command]
+pub(crate) fn snooze_update(hours: u64) -> Result<GuiSettings, String> {
+  let mut settings = get();
+  settings.update_snoozed_until =
+    (hours > 0).then(|| crate::unix_now().saturating_add(hours.min(24 * 365) * 3600));
+  store(settings)
+}
+
+#[tauri::command]
+pub(crate) fn reset_gui_settings() -> Result<GuiSettings, String> {
+  store(GuiSettings::default())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn object(value: Value) -> Map<String, Value> {
+    value.as_object().unwrap().clone()
+  }
+
+  #[test]
+  fn one_bad_value_keeps_the_other_settings() {
+    let (settings, dropped) = parse(object(serde_json::json!({
+      "tray_poll_secs": "fast",
+      "ipc_timeout_secs": 30,
+      "update_channel": "nightly",
+      "http_proxy": "http://127.0.0.1:7890",
+      "dismissed_warnings": { "abc": 1 },
+    })));
+    assert_eq!(dropped, ["tray_poll_secs", "update_channel"]);
+    assert_eq!(settings.tray_poll_secs, default_tray_poll_secs());
+    assert_eq!(settings.update_channel, UpdateChannel::Stable);
+    assert_eq!(settings.ipc_timeout_secs, 30);
+    assert_eq!(settings.http_proxy.as_deref(), Some("http://127.0.0.1:7890"));
+    assert_eq!(settings.extra["dismissed_warnings"], serde_json::json!({ "abc": 1 }));
+  }
+
+  #[test]
+  fn valid_settings_drop_nothing() {
+    let (settings, dropped) = parse(object(serde_json::json!({
+      "update_channel": "beta",
+      "sensitive_keys": ["token"],
+    })));
+    assert!(dropped.is_empty());
+    assert_eq!(settings.update_channel, UpdateChannel::Beta);
+    assert_eq!(settings.sensitive_keys, ["token"]);
+  }
+}
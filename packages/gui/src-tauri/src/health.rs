@@ -0,0 +1,90 @@
+//! One-call health summary for the traffic-light indicator.
+//!
+//! `get_health` asks the daemon for its status and bot list on one blocking
+//! task while `test_network` runs alongside, each bounded by `DEADLINE`, so
+//! a slow piece only turns its own light red. Bots aren't tested live
+//! (that connects every bot); a bot counts as failing when it has an
+//! undismissed warning or is bound to a session whose interactive bot is
+//! disconnected, as of the daemon's current status. With the daemon down
+//! the bot counts are null.
+
+use crate::{get_ipc_path, ipc_request_timeout, test_network, warnings, DaemonStatus};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::time::Duration;
+
+const DAEMON_TIMEOUT: Duration = Duration::from_millis(900);
+const DEADLINE: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Health {
+  daemon: bool,
+  /// None when the daemon isn't answering.
+  bots_ok: Option<usize>,
+  bots_failing: Option<usize>,
+  network_ok: bool,
+}
+
+#[derive(Default)]
+struct DaemonHealth {
+  running: bool,
+  bots: Option<(usize, usize)>,
+}
+
+fn bot_ids(bots: &Value) -> Vec<String> {
+  ["interactive", "push"]
+    .iter()
+    .filter_map(|kind| bots[kind].as_array())
+    .flatten()
+    .filter_map(|bot| bot["id"].as_str().map(String::from))
+    .collect()
+}
+
+fn probe_daemon() -> DaemonHealth {
+  let Some(ipc_path) = get_ipc_path() else {
+    return DaemonHealth::default();
+  };
+  let request = |req: &str| ipc_request_timeout(&ipc_path, req, DAEMON_TIMEOUT);
+  let Some(status) = request(r#"{"type":"status_request"}"#)
+    .and_then(|reply| serde_json::from_value::<DaemonStatus>(reply).ok())
+  else {
+    return DaemonHealth::default();
+  };
+  let Some(bots) = request(r#"{"type":"list_bots_request"}"#) else {
+    return DaemonHealth { running: true, bots: None };
+  };
+
+  let mut bot_warnings = status.payload.warnings.unwrap_or_default();
+  warnings::annotate(&mut bot_warnings);
+  let mut failing: HashSet<String> =
+    bot_warnings.into_iter().filter(|w| !w.dismissed).map(|w| w.bot_id).collect();
+  failing.extend(
+    status
+      .payload
+      .sessions
+      .into_iter()
+      .filter(|s| s.status != "ended" && s.interactive_bot_connected == Some(false))
+      .filter_map(|s| s.interactive_bot_id),
+  );
+
+  let ids = bot_ids(&bots["payload"]);
+  let failing = ids.iter().filter(|id| failing.contains(*id)).count();
+  DaemonHealth { running: true, bots: Some((ids.len() - failing, failing)) }
+}
+
+/// Daemon, bot and network health, within about `DEADLINE`.
+#[tauri::command]
+pub(crate) async fn get_health() -> Health {
+  let daemon = tokio::time::timeout(DEADLINE, tokio::task::spawn_blocking(probe_daemon));
+  let network = tokio::time::timeout(DEADLINE, test_network());
+  let (daemon, network) = tokio::join!(daemon, network);
+  // A timed-out check counts as failed
+  let daemon = daemon.ok().and_then(Result::ok).unwrap_or_default();
+  Health {
+    daemon: daemon.running,
+    bots_ok: daemon.bots.map(|(ok, _)| ok),
+    bots_failing: daemon.bots.map(|(_, failing)| failing),
+    network_ok: network.is_ok_and(|test| test.ok),
+  }
+}
@@ -0,0 +1,307 @@
+//! Whether Felay's installed hooks still point at this install's scripts.
+//!
+//! Codex's `notify` and Claude Code's Stop hook run a script by absolute
+//! path. After Felay is updated into another directory or moved, the old
+//! path stays in the CLI config and the hook fails without a trace. The
+//! local checks (`cli_config_local`) report the script a hook runs as
+//! `hook_path`, and `hook_path_stale` when that file is gone or isn't the
+//! script next to the daemon executable (`expected_hook_path`). Paths are
+//! compared canonicalized, and case-insensitively on Windows.
+//! `repair_cli_hooks` rewrites just the path in the hook entries, after a
+//! snapshot (see `cli_config_backup`).
+
+use crate::cli_setup_preview::{is_notify_line, CLAUDE_SCRIPT, CODEX_SCRIPT};
+use crate::{cli_config_backup, cli_config_local, cli_hook_watch, cli_paths};
+use crate::{find_daemon_exe, get_home_dir};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+const KEYS: &[&str] = &["hook_path", "hook_path_exists", "expected_hook_path", "hook_path_stale"];
+
+/// Directory of the daemon executable, and of the hook scripts with it.
+/// Recorded at startup since the checks run without an `AppHandle`.
+static INSTALL_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+pub(crate) fn remember_install_dir(app: &AppHandle) {
+  let dir = find_daemon_exe(app).ok().and_then(|exe| exe.parent().map(Path::to_path_buf));
+  *INSTALL_DIR.lock().unwrap_or_else(|e| e.into_inner()) = dir;
+}
+
+fn forward_slash(path: &Path) -> String {
+  path.to_string_lossy().replace('\\', "/")
+}
+
+fn expected(script: &str) -> Option<PathBuf> {
+  let dir = INSTALL_DIR.lock().unwrap_or_else(|e| e.into_inner()).clone()?;
+  Some(dir.join(script))
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+  let canonical = |p: &Path| fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf());
+  let (a, b) = (canonical(a), canonical(b));
+  if cfg!(target_os = "windows") {
+    let key = |p: &Path| p.to_string_lossy().replace('/', "\\").to_lowercase();
+    key(&a) == key(&b)
+  } else {
+    a == b
+  }
+}
+
+/// The leading token of `command`, quoted or up to the first whitespace,
+/// and the text after it.
+fn split_first(command: &str) -> Option<(&str, &str)> {
+  let quote = command.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+  let end = command[1..].find(quote)? + 1;
+  Some((&command[1..end], &command[end + 1..]))
+}
+
+/// The script path of a hook `command` that runs the `hook` script. The
+/// daemon writes `node <path>` without quotes, so a path with spaces spans
+/// several words: unless quoted, everything after the interpreter up to
+/// the end of the script name is the path.
+pub(crate) fn script_arg(command: &str, hook: &str) -> Option<String> {
+  let command = command.trim();
+  let (first, rest) = split_first(command).unwrap_or_else(|| {
+    let end = command.find(char::is_whitespace).unwrap_or(command.len());
+    (&command[..end], &command[end..])
+  });
+  // The script run directly, without an interpreter
+  if first.contains(hook) {
+    return Some(first.to_string());
+  }
+  let rest = rest.trim_start();
+  if let Some((quoted, _)) = split_first(rest) {
+    return quoted.contains(hook).then(|| quoted.to_string());
+  }
+  let end = rest.find(hook)? + hook.len();
+  Some(rest[..end].to_string())
+}
+
+/// Add `hook_path`, `hook_path_exists`, `expected_hook_path` and
+/// `hook_path_stale` to a check result for a hook running `hook_path`.
+pub(crate) fn annotate(status: &mut Value, hook_path: &str, script: &str) {
+  let path = Path::new(hook_path);
+  let exists = path.is_file();
+  let expected = expected(script);
+  let stale = !exists || expected.as_deref().is_some_and(|e| !same_path(path, e));
+  status["hook_path"] = Value::String(hook_path.to_string());
+  status["hook_path_exists"] = Value::Bool(exists);
+  status["expected_hook_path"] = serde_json::json!(expected.as_deref().map(forward_slash));
+  status["hook_path_stale"] = Value::Bool(stale);
+}
+
+/// Copy the hook path keys of the local check onto the daemon's answer.
+pub(crate) fn merge_into(status: &mut Value, local: &Value) {
+  if !status.is_object() {
+    return;
+  }
+  for key in KEYS {
+    if let Some(value) = local.get(key) {
+      status[key] = value.clone();
+    }
+  }
+}
+
+/// Codex: swap the script path in the `notify` line, written either as is
+/// or escaped in a TOML basic string.
+fn rewrite_codex(content: &str, old: &str, new: &str) -> Result<String, String> {
+  let escaped = old.replace('\\', "\\\\");
+  let lines: Vec<String> = content
+    .split('\n')
+    .map(|line| match is_notify_line(line) && line.contains(CODEX_SCRIPT) {
+      true => line.replace(&escaped, new).replace(old, new),
+      false => line.to_string(),
+    })
+    .collect();
+  let new_content = lines.join("\n");
+  if new_content.parse::<toml::Table>().is_err() {
+    return Err("rewriting the notify line would break config.toml".to_string());
+  }
+  Ok(new_content)
+}
+
+/// Claude Code: swap the script path in every hook command running it.
+fn rewrite_claude(content: &str, old: &str, new: &str) -> Result<String, String> {
+  let mut settings: Value =
+    serde_json::from_str(content).map_err(|e| format!("无法读取 settings.json: {}", e))?;
+  let events = settings.get_mut("hooks").and_then(|h| h.as_object_mut());
+  for groups in events.into_iter().flat_map(|events| events.values_mut()) {
+    let groups = groups.as_array_mut().into_iter().flatten();
+    let hooks = groups.filter_map(|group| group.get_mut("hooks")?.as_array_mut()).flatten();
+    for hook in hooks {
+      let Some(command) = hook["command"].as_str().filter(|c| c.contains(CLAUDE_SCRIPT)) else {
+        continue;
+      };
+      hook["command"] = Value::String(command.replace(old, new));
+    }
+  }
+  serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())
+}
+
+struct Hook {
+  name: &'static str,
+  path: fn(&Path) -> PathBuf,
+  check: fn(&Path) -> Value,
+  rewrite: fn(&str, &str, &str) -> Result<String, String>,
+}
+
+const HOOKS: &[Hook] = &[
+  Hook {
+    name: "codex",
+    path: cli_paths::codex_config_path,
+    check: cli_config_local::check_codex,
+    rewrite: rewrite_codex,
+  },
+  Hook {
+    name: "claude",
+    path: cli_paths::claude_settings_path,
+    check: cli_config_local::check_claude,
+    rewrite: rewrite_claude,
+  },
+];
+
+fn repair(hook: &Hook, home: &Path) -> Value {
+  let status = (hook.check)(home);
+  if status["hook_path_stale"] != true {
+    return serde_json::json!({ "name": hook.name, "ok": true, "repaired": false });
+  }
+  let (Some(old), Some(new)) = (status["hook_path"].as_str(), status["expected_hook_path"].as_str())
+  else {
+    return serde_json::json!({
+      "name": hook.name,
+      "ok": false,
+      "error": "找不到当前安装的 hook 脚本",
+      "status": status,
+    });
+  };
+
+  let path = (hook.path)(home);
+  let rewritten = fs::read_to_string(&path)
+    .map_err(|e| e.to_string())
+    .and_then(|content| (hook.rewrite)(&content, old, new));
+  let new_content = match rewritten {
+    Ok(c) => c,
+    Err(e) => return serde_json::json!({ "name": hook.name, "ok": false, "error": e }),
+  };
+
+  let _own_write = cli_hook_watch::own_write(hook.name);
+  let backup = match cli_config_backup::snapshot(hook.name) {
+    Ok(backup) => backup,
+    Err(e) => {
+      let error = format!("配置备份失败: {}", e);
+      return serde_json::json!({ "name": hook.name, "ok": false, "error": error });
+    }
+  };
+  let tmp = path.with_extension("felay-tmp");
+  if let Err(e) = fs::write(&tmp, &new_content).and_then(|_| fs::rename(&tmp, &path)) {
+    let _ = fs::remove_file(&tmp);
+    return serde_json::json!({ "name": hook.name, "ok": false, "error": e.to_string() });
+  }
+
+  let status = (hook.check)(home);
+  serde_json::json!({
+    "name": hook.name,
+    "ok": status["hook_path_stale"] != true,
+    "repaired": true,
+    "from": old,
+    "to": new,
+    "backup": backup,
+    "status": status,
+  })
+}
+
+/// Point stale Codex / Claude Code hooks at this install's scripts. One
+/// result per CLI with `repaired`, the `from` / `to` paths, the `backup`
+/// taken and the re-checked `status`.
+#[tauri::command]
+pub(crate) fn repair_cli_hooks(app: AppHandle) -> Value {
+  let Some(home) = get_home_dir().map(PathBuf::from) else {
+    return serde_json::json!({ "ok": false, "error": "cannot determine home directory" });
+  };
+  // The daemon may have been installed since startup
+  remember_install_dir(&app);
+  let results: Vec<Value> = HOOKS.iter().map(|hook| repair(hook, &home)).collect();
+  serde_json::json!({ "ok": results.iter().all(|r| r["ok"] == true), "results": results })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const HOOK: &str = CLAUDE_SCRIPT;
+
+  #[test]
+  fn script_arg_plain_path() {
+    let command = "node /opt/felay/felay-claude-hook.js";
+    assert_eq!(script_arg(command, HOOK).as_deref(), Some("/opt/felay/felay-claude-hook.js"));
+  }
+
+  #[test]
+  fn script_arg_unquoted_path_with_spaces() {
+    let command = "node C:/Program Files/Felay/felay-claude-hook.js";
+    let expected = "C:/Program Files/Felay/felay-claude-hook.js";
+    assert_eq!(script_arg(command, HOOK).as_deref(), Some(expected));
+  }
+
+  #[test]
+  fn script_arg_stops_at_the_script_name() {
+    let command = "node /home/a b/felay-claude-hook.js --verbose";
+    assert_eq!(script_arg(command, HOOK).as_deref(), Some("/home/a b/felay-claude-hook.js"));
+  }
+
+  #[test]
+  fn script_arg_quoted_paths() {
+    let double = r#"node "C:/Program Files/Felay/felay-claude-hook.js" --x"#;
+    let expected = "C:/Program Files/Felay/felay-claude-hook.js";
+    assert_eq!(script_arg(double, HOOK).as_deref(), Some(expected));
+    let single = "node '/Users/me/My Apps/felay-claude-hook.js'";
+    let expected = "/Users/me/My Apps/felay-claude-hook.js";
+    assert_eq!(script_arg(single, HOOK).as_deref(), Some(expected));
+  }
+
+  #[test]
+  fn script_arg_quoted_interpreter() {
+    let command = r#""C:\Program Files\nodejs\node.exe" C:\Felay App\felay-claude-hook.js"#;
+    let expected = r"C:\Felay App\felay-claude-hook.js";
+    assert_eq!(script_arg(command, HOOK).as_deref(), Some(expected));
+  }
+
+  #[test]
+  fn script_arg_windows_backslash_path() {
+    let command = r"node C:\Users\me\AppData\Local\Felay\felay-claude-hook.js";
+    let expected = r"C:\Users\me\AppData\Local\Felay\felay-claude-hook.js";
+    assert_eq!(script_arg(command, HOOK).as_deref(), Some(expected));
+  }
+
+  #[test]
+  fn script_arg_script_without_interpreter() {
+    let command = "/opt/felay/felay-claude-hook.js";
+    assert_eq!(script_arg(command, HOOK).as_deref(), Some(command));
+  }
+
+  #[test]
+  fn script_arg_other_hook() {
+    assert_eq!(script_arg("node /opt/other/hook.js", HOOK), None);
+    assert_eq!(script_arg(r#"node "/opt/other/hook.js""#, HOOK), None);
+  }
+
+  #[test]
+  fn rewrite_claude_replaces_a_path_with_spaces_whole() {
+    let old = "C:/Program Files/Felay/felay-claude-hook.js";
+    let new = "D:/Felay/felay-claude-hook.js";
+    let settings = serde_json::json!({
+      "hooks": { "Stop": [{ "hooks": [
+        { "type": "command", "command": format!("node {}", old) },
+        { "type": "command", "command": "node C:/Program Files/other.js" },
+      ]}]},
+    });
+    let rewritten = rewrite_claude(&settings.to_string(), old, new).unwrap();
+    let rewritten: Value = serde_json::from_str(&rewritten).unwrap();
+    let hooks = &rewritten["hooks"]["Stop"][0]["hooks"];
+    assert_eq!(hooks[0]["command"], format!("node {}", new));
+    assert_eq!(hooks[1]["command"], "node C:/Program Files/other.js");
+  }
+}
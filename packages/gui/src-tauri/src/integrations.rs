@@ -0,0 +1,280 @@
+//! CLI integrations whose config file the GUI can check and open.
+//!
+//! Codex, Claude Code and Gemini CLI are built in; their config is located as
+//! `cli_paths` describes and checked by the daemon. Users can add their own
+//! in `~/.felay/integrations.json`, an array of
+//! `{ "name", "configPath", "template" }`. `configPath` is absolute or
+//! relative to the home directory (`~/` allowed) and must stay inside the
+//! home directory; `template` is written when the file doesn't exist yet.
+//! Custom integrations are only checked locally, for the file's presence.
+
+use crate::{check_claude_config, check_codex_config, check_gemini_config, cli_paths};
+use crate::{editor, get_home_dir};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+const INTEGRATIONS_FILE: &str = "integrations.json";
+
+struct Builtin {
+  name: &'static str,
+  label: &'static str,
+  path: fn(&Path) -> PathBuf,
+  template: &'static str,
+  check: fn() -> Value,
+}
+
+const BUILTINS: &[Builtin] = &[
+  Builtin {
+    name: "codex",
+    label: "Codex",
+    path: cli_paths::codex_config_path,
+    template: "",
+    check: check_codex_config,
+  },
+  Builtin {
+    name: "claude",
+    label: "Claude Code",
+    path: cli_paths::claude_settings_path,
+    template: "{}",
+    check: check_claude_config,
+  },
+  Builtin {
+    name: "gemini",
+    label: "Gemini CLI",
+    path: cli_paths::gemini_settings_path,
+    template: "{}",
+    check: check_gemini_config,
+  },
+];
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CustomEntry {
+  name: String,
+  config_path: String,
+  #[serde(default)]
+  template: String,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Integration {
+  name: String,
+  label: String,
+  config_path: PathBuf,
+  builtin: bool,
+  exists: bool,
+  #[serde(skip)]
+  template: String,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct IntegrationList {
+  integrations: Vec<Integration>,
+  /// Custom entries that were skipped, and why.
+  errors: Vec<String>,
+}
+
+/// Resolve `.` and `..` without touching the filesystem.
+fn lexical_normalize(path: &Path) -> PathBuf {
+  let mut out = PathBuf::new();
+  for component in path.components() {
+    match component {
+      Component::CurDir => {}
+      Component::ParentDir => {
+        out.pop();
+      }
+      other => out.push(other),
+    }
+  }
+  out
+}
+
+/// Canonicalize the longest existing ancestor of `path`, so a symlink
+/// inside home can't point the rest of the path outside it.
+fn resolve_existing(path: &Path) -> PathBuf {
+  let mut existing = path;
+  let mut rest = Vec::new();
+  loop {
+    if let Ok(canonical) = fs::canonicalize(existing) {
+      return rest.iter().rev().fold(canonical, |p, c| p.join(c));
+    }
+    match (existing.parent(), existing.file_name()) {
+      (Some(parent), Some(name)) => {
+        rest.push(name.to_os_string());
+        existing = parent;
+      }
+      _ => return path.to_path_buf(),
+    }
+  }
+}
+
+/// The absolute path of a custom `configPath`, if it is inside `home`.
+fn resolve_config_path(home: &Path, config_path: &str) -> Result<PathBuf, String> {
+  let raw = config_path.trim();
+  if raw.is_empty() {
+    return Err("configPath is empty".to_string());
+  }
+  let relative = raw.strip_prefix("~/").or_else(|| raw.strip_prefix("~\\"));
+  let joined = match relative {
+    Some(rest) => home.join(rest),
+    None => home.join(raw),
+  };
+  let path = resolve_existing(&lexical_normalize(&joined));
+  let home = fs::canonicalize(home).unwrap_or_else(|_| home.to_path_buf());
+  if path.starts_with(&home) && path != home {
+    Ok(path)
+  } else {
+    Err(format!("configPath {} is outside the home directory", raw))
+  }
+}
+
+fn custom_entries(home: &Path) -> (Vec<CustomEntry>, Vec<String>) {
+  let path = home.join(".felay").join(INTEGRATIONS_FILE);
+  let Ok(text) = fs::read_to_string(&path) else {
+    return (Vec::new(), Vec::new());
+  };
+  match serde_json::from_str::<Vec<CustomEntry>>(&text) {
+    Ok(entries) => (entries, Vec::new()),
+    Err(e) => (Vec::new(), vec![format!("{}: {}", INTEGRATIONS_FILE, e)]),
+  }
+}
+
+fn all(home: &Path) -> IntegrationList {
+  let mut integrations: Vec<Integration> = BUILTINS
+    .iter()
+    .map(|b| {
+      let config_path = (b.path)(home);
+      Integration {
+        name: b.name.to_string(),
+        label: b.label.to_string(),
+        exists: config_path.exists(),
+        config_path,
+        builtin: true,
+        template: b.template.to_string(),
+      }
+    })
+    .collect();
+
+  let (entries, mut errors) = custom_entries(home);
+  for entry in entries {
+    let name = entry.name.trim().to_string();
+    if name.is_empty() {
+      errors.push("integration with an empty name".to_string());
+      continue;
+    }
+    if integrations.iter().any(|i| i.name == name) {
+      errors.push(format!("{}: name already in use", name));
+      continue;
+    }
+    match resolve_config_path(home, &entry.config_path) {
+      Ok(config_path) => integrations.push(Integration {
+        label: name.clone(),
+        name,
+        exists: config_path.exists(),
+        config_path,
+        builtin: false,
+        template: entry.template,
+      }),
+      Err(e) => errors.push(format!("{}: {}", name, e)),
+    }
+  }
+  IntegrationList {
+    integrations,
+    errors,
+  }
+}
+
+fn find(name: &str) -> Result<Integration, String> {
+  let home = get_home_dir().ok_or("cannot determine home directory")?;
+  all(Path::new(&home))
+    .integrations
+    .into_iter()
+    .find(|i| i.name == name)
+    .ok_or_else(|| format!("unknown integration: {}", name))
+}
+
+/// Absolute path of the config file of integration `name`.
+pub(crate) fn config_path(name: &str) -> Result<PathBuf, String> {
+  find(name).map(|i| i.config_path)
+}
+
+/// Built-in and custom integrations, plus any custom entries that were
+/// rejected.
+#[tauri::command]
+pub(crate) fn list_integrations() -> IntegrationList {
+  match get_home_dir() {
+    Some(home) => all(Path::new(&home)),
+    None => IntegrationList {
+      integrations: Vec::new(),
+      errors: vec!["cannot determine home directory".to_string()],
+    },
+  }
+}
+
+/// The daemon's check for a built-in integration (null when it isn't
+/// running); for a custom one, whether its config file exists.
+#[tauri::command]
+pub(crate) fn check_cli_config(name: String) -> Value {
+  if let Some(builtin) = BUILTINS.iter().find(|b| b.name == name) {
+    return (builtin.check)();
+  }
+  match find(&name) {
+    Ok(integration) => serde_json::json!({
+      "name": integration.name,
+      "configPath": integration.config_path,
+      "exists": integration.exists,
+      "source": "local",
+    }),
+    Err(e) => serde_json::json!({ "ok": false, "error": e }),
+  }
+}
+
+/// Open an integration's config file in the preferred editor (see
+/// `editor`), creating it from the template first if its directory exists.
+#[tauri::command]
+pub(crate) fn open_cli_config_file(name: String) -> Value {
+  let integration = match find(&name) {
+    Ok(i) => i,
+    Err(e) => return serde_json::json!({ "ok": false, "error": e }),
+  };
+  let config_path = &integration.config_path;
+
+  if !config_path.exists() {
+    // Create the file so the user can edit it
+    let dir_exists = config_path.parent().is_some_and(|d| d.is_dir());
+    if !dir_exists {
+      let dir = config_path.parent().unwrap_or(config_path).to_string_lossy();
+      return serde_json::json!({
+        "ok": false,
+        "error": format!("{} 目录不存在，请先安装 {}", dir, integration.label),
+      });
+    }
+    if let Err(e) = fs::write(config_path, &integration.template) {
+      let file = config_path.file_name().unwrap_or_default().to_string_lossy();
+      return serde_json::json!({ "ok": false, "error": format!("无法创建 {}: {}", file, e) });
+    }
+  }
+
+  editor::open(config_path)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn builtins_use_relocated_dirs() {
+    cli_paths::with_relocated_dirs(|home, codex, claude| {
+      fs::write(claude.join("settings.json"), "{}").unwrap();
+      let list = all(home);
+      let builtin = |name: &str| list.integrations.iter().find(|i| i.name == name).unwrap();
+      assert_eq!(builtin("codex").config_path, codex.join("config.toml"));
+      assert!(!builtin("codex").exists);
+      assert_eq!(builtin("claude").config_path, claude.join("settings.json"));
+      assert!(builtin("claude").exists);
+      assert!(list.errors.is_empty());
+    });
+  }
+}
@@ -0,0 +1,143 @@
+//! Optional trace of daemon IPC requests in `~/.felay/gui.log`.
+//!
+//! Off unless `FELAY_GUI_DEBUG` is set (to anything but "0"). Each request
+//! gets one line: time, level, request type, duration, outcome and the
+//! payload passed through `sanitize_value`, so `save_bot` / `save_config`
+//! secrets never reach the file. Replies are not logged. Requests whose
+//! reply carries a secret by design are listed in `SENSITIVE_REQUESTS` and
+//! logged with their type, timing and outcome only, marked `sensitive`.
+
+use crate::config_backup::format_timestamp;
+use crate::{get_home_dir, sanitize_value, unix_now, SanitizeOptions};
+use serde_json::Value;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+
+pub(crate) const GUI_LOG_FILE: &str = "gui.log";
+
+/// Requests answered with a plaintext secret. Neither their payload nor
+/// anything else about the reply goes into the trace.
+const SENSITIVE_REQUESTS: &[&str] = &["get_bot_secret_request"];
+
+/// Serializes writers so lines from concurrent requests don't interleave.
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+fn enabled() -> bool {
+  static ENABLED: OnceLock<bool> = OnceLock::new();
+  *ENABLED.get_or_init(|| {
+    std::env::var("FELAY_GUI_DEBUG").is_ok_and(|v| !v.trim().is_empty() && v.trim() != "0")
+  })
+}
+
+fn log_path() -> Option<PathBuf> {
+  let home = get_home_dir()?;
+  Some(PathBuf::from(home).join(".felay").join(GUI_LOG_FILE))
+}
+
+/// `(level, outcome)` of a request given its reply.
+fn outcome(
+  reply: Option<&Value>,
+  elapsed: Duration,
+  timeout: Duration,
+) -> (&'static str, &'static str) {
+  match reply {
+    Some(reply) if reply["type"] == "error" => ("WARN", "error"),
+    Some(_) => ("INFO", "ok"),
+    None if elapsed >= timeout => ("WARN", "timeout"),
+    None => ("WARN", "no_reply"),
+  }
+}
+
+/// The trace line for one request.
+fn trace_line(
+  now: u64,
+  request: &str,
+  elapsed: Duration,
+  timeout: Duration,
+  reply: Option<&Value>,
+) -> String {
+  let parsed: Value = serde_json::from_str(request).unwrap_or_default();
+  let request_type = parsed["type"].as_str().unwrap_or("?");
+  let (level, outcome) = outcome(reply, elapsed, timeout);
+  let mut line = format!(
+    "{} {:<5} ipc {} {}ms {}",
+    format_timestamp(now),
+    level,
+    request_type,
+    elapsed.as_millis(),
+    outcome,
+  );
+  if SENSITIVE_REQUESTS.contains(&request_type) {
+    line.push_str(" sensitive");
+    return line;
+  }
+
+  let mut payload = parsed.get("payload").cloned().unwrap_or(Value::Null);
+  sanitize_value(&mut payload, &SanitizeOptions::default());
+  if !payload.is_null() {
+    line.push_str(&format!(" payload={}", payload));
+  }
+  line
+}
+
+/// Log one request, if tracing is on. `timeout` is the reply wait the
+/// request had, to tell a timeout from a dropped connection.
+pub(crate) fn record(request: &str, started: Instant, timeout: Duration, reply: Option<&Value>) {
+  if !enabled() {
+    return;
+  }
+  let line = trace_line(unix_now(), request, started.elapsed(), timeout, reply);
+  let Some(path) = log_path() else {
+    return;
+  };
+  let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+  if fs::metadata(&path).is_ok_and(|m| m.len() > MAX_LOG_BYTES) {
+    let _ = fs::rename(&path, path.with_extension("log.1"));
+  }
+  let written = OpenOptions::new()
+    .create(true)
+    .append(true)
+    .open(&path)
+    .and_then(|mut f| writeln!(f, "{}", line));
+  if let Err(e) = written {
+    gui_println!("[gui] failed to write {}: {}", GUI_LOG_FILE, e);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const TIMEOUT: Duration = Duration::from_secs(10);
+
+  #[test]
+  fn secret_requests_are_marked_sensitive_and_logged_without_payload() {
+    let request = serde_json::json!({
+      "type": "get_bot_secret_request",
+      "payload": { "botType": "push", "botId": "p1", "field": "secret" },
+    })
+    .to_string();
+    let reply = serde_json::json!({
+      "type": "get_bot_secret_response",
+      "payload": { "ok": true, "value": "s3cret" },
+    });
+    let line = trace_line(0, &request, Duration::from_millis(3), TIMEOUT, Some(&reply));
+    assert!(line.ends_with("ipc get_bot_secret_request 3ms ok sensitive"), "{}", line);
+    assert!(!line.contains("payload="));
+    assert!(!line.contains("s3cret"));
+  }
+
+  #[test]
+  fn other_requests_keep_their_sanitized_payload() {
+    let request = r#"{"type":"unbind_bot_request","payload":{"sessionId":"s1","botType":"push"}}"#;
+    let line = trace_line(0, request, Duration::from_millis(3), TIMEOUT, None);
+    assert!(line.contains(" payload={"), "{}", line);
+    assert!(line.contains(r#""sessionId":"s1""#), "{}", line);
+    assert!(!line.contains("sensitive"));
+  }
+}
@@ -0,0 +1,155 @@
+//! First-launch and upgrade detection, for onboarding and "what's new".
+//!
+//! `~/.felay/gui-state.json` records the last app version the user has seen.
+//! It is read once in `setup()`; the launch context stays fixed for the
+//! session, and the file only moves forward once the frontend calls
+//! `ack_launch`, so a crash before the popup was shown shows it again.
+//! It also remembers whether a session has ever been seen, for onboarding,
+//! and the active daemon profile (see `profiles`), and what the window's
+//! close button does.
+
+use crate::{get_home_dir, version_gt};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct GuiState {
+  #[serde(default)]
+  last_seen_version: Option<String>,
+  #[serde(default)]
+  first_launch_done: bool,
+  #[serde(default)]
+  session_seen: bool,
+  /// Read by the daemon too, to find `config.json`.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  active_profile: Option<String>,
+  /// Hide the window on close instead of quitting; the tray's "打开面板"
+  /// shows it again.
+  #[serde(default)]
+  close_to_tray: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct LaunchContext {
+  first_launch: bool,
+  /// The previously seen version when this run is a newer one.
+  upgraded_from: Option<String>,
+  current_version: String,
+}
+
+static CONTEXT: Mutex<Option<LaunchContext>> = Mutex::new(None);
+
+/// Set once `session_seen` is known to be recorded, so polls don't re-read
+/// the file.
+static SESSION_SEEN: AtomicBool = AtomicBool::new(false);
+
+fn state_path() -> Option<PathBuf> {
+  let home = get_home_dir()?;
+  Some(PathBuf::from(home).join(".felay").join("gui-state.json"))
+}
+
+fn read_state() -> GuiState {
+  state_path()
+    .and_then(|path| fs::read_to_string(path).ok())
+    .and_then(|text| serde_json::from_str(&text).ok())
+    .unwrap_or_default()
+}
+
+fn write_state(state: &GuiState) -> Result<(), String> {
+  let path = state_path().ok_or("cannot determine home directory")?;
+  if let Some(dir) = path.parent() {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+  }
+  let text = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
+  let tmp = path.with_extension("json.tmp");
+  fs::write(&tmp, text).map_err(|e| e.to_string())?;
+  fs::rename(&tmp, &path).map_err(|e| e.to_string())
+}
+
+fn compute() -> LaunchContext {
+  let state = read_state();
+  LaunchContext {
+    first_launch: !state.first_launch_done,
+    upgraded_from: state
+      .last_seen_version
+      .filter(|last| version_gt(CURRENT_VERSION, last)),
+    current_version: CURRENT_VERSION.to_string(),
+  }
+}
+
+/// Read `gui-state.json` and fix the launch context for this session.
+pub(crate) fn init() {
+  let context = compute();
+  *CONTEXT.lock().unwrap_or_else(|e| e.into_inner()) = Some(context);
+}
+
+#[tauri::command]
+pub(crate) fn get_launch_context() -> LaunchContext {
+  CONTEXT
+    .lock()
+    .unwrap_or_else(|e| e.into_inner())
+    .get_or_insert_with(compute)
+    .clone()
+}
+
+/// The frontend has shown onboarding / what's new: record this version.
+#[tauri::command]
+pub(crate) fn ack_launch() -> Result<(), String> {
+  let mut state = read_state();
+  state.last_seen_version = Some(CURRENT_VERSION.to_string());
+  state.first_launch_done = true;
+  write_state(&state)
+}
+
+/// Record that the daemon has reported a session.
+pub(crate) fn record_session_seen() {
+  if SESSION_SEEN.swap(true, Ordering::Relaxed) {
+    return;
+  }
+  let mut state = read_state();
+  if !state.session_seen {
+    state.session_seen = true;
+    if let Err(e) = write_state(&state) {
+      gui_println!("[gui] failed to record the first session: {}", e);
+      SESSION_SEEN.store(false, Ordering::Relaxed);
+    }
+  }
+}
+
+/// Whether any session has ever been seen, in this run or an earlier one.
+pub(crate) fn session_seen() -> bool {
+  SESSION_SEEN.load(Ordering::Relaxed) || read_state().session_seen
+}
+
+/// The profile picked with `switch_profile`; None for the default one.
+pub(crate) fn active_profile() -> Option<String> {
+  read_state().active_profile
+}
+
+pub(crate) fn set_active_profile(profile: Option<String>) -> Result<(), String> {
+  let mut state = read_state();
+  state.active_profile = profile;
+  write_state(&state)
+}
+
+/// Whether closing the main window hides it to the tray rather than quitting.
+pub(crate) fn close_to_tray() -> bool {
+  read_state().close_to_tray
+}
+
+#[tauri::command]
+pub(crate) fn get_close_behavior() -> bool {
+  close_to_tray()
+}
+
+#[tauri::command]
+pub(crate) fn set_close_behavior(close_to_tray: bool) -> Result<(), String> {
+  let mut state = read_state();
+  state.close_to_tray = close_to_tray;
+  write_state(&state)
+}
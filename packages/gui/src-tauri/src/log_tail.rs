@@ -0,0 +1,164 @@
+//! Live tail of a felay log file for the troubleshooting view.
+//!
+//! A background thread polls the file, starting from its current end, and
+//! emits each appended line as a `daemon-log-line` event. Rotation is
+//! detected when the file is replaced (different file identity) or
+//! truncated (shorter than what was already read); the new file is then
+//! read from the start. The tail is an operation (see `operations`), so
+//! `cancel_operation` stops it as `stop_tail_daemon_log` does.
+
+use crate::operations::{self, OperationHandle};
+use crate::{get_home_dir, os_open, shutdown_requested, spawn_background};
+use serde::Serialize;
+use std::fs::{self, File, Metadata};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(300);
+
+pub(crate) const LOG_LINE_EVENT: &str = "daemon-log-line";
+
+/// Logs in `~/.felay` that may be tailed or opened.
+const TAILABLE_LOGS: &[&str] = &["proxy-debug.log", "proxy-hook-debug.log"];
+
+/// Bumped on every start/stop; a tail thread exits once it is outdated.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Serialize, Clone)]
+struct LogLine {
+  file: String,
+  line: String,
+}
+
+/// Identifies the file behind the path, to notice it being replaced.
+#[cfg(target_family = "unix")]
+fn file_identity(meta: &Metadata) -> Option<u128> {
+  use std::os::unix::fs::MetadataExt;
+  Some(u128::from(meta.ino()))
+}
+
+#[cfg(not(target_family = "unix"))]
+fn file_identity(meta: &Metadata) -> Option<u128> {
+  let created = meta.created().ok()?;
+  Some(created.duration_since(std::time::UNIX_EPOCH).ok()?.as_nanos())
+}
+
+struct Tail {
+  file: File,
+  identity: Option<u128>,
+  offset: u64,
+  /// Bytes after the last newline, held until the line is complete.
+  partial: Vec<u8>,
+}
+
+impl Tail {
+  fn open(path: &PathBuf, from_end: bool) -> Option<Self> {
+    let mut file = File::open(path).ok()?;
+    let meta = file.metadata().ok()?;
+    let offset = if from_end { meta.len() } else { 0 };
+    file.seek(SeekFrom::Start(offset)).ok()?;
+    Some(Tail {
+      file,
+      identity: file_identity(&meta),
+      offset,
+      partial: Vec::new(),
+    })
+  }
+
+  /// Complete lines appended since the last read.
+  fn read_lines(&mut self) -> Vec<String> {
+    let mut buf = Vec::new();
+    if let Ok(n) = self.file.read_to_end(&mut buf) {
+      self.offset += n as u64;
+    }
+    self.partial.extend_from_slice(&buf);
+
+    let Some(last_newline) = self.partial.iter().rposition(|b| *b == b'\n') else {
+      return Vec::new();
+    };
+    let rest = self.partial.split_off(last_newline + 1);
+    let complete = std::mem::replace(&mut self.partial, rest);
+    String::from_utf8_lossy(&complete)
+      .lines()
+      .map(|l| l.trim_end_matches('\r').to_string())
+      .collect()
+  }
+}
+
+fn tail(app: AppHandle, name: String, path: PathBuf, generation: u64, op: OperationHandle) {
+  let mut tail = Tail::open(&path, true);
+
+  while GENERATION.load(Ordering::Relaxed) == generation
+    && !op.is_cancelled()
+    && !shutdown_requested()
+  {
+    thread::sleep(POLL_INTERVAL);
+
+    let Ok(meta) = fs::metadata(&path) else {
+      // Removed (mid-rotation, or not created yet); wait for it to return
+      tail = None;
+      continue;
+    };
+    let rotated = tail
+      .as_ref()
+      .is_some_and(|t| file_identity(&meta) != t.identity || meta.len() < t.offset);
+    if tail.is_none() || rotated {
+      tail = Tail::open(&path, false);
+    }
+
+    let Some(t) = tail.as_mut() else {
+      continue;
+    };
+    for line in t.read_lines() {
+      let payload = LogLine {
+        file: name.clone(),
+        line,
+      };
+      if app.emit(LOG_LINE_EVENT, payload).is_err() {
+        return;
+      }
+    }
+  }
+}
+
+/// Start streaming `file` (default `proxy-debug.log`), replacing any
+/// running tail. Returns the tail's `operation_id` for `cancel_operation`.
+#[tauri::command]
+pub(crate) fn tail_daemon_log(app: AppHandle, file: Option<String>) -> Result<String, String> {
+  let name = file.unwrap_or_else(|| TAILABLE_LOGS[0].to_string());
+  if !TAILABLE_LOGS.contains(&name.as_str()) {
+    return Err(format!("cannot tail {}", name));
+  }
+  let home = get_home_dir().ok_or("Cannot determine home directory")?;
+  let path = PathBuf::from(home).join(".felay").join(&name);
+
+  let generation = GENERATION.fetch_add(1, Ordering::Relaxed) + 1;
+  let op = operations::begin(&app, "tail_daemon_log");
+  let operation_id = op.id().to_string();
+  spawn_background(move || tail(app, name, path, generation, op));
+  Ok(operation_id)
+}
+
+#[tauri::command]
+pub(crate) fn stop_tail_daemon_log() {
+  GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Open one of the known logs with the OS default app. Unlike the CLI
+/// config files, a missing log is an error rather than created empty.
+#[tauri::command]
+pub(crate) fn open_log_file(name: String) -> Result<(), String> {
+  if !TAILABLE_LOGS.contains(&name.as_str()) {
+    return Err(format!("unknown log: {}", name));
+  }
+  let home = get_home_dir().ok_or("Cannot determine home directory")?;
+  let path = PathBuf::from(home).join(".felay").join(&name);
+  if !path.is_file() {
+    return Err(format!("{} 不存在（尚未产生日志）", name));
+  }
+  os_open::open(path.as_os_str()).map_err(|e| format!("无法打开文件: {}", e))
+}
@@ -2,11 +2,17 @@
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::hash_map::RandomState;
+use std::collections::BTreeMap;
 use std::fs;
+use std::hash::{BuildHasher, Hasher};
 use std::io::{BufRead, BufReader, Write};
-use std::path::PathBuf;
-use std::thread;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 #[cfg(target_family = "unix")]
 use std::{env, os::unix::net::UnixStream};
 #[cfg(target_os = "windows")]
@@ -14,12 +20,69 @@ use std::{env, fs::OpenOptions};
 use tauri::{
   menu::{Menu, MenuItem},
   tray::{MouseButton, MouseButtonState, TrayIconEvent},
-  AppHandle, Manager,
+  AppHandle, Emitter, Manager, WindowEvent,
 };
-use tauri_plugin_dialog::DialogExt;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
 use zip::write::SimpleFileOptions;
 use zip::ZipWriter;
 
+/// `println!` that also keeps the line for the log bundle; see `recent_log`.
+macro_rules! gui_println {
+  ($($arg:tt)*) => {
+    $crate::recent_log::record(format!($($arg)*))
+  };
+}
+
+mod audit;
+mod bot_convert;
+mod bot_schema;
+mod capabilities;
+mod claude_project;
+mod cli_detect;
+mod cli_hook_watch;
+mod cli_config_backup;
+mod cli_config_lint;
+mod cli_config_local;
+mod cli_hooks;
+mod cli_paths;
+mod cli_setup_preview;
+mod config_autosave;
+mod config_backup;
+mod config_cache;
+mod config_diff;
+mod config_import;
+mod config_migrate;
+mod config_schema;
+mod config_text;
+mod config_undo;
+mod daemon_integrity;
+mod editor;
+mod felay_home;
+mod file_watcher;
+mod gui_settings;
+mod health;
+mod hook_paths;
+mod http;
+mod integrations;
+mod ipc_trace;
+mod launch_state;
+mod log_tail;
+mod onboarding;
+mod operations;
+mod os_open;
+mod profiles;
+mod project_overrides;
+mod quiet_hours;
+mod recent_log;
+mod release_notes;
+mod secret_scan;
+mod session_events;
+mod update_assets;
+mod update_download;
+mod update_scheduler;
+mod warnings;
+mod webhook;
+
 /* ── Structs ── */
 
 #[derive(Debug, Serialize)]
@@ -34,9 +97,12 @@ struct Session {
   interactive_bot_connected: Option<bool>,
   push_bot_id: Option<String>,
   push_enabled: Option<bool>,
+  /// Claude Code session whose cwd has project settings without Felay's
+  /// hook, shadowing the global one.
+  claude_project_config_issue: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
 struct DaemonSession {
   session_id: String,
@@ -55,6 +121,11 @@ struct DaemonSession {
 struct BotWarning {
   bot_id: String,
   message: String,
+  /// Stable hash of `bot_id + message`, filled in by the GUI for dismissal.
+  #[serde(default)]
+  hash: String,
+  #[serde(default)]
+  dismissed: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -101,7 +172,18 @@ struct DaemonLockFile {
   ipc: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum UpdateSource {
+  /// GitHub, or the `FELAY_UPDATE_URL` endpoint.
+  #[serde(alias = "network")]
+  Github,
+  /// `update_mirror_url`.
+  Mirror,
+  Cache,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct UpdateInfo {
   not_modified: bool,
   etag: String,
@@ -110,6 +192,199 @@ struct UpdateInfo {
   latest_version: String,
   release_url: String,
   release_notes: String,
+  /// Whether this result came from GitHub, the mirror or the on-disk cache.
+  source: UpdateSource,
+  /// Releases newer than the running version, counting the latest. 1 when
+  /// an update exists but the releases list couldn't be read.
+  #[serde(default)]
+  releases_behind: u32,
+  /// Tags of the skipped releases between this version and the latest,
+  /// newest first.
+  #[serde(default)]
+  intermediate_versions: Vec<String>,
+  /// The latest release's assets, classified by OS, architecture and
+  /// package type.
+  #[serde(default)]
+  assets: Vec<update_assets::AssetInfo>,
+  /// The asset for this OS and architecture; None if none clearly matches.
+  #[serde(default)]
+  recommended_asset: Option<update_assets::AssetInfo>,
+  /// The offered release is a pre-release (beta channel only).
+  #[serde(default)]
+  prerelease: bool,
+  /// The check failed and this is the last good result from the cache.
+  #[serde(default)]
+  stale: bool,
+  /// Age of that result.
+  #[serde(default)]
+  stale_age_secs: Option<u64>,
+  /// Why the check failed, when `stale`.
+  #[serde(default, skip_deserializing)]
+  stale_error: Option<UpdateError>,
+  /// An update exists but the user skipped this version or snoozed
+  /// reminders; the rest of the result is still the real data.
+  #[serde(default, skip_deserializing)]
+  suppressed: bool,
+  #[serde(default, skip_deserializing)]
+  suppressed_reason: Option<SuppressedReason>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum SuppressedReason {
+  Skipped,
+  Snoozed,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum UpdateErrorKind {
+  Timeout,
+  Dns,
+  Connect,
+  /// The proxy wants credentials.
+  ProxyAuth,
+  /// Certificate verification failed, often a proxy re-signing HTTPS.
+  Tls,
+  Http,
+  /// GitHub's API quota is used up (403 with no requests remaining, or 429).
+  RateLimited,
+  /// `disable_update_check` is set.
+  Disabled,
+  /// GitHub rejected `github_token` (401): it is wrong, expired or revoked.
+  BadToken,
+  Other,
+}
+
+/// Why an update check failed, so the UI can phrase it per cause.
+#[derive(Debug, Serialize, Clone)]
+struct UpdateError {
+  kind: UpdateErrorKind,
+  message: String,
+  /// HTTP status for `Http` errors.
+  status: Option<u16>,
+  /// When `RateLimited`, how long until the quota refills.
+  retry_after_secs: Option<u64>,
+}
+
+impl UpdateError {
+  fn other(message: impl Into<String>) -> Self {
+    UpdateError {
+      kind: UpdateErrorKind::Other,
+      message: message.into(),
+      status: None,
+      retry_after_secs: None,
+    }
+  }
+
+  fn http(status: reqwest::StatusCode) -> Self {
+    if status == reqwest::StatusCode::PROXY_AUTHENTICATION_REQUIRED {
+      return UpdateError {
+        kind: UpdateErrorKind::ProxyAuth,
+        message: http::hint(http::NetErrorKind::ProxyAuth).unwrap_or_default().to_string(),
+        status: Some(status.as_u16()),
+        retry_after_secs: None,
+      };
+    }
+    UpdateError {
+      kind: UpdateErrorKind::Http,
+      message: format!("Update endpoint returned {}", status),
+      status: Some(status.as_u16()),
+      retry_after_secs: None,
+    }
+  }
+
+  /// `http`, or `RateLimited` when the response says the quota is used up,
+  /// or `BadToken` when the request was `authed` and the token refused.
+  fn from_response(resp: &reqwest::Response, authed: bool) -> Self {
+    let status = resp.status();
+    if status == reqwest::StatusCode::UNAUTHORIZED && authed {
+      return UpdateError {
+        kind: UpdateErrorKind::BadToken,
+        message: "GitHub 拒绝了 gui-settings.json 中的 github_token，请检查它是否正确或已过期"
+          .to_string(),
+        status: Some(status.as_u16()),
+        retry_after_secs: None,
+      };
+    }
+    let header = |name: &str| {
+      resp.headers().get(name).and_then(|v| v.to_str().ok()).and_then(|v| v.trim().parse().ok())
+    };
+    let exhausted = status == reqwest::StatusCode::TOO_MANY_REQUESTS
+      || (status == reqwest::StatusCode::FORBIDDEN && header("x-ratelimit-remaining") == Some(0));
+    if !exhausted {
+      return UpdateError::http(status);
+    }
+    let retry_after_secs = header("retry-after")
+      .or_else(|| header("x-ratelimit-reset").map(|reset: u64| reset.saturating_sub(unix_now())));
+    UpdateError {
+      kind: UpdateErrorKind::RateLimited,
+      message: format!("Update endpoint rate limit exceeded ({})", status),
+      status: Some(status.as_u16()),
+      retry_after_secs,
+    }
+  }
+
+  fn disabled() -> Self {
+    UpdateError {
+      kind: UpdateErrorKind::Disabled,
+      message: "更新检查已在 gui-settings.json 中关闭 (disable_update_check)".to_string(),
+      status: None,
+      retry_after_secs: None,
+    }
+  }
+
+  fn from_reqwest(e: &reqwest::Error) -> Self {
+    let net_kind = http::error_kind(e);
+    let kind = match net_kind {
+      http::NetErrorKind::Timeout => UpdateErrorKind::Timeout,
+      http::NetErrorKind::Dns => UpdateErrorKind::Dns,
+      http::NetErrorKind::ProxyAuth => UpdateErrorKind::ProxyAuth,
+      http::NetErrorKind::Tls => UpdateErrorKind::Tls,
+      http::NetErrorKind::Connect => UpdateErrorKind::Connect,
+      http::NetErrorKind::Other => UpdateErrorKind::Other,
+    };
+    let message = match http::hint(net_kind) {
+      Some(hint) => format!("{} ({})", hint, e),
+      None => e.to_string(),
+    };
+    UpdateError {
+      kind,
+      message,
+      status: e.status().map(|s| s.as_u16()),
+      retry_after_secs: None,
+    }
+  }
+
+  /// Network hiccups and server errors are worth one retry; 4xx and
+  /// setting problems are not.
+  fn is_transient(&self) -> bool {
+    match self.kind {
+      UpdateErrorKind::Timeout | UpdateErrorKind::Dns | UpdateErrorKind::Connect => true,
+      UpdateErrorKind::Http => self.status.is_some_and(|s| s >= 500),
+      UpdateErrorKind::ProxyAuth
+      | UpdateErrorKind::Tls
+      | UpdateErrorKind::RateLimited
+      | UpdateErrorKind::Disabled
+      | UpdateErrorKind::BadToken
+      | UpdateErrorKind::Other => false,
+    }
+  }
+}
+
+/// `Err` when `disable_update_check` is set.
+fn ensure_update_check_enabled() -> Result<(), UpdateError> {
+  if gui_settings::with(|s| s.disable_update_check) {
+    return Err(UpdateError::disabled());
+  }
+  Ok(())
+}
+
+/// Last successful update check, persisted in `~/.felay/update-cache.json`.
+#[derive(Debug, Serialize, Deserialize)]
+struct UpdateCache {
+  checked_at: u64,
+  info: UpdateInfo,
 }
 
 /* ── Generic IPC response wrappers ── */
@@ -125,6 +400,39 @@ struct GenericOkResponse {
   payload: GenericOkPayload,
 }
 
+/// Result of a bot connectivity test. Older daemons only send `ok`/`error`,
+/// so every other field defaults to `None`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all(deserialize = "camelCase", serialize = "snake_case"))]
+struct TestBotResult {
+  ok: bool,
+  #[serde(default)]
+  error: Option<String>,
+  #[serde(default)]
+  bot_name: Option<String>,
+  #[serde(default)]
+  status_code: Option<u16>,
+  #[serde(default)]
+  latency_ms: Option<u64>,
+  #[serde(default)]
+  detail: Option<String>,
+}
+
+impl TestBotResult {
+  fn failed(error: &str) -> Self {
+    TestBotResult {
+      ok: false,
+      error: Some(error.to_string()),
+      ..Default::default()
+    }
+  }
+}
+
+#[derive(Debug, Deserialize)]
+struct TestBotResponse {
+  payload: TestBotResult,
+}
+
 /* ── Helpers ── */
 
 fn get_home_dir() -> Option<String> {
@@ -133,6 +441,13 @@ fn get_home_dir() -> Option<String> {
     .or_else(|| env::var("HOME").ok())
 }
 
+fn unix_now() -> u64 {
+  std::time::SystemTime::now()
+    .duration_since(std::time::UNIX_EPOCH)
+    .unwrap_or_default()
+    .as_secs()
+}
+
 fn get_lock_file_path() -> Option<PathBuf> {
   let home = get_home_dir()?;
   Some(PathBuf::from(home).join(".felay").join("daemon.json"))
@@ -150,76 +465,385 @@ fn default_ipc_path() -> Option<String> {
   }
 }
 
+/// Read `daemon.json`. The daemon writes it atomically, but older daemons
+/// rewrite it in place, so a file that doesn't parse is read again after a
+/// moment before giving up.
 fn read_lock_file() -> Option<DaemonLockFile> {
-  let lock_path = get_lock_file_path()?;
-  let lock_text = fs::read_to_string(lock_path).ok()?;
-  serde_json::from_str::<DaemonLockFile>(&lock_text).ok()
+  read_lock_file_at(&get_lock_file_path()?)
 }
 
-fn get_ipc_path() -> Option<String> {
-  read_lock_file()
-    .map(|lock| lock.ipc)
-    .or_else(default_ipc_path)
+fn read_lock_file_at(lock_path: &Path) -> Option<DaemonLockFile> {
+  const ATTEMPTS: u32 = 3;
+  for attempt in 1..=ATTEMPTS {
+    let lock_text = fs::read_to_string(lock_path).ok()?;
+    match serde_json::from_str::<DaemonLockFile>(&lock_text) {
+      Ok(lock) => return Some(lock),
+      Err(_) if attempt < ATTEMPTS => thread::sleep(Duration::from_millis(15)),
+      Err(_) => {}
+    }
+  }
+  None
 }
 
-/// Compare two semver strings: returns true if `a` > `b`.
-fn version_gt(a: &str, b: &str) -> bool {
-  let parse = |s: &str| -> Vec<u64> {
-    s.split('.').filter_map(|p| p.parse().ok()).collect()
-  };
-  let va = parse(a);
-  let vb = parse(b);
-  for i in 0..3 {
-    let a_part = va.get(i).copied().unwrap_or(0);
-    let b_part = vb.get(i).copied().unwrap_or(0);
-    if a_part > b_part {
-      return true;
+/// Whether `path` can name an IPC endpoint: a named pipe on Windows, an
+/// absolute socket path short enough for `sun_path` elsewhere.
+fn plausible_ipc_path(path: &str) -> bool {
+  #[cfg(target_os = "windows")]
+  {
+    let prefix = "\\\\.\\pipe\\";
+    path.len() > prefix.len()
+      && path.get(..prefix.len()).is_some_and(|p| p.eq_ignore_ascii_case(prefix))
+  }
+  #[cfg(target_family = "unix")]
+  {
+    let p = std::path::Path::new(path);
+    p.is_absolute() && path.len() < 104 && !p.is_dir()
+  }
+}
+
+/// Where the last IPC path came from, so a change of source is logged once
+/// rather than on every poll.
+static IPC_PATH_SOURCE: Mutex<Option<String>> = Mutex::new(None);
+
+fn log_ipc_path_source(path: &str, source: &str) {
+  let entry = format!("{} ({})", path, source);
+  let mut last = IPC_PATH_SOURCE.lock().unwrap_or_else(|e| e.into_inner());
+  if last.as_deref() != Some(entry.as_str()) {
+    gui_println!("[gui] IPC path: {}", entry);
+    *last = Some(entry);
+  }
+}
+
+/// `FELAY_IPC_PATH` (for a sandboxed daemon in development and tests), then
+/// the lock file, then the platform default.
+fn get_ipc_path() -> Option<String> {
+  let mut ignored = None;
+  if let Ok(path) = env::var("FELAY_IPC_PATH") {
+    let path = path.trim();
+    if plausible_ipc_path(path) {
+      log_ipc_path_source(path, "FELAY_IPC_PATH");
+      return Some(path.to_string());
     }
-    if a_part < b_part {
-      return false;
+    if !path.is_empty() {
+      ignored = Some(format!("; FELAY_IPC_PATH={} is not a socket or pipe path", path));
+    }
+  }
+  let (path, source) = match read_lock_file() {
+    Some(lock) => (lock.ipc, "daemon.json"),
+    None => (default_ipc_path()?, "default"),
+  };
+  log_ipc_path_source(&path, &format!("{}{}", source, ignored.unwrap_or_default()));
+  Some(path)
+}
+
+const DEFAULT_UPDATE_URL: &str = "https://api.github.com/repos/zqq-nuli/Felay/releases/latest";
+
+/// Resolve the release endpoint used by `check_update`.
+/// `FELAY_UPDATE_URL` lets forks and air-gapped installs point at a mirror
+/// that serves the same JSON shape; it must be https.
+fn update_endpoint() -> Result<String, String> {
+  match env::var("FELAY_UPDATE_URL") {
+    Ok(url) if !url.trim().is_empty() => {
+      let url = url.trim();
+      if !url.starts_with("https://") {
+        return Err("FELAY_UPDATE_URL must start with https://".to_string());
+      }
+      Ok(url.to_string())
     }
+    _ => Ok(DEFAULT_UPDATE_URL.to_string()),
+  }
+}
+
+/// The latest-release endpoint of `update_mirror_url`, if set; it must be
+/// https.
+fn update_mirror_endpoint() -> Result<Option<String>, String> {
+  let base = gui_settings::with(|s| s.update_mirror_url.clone());
+  let Some(base) = base.map(|b| b.trim().trim_end_matches('/').to_string()) else {
+    return Ok(None);
+  };
+  if base.is_empty() {
+    return Ok(None);
+  }
+  if !base.starts_with("https://") {
+    return Err("update_mirror_url must start with https://".to_string());
+  }
+  Ok(Some(format!("{}/releases/latest", base)))
+}
+
+/// Where an update check goes and the token it may carry.
+struct UpdateTarget {
+  /// `update_mirror_endpoint`, asked first.
+  mirror: Option<String>,
+  /// `update_endpoint`, GitHub unless `FELAY_UPDATE_URL` says otherwise.
+  github: String,
+  /// `github_token`, sent only to `token_origin`.
+  token: Option<String>,
+  token_origin: reqwest::Url,
+}
+
+impl UpdateTarget {
+  /// The endpoints and token from the environment and gui-settings. An
+  /// invalid mirror is logged and left out.
+  fn from_settings() -> Result<Self, UpdateError> {
+    let mirror = update_mirror_endpoint().unwrap_or_else(|e| {
+      gui_println!("[gui] update mirror ignored: {}", e);
+      None
+    });
+    Ok(UpdateTarget {
+      mirror,
+      github: update_endpoint().map_err(UpdateError::other)?,
+      token: http::github_token(),
+      token_origin: http::github_api_origin(),
+    })
+  }
+
+  /// The token to send with a request to `url`.
+  fn token_for(&self, url: &str) -> Option<String> {
+    http::token_for(url, &self.token_origin, self.token.as_deref())
   }
-  false
+}
+
+/// The releases list next to a `.../releases/latest` endpoint, for counting
+/// skipped releases and for the beta channel. Mirrors with another layout
+/// have none.
+fn releases_list_endpoint(latest: &str, per_page: u32) -> Option<String> {
+  latest.strip_suffix("/latest").map(|base| format!("{}?per_page={}", base, per_page))
+}
+
+fn update_channel() -> gui_settings::UpdateChannel {
+  gui_settings::with(|s| s.update_channel)
+}
+
+/// The version in a tag like "v0.1.0-beta.2", pre-release included.
+fn tag_version(tag: &str) -> &str {
+  tag.trim_start_matches(['v', 'V'])
+}
+
+/// Order two versions by SemVer 2.0 precedence. Build metadata is ignored,
+/// missing minor/patch numbers count as 0, and a pre-release sorts before
+/// its release: 1.0.0-alpha < 1.0.0-alpha.1 < 1.0.0-beta.2 < 1.0.0-beta.11
+/// < 1.0.0-rc.1 < 1.0.0.
+fn version_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+  use std::cmp::Ordering;
+
+  let split = |s: &str| -> (Vec<u64>, Option<String>) {
+    let s = tag_version(s.trim());
+    let s = s.split('+').next().unwrap_or_default();
+    let (core, pre) = match s.split_once('-') {
+      Some((core, pre)) => (core, Some(pre.to_string())),
+      None => (s, None),
+    };
+    let mut numbers: Vec<u64> = core.split('.').map(|p| p.parse().unwrap_or(0)).collect();
+    numbers.resize(3.max(numbers.len()), 0);
+    (numbers, pre)
+  };
+  // Numeric identifiers compare as numbers and sort before alphanumeric ones
+  let identifier = |a: &str, b: &str| match (a.parse::<u64>(), b.parse::<u64>()) {
+    (Ok(x), Ok(y)) => x.cmp(&y),
+    (Ok(_), Err(_)) => Ordering::Less,
+    (Err(_), Ok(_)) => Ordering::Greater,
+    (Err(_), Err(_)) => a.cmp(b),
+  };
+
+  let (core_a, pre_a) = split(a);
+  let (core_b, pre_b) = split(b);
+  core_a.cmp(&core_b).then_with(|| match (pre_a, pre_b) {
+    (None, None) => Ordering::Equal,
+    (None, Some(_)) => Ordering::Greater,
+    (Some(_), None) => Ordering::Less,
+    (Some(pa), Some(pb)) => {
+      let (mut ia, mut ib) = (pa.split('.'), pb.split('.'));
+      loop {
+        match (ia.next(), ib.next()) {
+          (None, None) => return Ordering::Equal,
+          // A larger set of identifiers wins when the rest are equal
+          (None, Some(_)) => return Ordering::Less,
+          (Some(_), None) => return Ordering::Greater,
+          (Some(x), Some(y)) => match identifier(x, y) {
+            Ordering::Equal => continue,
+            other => return other,
+          },
+        }
+      }
+    }
+  })
+}
+
+/// Whether version `a` is newer than `b` (see `version_cmp`).
+fn version_gt(a: &str, b: &str) -> bool {
+  version_cmp(a, b) == std::cmp::Ordering::Greater
 }
 
 /// Remove sensitive fields from a config JSON string.
 fn sanitize_config(raw: &str) -> String {
   if let Ok(mut json) = serde_json::from_str::<Value>(raw) {
-    sanitize_value(&mut json);
+    sanitize_value(&mut json, &SanitizeOptions::default());
     serde_json::to_string_pretty(&json).unwrap_or_else(|_| raw.to_string())
   } else {
     raw.to_string()
   }
 }
 
-fn sanitize_value(value: &mut Value) {
-  const SENSITIVE: &[&str] = &["appSecret", "encryptKey", "secret", "webhook"];
+/// Config keys whose values must never leave the machine in logs or audits.
+/// Matched case-insensitively as substrings, so `appSecret` also covers
+/// `clientSecret`; `sensitive_keys` in gui-settings and the bot fields the
+/// daemon's schema marks sensitive (see `bot_schema`) add more.
+const SENSITIVE_KEYS: &[&str] = &[
+  "appSecret",
+  "encryptKey",
+  "secret",
+  "webhook",
+  "token",
+  "apiKey",
+  "password",
+];
+
+fn is_sensitive_key(key: &str) -> bool {
+  let key = key.to_lowercase();
+  let matches = |s: &str| key.contains(&s.to_lowercase());
+  SENSITIVE_KEYS.iter().any(|s| matches(s))
+    || gui_settings::with(|g| g.sensitive_keys.iter().any(|s| !s.is_empty() && matches(s)))
+    || bot_schema::is_sensitive_field(&key)
+}
+
+/// How `sanitize_value` masks secrets.
+#[derive(Debug, Clone, Copy)]
+struct SanitizeOptions {
+  /// Keep the first and last two characters of values longer than 8
+  /// (`ab12…f9`), so a log bundle still shows which credential was set.
+  partial_reveal: bool,
+}
+
+impl Default for SanitizeOptions {
+  fn default() -> Self {
+    SanitizeOptions {
+      partial_reveal: true,
+    }
+  }
+}
+
+fn mask_secret(value: &str, options: &SanitizeOptions) -> String {
+  let chars: Vec<char> = value.chars().collect();
+  if options.partial_reveal && chars.len() > 8 {
+    let head: String = chars[..2].iter().collect();
+    let tail: String = chars[chars.len() - 2..].iter().collect();
+    format!("{}…{}", head, tail)
+  } else {
+    "***".to_string()
+  }
+}
+
+/// Whether a value looks like the output of `mask_secret` (or the daemon's
+/// `abc***xyz` masking) rather than a real secret.
+fn is_masked_secret(value: &str) -> bool {
+  value.contains("***") || value.contains('…')
+}
+
+/// Mask sensitive keys, and secrets embedded in any other string (a webhook
+/// URL pasted into a description).
+fn sanitize_value(value: &mut Value, options: &SanitizeOptions) {
   match value {
     Value::Object(map) => {
       for (k, v) in map.iter_mut() {
-        if SENSITIVE.iter().any(|s| k.contains(s)) {
-          if v.is_string() && !v.as_str().unwrap_or("").is_empty() {
-            *v = Value::String("***".to_string());
+        if is_sensitive_key(k) {
+          if let Some(s) = v.as_str().filter(|s| !s.is_empty()) {
+            *v = Value::String(mask_secret(s, options));
           }
         } else {
-          sanitize_value(v);
+          sanitize_value(v, options);
         }
       }
     }
     Value::Array(arr) => {
       for v in arr.iter_mut() {
-        sanitize_value(v);
+        sanitize_value(v, options);
+      }
+    }
+    Value::String(s) => {
+      if let Cow::Owned(masked) = secret_scan::mask_secrets(s, options) {
+        *s = masked;
       }
     }
     _ => {}
   }
 }
 
+/// Why a daemon connection could not be opened.
+#[derive(Debug)]
+enum IpcError {
+  /// No daemon is listening at the IPC path (stale lock file, daemon stopped).
+  NotRunning,
+  Io(std::io::Error),
+}
+
+/// A missing daemon is expected and stays quiet; anything else is logged.
+fn log_connect_error(err: IpcError) {
+  if let IpcError::Io(e) = err {
+    gui_println!("[gui] cannot connect to daemon: {}", e);
+  }
+}
+
+#[cfg(target_os = "windows")]
+#[link(name = "kernel32")]
+extern "system" {
+  fn WaitNamedPipeW(name: *const u16, timeout_ms: u32) -> i32;
+}
+
+/// Open the daemon's named pipe. `WaitNamedPipeW` fails immediately when no
+/// pipe by that name exists, so a stale path is reported as `NotRunning`
+/// instead of surfacing an opaque open error; a busy pipe gets up to 2s.
+#[cfg(target_os = "windows")]
+fn ipc_connect(ipc_path: &str) -> Result<fs::File, IpcError> {
+  use std::os::windows::ffi::OsStrExt;
+
+  let wide: Vec<u16> = std::ffi::OsStr::new(ipc_path)
+    .encode_wide()
+    .chain(std::iter::once(0))
+    .collect();
+  // SAFETY: `wide` is a NUL-terminated UTF-16 string that outlives the call.
+  if unsafe { WaitNamedPipeW(wide.as_ptr(), 2000) } == 0 {
+    let err = std::io::Error::last_os_error();
+    // ERROR_FILE_NOT_FOUND: no such pipe
+    return Err(if err.raw_os_error() == Some(2) {
+      IpcError::NotRunning
+    } else {
+      IpcError::Io(err)
+    });
+  }
+  OpenOptions::new()
+    .read(true)
+    .write(true)
+    .open(ipc_path)
+    .map_err(IpcError::Io)
+}
+
+/// Connect to the daemon's socket; a missing or refused socket means the
+/// daemon is not running.
+#[cfg(target_family = "unix")]
+fn ipc_connect(ipc_path: &str) -> Result<UnixStream, IpcError> {
+  UnixStream::connect(ipc_path).map_err(|e| match e.kind() {
+    std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused => IpcError::NotRunning,
+    _ => IpcError::Io(e),
+  })
+}
+
 /// Send a JSON-line request to the daemon and read one JSON-line reply.
 /// Returns the raw JSON Value of the full response.
-#[cfg(target_os = "windows")]
 fn ipc_request(ipc_path: &str, request: &str) -> Option<Value> {
-  let mut pipe = OpenOptions::new().read(true).write(true).open(ipc_path).ok()?;
+  ipc_request_timeout(ipc_path, request, gui_settings::with(|s| s.ipc_timeout()))
+}
+
+/// `ipc_request` with a custom wait for the reply.
+fn ipc_request_timeout(ipc_path: &str, request: &str, timeout: Duration) -> Option<Value> {
+  let started = Instant::now();
+  let reply = ipc_exchange(ipc_path, request, timeout);
+  ipc_trace::record(request, started, timeout, reply.as_ref());
+  reply
+}
+
+#[cfg(target_os = "windows")]
+fn ipc_exchange(ipc_path: &str, request: &str, timeout: Duration) -> Option<Value> {
+  let mut pipe = ipc_connect(ipc_path).map_err(log_connect_error).ok()?;
   pipe.write_all(request.as_bytes()).ok()?;
   pipe.write_all(b"\n").ok()?;
   pipe.flush().ok()?;
@@ -234,18 +858,16 @@ fn ipc_request(ipc_path: &str, request: &str) -> Option<Value> {
       let _ = tx.send(line);
     }
   });
-  let line = rx.recv_timeout(Duration::from_secs(10)).ok()?;
+  let line = rx.recv_timeout(timeout).ok()?;
   let _ = handle.join();
 
   serde_json::from_str::<Value>(line.trim()).ok()
 }
 
 #[cfg(target_family = "unix")]
-fn ipc_request(ipc_path: &str, request: &str) -> Option<Value> {
-  let mut socket = UnixStream::connect(ipc_path).ok()?;
-  socket
-    .set_read_timeout(Some(Duration::from_secs(10)))
-    .ok()?;
+fn ipc_exchange(ipc_path: &str, request: &str, timeout: Duration) -> Option<Value> {
+  let mut socket = ipc_connect(ipc_path).map_err(log_connect_error).ok()?;
+  socket.set_read_timeout(Some(timeout)).ok()?;
   socket.write_all(request.as_bytes()).ok()?;
   socket.write_all(b"\n").ok()?;
   socket.flush().ok()?;
@@ -257,15 +879,200 @@ fn ipc_request(ipc_path: &str, request: &str) -> Option<Value> {
   serde_json::from_str::<Value>(line.trim()).ok()
 }
 
-fn ipc_request_typed<T: for<'de> Deserialize<'de>>(ipc_path: &str, request: &str) -> Option<T> {
-  let value = ipc_request(ipc_path, request)?;
-  serde_json::from_value::<T>(value).ok()
+/// Cheap liveness check: can the daemon's IPC endpoint be opened at all?
+/// Unlike a status request this never waits on a reply.
+fn daemon_reachable(ipc_path: &str) -> bool {
+  ipc_connect(ipc_path).is_ok()
+}
+
+#[tauri::command]
+fn ping_daemon() -> bool {
+  get_ipc_path().is_some_and(|p| daemon_reachable(&p))
+}
+
+/// Why a daemon request produced no typed reply.
+#[derive(Debug)]
+enum DaemonError {
+  /// No usable reply: not running, timed out, or an unexpected shape.
+  NoResponse,
+  /// The daemon answered with a `{"type":"error"}` envelope.
+  Reported { code: Option<String>, message: String },
+}
+
+impl DaemonError {
+  fn from_envelope(reply: &Value) -> Option<Self> {
+    if reply["type"] != "error" {
+      return None;
+    }
+    let payload = &reply["payload"];
+    Some(DaemonError::Reported {
+      code: payload["code"].as_str().map(String::from),
+      message: payload["message"].as_str().unwrap_or("daemon error").to_string(),
+    })
+  }
+
+  /// Parse a reply as `T`, unless it is an error envelope.
+  fn check<T: for<'de> Deserialize<'de>>(reply: Option<Value>) -> Result<T, Self> {
+    let reply = reply.ok_or(DaemonError::NoResponse)?;
+    if let Some(err) = DaemonError::from_envelope(&reply) {
+      return Err(err);
+    }
+    serde_json::from_value::<T>(reply).map_err(|_| DaemonError::NoResponse)
+  }
+
+  /// On a batch connection, no reply means the connection dropped.
+  fn connection_message(&self) -> String {
+    match self {
+      DaemonError::NoResponse => "connection to daemon lost".to_string(),
+      err => err.to_string(),
+    }
+  }
+
+  /// The usual `{ ok: false, error }` command result, plus `error_code`.
+  fn to_json(&self) -> Value {
+    let code = match self {
+      DaemonError::Reported { code, .. } => code.clone(),
+      DaemonError::NoResponse => None,
+    };
+    serde_json::json!({ "ok": false, "error": self.to_string(), "error_code": code })
+  }
+}
+
+impl std::fmt::Display for DaemonError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      DaemonError::NoResponse => f.write_str("no response from daemon"),
+      DaemonError::Reported { message, .. } => f.write_str(message),
+    }
+  }
+}
+
+fn ipc_request_typed<T: for<'de> Deserialize<'de>>(
+  ipc_path: &str,
+  request: &str,
+) -> Result<T, DaemonError> {
+  DaemonError::check(ipc_request(ipc_path, request))
+}
+
+/// The untyped `payload` of a reply.
+fn ipc_payload(ipc_path: &str, request: &str) -> Result<Value, DaemonError> {
+  let reply = ipc_request_typed::<Value>(ipc_path, request)?;
+  reply.get("payload").cloned().ok_or(DaemonError::NoResponse)
+}
+
+/// A daemon connection reused for several JSON-line requests in a row.
+/// The daemon answers each line in order, so batch operations can avoid a
+/// connect per item. Any failed request means the connection is gone.
+struct IpcConnection {
+  #[cfg(target_family = "unix")]
+  reader: BufReader<UnixStream>,
+  #[cfg(target_os = "windows")]
+  pipe: fs::File,
+}
+
+impl IpcConnection {
+  #[cfg(target_family = "unix")]
+  fn open(ipc_path: &str) -> Option<Self> {
+    let socket = ipc_connect(ipc_path).ok()?;
+    socket
+      .set_read_timeout(Some(gui_settings::with(|s| s.ipc_timeout())))
+      .ok()?;
+    Some(Self {
+      reader: BufReader::new(socket),
+    })
+  }
+
+  #[cfg(target_os = "windows")]
+  fn open(ipc_path: &str) -> Option<Self> {
+    let pipe = ipc_connect(ipc_path).ok()?;
+    Some(Self { pipe })
+  }
+
+  fn request(&mut self, request: &str) -> Option<Value> {
+    let started = Instant::now();
+    let reply = self.exchange(request);
+    let timeout = gui_settings::with(|s| s.ipc_timeout());
+    ipc_trace::record(request, started, timeout, reply.as_ref());
+    reply
+  }
+
+  #[cfg(target_family = "unix")]
+  fn exchange(&mut self, request: &str) -> Option<Value> {
+    let socket = self.reader.get_mut();
+    socket.write_all(request.as_bytes()).ok()?;
+    socket.write_all(b"\n").ok()?;
+    socket.flush().ok()?;
+
+    let mut line = String::new();
+    if self.reader.read_line(&mut line).ok()? == 0 {
+      return None;
+    }
+    serde_json::from_str::<Value>(line.trim()).ok()
+  }
+
+  #[cfg(target_os = "windows")]
+  fn exchange(&mut self, request: &str) -> Option<Value> {
+    use std::io::Read;
+
+    self.pipe.write_all(request.as_bytes()).ok()?;
+    self.pipe.write_all(b"\n").ok()?;
+    self.pipe.flush().ok()?;
+
+    // Read byte-by-byte on a cloned handle so nothing past the reply line is
+    // buffered away from the next request; the thread gives us a timeout.
+    let mut reader = self.pipe.try_clone().ok()?;
+    let (tx, rx) = std::sync::mpsc::channel();
+    thread::spawn(move || {
+      let mut line = Vec::new();
+      let mut byte = [0u8; 1];
+      while let Ok(1) = reader.read(&mut byte) {
+        if byte[0] == b'\n' {
+          let _ = tx.send(line);
+          return;
+        }
+        line.push(byte[0]);
+      }
+    });
+    let line = rx.recv_timeout(gui_settings::with(|s| s.ipc_timeout())).ok()?;
+    serde_json::from_slice::<Value>(&line).ok()
+  }
+
+  fn request_typed<T: for<'de> Deserialize<'de>>(
+    &mut self,
+    request: &Value,
+  ) -> Result<T, DaemonError> {
+    let req_str = serde_json::to_string(request).unwrap_or_default();
+    DaemonError::check(self.request(&req_str))
+  }
+
+  /// The `list_bots` payload, or empty lists if the request fails.
+  fn list_bots(&mut self) -> Value {
+    self
+      .request(r#"{"type":"list_bots_request"}"#)
+      .and_then(|v| v.get("payload").cloned())
+      .unwrap_or_else(|| serde_json::json!({ "interactive": [], "push": [] }))
+  }
+
+  fn status(&mut self) -> Option<DaemonStatusPayload> {
+    self
+      .request_typed::<DaemonStatus>(&serde_json::json!({ "type": "status_request" }))
+      .map(|r| r.payload)
+      .ok()
+  }
+
+  /// Send a request answered with `{ ok, error }`. `NoResponse` means the
+  /// connection dropped before a reply arrived.
+  fn request_ok(&mut self, request: &Value) -> Result<GenericOkPayload, DaemonError> {
+    self
+      .request_typed::<GenericOkResponse>(request)
+      .map(|r| r.payload)
+  }
 }
 
 /* ── Platform-specific status/stop using new generic helper ── */
 
 fn request_daemon_status(ipc_path: &str) -> Option<DaemonStatusPayload> {
-  let resp = ipc_request_typed::<DaemonStatus>(ipc_path, r#"{"type":"status_request"}"#)?;
+  let resp = ipc_request_typed::<DaemonStatus>(ipc_path, r#"{"type":"status_request"}"#).ok()?;
   Some(resp.payload)
 }
 
@@ -292,7 +1099,7 @@ fn read_daemon_status() -> GuiStatus {
       daemon_pid: None,
       active_sessions: 0,
       sessions: vec![],
-      warnings: vec![],
+      warnings: cli_hook_watch::lost_warnings(),
     };
   };
 
@@ -302,10 +1109,15 @@ fn read_daemon_status() -> GuiStatus {
       daemon_pid: None,
       active_sessions: 0,
       sessions: vec![],
-      warnings: vec![],
+      warnings: cli_hook_watch::lost_warnings(),
     };
   };
 
+  let mut warnings = status.warnings.unwrap_or_default();
+  warnings::annotate(&mut warnings);
+  warnings.retain(|w| !w.dismissed);
+  warnings.extend(cli_hook_watch::lost_warnings());
+
   GuiStatus {
     running: true,
     daemon_pid: Some(status.daemon_pid),
@@ -313,81 +1125,564 @@ fn read_daemon_status() -> GuiStatus {
     sessions: status
       .sessions
       .into_iter()
-      .map(|s| Session {
-        session_id: s.session_id,
-        cli: s.cli,
-        cwd: s.cwd,
-        status: s.status,
-        started_at: s.started_at,
-        interactive_bot_id: s.interactive_bot_id,
-        interactive_bot_connected: s.interactive_bot_connected,
-        push_bot_id: s.push_bot_id,
-        push_enabled: s.push_enabled,
-      })
+      .map(Session::from)
       .collect(),
-    warnings: status.warnings.unwrap_or_default(),
+    warnings,
   }
 }
 
+/// Counters for the dashboard header.
+#[derive(Debug, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+struct Stats {
+  running: bool,
+  active_sessions: usize,
+  /// Active sessions whose interactive bot is connected.
+  interactive_connected: usize,
+  /// Active sessions with push enabled.
+  push_enabled: usize,
+  /// Undismissed warnings, as in `GuiStatus`.
+  warnings: usize,
+  /// Active sessions by CLI, keyed as in `read_sessions_grouped`.
+  by_cli: BTreeMap<String, usize>,
+}
+
 #[tauri::command]
-fn list_bots() -> Value {
-  let Some(ipc_path) = get_ipc_path() else {
-    return serde_json::json!({ "interactive": [], "push": [] });
+fn get_stats() -> Stats {
+  let status = read_daemon_status();
+  let mut stats = Stats {
+    running: status.running,
+    warnings: status.warnings.len(),
+    ..Stats::default()
   };
+  for s in status.sessions.iter().filter(|s| s.status != "ended") {
+    stats.active_sessions += 1;
+    stats.interactive_connected += usize::from(s.interactive_bot_connected == Some(true));
+    stats.push_enabled += usize::from(s.push_enabled == Some(true));
+    *stats.by_cli.entry(cli_group_name(&s.cli)).or_default() += 1;
+  }
+  stats
+}
 
-  let req = r#"{"type":"list_bots_request"}"#;
-  if let Some(value) = ipc_request(&ipc_path, req) {
-    // The response has { type, payload: { interactive, push } }
-    if let Some(payload) = value.get("payload") {
-      return payload.clone();
+impl From<DaemonSession> for Session {
+  fn from(s: DaemonSession) -> Self {
+    let claude_project_config_issue = claude_project::is_claude_session(&s.cli)
+      && claude_project::shadows_hook_cached(Path::new(&s.cwd));
+    Session {
+      session_id: s.session_id,
+      cli: s.cli,
+      cwd: s.cwd,
+      status: s.status,
+      started_at: s.started_at,
+      interactive_bot_id: s.interactive_bot_id,
+      interactive_bot_connected: s.interactive_bot_connected,
+      push_bot_id: s.push_bot_id,
+      push_enabled: s.push_enabled,
+      claude_project_config_issue,
     }
   }
-  serde_json::json!({ "interactive": [], "push": [] })
 }
 
-#[tauri::command]
-fn save_bot(bot_type: String, config: Value) -> Value {
-  let Some(ipc_path) = get_ipc_path() else {
-    return serde_json::json!({ "ok": false, "error": "daemon not running" });
-  };
+#[derive(Debug, Serialize, Default)]
+struct SessionGroup {
+  count: usize,
+  sessions: Vec<Session>,
+}
 
-  let req = if bot_type == "interactive" {
-    serde_json::json!({
-      "type": "save_bot_request",
-      "payload": { "botType": "interactive", "interactive": config }
-    })
-  } else {
-    serde_json::json!({
-      "type": "save_bot_request",
-      "payload": { "botType": "push", "push": config }
-    })
-  };
+#[derive(Debug, Serialize)]
+struct GroupedSessions {
+  running: bool,
+  total: usize,
+  active_sessions: i64,
+  groups: BTreeMap<String, SessionGroup>,
+}
 
-  let req_str = serde_json::to_string(&req).unwrap_or_default();
-  if let Some(resp) = ipc_request_typed::<GenericOkResponse>(&ipc_path, &req_str) {
-    serde_json::json!({ "ok": resp.payload.ok, "error": resp.payload.error })
+/// Group key for a session's `cli`: the executable name without directory
+/// or Windows extension, lowercased; empty values fall under "other".
+fn cli_group_name(cli: &str) -> String {
+  let base = cli.rsplit(['/', '\\']).next().unwrap_or("");
+  let lower = base.to_lowercase();
+  let name = [".exe", ".cmd", ".bat"]
+    .iter()
+    .find_map(|ext| lower.strip_suffix(ext))
+    .unwrap_or(&lower)
+    .trim();
+  if name.is_empty() {
+    "other".to_string()
   } else {
-    serde_json::json!({ "ok": false, "error": "no response from daemon" })
+    name.to_string()
   }
 }
 
+/// The same sessions `read_daemon_status` returns, grouped by CLI.
 #[tauri::command]
-fn delete_bot(bot_type: String, bot_id: String) -> Value {
+fn read_sessions_grouped() -> GroupedSessions {
+  let status = get_ipc_path().and_then(|p| request_daemon_status(&p));
+  let running = status.is_some();
+  let (active_sessions, sessions) = status
+    .map(|s| (s.active_sessions, s.sessions))
+    .unwrap_or_default();
+
+  let total = sessions.len();
+  let mut groups: BTreeMap<String, SessionGroup> = BTreeMap::new();
+  for session in sessions {
+    let group = groups.entry(cli_group_name(&session.cli)).or_default();
+    group.count += 1;
+    group.sessions.push(Session::from(session));
+  }
+
+  GroupedSessions {
+    running,
+    total,
+    active_sessions,
+    groups,
+  }
+}
+
+fn fetch_bots() -> Value {
   let Some(ipc_path) = get_ipc_path() else {
-    return serde_json::json!({ "ok": false, "error": "daemon not running" });
+    return serde_json::json!({ "interactive": [], "push": [] });
+  };
+
+  let req = r#"{"type":"list_bots_request"}"#;
+  // The response has { type, payload: { interactive, push } }
+  ipc_payload(&ipc_path, req)
+    .unwrap_or_else(|_| serde_json::json!({ "interactive": [], "push": [] }))
+}
+
+/// Look up a bot by type and id in a `list_bots` payload.
+fn find_bot<'a>(bots: &'a Value, bot_type: &str, bot_id: &str) -> Option<&'a Value> {
+  bots
+    .get(bot_type)?
+    .as_array()?
+    .iter()
+    .find(|b| b["id"].as_str() == Some(bot_id))
+}
+
+/// Case-insensitive match on name, bot type, provider and the id suffix
+/// (warnings show the last 6 characters of an id).
+fn bot_matches(bot: &Value, bot_type: &str, query: &str) -> bool {
+  let str_field = |key: &str| bot.get(key).and_then(|v| v.as_str()).unwrap_or("");
+  let id = str_field("id");
+  let id_tail: String = id.chars().skip(id.chars().count().saturating_sub(6)).collect();
+  let provider = match bot_type {
+    "push" => reqwest::Url::parse(str_field("webhook"))
+      .ok()
+      .and_then(|u| u.host_str().and_then(webhook::detect_provider))
+      .unwrap_or(""),
+    _ => "feishu",
+  };
+
+  [str_field("name"), bot_type, provider, id_tail.as_str()]
+    .iter()
+    .any(|field| field.to_lowercase().contains(query))
+}
+
+/// List bots. With no arguments the daemon payload is returned as-is apart
+/// from `quiet_hours_label` on push bots that have quiet hours; otherwise
+/// each list is filtered by `query`/`bot_type` and paged with
+/// `offset`/`limit`, and `total_*` report the unfiltered counts.
+#[tauri::command]
+fn list_bots(
+  query: Option<String>,
+  bot_type: Option<String>,
+  limit: Option<usize>,
+  offset: Option<usize>,
+) -> Value {
+  let mut bots = fetch_bots();
+  quiet_hours::annotate_bots(&mut bots);
+  if query.is_none() && bot_type.is_none() && limit.is_none() && offset.is_none() {
+    return bots;
+  }
+
+  let query = query.unwrap_or_default().trim().to_lowercase();
+  let select = |kind: &str| -> (Vec<Value>, usize) {
+    let all = bots
+      .get(kind)
+      .and_then(|v| v.as_array())
+      .cloned()
+      .unwrap_or_default();
+    let total = all.len();
+    if bot_type.as_deref().is_some_and(|t| t != kind) {
+      return (vec![], total);
+    }
+    let page = all
+      .into_iter()
+      .filter(|b| query.is_empty() || bot_matches(b, kind, &query))
+      .skip(offset.unwrap_or(0))
+      .take(limit.unwrap_or(usize::MAX))
+      .collect();
+    (page, total)
+  };
+
+  let (interactive, total_interactive) = select("interactive");
+  let (push, total_push) = select("push");
+  serde_json::json!({
+    "interactive": interactive,
+    "push": push,
+    "total_interactive": total_interactive,
+    "total_push": total_push,
+  })
+}
+
+#[tauri::command]
+async fn save_bot(bot_type: String, config: Value, verify: Option<bool>) -> Value {
+  // Optional webhook pre-check; the result is attached as a non-fatal warning
+  let webhook_check = match config.get("webhook").and_then(|w| w.as_str()) {
+    Some(url) if verify.unwrap_or(false) && bot_type == "push" => {
+      Some(webhook::probe_webhook(url).await)
+    }
+    _ => None,
   };
 
+  // The daemon IPC blocks, so it runs off the async runtime
+  let task = tokio::task::spawn_blocking(move || save_bot_ipc(&bot_type, &config));
+  let mut result = match task.await {
+    Ok(result) => result,
+    Err(e) => serde_json::json!({ "ok": false, "error": e.to_string() }),
+  };
+  if let Some(check) = webhook_check.filter(|c| !c.is_reachable()) {
+    result["warning"] = serde_json::to_value(check).unwrap_or(Value::Null);
+  }
+  result
+}
+
+/// The blocking part of `save_bot`: the daemon request and its audit entry.
+fn save_bot_ipc(bot_type: &str, config: &Value) -> Value {
+  let bot_id = config["id"].as_str().unwrap_or("").to_string();
+  let bot_name = config["name"].as_str().map(|n| n.to_string());
+  let Some(ipc_path) = get_ipc_path() else {
+    let result = serde_json::json!({ "ok": false, "error": "daemon not running" });
+    let changes = audit::diff_keys(None, Some(config));
+    audit::record("save", bot_type, &bot_id, bot_name.as_deref(), changes, &result);
+    return result;
+  };
+  let previous = find_bot(&fetch_bots(), bot_type, &bot_id).cloned();
+
+  let req = if bot_type == "interactive" {
+    serde_json::json!({
+      "type": "save_bot_request",
+      "payload": { "botType": "interactive", "interactive": config }
+    })
+  } else {
+    serde_json::json!({
+      "type": "save_bot_request",
+      "payload": { "botType": "push", "push": config }
+    })
+  };
+
+  let req_str = serde_json::to_string(&req).unwrap_or_default();
+  let _own_write = file_watcher::own_config_write();
+  let result = match ipc_request_typed::<GenericOkResponse>(&ipc_path, &req_str) {
+    Ok(resp) => serde_json::json!({ "ok": resp.payload.ok, "error": resp.payload.error }),
+    Err(e) => e.to_json(),
+  };
+  config_cache::invalidate();
+
+  let action = if previous.is_some() { "update" } else { "create" };
+  let changes = audit::diff_keys(previous.as_ref(), Some(config));
+  audit::record(action, bot_type, &bot_id, bot_name.as_deref(), changes, &result);
+  result
+}
+
+/// Ids of live sessions whose `bot_type` slot is bound to `bot_id`.
+fn sessions_bound_to(sessions: &[DaemonSession], bot_type: &str, bot_id: &str) -> Vec<String> {
+  sessions
+    .iter()
+    .filter(|s| s.status != "ended")
+    .filter(|s| {
+      let bound = if bot_type == "interactive" {
+        &s.interactive_bot_id
+      } else {
+        &s.push_bot_id
+      };
+      bound.as_deref() == Some(bot_id)
+    })
+    .map(|s| s.session_id.clone())
+    .collect()
+}
+
+/// One session slot that a bot is bound to.
+#[derive(Debug, Serialize)]
+struct BotUsage {
+  session_id: String,
+  cli: String,
+  cwd: String,
+  /// "interactive" or "push"; a session can use the same id in both slots.
+  bot_type: &'static str,
+}
+
+/// Live sessions' slots bound to `bot_id`.
+fn usage_of(sessions: &[DaemonSession], bot_id: &str) -> Vec<BotUsage> {
+  let mut usage = Vec::new();
+  for s in sessions.iter().filter(|s| s.status != "ended") {
+    let slots = [("interactive", &s.interactive_bot_id), ("push", &s.push_bot_id)];
+    for (bot_type, bound) in slots {
+      if bound.as_deref() == Some(bot_id) {
+        usage.push(BotUsage {
+          session_id: s.session_id.clone(),
+          cli: s.cli.clone(),
+          cwd: s.cwd.clone(),
+          bot_type,
+        });
+      }
+    }
+  }
+  usage
+}
+
+/// Live sessions bound to `bot_id` in either slot; empty when unused or the
+/// daemon is not running. Lets the UI warn before `delete_bot` refuses.
+#[tauri::command]
+fn bot_usage(bot_id: String) -> Vec<BotUsage> {
+  get_ipc_path()
+    .and_then(|p| request_daemon_status(&p))
+    .map(|status| usage_of(&status.sessions, &bot_id))
+    .unwrap_or_default()
+}
+
+/// Delete one bot on an open connection, applying the bound-session check.
+/// Err carries the partial result when the connection dropped midway.
+fn delete_bot_on(
+  conn: &mut IpcConnection,
+  sessions: &[DaemonSession],
+  bot_type: &str,
+  bot_id: &str,
+  force: bool,
+) -> Result<Value, Value> {
+  // Refuse to orphan sessions that still point at this bot unless forced
+  let bound = sessions_bound_to(sessions, bot_type, bot_id);
+  if !bound.is_empty() && !force {
+    let mut in_use_by = usage_of(sessions, bot_id);
+    in_use_by.retain(|u| u.bot_type == bot_type);
+    return Ok(serde_json::json!({
+      "ok": false,
+      "error": format!("bot is bound to {} session(s)", bound.len()),
+      "bound_sessions": bound,
+      "in_use_by": in_use_by,
+    }));
+  }
+
+  let mut unbound = Vec::new();
+  let mut unbind_failures = Vec::new();
+  for session_id in &bound {
+    let req = serde_json::json!({
+      "type": "unbind_bot_request",
+      "payload": { "sessionId": session_id, "botType": bot_type }
+    });
+    match conn.request_ok(&req) {
+      Ok(resp) if resp.ok => unbound.push(session_id.clone()),
+      Ok(_) => unbind_failures.push(session_id.clone()),
+      Err(e) => {
+        return Err(serde_json::json!({
+          "ok": false,
+          "error": e.connection_message(),
+          "unbound_sessions": unbound,
+          "unbind_failures": unbind_failures,
+        }))
+      }
+    }
+  }
+
   let req = serde_json::json!({
     "type": "delete_bot_request",
     "payload": { "botType": bot_type, "botId": bot_id }
   });
-  let req_str = serde_json::to_string(&req).unwrap_or_default();
+  let _own_write = file_watcher::own_config_write();
+  let resp = conn.request_ok(&req);
+  config_cache::invalidate();
+  match resp {
+    Ok(resp) => Ok(serde_json::json!({
+      "ok": resp.ok,
+      "error": resp.error,
+      "unbound_sessions": unbound,
+      "unbind_failures": unbind_failures,
+    })),
+    Err(e) => Err(serde_json::json!({
+      "ok": false,
+      "error": e.connection_message(),
+      "unbound_sessions": unbound,
+      "unbind_failures": unbind_failures,
+    })),
+  }
+}
 
-  if let Some(resp) = ipc_request_typed::<GenericOkResponse>(&ipc_path, &req_str) {
-    serde_json::json!({ "ok": resp.payload.ok, "error": resp.payload.error })
-  } else {
-    serde_json::json!({ "ok": false, "error": "no response from daemon" })
+/// Record a delete attempt in the bot audit log.
+fn audit_delete(bots: &Value, bot_type: &str, bot_id: &str, result: &Value) {
+  let existing = find_bot(bots, bot_type, bot_id);
+  let name = existing.and_then(|b| b["name"].as_str());
+  let changes = audit::diff_keys(existing, None);
+  audit::record("delete", bot_type, bot_id, name, changes, result);
+}
+
+#[tauri::command]
+fn delete_bot(bot_type: String, bot_id: String, force: Option<bool>) -> Value {
+  let Some(mut conn) = get_ipc_path().and_then(|p| IpcConnection::open(&p)) else {
+    return serde_json::json!({ "ok": false, "error": "daemon not running" });
+  };
+
+  let Some(status) = conn.status() else {
+    return serde_json::json!({ "ok": false, "error": "no response from daemon" });
+  };
+  let bots = conn.list_bots();
+  let result =
+    delete_bot_on(&mut conn, &status.sessions, &bot_type, &bot_id, force.unwrap_or(false))
+      .unwrap_or_else(|e| e);
+  audit_delete(&bots, &bot_type, &bot_id, &result);
+  result
+}
+
+/// Result of a batch operation that failed before processing any item.
+fn batch_failed(error: &str) -> Value {
+  serde_json::json!({ "ok": false, "error": error, "processed": 0, "results": [] })
+}
+
+#[derive(Debug, Deserialize)]
+struct BotRef {
+  bot_type: String,
+  bot_id: String,
+}
+
+/// Delete several bots over one connection. Each item gets the same
+/// bound-session check as `delete_bot`; if the connection drops, the batch
+/// stops and `processed` says how many items were attempted.
+#[tauri::command]
+fn delete_bots(items: Vec<BotRef>, force: bool) -> Value {
+  let Some(mut conn) = get_ipc_path().and_then(|p| IpcConnection::open(&p)) else {
+    return batch_failed("daemon not running");
+  };
+  let Some(status) = conn.status() else {
+    return batch_failed("no response from daemon");
+  };
+
+  let bots = conn.list_bots();
+  let mut results = Vec::new();
+  let mut aborted = false;
+  for item in &items {
+    let outcome =
+      delete_bot_on(&mut conn, &status.sessions, &item.bot_type, &item.bot_id, force);
+    let (mut result, lost) = match outcome {
+      Ok(r) => (r, false),
+      Err(r) => (r, true),
+    };
+    audit_delete(&bots, &item.bot_type, &item.bot_id, &result);
+    result["bot_type"] = Value::String(item.bot_type.clone());
+    result["bot_id"] = Value::String(item.bot_id.clone());
+    results.push(result);
+    if lost {
+      aborted = true;
+      break;
+    }
   }
+
+  let all_ok = results.iter().all(|r| r["ok"].as_bool().unwrap_or(false));
+  serde_json::json!({
+    "ok": !aborted && all_ok,
+    "error": aborted.then_some("connection to daemon lost"),
+    "aborted": aborted,
+    "processed": results.len(),
+    "total": items.len(),
+    "results": results,
+  })
+}
+
+/// Move every live session bound to `from_bot_id` over to `to_bot_id` with
+/// an unbind+bind pair each, so replacing a bot doesn't orphan sessions.
+/// The target must be another existing bot of `bot_type`, since the daemon
+/// binds any id it is given.
+#[tauri::command]
+fn rebind_sessions(from_bot_id: String, to_bot_id: String, bot_type: String) -> Value {
+  let Some(ipc_path) = get_ipc_path() else {
+    return batch_failed("daemon not running");
+  };
+  rebind_sessions_at(&ipc_path, &bot_type, &from_bot_id, &to_bot_id)
+}
+
+fn rebind_sessions_at(ipc_path: &str, bot_type: &str, from_bot_id: &str, to_bot_id: &str) -> Value {
+  if from_bot_id == to_bot_id {
+    return batch_failed("target bot is the same as the source bot");
+  }
+  let Some(mut conn) = IpcConnection::open(ipc_path) else {
+    return batch_failed("daemon not running");
+  };
+  let bots = conn.list_bots();
+  if find_bot(&bots, bot_type, to_bot_id).is_none() {
+    return batch_failed("target bot not found");
+  }
+  let Some(status) = conn.status() else {
+    return batch_failed("no response from daemon");
+  };
+  rebind_sessions_on(&mut conn, &status.sessions, bot_type, from_bot_id, to_bot_id)
+}
+
+/// The rebind loop of `rebind_sessions`. A session whose bind fails is
+/// bound back to `from_bot_id`, and `rolled_back` says whether that worked.
+fn rebind_sessions_on(
+  conn: &mut IpcConnection,
+  sessions: &[DaemonSession],
+  bot_type: &str,
+  from_bot_id: &str,
+  to_bot_id: &str,
+) -> Value {
+  let bind_request = |session_id: &str, bot_id: &str| {
+    serde_json::json!({
+      "type": "bind_bot_request",
+      "payload": { "sessionId": session_id, "botType": bot_type, "botId": bot_id }
+    })
+  };
+
+  let sessions = sessions_bound_to(sessions, bot_type, from_bot_id);
+  let mut results = Vec::new();
+  let mut abort_reason = None;
+  for session_id in &sessions {
+    let unbind = serde_json::json!({
+      "type": "unbind_bot_request",
+      "payload": { "sessionId": session_id, "botType": bot_type }
+    });
+
+    let outcome = conn.request_ok(&unbind).and_then(|unbound| {
+      if !unbound.ok {
+        return Ok(serde_json::json!({ "ok": false, "error": unbound.error, "stage": "unbind" }));
+      }
+      let bound = conn.request_ok(&bind_request(session_id, to_bot_id))?;
+      if bound.ok {
+        return Ok(serde_json::json!({ "ok": true, "error": null, "stage": "bind" }));
+      }
+      let restored = conn.request_ok(&bind_request(session_id, from_bot_id))?;
+      Ok(serde_json::json!({
+        "ok": false,
+        "error": bound.error,
+        "stage": "bind",
+        "rolled_back": restored.ok,
+      }))
+    });
+    match outcome {
+      Ok(mut result) => {
+        result["session_id"] = Value::String(session_id.clone());
+        results.push(result);
+      }
+      Err(e) => {
+        abort_reason = Some(e.connection_message());
+        results.push(serde_json::json!({
+          "session_id": session_id,
+          "ok": false,
+          "error": abort_reason,
+        }));
+        break;
+      }
+    }
+  }
+
+  let aborted = abort_reason.is_some();
+  let all_ok = results.iter().all(|r| r["ok"].as_bool().unwrap_or(false));
+  serde_json::json!({
+    "ok": !aborted && all_ok,
+    "error": abort_reason,
+    "aborted": aborted,
+    "processed": results.len(),
+    "total": sessions.len(),
+    "results": results,
+  })
 }
 
 #[tauri::command]
@@ -402,10 +1697,9 @@ fn bind_bot(session_id: String, bot_type: String, bot_id: String) -> Value {
   });
   let req_str = serde_json::to_string(&req).unwrap_or_default();
 
-  if let Some(resp) = ipc_request_typed::<GenericOkResponse>(&ipc_path, &req_str) {
-    serde_json::json!({ "ok": resp.payload.ok, "error": resp.payload.error })
-  } else {
-    serde_json::json!({ "ok": false, "error": "no response from daemon" })
+  match ipc_request_typed::<GenericOkResponse>(&ipc_path, &req_str) {
+    Ok(resp) => serde_json::json!({ "ok": resp.payload.ok, "error": resp.payload.error }),
+    Err(e) => e.to_json(),
   }
 }
 
@@ -421,32 +1715,106 @@ fn unbind_bot(session_id: String, bot_type: String) -> Value {
   });
   let req_str = serde_json::to_string(&req).unwrap_or_default();
 
-  if let Some(resp) = ipc_request_typed::<GenericOkResponse>(&ipc_path, &req_str) {
-    serde_json::json!({ "ok": resp.payload.ok, "error": resp.payload.error })
-  } else {
-    serde_json::json!({ "ok": false, "error": "no response from daemon" })
+  match ipc_request_typed::<GenericOkResponse>(&ipc_path, &req_str) {
+    Ok(resp) => serde_json::json!({ "ok": resp.payload.ok, "error": resp.payload.error }),
+    Err(e) => e.to_json(),
   }
 }
 
+/// Detach both bots from a session in one daemon request. A bot type that
+/// was not bound is reported as `false`, not as an error.
 #[tauri::command]
-fn test_bot(bot_type: String, bot_id: String) -> Value {
+fn unbind_all_bots(session_id: String) -> Value {
   let Some(ipc_path) = get_ipc_path() else {
     return serde_json::json!({ "ok": false, "error": "daemon not running" });
   };
 
+  if capabilities::supports("unbind_all_bots_request") == Some(false) {
+    return serde_json::json!({ "ok": false, "error": "daemon does not support unbind_all_bots" });
+  }
+  unbind_all_bots_at(&ipc_path, &session_id)
+}
+
+fn unbind_all_bots_at(ipc_path: &str, session_id: &str) -> Value {
+  let req = serde_json::json!({
+    "type": "unbind_all_bots_request",
+    "payload": { "sessionId": session_id }
+  });
+  let req_str = serde_json::to_string(&req).unwrap_or_default();
+
+  match ipc_payload(ipc_path, &req_str) {
+    Ok(payload) => serde_json::json!({
+      "ok": payload["ok"].as_bool().unwrap_or(false),
+      "error": payload.get("error"),
+      "unbound_interactive": payload["unbound"]["interactive"].as_bool().unwrap_or(false),
+      "unbound_push": payload["unbound"]["push"].as_bool().unwrap_or(false),
+    }),
+    Err(DaemonError::NoResponse) if daemon_reachable(ipc_path) => {
+      serde_json::json!({ "ok": false, "error": "daemon does not support unbind_all_bots" })
+    }
+    Err(e) => e.to_json(),
+  }
+}
+
+#[tauri::command]
+fn test_bot(bot_type: String, bot_id: String) -> TestBotResult {
+  let Some(ipc_path) = get_ipc_path() else {
+    return TestBotResult::failed("daemon not running");
+  };
+
   let req = serde_json::json!({
     "type": "test_bot_request",
     "payload": { "botType": bot_type, "botId": bot_id }
   });
   let req_str = serde_json::to_string(&req).unwrap_or_default();
 
-  if let Some(resp) = ipc_request_typed::<GenericOkResponse>(&ipc_path, &req_str) {
-    serde_json::json!({ "ok": resp.payload.ok, "error": resp.payload.error })
+  ipc_request_typed::<TestBotResponse>(&ipc_path, &req_str)
+    .map(|r| r.payload)
+    .unwrap_or_else(|e| TestBotResult::failed(&e.to_string()))
+}
+
+/// Test a draft bot config without saving it. The daemon connects with the
+/// inline credentials and reports success or the specific failure.
+#[tauri::command]
+fn test_bot_config(bot_type: String, config: Value) -> TestBotResult {
+  let Some(ipc_path) = get_ipc_path() else {
+    return TestBotResult::failed("daemon not running");
+  };
+
+  let req = if bot_type == "interactive" {
+    serde_json::json!({
+      "type": "test_bot_config_request",
+      "payload": { "botType": "interactive", "interactive": config }
+    })
   } else {
-    serde_json::json!({ "ok": false, "error": "no response from daemon" })
-  }
+    serde_json::json!({
+      "type": "test_bot_config_request",
+      "payload": { "botType": "push", "push": config }
+    })
+  };
+  let req_str = serde_json::to_string(&req).unwrap_or_default();
+
+  ipc_request_typed::<TestBotResponse>(&ipc_path, &req_str)
+    .map(|r| r.payload)
+    .unwrap_or_else(|e| TestBotResult::failed(&e.to_string()))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ActivateBotPayload {
+  ok: bool,
+  error: Option<String>,
+  #[serde(default)]
+  previous_bot_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ActivateBotResponse {
+  payload: ActivateBotPayload,
 }
 
+/// Activate a bot. `previous_bot_id` names the bot that was active before
+/// (null if none) so the UI can show what changed and offer a revert.
 #[tauri::command]
 fn activate_bot(bot_id: String) -> Value {
   let Some(ipc_path) = get_ipc_path() else {
@@ -459,44 +1827,327 @@ fn activate_bot(bot_id: String) -> Value {
   });
   let req_str = serde_json::to_string(&req).unwrap_or_default();
 
-  if let Some(resp) = ipc_request_typed::<GenericOkResponse>(&ipc_path, &req_str) {
-    serde_json::json!({ "ok": resp.payload.ok, "error": resp.payload.error })
-  } else {
-    serde_json::json!({ "ok": false, "error": "no response from daemon" })
+  match ipc_request_typed::<ActivateBotResponse>(&ipc_path, &req_str) {
+    Ok(resp) => serde_json::json!({
+      "ok": resp.payload.ok,
+      "error": resp.payload.error,
+      "previous_bot_id": resp.payload.previous_bot_id,
+    }),
+    Err(e) => e.to_json(),
   }
 }
 
+/* ── Secret reveal ── */
+
+/// Fields `reveal_bot_secret` may read back, per bot type.
+const REVEALABLE_FIELDS: &[(&str, &[&str])] = &[
+  ("interactive", &["appSecret", "encryptKey"]),
+  ("push", &["webhook", "secret"]),
+];
+
+/// Reveals are limited to one per this interval to make scraping harder.
+const REVEAL_INTERVAL: Duration = Duration::from_secs(10);
+
+static LAST_REVEAL: Mutex<Option<Instant>> = Mutex::new(None);
+
+#[derive(Debug, Deserialize)]
+struct GetBotSecretPayload {
+  ok: bool,
+  value: Option<String>,
+  error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GetBotSecretResponse {
+  payload: GetBotSecretPayload,
+}
+
+/// Claim the reveal slot in `last`: false if a reveal was already let
+/// through within `REVEAL_INTERVAL` of `now`. Checked and set under one
+/// lock, so two quick calls can't both pass.
+fn take_reveal_slot(last: &Mutex<Option<Instant>>, now: Instant) -> bool {
+  let mut last = last.lock().unwrap_or_else(|e| e.into_inner());
+  if last.is_some_and(|t| now.saturating_duration_since(t) < REVEAL_INTERVAL) {
+    return false;
+  }
+  *last = Some(now);
+  true
+}
+
+/// Read back a single stored secret after the user confirms a native
+/// dialog. The value is returned to the caller only and never logged; the
+/// daemon reply carries it in plaintext, and `ipc_trace` treats the
+/// request as sensitive.
 #[tauri::command]
-fn get_config() -> Value {
+async fn reveal_bot_secret(
+  app: AppHandle,
+  bot_type: String,
+  bot_id: String,
+  field: String,
+) -> Value {
+  let allowed = REVEALABLE_FIELDS
+    .iter()
+    .any(|(t, fields)| *t == bot_type && fields.contains(&field.as_str()));
+  if !allowed {
+    return serde_json::json!({ "ok": false, "error": "field cannot be revealed" });
+  }
+
+  // The dialog and the daemon IPC both block
+  let task = tokio::task::spawn_blocking(move || reveal_secret(&app, &bot_type, &bot_id, &field));
+  match task.await {
+    Ok(result) => result,
+    Err(e) => serde_json::json!({ "ok": false, "error": e.to_string() }),
+  }
+}
+
+fn reveal_secret(app: &AppHandle, bot_type: &str, bot_id: &str, field: &str) -> Value {
+  let Some(ipc_path) = get_ipc_path() else {
+    return serde_json::json!({ "ok": false, "error": "daemon not running" });
+  };
+  // Don't ask for confirmation when the daemon can't answer anyway
+  if capabilities::supports("get_bot_secret_request") == Some(false) {
+    return serde_json::json!({ "ok": false, "error": "daemon does not support revealing secrets" });
+  }
+
+  let bots = fetch_bots();
+  let Some(bot_name) = find_bot(&bots, bot_type, bot_id).and_then(|b| b["name"].as_str()) else {
+    return serde_json::json!({ "ok": false, "error": "bot not found" });
+  };
+
+  // Taken before the dialog, so a cancelled reveal also counts
+  if !take_reveal_slot(&LAST_REVEAL, Instant::now()) {
+    return serde_json::json!({ "ok": false, "error": "please wait before revealing again" });
+  }
+
+  let confirmed = app
+    .dialog()
+    .message(format!(
+      "即将显示机器人「{}」的 {}。\n请确认周围没有其他人能看到屏幕。",
+      bot_name, field
+    ))
+    .title("显示密钥?")
+    .kind(MessageDialogKind::Warning)
+    .buttons(MessageDialogButtons::OkCancel)
+    .blocking_show();
+  if !confirmed {
+    return serde_json::json!({ "ok": false, "error": "cancelled" });
+  }
+
+  let req = serde_json::json!({
+    "type": "get_bot_secret_request",
+    "payload": { "botType": bot_type, "botId": bot_id, "field": field }
+  });
+  let req_str = serde_json::to_string(&req).unwrap_or_default();
+
+  match ipc_request_typed::<GetBotSecretResponse>(&ipc_path, &req_str) {
+    Ok(resp) if resp.payload.ok => serde_json::json!({ "ok": true, "value": resp.payload.value }),
+    Ok(resp) => serde_json::json!({ "ok": false, "error": resp.payload.error }),
+    Err(e) => e.to_json(),
+  }
+}
+
+/// The daemon's config exactly as it reports it; null if it is not running.
+fn fetch_config() -> Value {
   let Some(ipc_path) = get_ipc_path() else {
     return serde_json::json!(null);
   };
 
   let req = r#"{"type":"get_config_request"}"#;
-  if let Some(value) = ipc_request(&ipc_path, req) {
-    if let Some(payload) = value.get("payload") {
-      return payload.clone();
+  ipc_payload(&ipc_path, req).unwrap_or(Value::Null)
+}
+
+/// The config the daemon runs with (its file merged over the built-in
+/// defaults), sanitized for display next to `get_config`. Daemons without
+/// `effective_config_request` give their raw config, with `effective: false`.
+#[tauri::command]
+fn get_effective_config() -> Value {
+  let Some(ipc_path) = get_ipc_path() else {
+    return serde_json::json!({ "ok": false, "error": "daemon not running" });
+  };
+  let reply = if capabilities::supports("effective_config_request") == Some(true) {
+    ipc_payload(&ipc_path, r#"{"type":"effective_config_request"}"#)
+      .map(|payload| (payload["config"].clone(), payload["configPath"].clone(), true))
+  } else {
+    ipc_payload(&ipc_path, r#"{"type":"get_config_request"}"#)
+      .map(|config| (config, Value::Null, false))
+  };
+  match reply {
+    Ok((mut config, config_path, effective)) => {
+      sanitize_value(&mut config, &SanitizeOptions::default());
+      serde_json::json!({
+        "ok": true,
+        "effective": effective,
+        "config": config,
+        "config_path": config_path,
+      })
     }
+    Err(e) => e.to_json(),
   }
-  serde_json::json!(null)
 }
 
+/// The daemon's config in the current layout. Older layouts are migrated in
+/// memory and tagged with `migrated_from`; saving writes the new layout.
+/// Callers go through `config_cache`.
+fn load_config() -> Value {
+  let config = fetch_config();
+  if config.is_null() {
+    return config;
+  }
+  let migration = config_migrate::migrate(config);
+  let migrated = migration.migrated();
+  let mut config = migration.config;
+  if migrated {
+    config["migrated_from"] = Value::from(migration.from);
+  }
+  config
+}
+
+/// Save the daemon config. Older layouts are migrated first; the result is
+/// validated against the expected shape, then the current `config.json` is
+/// backed up and the backup's file name is returned as `backup` on success.
+/// With `text` (the raw editor contents) the config is parsed from it
+/// leniently, as `format_config_text` does, and `config` is ignored.
 #[tauri::command]
-fn save_config(config: Value) -> Value {
-  let Some(ipc_path) = get_ipc_path() else {
+fn save_config(config: Option<Value>, text: Option<String>) -> Value {
+  let config = match config_input(config, text.as_deref()) {
+    Ok(config) => config,
+    Err(e) => return e,
+  };
+  save_config_at(get_ipc_path().as_deref(), config, config_backup::backup_current_config)
+}
+
+/// `save_config` against the daemon at `ipc_path`, with `backup` backing up
+/// the current config. Also the write path of config import and restore.
+fn save_config_at(
+  ipc_path: Option<&str>,
+  config: Value,
+  backup: impl FnOnce() -> Result<Option<String>, String>,
+) -> Value {
+  let mut config = config_migrate::migrate(config).config;
+  if let Some(map) = config.as_object_mut() {
+    map.remove("migrated_from");
+    map.remove("config_hash");
+  }
+  // Reject malformed configs locally, before the daemon ever sees them
+  let validation = config_schema::validate(&config);
+  if !validation.is_valid() {
+    return serde_json::to_value(validation).unwrap_or_default();
+  }
+
+  let Some(ipc_path) = ipc_path else {
     return serde_json::json!({ "ok": false, "error": "daemon not running" });
   };
 
+  let backup = match backup() {
+    Ok(name) => name,
+    Err(e) => {
+      return serde_json::json!({ "ok": false, "error": format!("配置备份失败: {}", e) })
+    }
+  };
+
   let req = serde_json::json!({
     "type": "save_config_request",
     "payload": config
   });
   let req_str = serde_json::to_string(&req).unwrap_or_default();
 
-  if let Some(resp) = ipc_request_typed::<GenericOkResponse>(&ipc_path, &req_str) {
-    serde_json::json!({ "ok": resp.payload.ok, "error": resp.payload.error })
+  let _own_write = file_watcher::own_config_write();
+  let resp = ipc_request_typed::<GenericOkResponse>(ipc_path, &req_str);
+  config_cache::invalidate();
+  match resp {
+    Ok(resp) => {
+      if let Some(name) = backup.as_deref().filter(|_| resp.payload.ok) {
+        config_undo::record_save(name);
+      }
+      serde_json::json!({ "ok": resp.payload.ok, "error": resp.payload.error, "backup": backup })
+    }
+    Err(e) => e.to_json(),
+  }
+}
+
+/// The config `save_config` was given: parsed from `text` when present,
+/// else `config`.
+fn config_input(config: Option<Value>, text: Option<&str>) -> Result<Value, Value> {
+  match (text, config) {
+    (Some(text), _) => config_text::parse_config_text(text).map_err(|e| e.to_json()),
+    (None, Some(config)) => Ok(config),
+    (None, None) => Err(serde_json::json!({ "ok": false, "error": "没有要保存的配置" })),
+  }
+}
+
+/// Mirror of the shared `defaultAppConfig`, used to reset daemons that
+/// predate `reset_config_request`.
+const DEFAULT_CONFIG: &str = r#"{
+  "schemaVersion": 2,
+  "bots": { "interactive": [], "push": [] },
+  "reconnect": { "maxRetries": 3, "initialInterval": 5, "backoffMultiplier": 2 },
+  "push": { "mergeWindow": 2000, "maxMessageBytes": 30000 },
+  "defaults": {},
+  "input": { "enterRetryCount": 2, "enterRetryInterval": 500 }
+}"#;
+
+/// Factory reset of the daemon config, after a confirmation dialog and a
+/// backup of the current `config.json`. `method` reports whether the daemon
+/// reset itself ("daemon") or the built-in defaults were saved ("fallback").
+#[tauri::command]
+fn reset_config(app: AppHandle) -> Value {
+  let Some(ipc_path) = get_ipc_path() else {
+    return serde_json::json!({ "ok": false, "error": "daemon not running" });
+  };
+  let Some(native) = capabilities::supports("reset_config_request") else {
+    return serde_json::json!({ "ok": false, "error": "daemon not running" });
+  };
+
+  let confirmed = app
+    .dialog()
+    .message("将清除所有机器人和设置，恢复为默认配置。\n当前配置会先自动备份。")
+    .title("恢复默认配置?")
+    .kind(MessageDialogKind::Warning)
+    .buttons(MessageDialogButtons::OkCancel)
+    .blocking_show();
+  if !confirmed {
+    return serde_json::json!({ "ok": false, "error": "cancelled" });
+  }
+
+  // Never reset without a backup to come back to
+  let backup = match config_backup::backup_current_config() {
+    Ok(name) => name,
+    Err(e) => {
+      return serde_json::json!({ "ok": false, "error": format!("配置备份失败: {}", e) })
+    }
+  };
+
+  reset_config_at(&ipc_path, native, backup)
+}
+
+/// Reset the daemon at `ipc_path`, natively or by saving the defaults, once
+/// confirmed and backed up.
+fn reset_config_at(ipc_path: &str, native: bool, backup: Option<String>) -> Value {
+  let (req, method) = if native {
+    (serde_json::json!({ "type": "reset_config_request" }), "daemon")
   } else {
-    serde_json::json!({ "ok": false, "error": "no response from daemon" })
+    let defaults: Value = serde_json::from_str(DEFAULT_CONFIG).unwrap_or_default();
+    let req = serde_json::json!({ "type": "save_config_request", "payload": defaults });
+    (req, "fallback")
+  };
+  let req_str = serde_json::to_string(&req).unwrap_or_default();
+
+  let _own_write = file_watcher::own_config_write();
+  let resp = ipc_request_typed::<GenericOkResponse>(ipc_path, &req_str);
+  config_cache::invalidate();
+  match resp {
+    Ok(resp) => serde_json::json!({
+      "ok": resp.payload.ok,
+      "error": resp.payload.error,
+      "backup": backup,
+      "method": method,
+    }),
+    Err(e) => {
+      let mut result = e.to_json();
+      result["backup"] = serde_json::json!(backup);
+      result["method"] = Value::from(method);
+      result
+    }
   }
 }
 
@@ -504,6 +2155,7 @@ fn save_config(config: Value) -> Value {
 
 fn show_main_window(app: &AppHandle) {
   if let Some(window) = app.get_webview_window("main") {
+    let _ = window.unminimize();
     let _ = window.show();
     let _ = window.set_focus();
   }
@@ -551,232 +2203,485 @@ fn find_daemon_exe(app: &AppHandle) -> Result<PathBuf, String> {
   Err(format!("daemon not found: {}", daemon_name))
 }
 
-/// Spawn the daemon process in detached mode.
+/// Spawn the daemon process in detached mode, after the integrity check.
 fn spawn_daemon(daemon_path: &std::path::Path) -> Result<(), String> {
+  daemon_integrity::check(daemon_path)?;
+
   #[cfg(target_os = "windows")]
-  {
+  let child = {
     use std::os::windows::process::CommandExt;
     const DETACHED_PROCESS: u32 = 0x00000008;
     std::process::Command::new(daemon_path)
       .creation_flags(DETACHED_PROCESS)
       .spawn()
-      .map_err(|e| e.to_string())?;
-  }
+      .map_err(|e| e.to_string())?
+  };
 
   #[cfg(not(target_os = "windows"))]
-  {
-    std::process::Command::new(daemon_path)
-      .stdin(std::process::Stdio::null())
-      .stdout(std::process::Stdio::null())
-      .stderr(std::process::Stdio::null())
-      .spawn()
-      .map_err(|e| e.to_string())?;
-  }
+  let child = std::process::Command::new(daemon_path)
+    .stdin(std::process::Stdio::null())
+    .stdout(std::process::Stdio::null())
+    .stderr(std::process::Stdio::null())
+    .spawn()
+    .map_err(|e| e.to_string())?;
 
+  *SPAWNED_DAEMON.lock().unwrap_or_else(|e| e.into_inner()) = Some((child, Instant::now()));
   Ok(())
 }
 
+/// How long a daemon this GUI spawned counts as booting while it runs but
+/// doesn't answer yet; one unreachable for longer is assumed stuck.
+const DAEMON_BOOT_GRACE: Duration = Duration::from_secs(60);
+
+/// The daemon process spawned last and when, kept to tell a booting daemon
+/// from none at all (and to reap it once it exits).
+static SPAWNED_DAEMON: Mutex<Option<(std::process::Child, Instant)>> = Mutex::new(None);
+
+/// Whether a daemon spawned by this GUI is still booting: running, but not
+/// yet reachable through the lock file. Spawning another one then would
+/// leave two daemons fighting over the socket.
+fn daemon_booting() -> bool {
+  let mut spawned = SPAWNED_DAEMON.lock().unwrap_or_else(|e| e.into_inner());
+  let Some((child, spawned_at)) = spawned.as_mut() else {
+    return false;
+  };
+  if !matches!(child.try_wait(), Ok(None)) {
+    *spawned = None;
+    return false;
+  }
+  spawned_at.elapsed() < DAEMON_BOOT_GRACE && !is_daemon_running()
+}
+
+/// Holds `AUTO_START_ACTIVE` for one start, from `start_daemon` or an
+/// auto-start run; None while another start holds it.
+struct StartGuard;
+
+impl StartGuard {
+  fn take() -> Option<Self> {
+    (!AUTO_START_ACTIVE.swap(true, Ordering::AcqRel)).then_some(StartGuard)
+  }
+}
+
+impl Drop for StartGuard {
+  fn drop(&mut self) {
+    AUTO_START_ACTIVE.store(false, Ordering::Release);
+  }
+}
+
+#[derive(Debug, Serialize, Default)]
+struct StartDaemonResult {
+  ok: bool,
+  already_running: bool,
+  error: Option<String>,
+  /// Pid from the lock file once the new daemon accepts connections; None
+  /// if it did not come up within the wait (it may still be starting).
+  spawned_pid: Option<i64>,
+}
+
+impl StartDaemonResult {
+  fn failed(error: String) -> Self {
+    StartDaemonResult {
+      error: Some(error),
+      ..Default::default()
+    }
+  }
+}
+
 #[tauri::command]
-fn start_daemon(app: AppHandle) -> Value {
+async fn start_daemon(app: AppHandle) -> StartDaemonResult {
   // If daemon is already running, return immediately
   if is_daemon_running() {
-    return serde_json::json!({ "ok": true, "already_running": true });
+    return StartDaemonResult {
+      ok: true,
+      already_running: true,
+      ..Default::default()
+    };
   }
 
-  let daemon_path = match find_daemon_exe(&app) {
-    Ok(p) => p,
-    Err(e) => return serde_json::json!({ "ok": false, "error": e }),
+  let Some(_start) = StartGuard::take() else {
+    return StartDaemonResult::failed("daemon is already starting".to_string());
   };
+  // A daemon still booting from an earlier start is waited for instead
+  if !daemon_booting() {
+    let daemon_path = match find_daemon_exe(&app) {
+      Ok(p) => p,
+      Err(e) => return StartDaemonResult::failed(e),
+    };
+    if let Err(e) = spawn_daemon(&daemon_path) {
+      return StartDaemonResult::failed(e);
+    }
+  }
 
-  match spawn_daemon(&daemon_path) {
-    Ok(_) => serde_json::json!({ "ok": true }),
-    Err(e) => serde_json::json!({ "ok": false, "error": e }),
+  // The lock file may be left over from a previous daemon, so only trust
+  // its pid once the daemon behind it is reachable (up to ~6 seconds)
+  for _ in 0..20 {
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    if let Some(lock) = read_lock_file().filter(|lock| daemon_reachable(&lock.ipc)) {
+      return StartDaemonResult {
+        ok: true,
+        spawned_pid: Some(lock.pid),
+        ..Default::default()
+      };
+    }
+  }
+  StartDaemonResult {
+    ok: true,
+    ..Default::default()
   }
 }
 
+/// The daemon's check, or the same check done locally (`source: "local"`)
+/// when it can't be asked. Null only if the home directory is unknown.
 #[tauri::command]
 fn check_codex_config() -> Value {
+  let local = || match get_home_dir() {
+    Some(home) => cli_config_local::check_codex(Path::new(&home)),
+    None => Value::Null,
+  };
   let Some(ipc_path) = get_ipc_path() else {
-    return serde_json::json!(null);
+    return local();
   };
 
   let req = r#"{"type":"check_codex_config_request"}"#;
-  if let Some(value) = ipc_request(&ipc_path, req) {
-    if let Some(payload) = value.get("payload") {
-      return payload.clone();
+  match ipc_payload(&ipc_path, req) {
+    Ok(mut status) => {
+      hook_paths::merge_into(&mut status, &local());
+      status
     }
+    Err(_) => local(),
   }
-  serde_json::json!(null)
 }
 
 #[tauri::command]
 fn open_codex_config_file() -> Value {
-  let Some(home) = get_home_dir() else {
-    return serde_json::json!({ "ok": false, "error": "cannot determine home directory" });
-  };
-  let config_path = PathBuf::from(&home).join(".codex").join("config.toml");
+  integrations::open_cli_config_file("codex".to_string())
+}
 
-  if !config_path.exists() {
-    // Create the file so the user can edit it
-    let codex_dir = PathBuf::from(&home).join(".codex");
-    if !codex_dir.exists() {
-      return serde_json::json!({ "ok": false, "error": "~/.codex/ 目录不存在，请先安装 Codex" });
-    }
-    if let Err(e) = fs::write(&config_path, "") {
-      return serde_json::json!({ "ok": false, "error": format!("无法创建 config.toml: {}", e) });
-    }
+/// Ask the daemon what a CLI config setup would change, without writing.
+/// Returns the daemon's payload (`changed`, `currentContent`, `newContent`, `diff`).
+fn setup_config_preview(ipc_path: &str, request_type: &str) -> Value {
+  // A daemon that doesn't advertise it would ignore dryRun and apply the change
+  if !capabilities::has_feature(capabilities::SETUP_DRY_RUN) {
+    return serde_json::json!({ "ok": false, "error": "daemon does not support dry run" });
   }
 
-  let result = {
-    #[cfg(target_os = "windows")]
-    {
-      std::process::Command::new("cmd")
-        .args(["/c", "start", "", &config_path.to_string_lossy()])
-        .spawn()
-    }
-    #[cfg(target_os = "macos")]
-    {
-      std::process::Command::new("open")
-        .arg(&config_path)
-        .spawn()
-    }
-    #[cfg(target_os = "linux")]
-    {
-      std::process::Command::new("xdg-open")
-        .arg(&config_path)
-        .spawn()
-    }
-  };
-
-  match result {
-    Ok(_) => serde_json::json!({ "ok": true }),
-    Err(e) => serde_json::json!({ "ok": false, "error": format!("无法打开文件: {}", e) }),
+  let req = serde_json::json!({ "type": request_type, "payload": { "dryRun": true } });
+  let req_str = serde_json::to_string(&req).unwrap_or_default();
+  match ipc_payload(ipc_path, &req_str) {
+    Ok(payload) => payload,
+    Err(e) => e.to_json(),
   }
 }
 
-#[tauri::command]
-fn setup_codex_config() -> Value {
+/// Snapshot `name`'s config and have the daemon add Felay's hook to it with
+/// `request_type`; with `dry_run`, only preview. An `expected_hash` from a
+/// preview must still match the file.
+fn setup_cli_config(
+  name: &str,
+  request_type: &str,
+  dry_run: Option<bool>,
+  expected_hash: Option<String>,
+) -> Value {
   let Some(ipc_path) = get_ipc_path() else {
     return serde_json::json!({ "ok": false, "error": "daemon not running" });
   };
 
-  let req = r#"{"type":"setup_codex_config_request"}"#;
-  if let Some(resp) = ipc_request_typed::<GenericOkResponse>(&ipc_path, req) {
-    serde_json::json!({ "ok": resp.payload.ok, "error": resp.payload.error })
-  } else {
-    serde_json::json!({ "ok": false, "error": "no response from daemon" })
+  if dry_run.unwrap_or(false) {
+    return setup_config_preview(&ipc_path, request_type);
   }
+  if let Err(conflict) = cli_setup_preview::ensure_unchanged(name, expected_hash.as_deref()) {
+    return conflict;
+  }
+
+  let _own_write = cli_hook_watch::own_write(name);
+  let backup = match cli_config_backup::snapshot(name) {
+    Ok(name) => name,
+    Err(e) => {
+      return serde_json::json!({ "ok": false, "error": format!("配置备份失败: {}", e) })
+    }
+  };
+
+  let req = serde_json::json!({ "type": request_type }).to_string();
+  match ipc_request_typed::<GenericOkResponse>(&ipc_path, &req) {
+    Ok(resp) => {
+      serde_json::json!({ "ok": resp.payload.ok, "error": resp.payload.error, "backup": backup })
+    }
+    Err(e) => e.to_json(),
+  }
+}
+
+#[tauri::command]
+fn setup_codex_config(dry_run: Option<bool>, expected_hash: Option<String>) -> Value {
+  setup_cli_config("codex", "setup_codex_config_request", dry_run, expected_hash)
 }
 
+/// Like `check_codex_config`, for Claude Code's Stop hook.
 #[tauri::command]
 fn check_claude_config() -> Value {
+  let local = || match get_home_dir() {
+    Some(home) => cli_config_local::check_claude(Path::new(&home)),
+    None => Value::Null,
+  };
   let Some(ipc_path) = get_ipc_path() else {
-    return serde_json::json!(null);
+    return local();
   };
 
   let req = r#"{"type":"check_claude_config_request"}"#;
-  if let Some(value) = ipc_request(&ipc_path, req) {
-    if let Some(payload) = value.get("payload") {
-      return payload.clone();
+  match ipc_payload(&ipc_path, req) {
+    Ok(mut status) => {
+      hook_paths::merge_into(&mut status, &local());
+      status
     }
+    Err(_) => local(),
   }
-  serde_json::json!(null)
 }
 
 #[tauri::command]
-fn setup_claude_config() -> Value {
-  let Some(ipc_path) = get_ipc_path() else {
-    return serde_json::json!({ "ok": false, "error": "daemon not running" });
-  };
-
-  let req = r#"{"type":"setup_claude_config_request"}"#;
-  if let Some(resp) = ipc_request_typed::<GenericOkResponse>(&ipc_path, req) {
-    serde_json::json!({ "ok": resp.payload.ok, "error": resp.payload.error })
-  } else {
-    serde_json::json!({ "ok": false, "error": "no response from daemon" })
-  }
+fn setup_claude_config(dry_run: Option<bool>, expected_hash: Option<String>) -> Value {
+  setup_cli_config("claude", "setup_claude_config_request", dry_run, expected_hash)
 }
 
 #[tauri::command]
 fn open_claude_config_file() -> Value {
+  integrations::open_cli_config_file("claude".to_string())
+}
+
+/// Gemini CLI's settings, checked locally. Felay has no Gemini hook yet (no
+/// daemon handler, no hook script), so the result always says
+/// `supported: false`.
+#[tauri::command]
+fn check_gemini_config() -> Value {
   let Some(home) = get_home_dir() else {
-    return serde_json::json!({ "ok": false, "error": "cannot determine home directory" });
+    return Value::Null;
   };
-  let config_path = PathBuf::from(&home).join(".claude").join("settings.json");
+  let mut status = cli_config_local::check_gemini(Path::new(&home));
+  status["supported"] = Value::Bool(false);
+  status
+}
 
-  if !config_path.exists() {
-    let claude_dir = PathBuf::from(&home).join(".claude");
-    if !claude_dir.exists() {
-      return serde_json::json!({ "ok": false, "error": "~/.claude/ 目录不存在，请先安装 Claude Code" });
-    }
-    if let Err(e) = fs::write(&config_path, "{}") {
-      return serde_json::json!({ "ok": false, "error": format!("无法创建 settings.json: {}", e) });
+/// Gemini CLI can't be set up yet; see `check_gemini_config`.
+#[tauri::command]
+fn setup_gemini_config(_dry_run: Option<bool>, _expected_hash: Option<String>) -> Value {
+  serde_json::json!({
+    "ok": false,
+    "supported": false,
+    "error": "Felay 暂不支持 Gemini CLI",
+  })
+}
+
+#[tauri::command]
+fn open_gemini_config_file() -> Value {
+  integrations::open_cli_config_file("gemini".to_string())
+}
+
+/// Pause before the single retry of a transient failure.
+const UPDATE_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+fn update_timeout() -> Duration {
+  gui_settings::with(|s| s.update_check_timeout())
+}
+
+/// Each channel has its own cache, so switching never answers with the
+/// other channel's result or ETag.
+fn update_cache_file(channel: gui_settings::UpdateChannel) -> Option<PathBuf> {
+  let home = get_home_dir()?;
+  let name = match channel {
+    gui_settings::UpdateChannel::Stable => "update-cache.json",
+    gui_settings::UpdateChannel::Beta => "update-cache-beta.json",
+  };
+  Some(PathBuf::from(home).join(".felay").join(name))
+}
+
+fn update_cache_path() -> Option<PathBuf> {
+  update_cache_file(update_channel())
+}
+
+fn read_update_cache() -> Option<UpdateCache> {
+  let text = fs::read_to_string(update_cache_path()?).ok()?;
+  serde_json::from_str::<UpdateCache>(&text).ok()
+}
+
+/// The last full result written to the cache and its channel, for
+/// resolving a 304 when the cache file can't be read back.
+static LAST_FULL_UPDATE: Mutex<Option<(gui_settings::UpdateChannel, UpdateInfo)>> =
+  Mutex::new(None);
+
+fn last_full_update() -> Option<UpdateInfo> {
+  let last = LAST_FULL_UPDATE.lock().unwrap_or_else(|e| e.into_inner()).clone();
+  last.filter(|(channel, _)| *channel == update_channel()).map(|(_, info)| info)
+}
+
+fn write_update_cache(info: &UpdateInfo) {
+  *LAST_FULL_UPDATE.lock().unwrap_or_else(|e| e.into_inner()) =
+    Some((update_channel(), info.clone()));
+  let Some(path) = update_cache_path() else {
+    return;
+  };
+  let cache = UpdateCache {
+    checked_at: unix_now(),
+    info: info.clone(),
+  };
+  if let Some(dir) = path.parent() {
+    let _ = fs::create_dir_all(dir);
+  }
+  // Through a temp file, so a crash can't leave a half-written cache
+  if let Ok(text) = serde_json::to_string_pretty(&cache) {
+    let tmp = path.with_extension("json.tmp");
+    if fs::write(&tmp, text).and_then(|_| fs::rename(&tmp, &path)).is_err() {
+      let _ = fs::remove_file(&tmp);
     }
   }
+}
 
-  let result = {
-    #[cfg(target_os = "windows")]
-    {
-      std::process::Command::new("cmd")
-        .args(["/c", "start", "", &config_path.to_string_lossy()])
-        .spawn()
-    }
-    #[cfg(target_os = "macos")]
-    {
-      std::process::Command::new("open")
-        .arg(&config_path)
-        .spawn()
-    }
-    #[cfg(target_os = "linux")]
-    {
-      std::process::Command::new("xdg-open")
-        .arg(&config_path)
-        .spawn()
+/// Forget the cached update results of both channels, so the next check
+/// asks the network unconditionally.
+#[tauri::command]
+fn clear_update_cache() -> Result<(), String> {
+  *LAST_FULL_UPDATE.lock().unwrap_or_else(|e| e.into_inner()) = None;
+  let channels = [gui_settings::UpdateChannel::Stable, gui_settings::UpdateChannel::Beta];
+  for path in channels.into_iter().filter_map(update_cache_file) {
+    match fs::remove_file(&path) {
+      Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+        return Err(format!("无法删除 {}: {}", path.display(), e));
+      }
+      _ => {}
     }
+  }
+  Ok(())
+}
+
+/// The cached result in place of a failed check, flagged `stale`; the
+/// error if there is none.
+fn stale_or_error(
+  cache: Option<UpdateCache>,
+  error: UpdateError,
+) -> Result<UpdateInfo, UpdateError> {
+  let Some(cache) = cache else {
+    return Err(error);
   };
+  gui_println!("[gui] update check failed ({}), answering from the cache", error.message);
+  let mut info = refresh_cached_info(cache.info, UpdateSource::Cache);
+  info.stale = true;
+  info.stale_age_secs = Some(unix_now().saturating_sub(cache.checked_at));
+  info.stale_error = Some(error);
+  Ok(info)
+}
 
-  match result {
-    Ok(_) => serde_json::json!({ "ok": true }),
-    Err(e) => serde_json::json!({ "ok": false, "error": format!("无法打开文件: {}", e) }),
-  }
+/// The last full update check's result, from memory or the cache.
+fn last_update_info() -> Option<UpdateInfo> {
+  last_full_update().or_else(|| read_update_cache().map(|cache| cache.info))
 }
 
-#[tauri::command]
-async fn check_update(cached_etag: Option<String>) -> Result<UpdateInfo, String> {
+/// Release notes of the last full update check, for checksums published
+/// there.
+fn last_release_notes() -> Option<String> {
+  last_update_info().map(|info| info.release_notes)
+}
+
+/// Re-evaluate a cached result against the running version, which may have
+/// changed since the cache was written.
+fn refresh_cached_info(mut info: UpdateInfo, source: UpdateSource) -> UpdateInfo {
   let current = env!("CARGO_PKG_VERSION");
+  info.has_update = version_gt(tag_version(&info.latest_version), current);
+  info.current_version = current.to_string();
+  // The running version may now be one of the skipped releases
+  info.intermediate_versions.retain(|tag| version_gt(tag_version(tag), current));
+  info.releases_behind = match info.has_update {
+    true => info.intermediate_versions.len() as u32 + 1,
+    false => 0,
+  };
+  // Cached by another build of the app, perhaps
+  info.recommended_asset = update_assets::recommend_for_host(&info.assets);
+  info.not_modified = false;
+  info.source = source;
+  info.stale = false;
+  info.stale_age_secs = None;
+  info.stale_error = None;
+  info
+}
 
-  let client = reqwest::Client::builder()
-    .user_agent("Felay-Updater")
-    .timeout(Duration::from_secs(15))
-    .build()
-    .map_err(|e| e.to_string())?;
+/// Tags of the published releases newer than `current`, excluding
+/// `latest_tag`, newest first; pre-releases only with `include_prereleases`.
+/// `None` if the list can't be read.
+async fn intermediate_releases(
+  client: &reqwest::Client,
+  target: &UpdateTarget,
+  endpoint: &str,
+  current: &str,
+  latest_tag: &str,
+  include_prereleases: bool,
+) -> Option<Vec<String>> {
+  let url = releases_list_endpoint(endpoint, 100)?;
+  let token = target.token_for(&url);
+  let resp = http::get_with_token(client, &url, token.as_deref()).send().await.ok()?;
+  if !resp.status().is_success() {
+    return None;
+  }
+  let releases: Vec<Value> = resp.json().await.ok()?;
+  let mut tags: Vec<String> = releases
+    .iter()
+    .filter(|r| r["draft"] != true && (include_prereleases || r["prerelease"] != true))
+    .filter_map(|r| r["tag_name"].as_str())
+    .filter(|tag| *tag != latest_tag && version_gt(tag_version(tag), current))
+    .map(str::to_string)
+    .collect();
+  tags.sort_by(|a, b| version_cmp(b, a));
+  Some(tags)
+}
+
+/// Query `endpoint`, one of `target`'s as `source` says, sending `etag` as
+/// `If-None-Match` if given.
+async fn fetch_update_once(
+  client: &reqwest::Client,
+  target: &UpdateTarget,
+  endpoint: &str,
+  source: UpdateSource,
+  etag: Option<&str>,
+) -> Result<UpdateInfo, UpdateError> {
+  let current = env!("CARGO_PKG_VERSION");
 
-  let mut req = client.get("https://api.github.com/repos/zqq-nuli/Felay/releases/latest");
+  // The beta channel picks from the recent releases, pre-releases included
+  let beta = update_channel() == gui_settings::UpdateChannel::Beta;
+  let list_url = releases_list_endpoint(endpoint, 10).filter(|_| beta);
+  let url = list_url.as_deref().unwrap_or(endpoint);
+  let token = target.token_for(url);
+  let mut req = http::get_with_token(client, url, token.as_deref());
 
   // ETag conditional request — 304 responses don't count against rate limit
-  if let Some(ref etag) = cached_etag {
+  if let Some(etag) = etag {
     if !etag.is_empty() {
-      req = req.header("If-None-Match", etag.as_str());
+      req = req.header("If-None-Match", etag);
     }
   }
 
-  let resp = req.send().await.map_err(|e| e.to_string())?;
+  let resp = req.send().await.map_err(|e| UpdateError::from_reqwest(&e))?;
 
   // 304 Not Modified — cached data is still valid
   if resp.status() == reqwest::StatusCode::NOT_MODIFIED {
     return Ok(UpdateInfo {
       not_modified: true,
-      etag: cached_etag.unwrap_or_default(),
+      etag: etag.unwrap_or_default().to_string(),
       has_update: false,
       current_version: current.to_string(),
       latest_version: String::new(),
       release_url: String::new(),
       release_notes: String::new(),
+      source,
+      releases_behind: 0,
+      intermediate_versions: Vec::new(),
+      assets: Vec::new(),
+      recommended_asset: None,
+      prerelease: false,
+      stale: false,
+      stale_age_secs: None,
+      stale_error: None,
+      suppressed: false,
+      suppressed_reason: None,
     });
   }
 
   if !resp.status().is_success() {
-    return Err(format!("GitHub API returned {}", resp.status()));
+    return Err(UpdateError::from_response(&resp, token.is_some()));
   }
 
   // Extract ETag from response headers before consuming the body
@@ -787,36 +2692,304 @@ async fn check_update(cached_etag: Option<String>) -> Result<UpdateInfo, String>
     .unwrap_or("")
     .to_string();
 
-  let json: Value = resp.json().await.map_err(|e| e.to_string())?;
+  let json: Value = resp.json().await.map_err(|e| UpdateError::from_reqwest(&e))?;
+  let json = match (&list_url, json) {
+    (Some(_), Value::Array(releases)) => newest_release(releases)
+      .ok_or_else(|| UpdateError::other("no published releases".to_string()))?,
+    (_, json) => json,
+  };
 
   let tag = json["tag_name"].as_str().unwrap_or("v0.0.0");
-  // tag_name is like "v0.1.0-beta" — extract the numeric version part
-  let latest = tag
-    .trim_start_matches('v')
-    .split('-')
-    .next()
-    .unwrap_or("0.0.0");
+  let has_update = version_gt(tag_version(tag), current);
 
+  // Only worth the extra request when there is something to count
+  let (releases_behind, intermediate_versions) = if has_update {
+    match intermediate_releases(client, target, endpoint, current, tag, beta).await {
+      Some(tags) => (tags.len() as u32 + 1, tags),
+      None => (1, Vec::new()),
+    }
+  } else {
+    (0, Vec::new())
+  };
+
+  let assets = update_assets::classify(&json);
   Ok(UpdateInfo {
     not_modified: false,
     etag,
-    has_update: version_gt(latest, current),
+    has_update,
     current_version: current.to_string(),
     latest_version: tag.to_string(),
     release_url: json["html_url"].as_str().unwrap_or("").to_string(),
     release_notes: json["body"].as_str().unwrap_or("").to_string(),
+    source,
+    releases_behind,
+    intermediate_versions,
+    recommended_asset: update_assets::recommend_for_host(&assets),
+    assets,
+    prerelease: json["prerelease"] == true,
+    stale: false,
+    stale_age_secs: None,
+    stale_error: None,
+    suppressed: false,
+    suppressed_reason: None,
+  })
+}
+
+/// The release with the highest version in a releases list, drafts aside.
+fn newest_release(releases: Vec<Value>) -> Option<Value> {
+  releases
+    .into_iter()
+    .filter(|r| r["draft"] != true && r["tag_name"].is_string())
+    .max_by(|a, b| {
+      let tag = |r: &Value| tag_version(r["tag_name"].as_str().unwrap_or_default()).to_string();
+      version_cmp(&tag(a), &tag(b))
+    })
+}
+
+/// `fetch_update_from` the configured endpoints.
+async fn fetch_update(etag: Option<&str>) -> Result<UpdateInfo, UpdateError> {
+  let target = UpdateTarget::from_settings()?;
+  let client = http::http_client(update_timeout()).map_err(UpdateError::other)?;
+  fetch_update_from(&client, &target, etag).await
+}
+
+/// The mirror, if one is set, then GitHub. A failed GitHub request is
+/// retried once after a short delay on transient failures, with the same
+/// ETag; the mirror gets one try before GitHub is asked.
+async fn fetch_update_from(
+  client: &reqwest::Client,
+  target: &UpdateTarget,
+  etag: Option<&str>,
+) -> Result<UpdateInfo, UpdateError> {
+  if let Some(mirror) = &target.mirror {
+    match fetch_update_once(client, target, mirror, UpdateSource::Mirror, etag).await {
+      Ok(info) => return Ok(info),
+      Err(e) => gui_println!("[gui] update mirror failed ({}), trying GitHub", e.message),
+    }
+  }
+
+  let github = &target.github;
+  match fetch_update_once(client, target, github, UpdateSource::Github, etag).await {
+    Err(e) if e.is_transient() => {
+      gui_println!("[gui] update check failed ({}), retrying once", e.message);
+      tokio::time::sleep(UPDATE_RETRY_DELAY).await;
+      fetch_update_once(client, target, github, UpdateSource::Github, etag).await
+    }
+    result => result,
+  }
+}
+
+/// Mark `info` suppressed if its update is the skipped version or reminders
+/// are snoozed. Applied per answer, never cached.
+fn apply_suppression(mut info: UpdateInfo) -> UpdateInfo {
+  let (skipped, snoozed_until) =
+    gui_settings::with(|s| (s.skipped_update_version.clone(), s.update_snoozed_until));
+  // Only that exact version: a newer release is offered again
+  let skipped = skipped.is_some_and(|skipped| {
+    let latest = tag_version(&info.latest_version);
+    version_cmp(tag_version(&skipped), latest) == std::cmp::Ordering::Equal
+  });
+  let snoozed = snoozed_until.is_some_and(|until| unix_now() < until);
+  info.suppressed_reason = if !info.has_update {
+    None
+  } else if skipped {
+    Some(SuppressedReason::Skipped)
+  } else if snoozed {
+    Some(SuppressedReason::Snoozed)
+  } else {
+    None
+  };
+  info.suppressed = info.suppressed_reason.is_some();
+  info
+}
+
+/// Throttled update check for automatic/background use. Within
+/// `update_check_interval_hours` of the last check the cached result is
+/// returned without touching the network. A 304 comes back as the last full
+/// result with `not_modified` set. A skipped or snoozed update comes back
+/// `suppressed`.
+#[tauri::command]
+async fn check_update(cached_etag: Option<String>) -> Result<UpdateInfo, UpdateError> {
+  throttled_check_update(cached_etag).await.map(apply_suppression)
+}
+
+async fn throttled_check_update(cached_etag: Option<String>) -> Result<UpdateInfo, UpdateError> {
+  ensure_update_check_enabled()?;
+  let cache = read_update_cache();
+  if let Some(cache) = cache.as_ref() {
+    let interval = gui_settings::with(|s| s.update_check_interval()).as_secs();
+    if unix_now().saturating_sub(cache.checked_at) < interval {
+      return Ok(refresh_cached_info(cache.info.clone(), UpdateSource::Cache));
+    }
+  }
+
+  let etag = cached_etag.or_else(|| cache.as_ref().map(|c| c.info.etag.clone()));
+  let info = match fetch_update(etag.as_deref()).await {
+    Ok(info) => info,
+    Err(e) => return stale_or_error(cache, e),
+  };
+  if !info.not_modified {
+    write_update_cache(&info);
+    return Ok(info);
+  }
+
+  // Still current: answer with the full result the ETag belongs to, so the
+  // UI keeps the release details, and refresh its timestamp
+  let known = cache
+    .map(|c| c.info)
+    .or_else(last_full_update)
+    .filter(|known| known.etag == info.etag);
+  match known {
+    Some(known) => {
+      write_update_cache(&known);
+      let mut resolved = refresh_cached_info(known, info.source);
+      resolved.not_modified = true;
+      Ok(resolved)
+    }
+    None => Ok(info),
+  }
+}
+
+/// "Check now": always hits the network, ignoring the throttle. The ETag is
+/// still sent, but a 304 is resolved to the cached full result rather than
+/// returned as `not_modified`. Suppression is reported as by `check_update`.
+#[tauri::command]
+async fn force_check_update() -> Result<UpdateInfo, UpdateError> {
+  forced_check_update().await.map(apply_suppression)
+}
+
+async fn forced_check_update() -> Result<UpdateInfo, UpdateError> {
+  ensure_update_check_enabled()?;
+  let cache = read_update_cache();
+  let etag = cache.as_ref().map(|c| c.info.etag.clone());
+
+  let mut info = match fetch_update(etag.as_deref()).await {
+    Ok(info) => info,
+    Err(e) => return stale_or_error(cache, e),
+  };
+  if info.not_modified {
+    info = match cache {
+      Some(cache) => refresh_cached_info(cache.info, info.source),
+      // Nothing to resolve the 304 against; ask again unconditionally
+      None => fetch_update(None).await?,
+    };
+  }
+  write_update_cache(&info);
+  Ok(info)
+}
+
+const GITHUB_RATE_LIMIT_URL: &str = "https://api.github.com/rate_limit";
+
+/// The GitHub REST quota that update checks draw from: per IP address, or
+/// the larger per-user one with `github_token`.
+#[derive(Debug, Serialize)]
+struct RateLimit {
+  limit: u64,
+  remaining: u64,
+  /// Unix time at which `remaining` is refilled.
+  reset_at: u64,
+}
+
+/// Current GitHub API quota, for explaining throttled update checks. Uses
+/// the same client (timeout, proxy, token) as `check_update`; querying the
+/// quota does not consume it.
+#[tauri::command]
+async fn get_update_ratelimit() -> Result<RateLimit, UpdateError> {
+  let client = http::http_client(update_timeout()).map_err(UpdateError::other)?;
+  let resp = http::get(&client, GITHUB_RATE_LIMIT_URL)
+    .send()
+    .await
+    .map_err(|e| UpdateError::from_reqwest(&e))?;
+  if !resp.status().is_success() {
+    let authed = http::github_token_for(GITHUB_RATE_LIMIT_URL).is_some();
+    return Err(UpdateError::from_response(&resp, authed));
+  }
+  let json: Value = resp.json().await.map_err(|e| UpdateError::from_reqwest(&e))?;
+
+  let core = &json["resources"]["core"];
+  let field = |name: &str| {
+    core[name]
+      .as_u64()
+      .ok_or_else(|| UpdateError::other(format!("rate_limit response has no {}", name)))
+  };
+  Ok(RateLimit {
+    limit: field("limit")?,
+    remaining: field("remaining")?,
+    reset_at: field("reset")?,
   })
 }
 
+/// Short on purpose: the test is a button the user is waiting on.
+const NETWORK_TEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Result of `test_network`.
+#[derive(Debug, Serialize)]
+struct NetworkTest {
+  ok: bool,
+  url: String,
+  latency_ms: Option<u64>,
+  /// HTTP status of the answer; any status means the host was reached.
+  status: Option<u16>,
+  error: Option<String>,
+  error_kind: Option<UpdateErrorKind>,
+  used_proxy: bool,
+}
+
+/// Reach the root of the update endpoint's host with the update client
+/// (same proxy settings), to tell network trouble from a broken endpoint.
+#[tauri::command]
+async fn test_network() -> NetworkTest {
+  let mut result = NetworkTest {
+    ok: false,
+    url: String::new(),
+    latency_ms: None,
+    status: None,
+    error: None,
+    error_kind: None,
+    used_proxy: false,
+  };
+  let url = update_endpoint().and_then(|endpoint| {
+    let mut url = reqwest::Url::parse(&endpoint).map_err(|e| e.to_string())?;
+    url.set_path("/");
+    url.set_query(None);
+    Ok(url)
+  });
+  let client = |url| Ok((url, http::http_client(NETWORK_TEST_TIMEOUT)?));
+  let (url, client) = match url.and_then(client) {
+    Ok(pair) => pair,
+    Err(e) => {
+      result.error = Some(e);
+      result.error_kind = Some(UpdateErrorKind::Other);
+      return result;
+    }
+  };
+  result.url = url.to_string();
+  result.used_proxy = http::proxied(&url);
+
+  let started = Instant::now();
+  match client.get(url).send().await {
+    Ok(resp) => {
+      result.ok = true;
+      result.status = Some(resp.status().as_u16());
+      result.latency_ms = Some(started.elapsed().as_millis() as u64);
+    }
+    Err(e) => {
+      let e = UpdateError::from_reqwest(&e);
+      result.error = Some(e.message);
+      result.error_kind = Some(e.kind);
+    }
+  }
+  result
+}
+
+/// Bundle logs, sanitized config and system info into a zip the user picks
+/// a place for. With `reveal`, the zip is then shown in the file manager.
 #[tauri::command]
-fn collect_logs(app: AppHandle) -> Result<String, String> {
+fn collect_logs(app: AppHandle, reveal: Option<bool>) -> Result<String, String> {
   let home = get_home_dir().ok_or("Cannot determine home directory")?;
   let felay_dir = PathBuf::from(&home).join(".felay");
 
-  let now = std::time::SystemTime::now()
-    .duration_since(std::time::UNIX_EPOCH)
-    .unwrap_or_default()
-    .as_secs();
+  let now = unix_now();
   let default_name = format!("felay-logs-{}.zip", now);
 
   // Show native save-file dialog
@@ -838,23 +3011,33 @@ fn collect_logs(app: AppHandle) -> Result<String, String> {
   let options =
     SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
 
-  // Collect log files
-  for name in ["daemon.json", "proxy-debug.log", "proxy-hook-debug.log"] {
+  // Collect log files, with secrets that leaked into them masked
+  let sanitize = SanitizeOptions::default();
+  let logs = [
+    "daemon.json",
+    "proxy-debug.log",
+    "proxy-hook-debug.log",
+    audit::AUDIT_FILE,
+    ipc_trace::GUI_LOG_FILE,
+  ];
+  for name in logs {
     let path = felay_dir.join(name);
     if path.exists() {
       if let Ok(content) = fs::read(&path) {
+        let content = String::from_utf8_lossy(&content);
+        let content = secret_scan::mask_secrets(&content, &sanitize);
         zip
           .start_file(name, options)
           .map_err(|e| format!("zip start_file '{}': {}", name, e))?;
         zip
-          .write_all(&content)
+          .write_all(content.as_bytes())
           .map_err(|e| format!("zip write '{}': {}", name, e))?;
       }
     }
   }
 
   // Sanitized config.json (sensitive fields replaced with ***)
-  let config_path = felay_dir.join("config.json");
+  let config_path = profiles::config_path().unwrap_or_else(|| felay_dir.join("config.json"));
   if config_path.exists() {
     if let Ok(raw) = fs::read_to_string(&config_path) {
       let sanitized = sanitize_config(&raw);
@@ -867,14 +3050,27 @@ fn collect_logs(app: AppHandle) -> Result<String, String> {
     }
   }
 
+  // The GUI's own recent output, which may never have reached a file
+  let recent = recent_log::contents();
+  let recent = secret_scan::mask_secrets(&recent, &sanitize);
+  zip
+    .start_file("gui-recent.log", options)
+    .map_err(|e| format!("zip start_file gui-recent.log: {}", e))?;
+  zip
+    .write_all(recent.as_bytes())
+    .map_err(|e| format!("zip write gui-recent.log: {}", e))?;
+
   // System information
   let sysinfo = format!(
-    "App Version: {}\nOS: {}\nArch: {}\nDaemon Lock Exists: {}\nTimestamp: {}",
+    "App Version: {}\nOS: {}\nArch: {}\nDaemon Lock Exists: {}\nTimestamp: {}\n\nCLI Tools:\n{}\n\n\
+     CLI Configs:\n{}",
     env!("CARGO_PKG_VERSION"),
     std::env::consts::OS,
     std::env::consts::ARCH,
     felay_dir.join("daemon.json").exists(),
     now,
+    cli_detect::summary(),
+    cli_paths::summary(Path::new(&home)),
   );
   zip
     .start_file("system-info.txt", options)
@@ -887,74 +3083,374 @@ fn collect_logs(app: AppHandle) -> Result<String, String> {
     .finish()
     .map_err(|e| format!("Failed to finalize zip: {}", e))?;
 
+  // The bundle is written either way; not finding it is no reason to fail
+  if reveal == Some(true) {
+    if let Err(e) = os_open::reveal(&save_path) {
+      gui_println!("[gui] failed to reveal {:?}: {}", save_path, e);
+    }
+  }
   Ok(save_path.to_string_lossy().to_string())
 }
 
+/// Show an existing file inside the home directory, such as a log bundle,
+/// selected in the OS file manager.
 #[tauri::command]
-fn open_url(url: String) -> Value {
+fn reveal_in_file_manager(path: String) -> Result<(), String> {
+  let home = get_home_dir().ok_or("Cannot determine home directory")?;
+  let home = fs::canonicalize(&home).unwrap_or_else(|_| PathBuf::from(&home));
+  let target = fs::canonicalize(&path).map_err(|_| format!("File not found: {}", path))?;
+  if !target.starts_with(&home) || target == home {
+    return Err(format!("{} is outside the home directory", path));
+  }
+  // The path as given: canonical Windows paths carry a `\\?\` prefix the
+  // shell doesn't take
+  os_open::reveal(Path::new(&path)).map_err(|e| format!("Cannot reveal {}: {}", path, e))
+}
+
+/// True if any sensitive key anywhere in `value` holds a non-empty string, or
+/// any other string embeds a secret.
+fn has_sensitive_values(value: &Value) -> bool {
+  match value {
+    Value::Object(map) => map.iter().any(|(k, v)| {
+      if is_sensitive_key(k) {
+        v.as_str().is_some_and(|s| !s.is_empty())
+      } else {
+        has_sensitive_values(v)
+      }
+    }),
+    Value::Array(arr) => arr.iter().any(has_sensitive_values),
+    Value::String(s) => secret_scan::contains_secret(s),
+    _ => false,
+  }
+}
+
+/// Export the config as pretty JSON to a file chosen in the save dialog.
+/// The daemon's copy is used when it is up; otherwise `config.json` is read
+/// directly, and exporting that unsanitized requires `confirm_unsafe`.
+#[tauri::command]
+fn export_config(
+  app: AppHandle,
+  sanitized: bool,
+  confirm_unsafe: Option<bool>,
+) -> Result<String, String> {
+  let from_daemon = config_cache::config();
+  let (mut config, direct_read) = if from_daemon.is_null() {
+    let path = profiles::config_path().ok_or("Cannot determine home directory")?;
+    let raw = fs::read_to_string(&path).map_err(|e| format!("Cannot read config: {}", e))?;
+    let config =
+      serde_json::from_str::<Value>(&raw).map_err(|e| format!("Invalid config.json: {}", e))?;
+    (config, true)
+  } else {
+    (from_daemon, false)
+  };
+
+  if sanitized {
+    sanitize_value(&mut config, &SanitizeOptions::default());
+  } else if direct_read && has_sensitive_values(&config) && confirm_unsafe != Some(true) {
+    return Err("config.json contains secrets; pass confirm_unsafe to export it unsanitized".into());
+  }
+
+  // Backup timestamps are YYYYMMDD-HHMMSS; only the date goes in the name
+  let stamp = config_backup::format_timestamp(unix_now());
+  let date = stamp.split('-').next().unwrap_or(&stamp);
+  let save_path = app
+    .dialog()
+    .file()
+    .set_file_name(format!("felay-config-{}.json", date))
+    .add_filter("JSON", &["json"])
+    .blocking_save_file()
+    .ok_or("User cancelled")?
+    .into_path()
+    .map_err(|_| "Invalid save path".to_string())?;
+
+  let text = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+  fs::write(&save_path, text).map_err(|e| format!("Cannot write file: {}", e))?;
+  Ok(save_path.to_string_lossy().to_string())
+}
+
+/// Counterpart of `config_import::import_config_file`: export to a file
+/// chosen in the save dialog, sanitized unless `include_secrets` is set.
+/// Opting in also counts as `confirm_unsafe` for `export_config`.
+#[tauri::command]
+fn export_config_file(app: AppHandle, include_secrets: Option<bool>) -> Result<String, String> {
+  let include_secrets = include_secrets.unwrap_or(false);
+  export_config(app, !include_secrets, Some(include_secrets))
+}
+
+/// Open an http(s) URL in the system browser. The URL is parsed first so
+/// nothing but a well-formed web address ever reaches the shell.
+fn open_external(url: &str) -> Result<(), String> {
   // Validate URL scheme to prevent command injection
   if !url.starts_with("https://") && !url.starts_with("http://") {
-    return serde_json::json!({ "ok": false, "error": "URL must start with http:// or https://" });
+    return Err("URL must start with http:// or https://".to_string());
   }
+  let url = reqwest::Url::parse(url)
+    .map_err(|e| format!("invalid URL: {}", e))?
+    .to_string();
 
-  let result = {
-    #[cfg(target_os = "windows")]
-    {
-      std::process::Command::new("cmd")
-        .args(["/c", "start", "", &url])
-        .spawn()
-    }
-    #[cfg(target_os = "macos")]
-    {
-      std::process::Command::new("open").arg(&url).spawn()
-    }
-    #[cfg(target_os = "linux")]
-    {
-      std::process::Command::new("xdg-open").arg(&url).spawn()
-    }
-  };
+  os_open::open(std::ffi::OsStr::new(&url)).map_err(|e| format!("{}", e))
+}
 
-  match result {
+#[tauri::command]
+fn open_url(url: String) -> Value {
+  match open_external(&url) {
     Ok(_) => serde_json::json!({ "ok": true }),
-    Err(e) => serde_json::json!({ "ok": false, "error": format!("{}", e) }),
+    Err(e) => serde_json::json!({ "ok": false, "error": e }),
   }
 }
 
-/// Auto-start the daemon on app launch.
-/// Spawns the daemon if not already running, then waits up to ~6 seconds
-/// for it to become reachable. Runs on a background thread so the UI is
-/// not blocked.
-fn auto_start_daemon(app: &AppHandle) {
-  if is_daemon_running() {
-    println!("[gui] daemon already running, skipping auto-start");
-    return;
+const RELEASES_URL: &str = "https://github.com/zqq-nuli/Felay/releases";
+
+/// Open the release notes for the running version. Falls back to the
+/// releases index when the tag page doesn't exist (e.g. local builds).
+#[tauri::command]
+async fn open_current_release_notes() -> Value {
+  let tag_url = format!("{}/tag/v{}", RELEASES_URL, env!("CARGO_PKG_VERSION"));
+
+  // A failed HEAD (offline, timeout) still opens the tag page; only a
+  // definite 404 switches to the index.
+  let missing = match http::http_client(Duration::from_secs(5)) {
+    Ok(client) => client
+      .head(&tag_url)
+      .send()
+      .await
+      .is_ok_and(|r| r.status() == reqwest::StatusCode::NOT_FOUND),
+    Err(_) => false,
+  };
+  let url = if missing { RELEASES_URL.to_string() } else { tag_url };
+
+  match open_external(&url) {
+    Ok(_) => serde_json::json!({ "ok": true, "url": url, "fallback": missing }),
+    Err(e) => serde_json::json!({ "ok": false, "error": e }),
   }
+}
 
-  let daemon_path = match find_daemon_exe(app) {
-    Ok(p) => p,
-    Err(e) => {
-      println!("[gui] auto-start skipped: {}", e);
-      return;
+/// Longest `auto_start_daemon` waits for the spawned daemon to answer.
+const AUTO_START_TIMEOUT: Duration = Duration::from_secs(6);
+/// Poll interval of that wait, varied by up to `AUTO_START_JITTER` either way
+/// so GUIs started together (e.g. at login on a shared machine) don't poll
+/// in lockstep.
+const AUTO_START_POLL: Duration = Duration::from_millis(300);
+const AUTO_START_JITTER: Duration = Duration::from_millis(100);
+
+const DAEMON_START_ATTEMPT_EVENT: &str = "daemon-start-attempt";
+
+/// How an auto-start run ended.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum AutoStartOutcome {
+  AlreadyRunning,
+  Started,
+  /// The daemon didn't answer within `AUTO_START_TIMEOUT`; it may still be
+  /// starting.
+  TimedOut,
+  /// No daemon executable, or it couldn't be spawned; see `error`.
+  Failed,
+  /// Another run is still waiting; nothing was done.
+  InProgress,
+  /// The app is quitting.
+  Cancelled,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct AutoStartResult {
+  outcome: AutoStartOutcome,
+  error: Option<String>,
+  elapsed_ms: u64,
+}
+
+/// Set while `start_daemon` or an auto-start run is spawning or waiting, so
+/// another start can't spawn a second daemon next to it; see `StartGuard`.
+static AUTO_START_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// The last run's result, for the UI to offer a retry after a failed one.
+static LAST_AUTO_START: Mutex<Option<AutoStartResult>> = Mutex::new(None);
+
+/// `AUTO_START_POLL` plus or minus a random amount up to `AUTO_START_JITTER`.
+fn auto_start_interval() -> Duration {
+  // A fresh RandomState is randomly keyed, which is all the randomness needed
+  let random = RandomState::new().build_hasher().finish();
+  let jitter = AUTO_START_JITTER.as_millis() as u64;
+  AUTO_START_POLL - AUTO_START_JITTER + Duration::from_millis(random % (2 * jitter + 1))
+}
+
+/// Auto-start the daemon, on app launch and from `retry_auto_start`.
+/// Spawns the daemon if not already running, then waits up to
+/// `AUTO_START_TIMEOUT` for it to become reachable, emitting a
+/// `daemon-start-attempt` event per check. Blocks; launch runs it on a
+/// background thread so the UI is not blocked.
+fn auto_start_daemon(app: &AppHandle) -> AutoStartResult {
+  let started = Instant::now();
+  let finish = |outcome, error| {
+    let result = AutoStartResult {
+      outcome,
+      error,
+      elapsed_ms: started.elapsed().as_millis() as u64,
+    };
+    // An overlapping retry says nothing about how the daemon start went
+    if outcome != AutoStartOutcome::InProgress {
+      *LAST_AUTO_START.lock().unwrap_or_else(|e| e.into_inner()) = Some(result.clone());
     }
+    result
+  };
+
+  if is_daemon_running() {
+    gui_println!("[gui] daemon already running, skipping auto-start");
+    return finish(AutoStartOutcome::AlreadyRunning, None);
+  }
+  let Some(start) = StartGuard::take() else {
+    return finish(AutoStartOutcome::InProgress, None);
   };
+  let outcome = spawn_and_wait(app, started);
+  drop(start);
+  match outcome {
+    Ok(outcome) => finish(outcome, None),
+    Err(e) => finish(AutoStartOutcome::Failed, Some(e)),
+  }
+}
 
-  println!("[gui] auto-starting daemon from {:?}", daemon_path);
+/// The spawning and waiting of `auto_start_daemon`, timed from `started`.
+fn spawn_and_wait(app: &AppHandle, started: Instant) -> Result<AutoStartOutcome, String> {
+  // E.g. a retry after `timed_out`: the daemon from the last run may still come up
+  if daemon_booting() {
+    gui_println!("[gui] daemon spawned earlier is still starting, waiting for it");
+  } else {
+    let daemon_path = find_daemon_exe(app).inspect_err(|e| {
+      gui_println!("[gui] auto-start skipped: {}", e);
+    })?;
 
-  if let Err(e) = spawn_daemon(&daemon_path) {
-    println!("[gui] failed to auto-start daemon: {}", e);
-    return;
+    gui_println!("[gui] auto-starting daemon from {:?}", daemon_path);
+
+    spawn_daemon(&daemon_path).inspect_err(|e| {
+      gui_println!("[gui] failed to auto-start daemon: {}", e);
+    })?;
   }
 
-  // Wait for the daemon to become reachable (up to ~6 seconds)
-  for _ in 0..20 {
-    thread::sleep(Duration::from_millis(300));
-    if is_daemon_running() {
-      println!("[gui] daemon is now running");
-      return;
+  // Wait for the daemon to become reachable
+  let mut attempt = 0u32;
+  while started.elapsed() < AUTO_START_TIMEOUT {
+    if !sleep_unless_shutdown(auto_start_interval()) {
+      return Ok(AutoStartOutcome::Cancelled);
+    }
+    attempt += 1;
+    let running = is_daemon_running();
+    let progress = serde_json::json!({
+      "attempt": attempt,
+      "elapsed_ms": started.elapsed().as_millis() as u64,
+      "timeout_ms": AUTO_START_TIMEOUT.as_millis() as u64,
+      "running": running,
+    });
+    if let Err(e) = app.emit(DAEMON_START_ATTEMPT_EVENT, progress) {
+      gui_println!("[gui] failed to emit {}: {}", DAEMON_START_ATTEMPT_EVENT, e);
+    }
+    if running {
+      gui_println!("[gui] daemon is now running");
+      return Ok(AutoStartOutcome::Started);
+    }
+  }
+
+  gui_println!("[gui] daemon auto-start: timeout waiting for daemon to become reachable");
+  Ok(AutoStartOutcome::TimedOut)
+}
+
+/// Run the auto-start again, e.g. after it timed out at launch. Returns at
+/// once when the daemon is already up, or with `in_progress` while another
+/// run is still waiting.
+#[tauri::command]
+async fn retry_auto_start(app: AppHandle) -> AutoStartResult {
+  match tokio::task::spawn_blocking(move || auto_start_daemon(&app)).await {
+    Ok(result) => result,
+    Err(e) => AutoStartResult {
+      outcome: AutoStartOutcome::Failed,
+      error: Some(e.to_string()),
+      elapsed_ms: 0,
+    },
+  }
+}
+
+/// How the last auto-start run ended; null before the launch run finishes.
+#[tauri::command]
+fn get_auto_start_result() -> Option<AutoStartResult> {
+  LAST_AUTO_START.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/* ── Background thread lifecycle ── */
+
+/// Set by the "quit" menu item; background loops check it and exit.
+static SHUTDOWN: AtomicBool = AtomicBool::new(false);
+
+static BACKGROUND_THREADS: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+
+fn shutdown_requested() -> bool {
+  SHUTDOWN.load(Ordering::Relaxed)
+}
+
+fn spawn_background<F: FnOnce() + Send + 'static>(f: F) {
+  let handle = thread::spawn(f);
+  let mut threads = BACKGROUND_THREADS.lock().unwrap_or_else(|e| e.into_inner());
+  // Short-lived workers (e.g. autosave) would otherwise pile up here
+  threads.retain(|h| !h.is_finished());
+  threads.push(handle);
+}
+
+/// Sleep in short slices so a shutdown request is noticed promptly.
+/// Returns false if shutdown was requested before the full duration elapsed.
+fn sleep_unless_shutdown(duration: Duration) -> bool {
+  let deadline = Instant::now() + duration;
+  while Instant::now() < deadline {
+    if shutdown_requested() {
+      return false;
+    }
+    thread::sleep(Duration::from_millis(200).min(deadline - Instant::now()));
+  }
+  !shutdown_requested()
+}
+
+/// Set when the tray status should be refreshed without waiting for the
+/// next poll (e.g. `daemon.json` appeared or disappeared).
+static TRAY_REFRESH: AtomicBool = AtomicBool::new(false);
+
+fn request_tray_refresh() {
+  TRAY_REFRESH.store(true, Ordering::Relaxed);
+}
+
+/// Like `sleep_unless_shutdown`, but also returns early (with true) when a
+/// tray refresh is requested.
+fn sleep_until_tray_refresh(duration: Duration) -> bool {
+  let deadline = Instant::now() + duration;
+  while Instant::now() < deadline {
+    if shutdown_requested() {
+      return false;
+    }
+    if TRAY_REFRESH.swap(false, Ordering::Relaxed) {
+      return true;
     }
+    thread::sleep(Duration::from_millis(200).min(deadline - Instant::now()));
   }
+  !shutdown_requested()
+}
 
-  println!("[gui] daemon auto-start: timeout waiting for daemon to become reachable");
+/// Signal background threads to stop and wait up to `timeout` for them.
+/// A thread still blocked in IPC past the deadline is left to the process exit.
+fn stop_background_threads(timeout: Duration) {
+  SHUTDOWN.store(true, Ordering::Relaxed);
+  let handles: Vec<_> = BACKGROUND_THREADS
+    .lock()
+    .unwrap_or_else(|e| e.into_inner())
+    .drain(..)
+    .collect();
+
+  let deadline = Instant::now() + timeout;
+  for handle in handles {
+    while !handle.is_finished() && Instant::now() < deadline {
+      thread::sleep(Duration::from_millis(20));
+    }
+    if handle.is_finished() {
+      let _ = handle.join();
+    } else {
+      gui_println!("[gui] background thread did not stop in time");
+    }
+  }
 }
 
 /* ── Entry point ── */
@@ -963,34 +3459,135 @@ fn main() {
   tauri::Builder::default()
     .invoke_handler(tauri::generate_handler![
       read_daemon_status,
+      get_stats,
+      read_sessions_grouped,
       list_bots,
       save_bot,
       delete_bot,
+      bot_usage,
+      delete_bots,
+      rebind_sessions,
       bind_bot,
       unbind_bot,
+      unbind_all_bots,
       test_bot,
+      test_bot_config,
+      reveal_bot_secret,
       activate_bot,
-      get_config,
+      config_cache::get_config,
+      get_effective_config,
       save_config,
       start_daemon,
+      retry_auto_start,
+      get_auto_start_result,
+      ping_daemon,
       check_codex_config,
       setup_codex_config,
       open_codex_config_file,
       check_claude_config,
       setup_claude_config,
       open_claude_config_file,
+      check_gemini_config,
+      setup_gemini_config,
+      open_gemini_config_file,
+      cli_config_lint::lint_codex_config,
+      cli_config_lint::lint_claude_config,
+      cli_config_lint::read_codex_config,
+      claude_project::check_claude_project_config,
+      claude_project::setup_claude_project_config,
+      integrations::list_integrations,
+      integrations::check_cli_config,
+      integrations::open_cli_config_file,
+      cli_config_backup::revert_cli_config,
+      cli_config_backup::rollback_codex_config,
+      cli_config_backup::rollback_claude_config,
+      cli_setup_preview::preview_codex_setup,
+      cli_setup_preview::preview_claude_setup,
+      cli_hooks::remove_codex_hooks,
+      hook_paths::repair_cli_hooks,
+      cli_hooks::remove_claude_hooks,
+      cli_detect::detect_cli_tools,
+      cli_detect::detect_installed_clis,
       check_update,
+      force_check_update,
+      clear_update_cache,
+      update_scheduler::get_scheduled_update,
+      get_update_ratelimit,
+      test_network,
+      health::get_health,
+      update_download::download_update,
+      operations::cancel_operation,
       collect_logs,
+      reveal_in_file_manager,
+      export_config,
+      export_config_file,
+      reset_config,
+      gui_settings::get_gui_settings,
+      gui_settings::set_gui_setting,
+      gui_settings::reset_gui_settings,
+      gui_settings::set_update_channel,
+      gui_settings::skip_update_version,
+      gui_settings::snooze_update,
+      editor::set_preferred_editor,
+      config_import::import_config,
+      config_import::import_config_file,
+      config_migrate::migrate_config,
+      config_migrate::migrate_config_file,
+      config_undo::undo_last_config_save,
+      config_text::format_config_text,
+      project_overrides::get_project_overrides,
+      project_overrides::set_project_override,
+      launch_state::get_launch_context,
+      launch_state::ack_launch,
+      launch_state::get_close_behavior,
+      launch_state::set_close_behavior,
+      onboarding::get_onboarding_state,
+      felay_home::initialize_felay_home,
+      profiles::list_profiles,
+      profiles::switch_profile,
       open_url,
+      open_current_release_notes,
+      release_notes::get_release_notes_since,
+      release_notes::get_changelog_since,
+      webhook::check_webhook,
+      webhook::validate_webhook,
+      audit::get_bot_audit,
+      bot_convert::convert_bot,
+      config_backup::list_config_backups,
+      config_backup::restore_config_backup,
+      config_schema::validate_config,
+      bot_schema::get_bot_schema,
+      config_diff::diff_config,
+      config_autosave::save_config_debounced,
+      file_watcher::restart_file_watcher,
+      daemon_integrity::verify_daemon_binary,
+      capabilities::get_daemon_capabilities,
+      log_tail::tail_daemon_log,
+      log_tail::stop_tail_daemon_log,
+      log_tail::open_log_file,
+      quiet_hours::set_bot_quiet_hours,
+      quiet_hours::get_bot_quiet_hours,
+      warnings::dismiss_warning,
+      warnings::get_all_warnings,
     ])
     .plugin(tauri_plugin_dialog::init())
+    .plugin(tauri_plugin_notification::init())
     .setup(|app| {
+      // Before the frontend can ask; it must see the state from before this run
+      launch_state::init();
+      hook_paths::remember_install_dir(app.handle());
+      // Before the daemon is spawned, which would create ~/.felay itself
+      felay_home::init_on_first_run();
+
       // Auto-start daemon on a background thread so UI is not blocked
       let app_handle = app.handle().clone();
-      thread::spawn(move || {
+      spawn_background(move || {
         auto_start_daemon(&app_handle);
       });
 
+      // Pick up hand edits of config.json and daemon start/stop right away
+      file_watcher::start(app.handle().clone());
+
       let open = MenuItem::with_id(app, "open", "打开面板", true, None::<&str>)?;
       let sessions_item =
         MenuItem::with_id(app, "sessions", "活跃会话: 0", false, None::<&str>)?;
@@ -1004,46 +3601,87 @@ fn main() {
       // Clone menu items for background status polling thread
       let sessions_clone = sessions_item.clone();
       let status_clone = status_item.clone();
-
-      thread::spawn(move || loop {
-        thread::sleep(Duration::from_secs(5));
-
-        let ipc_path = match get_ipc_path() {
-          Some(p) => p,
-          None => {
-            let _ = status_clone.set_text("Daemon: 未运行");
-            let _ = sessions_clone.set_text("活跃会话: 0");
-            continue;
+      let poller_app = app.handle().clone();
+
+      // Poll every `tray_poll_secs` while the daemon is up; back off to 2x,
+      // then 6x while it is unreachable, and reset on the first successful ping.
+      // The file watcher cuts a wait short when daemon.json comes or goes.
+      spawn_background(move || {
+        // Multiples of the configured poll interval (5s by default)
+        const BACKOFF: &[u32] = &[1, 2, 6];
+        let mut misses = 0usize;
+        loop {
+          let factor = BACKOFF[misses.min(BACKOFF.len() - 1)];
+          let delay = gui_settings::with(|s| s.tray_poll()) * factor;
+          if !sleep_until_tray_refresh(delay) {
+            break;
           }
-        };
 
-        match request_daemon_status(&ipc_path) {
-          Some(payload) => {
-            let _ = status_clone.set_text("Daemon: 运行中");
-            let _ =
-              sessions_clone.set_text(format!("活跃会话: {}", payload.active_sessions));
-          }
-          None => {
-            let _ = status_clone.set_text("Daemon: 未运行");
-            let _ = sessions_clone.set_text("活跃会话: 0");
+          let ipc_path = get_ipc_path().filter(|p| daemon_reachable(p));
+          let payload = ipc_path.as_deref().and_then(request_daemon_status);
+
+          match payload {
+            Some(payload) => {
+              misses = 0;
+              let changes = session_events::track(&payload.sessions);
+              if let Some(path) = ipc_path.as_deref() {
+                project_overrides::apply_to_new_sessions(
+                  path,
+                  &changes.started,
+                  &payload.sessions,
+                );
+              }
+              session_events::emit(&poller_app, &changes);
+              let _ = status_clone.set_text("Daemon: 运行中");
+              let _ =
+                sessions_clone.set_text(format!("活跃会话: {}", payload.active_sessions));
+            }
+            None => {
+              misses += 1;
+              let _ = status_clone.set_text("Daemon: 未运行");
+              let _ = sessions_clone.set_text("活跃会话: 0");
+            }
           }
         }
       });
 
+      update_scheduler::start(app.handle().clone(), menu.clone());
+
       let tray = app.tray_by_id("main").expect("tray icon 'main' not found");
       tray.set_menu(Some(menu))?;
       tray.on_menu_event(|app, event| match event.id.as_ref() {
         "open" => show_main_window(app),
+        update_scheduler::UPDATE_MENU_ID => update_scheduler::open_release_page(),
         "stop" => {
           if daemon_stop() {
-            println!("[gui] stop daemon requested");
+            gui_println!("[gui] stop daemon requested");
           } else {
-            println!("[gui] daemon stop request failed");
+            gui_println!("[gui] daemon stop request failed");
           }
         }
-        "quit" => app.exit(0),
+        "quit" => {
+          stop_background_threads(Duration::from_secs(2));
+          app.exit(0);
+        }
         _ => {}
       });
+      // The close button hides to the tray or quits, per `close_to_tray`;
+      // quitting stops the background threads as the tray's "quit" does
+      if let Some(window) = app.get_webview_window("main") {
+        let handle = app.handle().clone();
+        let target = window.clone();
+        window.on_window_event(move |event| {
+          if let WindowEvent::CloseRequested { api, .. } = event {
+            if launch_state::close_to_tray() {
+              api.prevent_close();
+              let _ = target.hide();
+            } else {
+              stop_background_threads(Duration::from_secs(2));
+              handle.exit(0);
+            }
+          }
+        });
+      }
       tray.on_tray_icon_event(|tray, event| {
         if let TrayIconEvent::Click {
           button: MouseButton::Left,
@@ -1060,3 +3698,608 @@ fn main() {
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::io::Read;
+  use std::net::TcpListener;
+  use std::sync::Arc;
+
+  /// A local HTTP server answering each request with `respond(path)` and
+  /// keeping the request heads, lowercased.
+  pub(crate) struct MockServer {
+    pub(crate) url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+  }
+
+  impl MockServer {
+    fn start(respond: fn(&str) -> (u16, &'static str)) -> Self {
+      Self::start_with(move |head| {
+        let (status, body) = respond(head.split(' ').nth(1).unwrap_or("/"));
+        (status, String::new(), body.to_string())
+      })
+    }
+
+    /// Like `start`, with `respond` given the whole request head and
+    /// returning extra response header lines (each ending in CRLF) too.
+    pub(crate) fn start_with(
+      respond: impl Fn(&str) -> (u16, String, String) + Send + 'static,
+    ) -> Self {
+      let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+      let url = format!("http://{}", listener.local_addr().unwrap());
+      let requests = Arc::new(Mutex::new(Vec::new()));
+      let seen = requests.clone();
+      thread::spawn(move || {
+        for stream in listener.incoming() {
+          let Ok(mut stream) = stream else { break };
+          let mut head = Vec::new();
+          let mut byte = [0u8; 1];
+          while !head.ends_with(b"\r\n\r\n") && stream.read(&mut byte).unwrap_or(0) == 1 {
+            head.push(byte[0]);
+          }
+          let head = String::from_utf8_lossy(&head).to_lowercase();
+          seen.lock().unwrap().push(head.clone());
+          let (status, headers, body) = respond(&head);
+          let _ = write!(
+            stream,
+            "HTTP/1.1 {} X\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\
+             {}connection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            headers,
+            body
+          );
+        }
+      });
+      MockServer { url, requests }
+    }
+
+    fn endpoint(&self) -> String {
+      format!("{}/releases/latest", self.url)
+    }
+
+    pub(crate) fn requests(&self) -> Vec<String> {
+      self.requests.lock().unwrap().clone()
+    }
+  }
+
+  const RELEASE: &str = r#"{ "tag_name": "v0.0.1", "html_url": "https://x/r", "body": "notes" }"#;
+
+  fn release(_: &str) -> (u16, &'static str) {
+    (200, RELEASE)
+  }
+
+  fn unavailable(_: &str) -> (u16, &'static str) {
+    (503, "{}")
+  }
+
+  fn unauthorized(_: &str) -> (u16, &'static str) {
+    (401, r#"{ "message": "Bad credentials" }"#)
+  }
+
+  fn client() -> reqwest::Client {
+    reqwest::Client::builder().no_proxy().build().unwrap()
+  }
+
+  /// The mirror and GitHub endpoints, with the token going to `github`'s origin.
+  fn target(mirror: Option<&MockServer>, github: &MockServer, token: Option<&str>) -> UpdateTarget {
+    UpdateTarget {
+      mirror: mirror.map(MockServer::endpoint),
+      github: github.endpoint(),
+      token: token.map(String::from),
+      token_origin: reqwest::Url::parse(&github.url).unwrap(),
+    }
+  }
+
+  #[tokio::test]
+  async fn mirror_is_asked_first() {
+    let (mirror, github) = (MockServer::start(release), MockServer::start(release));
+    let info = fetch_update_from(&client(), &target(Some(&mirror), &github, None), None)
+      .await
+      .unwrap();
+    assert_eq!(info.source, UpdateSource::Mirror);
+    assert_eq!(info.latest_version, "v0.0.1");
+    assert_eq!(mirror.requests().len(), 1);
+    assert!(github.requests().is_empty());
+  }
+
+  #[tokio::test]
+  async fn github_answers_when_the_mirror_fails() {
+    let (mirror, github) = (MockServer::start(unavailable), MockServer::start(release));
+    let info = fetch_update_from(&client(), &target(Some(&mirror), &github, Some("t0k")), None)
+      .await
+      .unwrap();
+    assert_eq!(info.source, UpdateSource::Github);
+    assert_eq!(info.release_notes, "notes");
+    // The mirror gets one try, without the token
+    let mirror_requests = mirror.requests();
+    assert_eq!(mirror_requests.len(), 1);
+    assert!(!mirror_requests[0].contains("authorization"));
+    assert!(github.requests()[0].contains("authorization: bearer t0k"));
+  }
+
+  #[tokio::test]
+  async fn github_without_mirror() {
+    let github = MockServer::start(release);
+    let info = fetch_update_from(&client(), &target(None, &github, None), None).await.unwrap();
+    assert_eq!(info.source, UpdateSource::Github);
+    assert!(!github.requests()[0].contains("authorization"));
+  }
+
+  #[tokio::test]
+  async fn refused_token_is_bad_token() {
+    let (mirror, github) = (MockServer::start(unavailable), MockServer::start(unauthorized));
+    let e = fetch_update_from(&client(), &target(Some(&mirror), &github, Some("t0k")), None)
+      .await
+      .unwrap_err();
+    assert_eq!(e.kind, UpdateErrorKind::BadToken);
+    assert_eq!(e.status, Some(401));
+    // Not transient, so not retried
+    assert_eq!(github.requests().len(), 1);
+  }
+
+  #[tokio::test]
+  async fn unauthorized_without_token_is_plain_http() {
+    let github = MockServer::start(unauthorized);
+    let e = fetch_update_from(&client(), &target(None, &github, None), None).await.unwrap_err();
+    assert_eq!(e.kind, UpdateErrorKind::Http);
+  }
+
+  const LOCK: &str = r#"{ "pid": 4242, "ipc": "/tmp/felay.sock", "started_at": "2026-01-01" }"#;
+
+  #[test]
+  fn lock_file_completed_during_the_retry_is_read() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("daemon.json");
+    fs::write(&path, &LOCK[..20]).unwrap();
+    let writer = {
+      let path = path.clone();
+      thread::spawn(move || {
+        thread::sleep(Duration::from_millis(5));
+        fs::write(path, LOCK).unwrap();
+      })
+    };
+    let lock = read_lock_file_at(&path).unwrap();
+    writer.join().unwrap();
+    assert_eq!(lock.pid, 4242);
+    assert_eq!(lock.ipc, "/tmp/felay.sock");
+  }
+
+  #[test]
+  fn lock_file_that_stays_partial_is_none() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("daemon.json");
+    for partial in ["", "{", r#"{ "pid": 4242"#, r#"{ "pid": 4242, "ipc": "#, r#"{ "pid": 4242 }"#] {
+      fs::write(&path, partial).unwrap();
+      assert!(read_lock_file_at(&path).is_none(), "{:?}", partial);
+    }
+    assert!(read_lock_file_at(&dir.path().join("missing.json")).is_none());
+  }
+
+  #[test]
+  fn version_precedence() {
+    use std::cmp::Ordering::{Equal, Greater, Less};
+    let table = [
+      // The SemVer 2.0 spec's own example chain
+      ("1.0.0-alpha", "1.0.0-alpha.1", Less),
+      ("1.0.0-alpha.1", "1.0.0-alpha.beta", Less),
+      ("1.0.0-alpha.beta", "1.0.0-beta", Less),
+      ("1.0.0-beta", "1.0.0-beta.2", Less),
+      ("1.0.0-beta.2", "1.0.0-beta.11", Less),
+      ("1.0.0-beta.11", "1.0.0-rc.1", Less),
+      ("1.0.0-rc.1", "1.0.0", Less),
+      // Numeric identifiers compare as numbers, before alphanumeric ones
+      ("1.0.0-2", "1.0.0-10", Less),
+      ("1.0.0-1", "1.0.0-a", Less),
+      ("1.0.0-rc.1", "1.0.0-rc.a", Less),
+      ("1.0.0-Beta", "1.0.0-alpha", Less),
+      // Core numbers
+      ("1.10.0", "1.9.0", Greater),
+      ("2.0.0", "1.99.99", Greater),
+      ("0.1.26", "0.1.3", Greater),
+      ("1.2", "1.2.0", Equal),
+      ("1", "1.0.1", Less),
+      // Build metadata is ignored
+      ("1.0.0+build.1", "1.0.0+build.2", Equal),
+      ("1.0.0-rc.1+sha.abc", "1.0.0-rc.1", Equal),
+      ("1.0.0+exp-sha", "1.0.0-rc.1", Greater),
+      // Tags and whitespace
+      ("v1.2.3", "1.2.3", Equal),
+      (" V1.2.4 ", "v1.2.3", Greater),
+    ];
+    for (a, b, expected) in table {
+      assert_eq!(version_cmp(a, b), expected, "{} vs {}", a, b);
+      assert_eq!(version_cmp(b, a), expected.reverse(), "{} vs {}", b, a);
+    }
+    assert!(version_gt("v0.2.0-beta.1", "0.1.26"));
+    assert!(!version_gt("0.1.26", "0.1.26+1"));
+  }
+
+  #[test]
+  fn token_goes_to_the_github_api_only() {
+    let origin = http::github_api_origin();
+    let token = Some("t0k");
+    let sent = |url: &str| http::token_for(url, &origin, token);
+    assert_eq!(sent("https://api.github.com/repos/a/b/releases").as_deref(), Some("t0k"));
+    assert_eq!(sent("https://api.github.com:443/rate_limit").as_deref(), Some("t0k"));
+    assert_eq!(sent("http://api.github.com/repos/a/b/releases"), None);
+    assert_eq!(sent("https://api.github.com:8443/repos"), None);
+    assert_eq!(sent("https://mirror.example.com/releases/latest"), None);
+    assert_eq!(sent("https://api.github.com.evil.example/releases"), None);
+    assert_eq!(sent("not a url"), None);
+    assert_eq!(http::token_for("https://api.github.com/", &origin, Some("  ")), None);
+  }
+
+  #[test]
+  fn save_config_input_prefers_the_text() {
+    let config = serde_json::json!({ "push": { "mergeWindow": 1 } });
+    let text = "{ // edited\n  \"push\": { \"mergeWindow\": 2, },\n}";
+    let parsed = config_input(Some(config.clone()), Some(text)).unwrap();
+    assert_eq!(parsed["push"]["mergeWindow"], 2);
+    assert_eq!(config_input(None, Some(text)).unwrap(), parsed);
+    assert_eq!(config_input(Some(config.clone()), None).unwrap(), config);
+
+    let err = config_input(Some(config), Some("{ \"push\": }")).unwrap_err();
+    assert_eq!((err["ok"].clone(), err["line"].clone()), (Value::Bool(false), 1.into()));
+    assert_eq!(config_input(None, None).unwrap_err()["ok"], false);
+  }
+
+  #[test]
+  fn mask_secret_reveals_ends_of_long_values_only() {
+    let partial = SanitizeOptions::default();
+    let full = SanitizeOptions { partial_reveal: false };
+    assert_eq!(mask_secret("12345678", &partial), "***");
+    assert_eq!(mask_secret("123456789", &partial), "12…89");
+    assert_eq!(mask_secret("SECa1b2c3d4e5f6", &partial), "SE…f6");
+    assert_eq!(mask_secret("SECa1b2c3d4e5f6", &full), "***");
+    // Characters, not bytes
+    assert_eq!(mask_secret("密钥密钥密钥密钥", &partial), "***");
+    assert_eq!(mask_secret("密钥密钥密钥密钥值", &partial), "密钥…钥值");
+  }
+
+  #[test]
+  fn masked_values_are_recognized() {
+    let options = SanitizeOptions::default();
+    for secret in ["short", "a-much-longer-secret", "密钥密钥密钥密钥值"] {
+      assert!(is_masked_secret(&mask_secret(secret, &options)), "{}", secret);
+    }
+    assert!(is_masked_secret("abc***xyz"));
+    assert!(!is_masked_secret("a-much-longer-secret"));
+  }
+
+  #[test]
+  fn sanitize_masks_sensitive_keys_at_any_depth() {
+    let mut config = serde_json::json!({
+      "bots": {
+        "interactive": [{ "name": "团队助手", "APPSECRET": "app-secret-value", "encryptKey": "" }],
+        "push": [{ "webhook": "https://open.feishu.cn/open-apis/bot/v2/hook/abcdef12" }]
+      },
+      "proxy": { "Password": "hunter22", "port": 8080 },
+      "githubToken": "ghp_0123456789abcdef",
+      "note": "hook at https://open.feishu.cn/open-apis/bot/v2/hook/0b7e2d4c-91aa-4f3e-8c1d"
+    });
+    sanitize_value(&mut config, &SanitizeOptions::default());
+    assert_eq!(config["bots"]["interactive"][0]["name"], "团队助手");
+    assert_eq!(config["bots"]["interactive"][0]["APPSECRET"], "ap…ue");
+    assert_eq!(config["bots"]["interactive"][0]["encryptKey"], "", "empty stays empty");
+    assert_eq!(config["bots"]["push"][0]["webhook"], "ht…12");
+    assert_eq!(config["proxy"]["Password"], "***");
+    assert_eq!(config["proxy"]["port"], 8080);
+    assert_eq!(config["githubToken"], "gh…ef");
+    let note = config["note"].as_str().unwrap();
+    assert!(!note.contains("91aa-4f3e"), "{}", note);
+  }
+
+  #[test]
+  fn cli_group_names() {
+    assert_eq!(cli_group_name("claude"), "claude");
+    assert_eq!(cli_group_name(r"C:\Tools\Codex.EXE"), "codex");
+    assert_eq!(cli_group_name("/usr/local/bin/gemini.cmd"), "gemini");
+    assert_eq!(cli_group_name(""), "other");
+    assert_eq!(cli_group_name("/usr/bin/"), "other");
+  }
+
+  #[test]
+  fn reveal_slot_allows_one_reveal_per_interval() {
+    let last = Mutex::new(None);
+    let start = Instant::now();
+    assert!(take_reveal_slot(&last, start));
+    assert!(!take_reveal_slot(&last, start));
+    assert!(!take_reveal_slot(&last, start + REVEAL_INTERVAL - Duration::from_millis(1)));
+    assert!(take_reveal_slot(&last, start + REVEAL_INTERVAL));
+  }
+
+  /// A fake daemon on a Unix socket, answering each JSON line with
+  /// `respond(request)` and keeping the requests. None drops the connection.
+  #[cfg(unix)]
+  pub(crate) struct MockDaemon {
+    pub(crate) path: String,
+    pub(crate) requests: Arc<Mutex<Vec<Value>>>,
+    _dir: tempfile::TempDir,
+  }
+
+  #[cfg(unix)]
+  impl MockDaemon {
+    pub(crate) fn start(respond: impl Fn(&Value) -> Option<Value> + Send + 'static) -> Self {
+      let dir = tempfile::tempdir().unwrap();
+      let path = dir.path().join("daemon.sock");
+      let listener = std::os::unix::net::UnixListener::bind(&path).unwrap();
+      let requests = Arc::new(Mutex::new(Vec::new()));
+      let seen = requests.clone();
+      thread::spawn(move || {
+        for stream in listener.incoming() {
+          let Ok(stream) = stream else { break };
+          let mut writer = stream.try_clone().unwrap();
+          for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else { break };
+            let request: Value = serde_json::from_str(&line).unwrap_or_default();
+            seen.lock().unwrap().push(request.clone());
+            let Some(reply) = respond(&request) else {
+              break;
+            };
+            let _ = writeln!(writer, "{}", reply);
+          }
+        }
+      });
+      MockDaemon { path: path.to_string_lossy().to_string(), requests, _dir: dir }
+    }
+
+    fn connect(&self) -> IpcConnection {
+      IpcConnection::open(&self.path).unwrap()
+    }
+
+    /// `type` and `payload.sessionId` of each request.
+    fn sent(&self) -> Vec<(String, String)> {
+      let requests = self.requests.lock().unwrap();
+      let field = |v: &Value| v.as_str().unwrap_or_default().to_string();
+      requests.iter().map(|r| (field(&r["type"]), field(&r["payload"]["sessionId"]))).collect()
+    }
+  }
+
+  #[cfg(unix)]
+  pub(crate) fn ok_reply(ok: bool) -> Option<Value> {
+    Some(serde_json::json!({ "type": "bind_bot_response", "payload": { "ok": ok } }))
+  }
+
+  fn live_session(id: &str, push_bot_id: Option<&str>, status: &str) -> DaemonSession {
+    serde_json::from_value(serde_json::json!({
+      "sessionId": id,
+      "cli": "codex",
+      "cwd": "/work",
+      "status": status,
+      "startedAt": "2026-01-01T00:00:00Z",
+      "pushBotId": push_bot_id,
+    }))
+    .unwrap()
+  }
+
+  fn bound_sessions() -> Vec<DaemonSession> {
+    vec![
+      live_session("s1", Some("p1"), "listening"),
+      live_session("s2", Some("p1"), "proxy_on"),
+      live_session("s3", Some("p2"), "listening"),
+      live_session("old", Some("p1"), "ended"),
+    ]
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn delete_of_a_bound_bot_is_refused_without_force() {
+    let daemon = MockDaemon::start(|_| ok_reply(true));
+    let mut conn = daemon.connect();
+    let result = delete_bot_on(&mut conn, &bound_sessions(), "push", "p1", false).unwrap();
+    assert_eq!(result["ok"], false);
+    assert_eq!(result["bound_sessions"], serde_json::json!(["s1", "s2"]));
+    let in_use_by = result["in_use_by"].as_array().unwrap();
+    assert_eq!(in_use_by.len(), 2);
+    assert!(in_use_by.iter().all(|u| u["bot_type"] == "push"));
+    assert!(daemon.sent().is_empty(), "nothing reaches the daemon");
+
+    // The same id in the other slot doesn't count
+    let result = delete_bot_on(&mut conn, &bound_sessions(), "interactive", "p1", false).unwrap();
+    assert_eq!(result["ok"], true);
+    assert_eq!(daemon.sent(), [("delete_bot_request".to_string(), String::new())]);
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn forced_delete_unbinds_live_sessions_first() {
+    let daemon = MockDaemon::start(|_| ok_reply(true));
+    let mut conn = daemon.connect();
+    let result = delete_bot_on(&mut conn, &bound_sessions(), "push", "p1", true).unwrap();
+    assert_eq!(result["ok"], true);
+    assert_eq!(result["unbound_sessions"], serde_json::json!(["s1", "s2"]));
+    assert_eq!(result["unbind_failures"], serde_json::json!([]));
+    let sent: Vec<(String, String)> = [
+      ("unbind_bot_request", "s1"),
+      ("unbind_bot_request", "s2"),
+      ("delete_bot_request", ""),
+    ]
+    .iter()
+    .map(|(t, s)| (t.to_string(), s.to_string()))
+    .collect();
+    assert_eq!(daemon.sent(), sent);
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn forced_delete_reports_unbind_failures_and_lost_connections() {
+    let daemon = MockDaemon::start(|r| ok_reply(r["payload"]["sessionId"] != "s1"));
+    let mut conn = daemon.connect();
+    let result = delete_bot_on(&mut conn, &bound_sessions(), "push", "p1", true).unwrap();
+    assert_eq!(result["unbound_sessions"], serde_json::json!(["s2"]));
+    assert_eq!(result["unbind_failures"], serde_json::json!(["s1"]));
+
+    // The daemon goes away after the first unbind
+    let daemon = MockDaemon::start(|r| {
+      let first = r["payload"]["sessionId"] == "s1";
+      if first { ok_reply(true) } else { None }
+    });
+    let mut conn = daemon.connect();
+    let partial = delete_bot_on(&mut conn, &bound_sessions(), "push", "p1", true).unwrap_err();
+    assert_eq!(partial["ok"], false);
+    assert_eq!(partial["unbound_sessions"], serde_json::json!(["s1"]));
+    assert!(!daemon.sent().iter().any(|(t, _)| t == "delete_bot_request"));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn rebind_moves_live_sessions_to_the_new_bot() {
+    let daemon = MockDaemon::start(|_| ok_reply(true));
+    let mut conn = daemon.connect();
+    let result = rebind_sessions_on(&mut conn, &bound_sessions(), "push", "p1", "p9");
+    assert_eq!(result["ok"], true);
+    assert_eq!(result["processed"], 2);
+    let requests = daemon.requests.lock().unwrap();
+    let bound_to: Vec<&str> = requests
+      .iter()
+      .filter(|r| r["type"] == "bind_bot_request")
+      .map(|r| r["payload"]["botId"].as_str().unwrap())
+      .collect();
+    assert_eq!(bound_to, ["p9", "p9"]);
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn rebind_rejects_unknown_and_same_targets() {
+    let daemon = MockDaemon::start(|r| {
+      let listing = r["type"] == "list_bots_request";
+      Some(if listing {
+        serde_json::json!({
+          "type": "list_bots_response",
+          "payload": { "interactive": [{ "id": "p9" }], "push": [{ "id": "p1" }] },
+        })
+      } else {
+        serde_json::json!({ "type": "status_response", "payload": {} })
+      })
+    });
+    let same = rebind_sessions_at(&daemon.path, "push", "p1", "p1");
+    assert_eq!(same["error"], "target bot is the same as the source bot");
+    // p9 exists, but as an interactive bot
+    let unknown = rebind_sessions_at(&daemon.path, "push", "p1", "p9");
+    assert_eq!(unknown["error"], "target bot not found");
+    assert!(daemon.sent().iter().all(|(t, _)| t == "list_bots_request"));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn failed_rebind_restores_the_old_bot() {
+    // Binding s1 to the new bot fails; binding it back succeeds
+    let daemon = MockDaemon::start(|r| {
+      let failing = r["type"] == "bind_bot_request"
+        && r["payload"]["sessionId"] == "s1"
+        && r["payload"]["botId"] == "p9";
+      ok_reply(!failing)
+    });
+    let mut conn = daemon.connect();
+    let result = rebind_sessions_on(&mut conn, &bound_sessions(), "push", "p1", "p9");
+    assert_eq!(result["ok"], false);
+    assert_eq!(result["aborted"], false);
+    let results = result["results"].as_array().unwrap();
+    assert_eq!(results[0]["session_id"], "s1");
+    assert_eq!(results[0]["stage"], "bind");
+    assert_eq!(results[0]["rolled_back"], true);
+    assert_eq!(results[1]["ok"], true);
+
+    let requests = daemon.requests.lock().unwrap();
+    let s1_binds: Vec<&str> = requests
+      .iter()
+      .filter(|r| r["type"] == "bind_bot_request" && r["payload"]["sessionId"] == "s1")
+      .map(|r| r["payload"]["botId"].as_str().unwrap())
+      .collect();
+    assert_eq!(s1_binds, ["p9", "p1"]);
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn rebind_stops_when_the_connection_drops() {
+    let daemon = MockDaemon::start(|r| {
+      let unbind_s1 = r["type"] == "unbind_bot_request" && r["payload"]["sessionId"] == "s1";
+      if unbind_s1 { ok_reply(true) } else { None }
+    });
+    let mut conn = daemon.connect();
+    let result = rebind_sessions_on(&mut conn, &bound_sessions(), "push", "p1", "p9");
+    assert_eq!(result["aborted"], true);
+    assert_eq!(result["processed"], 1);
+    assert_eq!(result["total"], 2);
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn unbind_all_reports_each_bot_type() {
+    let daemon = MockDaemon::start(|r| {
+      let found = r["payload"]["sessionId"] == "s1";
+      Some(serde_json::json!({
+        "type": "unbind_all_bots_response",
+        "payload": if found {
+          serde_json::json!({ "ok": true, "unbound": { "interactive": false, "push": true } })
+        } else {
+          serde_json::json!({ "ok": false, "error": "session not found" })
+        },
+      }))
+    });
+    let result = unbind_all_bots_at(&daemon.path, "s1");
+    assert_eq!(result["ok"], true);
+    assert_eq!(result["unbound_interactive"], false);
+    assert_eq!(result["unbound_push"], true);
+    assert_eq!(daemon.sent(), [("unbind_all_bots_request".to_string(), "s1".to_string())]);
+
+    let missing = unbind_all_bots_at(&daemon.path, "gone");
+    assert_eq!(missing["ok"], false);
+    assert_eq!(missing["error"], "session not found");
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn unbind_all_on_a_daemon_without_it_is_unsupported() {
+    // An older daemon gives no reply to a request it doesn't know
+    let daemon = MockDaemon::start(|_| None);
+    let result = unbind_all_bots_at(&daemon.path, "s1");
+    assert_eq!(result["ok"], false);
+    assert_eq!(result["error"], "daemon does not support unbind_all_bots");
+  }
+
+  #[cfg(unix)]
+  fn default_config() -> Value {
+    serde_json::from_str(DEFAULT_CONFIG).unwrap()
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn save_config_invalidates_the_cache() {
+    for ok in [true, false] {
+      let daemon = MockDaemon::start(move |_| ok_reply(ok));
+      let mut result = Value::Null;
+      let cached = config_cache::cached_after(&default_config(), || {
+        result = save_config_at(Some(&daemon.path), default_config(), || Ok(None));
+      });
+      assert_eq!(result["ok"], ok);
+      assert!(!cached, "cache kept after a save with ok={}", ok);
+      assert_eq!(daemon.sent()[0].0, "save_config_request");
+    }
+
+    // Also when the daemon went away meanwhile
+    let gone = tempfile::tempdir().unwrap();
+    let path = gone.path().join("daemon.sock").to_string_lossy().to_string();
+    let cached = config_cache::cached_after(&default_config(), || {
+      save_config_at(Some(&path), default_config(), || Ok(None));
+    });
+    assert!(!cached);
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn reset_config_invalidates_the_cache() {
+    for (native, request) in [(true, "reset_config_request"), (false, "save_config_request")] {
+      let daemon = MockDaemon::start(|_| ok_reply(true));
+      let mut result = Value::Null;
+      let cached = config_cache::cached_after(&default_config(), || {
+        result = reset_config_at(&daemon.path, native, None);
+      });
+      assert_eq!(result["ok"], true);
+      assert!(!cached, "cache kept after a {}", request);
+      assert_eq!(daemon.sent()[0].0, request);
+    }
+  }
+}
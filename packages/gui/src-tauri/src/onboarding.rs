@@ -0,0 +1,225 @@
+//! First-run wizard state in one call.
+//!
+//! The wizard used to make five calls and pick its step in JS.
+//! `get_onboarding_state` gathers the same facts (`~/.felay`, the daemon
+//! executable, whether the daemon answers, whether a bot is configured, the
+//! CLI hooks, whether a session was ever seen) and picks `next_step`. The
+//! daemon is asked with short timeouts on one blocking task while the local
+//! checks run on another, and the whole call is bounded by `DEADLINE`, so
+//! an unreachable daemon can't stall the wizard. Hooks use the local checks
+//! the check commands fall back to; while the daemon isn't answering, bots
+//! are counted from `config.json` on disk.
+
+use crate::{cli_config_local, config_migrate, find_daemon_exe, get_home_dir, get_ipc_path};
+use crate::{felay_home, ipc_request_timeout, launch_state, profiles};
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::AppHandle;
+
+/// Per daemon request; two of them fit in `DEADLINE`.
+const DAEMON_TIMEOUT: Duration = Duration::from_millis(900);
+const DEADLINE: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum NextStep {
+  InstallDaemon,
+  StartDaemon,
+  AddBot,
+  SetupHooks,
+  StartSession,
+  Done,
+}
+
+#[derive(Debug, Serialize)]
+struct CliHook {
+  name: &'static str,
+  /// The CLI's config directory exists.
+  installed: bool,
+  configured: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct OnboardingState {
+  /// This run found no `~/.felay`.
+  first_run: bool,
+  felay_dir_exists: bool,
+  /// Why `~/.felay` couldn't be created; `initialize_felay_home` retries.
+  felay_dir_error: Option<String>,
+  daemon_found: bool,
+  daemon_running: bool,
+  bots_configured: bool,
+  hooks: Vec<CliHook>,
+  session_seen: bool,
+  next_step: NextStep,
+}
+
+#[derive(Default)]
+struct DaemonProbe {
+  running: bool,
+  /// None if the bot list didn't come back.
+  bots: Option<usize>,
+  has_sessions: bool,
+}
+
+#[derive(Default)]
+struct LocalChecks {
+  felay_dir_exists: bool,
+  disk_bots: usize,
+  hooks: Vec<CliHook>,
+  session_seen: bool,
+}
+
+fn bot_count(bots: &Value) -> usize {
+  ["interactive", "push"].iter().filter_map(|kind| bots[kind].as_array()).map(Vec::len).sum()
+}
+
+fn probe_daemon() -> DaemonProbe {
+  let Some(ipc_path) = get_ipc_path() else {
+    return DaemonProbe::default();
+  };
+  let request = |req: &str| ipc_request_timeout(&ipc_path, req, DAEMON_TIMEOUT);
+  let Some(status) = request(r#"{"type":"status_request"}"#) else {
+    return DaemonProbe::default();
+  };
+  let has_sessions = status["payload"]["sessions"].as_array().is_some_and(|s| !s.is_empty());
+  if has_sessions {
+    launch_state::record_session_seen();
+  }
+  DaemonProbe {
+    running: true,
+    bots: request(r#"{"type":"list_bots_request"}"#).map(|reply| bot_count(&reply["payload"])),
+    has_sessions,
+  }
+}
+
+/// Bots in the active profile's `config.json`, in whatever layout it was
+/// written.
+fn disk_bot_count() -> usize {
+  let config = profiles::config_path()
+    .and_then(|path| fs::read_to_string(path).ok())
+    .and_then(|text| serde_json::from_str::<Value>(&text).ok());
+  config.map_or(0, |config| bot_count(&config_migrate::migrate(config).config["bots"]))
+}
+
+fn local_checks() -> LocalChecks {
+  let Some(home) = get_home_dir().map(PathBuf::from) else {
+    return LocalChecks::default();
+  };
+  let felay_dir = home.join(".felay");
+  let hook = |name, check: fn(&Path) -> Value, installed: &str, configured: &str| {
+    let status = check(&home);
+    CliHook { name, installed: status[installed] == true, configured: status[configured] == true }
+  };
+  LocalChecks {
+    felay_dir_exists: felay_dir.is_dir(),
+    disk_bots: disk_bot_count(),
+    hooks: vec![
+      hook("codex", cli_config_local::check_codex, "codexInstalled", "notifyConfigured"),
+      hook("claude", cli_config_local::check_claude, "claudeInstalled", "hookConfigured"),
+    ],
+    session_seen: launch_state::session_seen(),
+  }
+}
+
+/// Only CLIs Felay can hook count; Gemini CLI is detected but has no hook
+/// yet, so it is left out of `hooks`.
+fn next_step(state: &OnboardingState) -> NextStep {
+  // Only one of the installed CLIs needs the hook to get going
+  let installed: Vec<&CliHook> = state.hooks.iter().filter(|h| h.installed).collect();
+  let hooks_missing = !installed.is_empty() && !installed.iter().any(|h| h.configured);
+  if !state.daemon_running && !state.daemon_found {
+    NextStep::InstallDaemon
+  } else if !state.daemon_running {
+    NextStep::StartDaemon
+  } else if !state.bots_configured {
+    NextStep::AddBot
+  } else if hooks_missing {
+    NextStep::SetupHooks
+  } else if !state.session_seen {
+    NextStep::StartSession
+  } else {
+    NextStep::Done
+  }
+}
+
+/// Everything the first-run wizard needs, with the step to show next.
+#[tauri::command]
+pub(crate) async fn get_onboarding_state(app: AppHandle) -> OnboardingState {
+  let daemon = tokio::time::timeout(DEADLINE, tokio::task::spawn_blocking(probe_daemon));
+  let local = tokio::time::timeout(DEADLINE, tokio::task::spawn_blocking(local_checks));
+  let (daemon, local) = tokio::join!(daemon, local);
+  // A timed-out daemon counts as not running
+  let daemon = daemon.ok().and_then(Result::ok).unwrap_or_default();
+  let local = local.ok().and_then(Result::ok).unwrap_or_default();
+
+  let bots = daemon.bots.unwrap_or(local.disk_bots);
+  let mut state = OnboardingState {
+    first_run: felay_home::first_run(),
+    felay_dir_exists: local.felay_dir_exists,
+    felay_dir_error: felay_home::init_error(),
+    daemon_found: find_daemon_exe(&app).is_ok(),
+    daemon_running: daemon.running,
+    bots_configured: bots > 0,
+    hooks: local.hooks,
+    session_seen: local.session_seen || daemon.has_sessions,
+    next_step: NextStep::Done,
+  };
+  state.next_step = next_step(&state);
+  state
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn state(hooks: Vec<CliHook>) -> OnboardingState {
+    OnboardingState {
+      first_run: false,
+      felay_dir_exists: true,
+      felay_dir_error: None,
+      daemon_found: true,
+      daemon_running: true,
+      bots_configured: true,
+      hooks,
+      session_seen: false,
+      next_step: NextStep::Done,
+    }
+  }
+
+  fn hook(name: &'static str, installed: bool, configured: bool) -> CliHook {
+    CliHook { name, installed, configured }
+  }
+
+  #[test]
+  fn daemon_and_bot_steps_come_first() {
+    let mut s = state(Vec::new());
+    s.daemon_running = false;
+    assert_eq!(next_step(&s), NextStep::StartDaemon);
+    s.daemon_found = false;
+    assert_eq!(next_step(&s), NextStep::InstallDaemon);
+    let mut s = state(Vec::new());
+    s.bots_configured = false;
+    assert_eq!(next_step(&s), NextStep::AddBot);
+  }
+
+  #[test]
+  fn setup_hooks_only_while_no_installed_cli_is_hooked() {
+    let s = state(vec![hook("codex", true, false), hook("claude", false, false)]);
+    assert_eq!(next_step(&s), NextStep::SetupHooks);
+    let s = state(vec![hook("codex", true, false), hook("claude", true, true)]);
+    assert_eq!(next_step(&s), NextStep::StartSession);
+  }
+
+  #[test]
+  fn no_hookable_cli_installed_moves_on() {
+    let s = state(vec![hook("codex", false, false), hook("claude", false, false)]);
+    assert_eq!(next_step(&s), NextStep::StartSession);
+    let mut s = state(Vec::new());
+    s.session_seen = true;
+    assert_eq!(next_step(&s), NextStep::Done);
+  }
+}
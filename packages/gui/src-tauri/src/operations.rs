@@ -0,0 +1,96 @@
+//! Cancellable long-running commands.
+//!
+//! A long command registers itself with `begin` and hands the returned
+//! handle's `operation_id` to the frontend (as its result, or in its first
+//! event when the command only returns once done). `cancel_operation` sets
+//! the handle's flag, which the operation checks between steps; when it
+//! stops and drops the handle, `operation-cancelled` is emitted. Dropping
+//! the handle also unregisters the operation, however it ended.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+
+pub(crate) const OPERATION_CANCELLED_EVENT: &str = "operation-cancelled";
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+type Operations = HashMap<String, (&'static str, Arc<AtomicBool>)>;
+
+/// Running operations by id: their kind and cancel flag.
+static OPERATIONS: Mutex<Option<Operations>> = Mutex::new(None);
+
+#[derive(Debug, Serialize, Clone)]
+struct OperationCancelled {
+  operation_id: String,
+  kind: &'static str,
+}
+
+pub(crate) struct OperationHandle {
+  app: AppHandle,
+  id: String,
+  kind: &'static str,
+  cancel: Arc<AtomicBool>,
+}
+
+impl OperationHandle {
+  pub(crate) fn id(&self) -> &str {
+    &self.id
+  }
+
+  pub(crate) fn is_cancelled(&self) -> bool {
+    self.cancel.load(Ordering::Relaxed)
+  }
+}
+
+impl Drop for OperationHandle {
+  fn drop(&mut self) {
+    if let Some(operations) = OPERATIONS.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+      operations.remove(&self.id);
+    }
+    if self.is_cancelled() {
+      let payload = OperationCancelled { operation_id: self.id.clone(), kind: self.kind };
+      if let Err(e) = self.app.emit(OPERATION_CANCELLED_EVENT, payload) {
+        gui_println!("[gui] failed to emit {}: {}", OPERATION_CANCELLED_EVENT, e);
+      }
+    }
+  }
+}
+
+fn insert(operations: &mut Operations, app: &AppHandle, kind: &'static str) -> OperationHandle {
+  let id = format!("{}-{}", kind, NEXT_ID.fetch_add(1, Ordering::Relaxed));
+  let cancel = Arc::new(AtomicBool::new(false));
+  operations.insert(id.clone(), (kind, cancel.clone()));
+  OperationHandle { app: app.clone(), id, kind, cancel }
+}
+
+/// Register a new operation of `kind` (the command's name).
+pub(crate) fn begin(app: &AppHandle, kind: &'static str) -> OperationHandle {
+  let mut operations = OPERATIONS.lock().unwrap_or_else(|e| e.into_inner());
+  insert(operations.get_or_insert_with(HashMap::new), app, kind)
+}
+
+/// `begin`, unless an operation of `kind` is already running.
+pub(crate) fn begin_exclusive(app: &AppHandle, kind: &'static str) -> Option<OperationHandle> {
+  let mut operations = OPERATIONS.lock().unwrap_or_else(|e| e.into_inner());
+  let operations = operations.get_or_insert_with(HashMap::new);
+  if operations.values().any(|(k, _)| *k == kind) {
+    return None;
+  }
+  Some(insert(operations, app, kind))
+}
+
+/// Ask the operation to stop. False if it isn't running (any more).
+#[tauri::command]
+pub(crate) fn cancel_operation(operation_id: String) -> bool {
+  let operations = OPERATIONS.lock().unwrap_or_else(|e| e.into_inner());
+  match operations.as_ref().and_then(|operations| operations.get(&operation_id)) {
+    Some((_, cancel)) => {
+      cancel.store(true, Ordering::Relaxed);
+      true
+    }
+    None => false,
+  }
+}
@@ -0,0 +1,198 @@
+//! Handing a file or URL to the app the OS associates with it.
+//!
+//! This used to be `cmd /c start "" <target>` on Windows, which lets cmd
+//! parse the target: `&` ends the command, `%VAR%` is expanded, and
+//! `start` reports success whatever happened to the rest. `ShellExecuteW`
+//! takes the target as one UTF-16 string with no shell in between, so
+//! paths and URLs with spaces, `&`, `%` or CJK characters arrive verbatim.
+//! Shell handlers may be COM objects, so COM is initialized for the call
+//! as the Win32 docs ask. `open` / `xdg-open` already get the target as a
+//! single argument.
+//!
+//! `reveal` shows a file selected in the file manager. Explorer's
+//! `/select,` switch has its own quoting rules, so on Windows the path goes
+//! to `SHOpenFolderAndSelectItems` as an item ID list instead, under the
+//! same COM setup. The Win32 calls all go through `windows-sys`.
+
+use std::ffi::OsStr;
+use std::io;
+use std::path::Path;
+
+/// `units` NUL-terminated, as Win32 wide-string arguments are.
+#[cfg_attr(not(any(target_os = "windows", test)), allow(dead_code))]
+fn nul_terminated(units: impl Iterator<Item = u16>) -> io::Result<Vec<u16>> {
+  let mut wide: Vec<u16> = units.collect();
+  // An interior NUL would silently cut the target short
+  if wide.contains(&0) {
+    return Err(io::Error::new(io::ErrorKind::InvalidInput, "path contains a NUL character"));
+  }
+  wide.push(0);
+  Ok(wide)
+}
+
+#[cfg(target_os = "windows")]
+fn wide(s: &OsStr) -> io::Result<Vec<u16>> {
+  use std::os::windows::ffi::OsStrExt;
+  nul_terminated(s.encode_wide())
+}
+
+/// COM initialized on this thread for as long as it lives. Uninitialized
+/// on drop only if this guard's call took a reference: a thread already
+/// in another apartment (`RPC_E_CHANGED_MODE`) is used as it is.
+#[cfg(target_os = "windows")]
+struct ComGuard(bool);
+
+#[cfg(target_os = "windows")]
+impl ComGuard {
+  fn new() -> Self {
+    use windows_sys::Win32::System::Com::{
+      CoInitializeEx, COINIT_APARTMENTTHREADED, COINIT_DISABLE_OLE1DDE,
+    };
+    // SAFETY: the reserved argument must be null
+    let hr = unsafe {
+      CoInitializeEx(std::ptr::null(), (COINIT_APARTMENTTHREADED | COINIT_DISABLE_OLE1DDE) as u32)
+    };
+    // S_OK or S_FALSE: this call owns a reference
+    ComGuard(hr >= 0)
+  }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for ComGuard {
+  fn drop(&mut self) {
+    if self.0 {
+      // SAFETY: balances the successful CoInitializeEx in `new`
+      unsafe { windows_sys::Win32::System::Com::CoUninitialize() };
+    }
+  }
+}
+
+/// Open `target`, a path or URL, with its default handler.
+#[cfg(target_os = "windows")]
+pub(crate) fn open(target: &OsStr) -> io::Result<()> {
+  use windows_sys::Win32::UI::Shell::ShellExecuteW;
+  use windows_sys::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+  let verb = wide(OsStr::new("open"))?;
+  let file = wide(target)?;
+  let _com = ComGuard::new();
+  // SAFETY: both strings are NUL-terminated and outlive the call; the
+  // null window, parameters and directory are all allowed
+  let result = unsafe {
+    ShellExecuteW(
+      std::ptr::null_mut(),
+      verb.as_ptr(),
+      file.as_ptr(),
+      std::ptr::null(),
+      std::ptr::null(),
+      SW_SHOWNORMAL,
+    )
+  };
+  // Values up to 32 are error codes, and GetLastError has the details
+  if result as isize > 32 {
+    Ok(())
+  } else {
+    Err(io::Error::last_os_error())
+  }
+}
+
+/// The command handing `target` to the desktop's opener, as one argument.
+#[cfg(not(target_os = "windows"))]
+fn open_command(target: &OsStr) -> std::process::Command {
+  let program = if cfg!(target_os = "macos") { "open" } else { "xdg-open" };
+  let mut command = std::process::Command::new(program);
+  command.arg(target);
+  command
+}
+
+/// Open `target`, a path or URL, with its default handler.
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn open(target: &OsStr) -> io::Result<()> {
+  open_command(target).spawn().map(|_| ())
+}
+
+/// Show `path` selected in the file manager.
+#[cfg(target_os = "windows")]
+pub(crate) fn reveal(path: &Path) -> io::Result<()> {
+  use windows_sys::Win32::UI::Shell::{ILCreateFromPathW, ILFree, SHOpenFolderAndSelectItems};
+
+  let path = wide(path.as_os_str())?;
+  let _com = ComGuard::new();
+  // SAFETY: `path` is NUL-terminated and outlives the call
+  let pidl = unsafe { ILCreateFromPathW(path.as_ptr()) };
+  if pidl.is_null() {
+    return Err(io::Error::new(io::ErrorKind::NotFound, "cannot resolve path"));
+  }
+  // SAFETY: `pidl` is a valid ID list, freed right after. With `cidl` 0
+  // the shell opens the item's parent folder and selects the item itself.
+  let hr = unsafe {
+    let hr = SHOpenFolderAndSelectItems(pidl, 0, std::ptr::null(), 0);
+    ILFree(pidl);
+    hr
+  };
+  match hr {
+    0 => Ok(()),
+    hr => Err(io::Error::other(format!("SHOpenFolderAndSelectItems failed: {:#x}", hr))),
+  }
+}
+
+/// Show `path` selected in the file manager.
+#[cfg(target_os = "macos")]
+pub(crate) fn reveal(path: &Path) -> io::Result<()> {
+  std::process::Command::new("open").arg("-R").arg(path).spawn().map(|_| ())
+}
+
+/// Open the folder containing `path`; xdg-open has no way to select a file.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub(crate) fn reveal(path: &Path) -> io::Result<()> {
+  open(path.parent().unwrap_or(path).as_os_str())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Targets a shell would split, expand or mangle.
+  const TARGETS: &[&str] = &[
+    r"C:\Users\Jane Doe\My Documents\felay config.json",
+    "https://example.com/search?q=a&b=c&lang=zh",
+    r"C:\Temp\100% done\%APPDATA%\log.txt",
+    r"C:\用户\张三\配置\设置.json",
+    "/home/me/日志 & notes/%HOME% 100%.md",
+  ];
+
+  #[test]
+  fn wide_strings_keep_the_target_verbatim() {
+    for target in TARGETS {
+      let wide = nul_terminated(target.encode_utf16()).unwrap();
+      assert_eq!(wide.last(), Some(&0));
+      assert_eq!(wide.iter().filter(|u| **u == 0).count(), 1);
+      assert_eq!(String::from_utf16(&wide[..wide.len() - 1]).unwrap(), *target);
+    }
+  }
+
+  #[test]
+  fn interior_nul_is_refused() {
+    let e = nul_terminated("C:\\a\0.exe".encode_utf16()).unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+  }
+
+  #[cfg(target_os = "windows")]
+  #[test]
+  fn wide_keeps_the_target_verbatim() {
+    for target in TARGETS {
+      let wide = wide(OsStr::new(target)).unwrap();
+      assert_eq!(String::from_utf16(&wide[..wide.len() - 1]).unwrap(), *target);
+    }
+  }
+
+  #[cfg(not(target_os = "windows"))]
+  #[test]
+  fn target_is_one_unquoted_argument() {
+    for target in TARGETS {
+      let command = open_command(OsStr::new(target));
+      let args: Vec<&OsStr> = command.get_args().collect();
+      assert_eq!(args, [OsStr::new(target)]);
+    }
+  }
+}
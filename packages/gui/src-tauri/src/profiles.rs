@@ -0,0 +1,134 @@
+//! Named daemon profiles: separate `config.json`s for, say, work and home bots.
+//!
+//! A profile is a directory under `~/.felay/profiles/`; the default profile
+//! is `~/.felay` itself. Only `config.json` moves with the profile. The lock
+//! file, socket, logs and secret key stay in `~/.felay`, so the CLI and the
+//! hook scripts find the daemon whichever profile it runs. The active
+//! profile is kept in `gui-state.json`, where the daemon's `configManager.ts`
+//! reads it at startup; switching therefore stops the daemon first and
+//! starts it again afterwards.
+
+use crate::{
+  config_cache, daemon_reachable, file_watcher, get_home_dir, get_ipc_path, launch_state,
+};
+use crate::{send_stop_request, start_daemon};
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::AppHandle;
+
+fn felay_dir() -> Option<PathBuf> {
+  Some(PathBuf::from(get_home_dir()?).join(".felay"))
+}
+
+fn profiles_dir() -> Option<PathBuf> {
+  Some(felay_dir()?.join("profiles"))
+}
+
+/// A name usable as a single directory under `profiles/`.
+fn valid_name(name: &str) -> bool {
+  !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\'])
+}
+
+/// The directory holding the active profile's `config.json`.
+pub(crate) fn felay_data_dir() -> Option<PathBuf> {
+  match launch_state::active_profile().filter(|name| valid_name(name)) {
+    Some(name) => Some(profiles_dir()?.join(name)),
+    None => felay_dir(),
+  }
+}
+
+/// The active profile's `config.json`.
+pub(crate) fn config_path() -> Option<PathBuf> {
+  Some(felay_data_dir()?.join("config.json"))
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Profile {
+  name: String,
+  has_config: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ProfileList {
+  /// None when the default profile is active.
+  active: Option<String>,
+  profiles: Vec<Profile>,
+}
+
+/// The subdirectories of `~/.felay/profiles/`, sorted by name.
+#[tauri::command]
+pub(crate) fn list_profiles() -> ProfileList {
+  let mut profiles: Vec<Profile> = profiles_dir()
+    .and_then(|dir| fs::read_dir(dir).ok())
+    .map(|entries| {
+      entries
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| Some((e.file_name().into_string().ok()?, e.path())))
+        .filter(|(name, _)| valid_name(name))
+        .map(|(name, path)| Profile { has_config: path.join("config.json").is_file(), name })
+        .collect()
+    })
+    .unwrap_or_default();
+  profiles.sort_by(|a, b| a.name.cmp(&b.name));
+  ProfileList { active: launch_state::active_profile(), profiles }
+}
+
+/// Ask the daemon to stop and wait (up to ~5 seconds) until it is gone.
+fn stop_daemon_and_wait(ipc_path: &str) -> Result<(), String> {
+  if !send_stop_request(ipc_path) {
+    return Err("无法停止 daemon".to_string());
+  }
+  for _ in 0..25 {
+    if !daemon_reachable(ipc_path) {
+      return Ok(());
+    }
+    std::thread::sleep(Duration::from_millis(200));
+  }
+  Err("daemon 未在预期时间内退出".to_string())
+}
+
+/// Switch to the profile `name` (None for the default one). A running
+/// daemon is stopped first and started again on the new profile. Returns
+/// `profile`, `changed` and `restarted`.
+#[tauri::command]
+pub(crate) async fn switch_profile(app: AppHandle, name: Option<String>) -> Value {
+  if let Some(name) = &name {
+    let exists = profiles_dir().is_some_and(|d| d.join(name).is_dir());
+    if !valid_name(name) || !exists {
+      return serde_json::json!({ "ok": false, "error": format!("配置档案不存在: {}", name) });
+    }
+  }
+  if launch_state::active_profile() == name {
+    return serde_json::json!({ "ok": true, "profile": name, "changed": false, "restarted": false });
+  }
+
+  let running = get_ipc_path().filter(|p| daemon_reachable(p));
+  if let Some(ipc_path) = running.clone() {
+    let stopped = tokio::task::spawn_blocking(move || stop_daemon_and_wait(&ipc_path)).await;
+    if let Err(e) = stopped.unwrap_or_else(|e| Err(e.to_string())) {
+      return serde_json::json!({ "ok": false, "error": e });
+    }
+  }
+
+  if let Err(e) = launch_state::set_active_profile(name.clone()) {
+    return serde_json::json!({ "ok": false, "error": format!("无法保存配置档案: {}", e) });
+  }
+  config_cache::invalidate();
+  file_watcher::start(app.clone());
+
+  if running.is_none() {
+    return serde_json::json!({ "ok": true, "profile": name, "changed": true, "restarted": false });
+  }
+  let started = start_daemon(app).await;
+  serde_json::json!({
+    "ok": true,
+    "profile": name,
+    "changed": true,
+    "restarted": started.ok,
+    "error": started.error,
+  })
+}
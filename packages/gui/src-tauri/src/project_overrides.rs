@@ -0,0 +1,426 @@
+//! Per-project default bots, keyed by directory.
+//!
+//! An override names the bots a session should use when its cwd is inside
+//! the project. Overrides live in gui-settings under `project_overrides`,
+//! keyed by the normalized project path. The daemon still auto-binds its
+//! global defaults on registration; the tray status poll then moves new
+//! sessions under an overridden project to that project's bots.
+//!
+//! An override's `quiet_hours` is sent as a window for each such session,
+//! which the daemon uses in place of the push bot's own; the bot, and
+//! sessions of other projects pushing through it, are left alone. A session
+//! the daemon didn't answer for is tried again on the next poll.
+//!
+//! Paths are compared after canonicalization (symlinks resolved where the
+//! path exists), without trailing separators, and case-insensitively on
+//! Windows. The longest matching project wins.
+
+use crate::quiet_hours::{parse_hhmm, quiet_hours_value};
+use crate::{capabilities, gui_settings, DaemonError, DaemonSession, IpcConnection};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, MAIN_SEPARATOR};
+use std::sync::Mutex;
+
+const OVERRIDES_KEY: &str = "project_overrides";
+const SESSION_QUIET_HOURS_REQUEST: &str = "set_session_quiet_hours_request";
+
+/// Sessions whose override is still to apply because the daemon didn't
+/// answer, by id.
+static RETRY: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+struct QuietWindow {
+  start: String,
+  end: String,
+  #[serde(default)]
+  timezone: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ProjectOverride {
+  #[serde(default)]
+  interactive_bot_id: Option<String>,
+  #[serde(default)]
+  push_bot_id: Option<String>,
+  #[serde(default)]
+  quiet_hours: Option<QuietWindow>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ProjectOverrideEntry {
+  project_path: String,
+  #[serde(rename = "override")]
+  project_override: ProjectOverride,
+  /// False when the directory is gone; the override is kept until removed.
+  exists: bool,
+}
+
+/// Canonical form used for storage and matching.
+fn normalize(path: &str) -> String {
+  let path = Path::new(path.trim());
+  let resolved = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+  let mut s = resolved.to_string_lossy().to_string();
+  if cfg!(target_os = "windows") {
+    // canonicalize yields verbatim `\\?\C:\...` paths; cwd reports don't
+    if let Some(rest) = s.strip_prefix(r"\\?\") {
+      s = rest.to_string();
+    }
+    s = s.replace('/', "\\").to_lowercase();
+  }
+  while s.len() > 1 && s.ends_with(MAIN_SEPARATOR) {
+    s.pop();
+  }
+  s
+}
+
+/// Whether normalized `cwd` is `project` or inside it.
+fn is_under(cwd: &str, project: &str) -> bool {
+  match cwd.strip_prefix(project) {
+    Some("") => true,
+    Some(rest) => rest.starts_with(MAIN_SEPARATOR) || project.ends_with(MAIN_SEPARATOR),
+    None => false,
+  }
+}
+
+fn load_all() -> Map<String, Value> {
+  gui_settings::load()
+    .get(OVERRIDES_KEY)
+    .and_then(|v| v.as_object())
+    .cloned()
+    .unwrap_or_default()
+}
+
+/// The override of the innermost project in `overrides` containing `cwd`.
+fn find_in(overrides: Map<String, Value>, cwd: &str) -> Option<(String, ProjectOverride)> {
+  let cwd = normalize(cwd);
+  overrides
+    .into_iter()
+    .filter(|(project, _)| is_under(&cwd, project))
+    .max_by_key(|(project, _)| project.len())
+    .and_then(|(project, v)| Some((project, serde_json::from_value(v).ok()?)))
+}
+
+fn find(cwd: &str) -> Option<(String, ProjectOverride)> {
+  find_in(load_all(), cwd)
+}
+
+/// Parse an override as sent by the frontend; null means remove.
+fn parse_override(value: Value) -> Result<Option<ProjectOverride>, String> {
+  if value.is_null() {
+    return Ok(None);
+  }
+  let parsed: ProjectOverride =
+    serde_json::from_value(value).map_err(|e| format!("invalid override: {}", e))?;
+  if let Some(window) = &parsed.quiet_hours {
+    let (Some(start), Some(end)) = (parse_hhmm(&window.start), parse_hhmm(&window.end)) else {
+      return Err("时间格式应为 HH:MM".to_string());
+    };
+    if start == end {
+      return Err("开始与结束时间不能相同".to_string());
+    }
+  }
+  Ok(Some(parsed))
+}
+
+#[tauri::command]
+pub(crate) fn get_project_overrides() -> Vec<ProjectOverrideEntry> {
+  load_all()
+    .into_iter()
+    .filter_map(|(project_path, v)| {
+      Some(ProjectOverrideEntry {
+        exists: Path::new(&project_path).is_dir(),
+        project_override: serde_json::from_value(v).ok()?,
+        project_path,
+      })
+    })
+    .collect()
+}
+
+/// Set or (with a null `project_override`) remove the override for
+/// `project_path`. It applies to sessions started afterwards.
+#[tauri::command]
+pub(crate) fn set_project_override(project_path: String, project_override: Value) -> Value {
+  if project_path.trim().is_empty() {
+    return serde_json::json!({ "ok": false, "error": "project path is empty" });
+  }
+  let key = normalize(&project_path);
+  let mut overrides = load_all();
+
+  let parsed = match parse_override(project_override) {
+    Ok(parsed) => parsed,
+    Err(e) => return serde_json::json!({ "ok": false, "error": e }),
+  };
+
+  match &parsed {
+    Some(o) => {
+      overrides.insert(key.clone(), serde_json::to_value(o).unwrap_or_default());
+    }
+    None => {
+      overrides.remove(&key);
+    }
+  }
+  let mut settings = gui_settings::load();
+  settings[OVERRIDES_KEY] = Value::Object(overrides);
+  if let Err(e) = gui_settings::save(&settings) {
+    return serde_json::json!({ "ok": false, "error": e });
+  }
+
+  serde_json::json!({ "ok": true, "project_path": key })
+}
+
+/// The sessions to apply overrides to: those that just started, and the
+/// still active ones left in `retry`.
+fn pending(
+  fresh: &[DaemonSession],
+  sessions: &[DaemonSession],
+  retry: &[String],
+) -> Vec<DaemonSession> {
+  let retried = sessions.iter().filter(|s| {
+    s.status != "ended"
+      && retry.contains(&s.session_id)
+      && !fresh.iter().any(|f| f.session_id == s.session_id)
+  });
+  fresh.iter().chain(retried).cloned().collect()
+}
+
+/// Send `requests` in order on the shared connection, opened on first use.
+/// False when the daemon didn't answer, so the session is tried again; a
+/// refusal is logged and not retried.
+fn send(conn: &mut Option<IpcConnection>, ipc_path: &str, what: &str, requests: &[Value]) -> bool {
+  if conn.is_none() {
+    *conn = IpcConnection::open(ipc_path);
+  }
+  let Some(c) = conn.as_mut() else {
+    return false;
+  };
+  for request in requests {
+    match c.request_ok(request) {
+      Ok(r) if r.ok => {}
+      Ok(r) => {
+        gui_println!("[gui] {} failed: {}", what, r.error.unwrap_or_default());
+        return true;
+      }
+      Err(DaemonError::NoResponse) => {
+        // A late reply would be read as the next request's
+        *conn = None;
+        return false;
+      }
+      Err(e) => {
+        gui_println!("[gui] {} failed: {}", what, e);
+        return true;
+      }
+    }
+  }
+  gui_println!("[gui] {}", what);
+  true
+}
+
+/// Apply the override of `session`'s project, if any. False when the
+/// daemon didn't answer.
+fn apply(conn: &mut Option<IpcConnection>, ipc_path: &str, session: &DaemonSession) -> bool {
+  let Some((project, o)) = find(&session.cwd) else {
+    return true;
+  };
+  let targets = [
+    ("interactive", o.interactive_bot_id, &session.interactive_bot_id),
+    ("push", o.push_bot_id, &session.push_bot_id),
+  ];
+  for (bot_type, wanted, current) in targets {
+    let Some(bot_id) = wanted.filter(|w| Some(w) != current.as_ref()) else {
+      continue;
+    };
+    let unbind = serde_json::json!({
+      "type": "unbind_bot_request",
+      "payload": { "sessionId": session.session_id, "botType": bot_type }
+    });
+    let bind = serde_json::json!({
+      "type": "bind_bot_request",
+      "payload": { "sessionId": session.session_id, "botType": bot_type, "botId": bot_id }
+    });
+    let what = format!(
+      "project {}: bind {} bot {} to session {}",
+      project, bot_type, bot_id, session.session_id
+    );
+    if !send(conn, ipc_path, &what, &[unbind, bind]) {
+      return false;
+    }
+  }
+
+  let Some(window) = o.quiet_hours else {
+    return true;
+  };
+  if capabilities::supports(SESSION_QUIET_HOURS_REQUEST) == Some(false) {
+    gui_println!("[gui] project {}: daemon does not support per-session quiet hours", project);
+    return true;
+  }
+  let request = serde_json::json!({
+    "type": SESSION_QUIET_HOURS_REQUEST,
+    "payload": {
+      "sessionId": session.session_id,
+      "quietHours": quiet_hours_value(&window.start, &window.end, window.timezone),
+    }
+  });
+  let what = format!("project {}: quiet hours for session {}", project, session.session_id);
+  send(conn, ipc_path, &what, &[request])
+}
+
+/// Move sessions onto their project's bots and quiet hours. Called from the
+/// tray status poll with `session_events::track`'s `started` and the full
+/// session list; sessions the daemon didn't answer for are kept for the
+/// next call.
+pub(crate) fn apply_to_new_sessions(
+  ipc_path: &str,
+  fresh: &[DaemonSession],
+  sessions: &[DaemonSession],
+) {
+  let mut retry = RETRY.lock().unwrap_or_else(|e| e.into_inner());
+  let mut conn = None;
+  let mut failed = Vec::new();
+  for session in pending(fresh, sessions, &retry) {
+    if !apply(&mut conn, ipc_path, &session) {
+      failed.push(session.session_id);
+    }
+  }
+  *retry = failed;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn overrides(entries: &[(&str, Value)]) -> Map<String, Value> {
+    entries.iter().map(|(p, v)| (normalize(p), v.clone())).collect()
+  }
+
+  fn session(id: &str, status: &str) -> DaemonSession {
+    serde_json::from_value(json!({
+      "sessionId": id,
+      "cli": "claude",
+      "cwd": "/tmp",
+      "status": status,
+      "startedAt": "2026-01-01T00:00:00Z",
+    }))
+    .unwrap()
+  }
+
+  fn ids(sessions: &[DaemonSession]) -> Vec<&str> {
+    sessions.iter().map(|s| s.session_id.as_str()).collect()
+  }
+
+  #[test]
+  fn cwd_matches_the_project_and_its_subdirectories() {
+    let dir = tempfile::tempdir().unwrap();
+    let project = dir.path().join("app");
+    fs::create_dir_all(project.join("src/ui")).unwrap();
+    let map = overrides(&[(project.to_str().unwrap(), json!({ "push_bot_id": "p1" }))]);
+
+    for cwd in [project.clone(), project.join("src"), project.join("src/ui")] {
+      let (found, o) = find_in(map.clone(), cwd.to_str().unwrap()).unwrap();
+      assert_eq!(found, normalize(project.to_str().unwrap()));
+      assert_eq!(o.push_bot_id.as_deref(), Some("p1"));
+    }
+    let with_separator = format!("{}{}", project.display(), MAIN_SEPARATOR);
+    assert!(find_in(map.clone(), &with_separator).is_some());
+    assert!(find_in(map, dir.path().to_str().unwrap()).is_none());
+  }
+
+  #[test]
+  fn sibling_with_a_common_prefix_does_not_match() {
+    let dir = tempfile::tempdir().unwrap();
+    let project = dir.path().join("app");
+    let sibling = dir.path().join("app-old");
+    fs::create_dir_all(&project).unwrap();
+    fs::create_dir_all(&sibling).unwrap();
+    let map = overrides(&[(project.to_str().unwrap(), json!({}))]);
+    assert!(find_in(map, sibling.to_str().unwrap()).is_none());
+  }
+
+  #[test]
+  fn innermost_project_wins() {
+    let dir = tempfile::tempdir().unwrap();
+    let outer = dir.path().join("mono");
+    let inner = outer.join("packages/web");
+    fs::create_dir_all(inner.join("src")).unwrap();
+    let map = overrides(&[
+      (outer.to_str().unwrap(), json!({ "push_bot_id": "outer" })),
+      (inner.to_str().unwrap(), json!({ "push_bot_id": "inner" })),
+    ]);
+
+    let (_, o) = find_in(map.clone(), inner.join("src").to_str().unwrap()).unwrap();
+    assert_eq!(o.push_bot_id.as_deref(), Some("inner"));
+    let (_, o) = find_in(map, outer.join("packages").to_str().unwrap()).unwrap();
+    assert_eq!(o.push_bot_id.as_deref(), Some("outer"));
+  }
+
+  #[test]
+  fn missing_directories_are_compared_as_given() {
+    let root = std::env::temp_dir().join("felay-no-such-project");
+    let map = overrides(&[(root.to_str().unwrap(), json!({ "push_bot_id": "p1" }))]);
+    assert!(find_in(map.clone(), root.join("sub").to_str().unwrap()).is_some());
+    assert!(find_in(map, "relative/elsewhere").is_none());
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn symlinked_cwd_matches_its_target() {
+    let dir = tempfile::tempdir().unwrap();
+    let project = dir.path().join("real");
+    fs::create_dir_all(project.join("src")).unwrap();
+    let link = dir.path().join("link");
+    std::os::unix::fs::symlink(&project, &link).unwrap();
+    let map = overrides(&[(project.to_str().unwrap(), json!({}))]);
+    assert!(find_in(map, link.join("src").to_str().unwrap()).is_some());
+  }
+
+  #[cfg(windows)]
+  #[test]
+  fn windows_paths_match_whatever_their_case() {
+    let project = normalize(r"C:\Users\Foo\Proj");
+    assert!(is_under(&normalize(r"c:\users\foo\proj\sub"), &project));
+    assert!(is_under(&normalize("c:/Users/foo/PROJ/"), &project));
+    assert!(!is_under(&normalize(r"c:\users\foo\project"), &project));
+
+    let map = overrides(&[(r"C:\Users\Foo\Proj", json!({ "push_bot_id": "p1" }))]);
+    let (found, o) = find_in(map, r"c:\users\foo\proj\sub").unwrap();
+    assert_eq!(found, r"c:\users\foo\proj");
+    assert_eq!(o.push_bot_id.as_deref(), Some("p1"));
+  }
+
+  #[test]
+  fn unreadable_override_is_skipped() {
+    let dir = tempfile::tempdir().unwrap();
+    let map = overrides(&[(dir.path().to_str().unwrap(), json!({ "push_bot_id": 7 }))]);
+    assert!(find_in(map, dir.path().to_str().unwrap()).is_none());
+  }
+
+  #[test]
+  fn parse_override_checks_the_window() {
+    assert!(parse_override(Value::Null).unwrap().is_none());
+    let o = parse_override(json!({ "quiet_hours": { "start": "22:00", "end": "07:00" } }));
+    assert!(o.unwrap().unwrap().quiet_hours.is_some(), "no push bot is needed");
+    let bad = json!({ "quiet_hours": { "start": "7:00", "end": "08:00" } });
+    assert_eq!(parse_override(bad).unwrap_err(), "时间格式应为 HH:MM");
+    let same = json!({ "quiet_hours": { "start": "08:00", "end": "08:00" } });
+    assert!(parse_override(same).is_err());
+    assert!(parse_override(json!({ "push_bot": "p1" })).is_err());
+  }
+
+  #[test]
+  fn pending_adds_listed_sessions_left_to_retry() {
+    let fresh = [session("new", "listening")];
+    let sessions = [
+      session("new", "listening"),
+      session("failed", "listening"),
+      session("done", "listening"),
+      session("gone", "ended"),
+    ];
+    let retry = ["failed".to_string(), "gone".to_string(), "new".to_string()];
+    assert_eq!(ids(&pending(&fresh, &sessions, &retry)), ["new", "failed"]);
+    assert_eq!(ids(&pending(&[], &sessions, &[])), Vec::<&str>::new());
+  }
+}
@@ -0,0 +1,174 @@
+//! Per-bot quiet hours for push bots.
+//!
+//! The daemon holds pushes back during the window and delivers them once it
+//! ends. Daemons that predate the feature silently ignore the request, so a
+//! missing reply from a reachable daemon is reported as `supported: false`
+//! and the frontend hides the control.
+
+use crate::{daemon_reachable, get_ipc_path, ipc_payload, DaemonError};
+use serde_json::Value;
+
+/// Parse "HH:MM" (24h) into minutes after midnight.
+pub(crate) fn parse_hhmm(value: &str) -> Option<u32> {
+  let (h, m) = value.trim().split_once(':')?;
+  if h.len() != 2 || m.len() != 2 {
+    return None;
+  }
+  let (h, m) = (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?);
+  (h < 24 && m < 60).then_some(h * 60 + m)
+}
+
+/// Display form of a stored window, e.g. `23:00–08:00 (次日)` when it wraps
+/// past midnight. None if the stored value is malformed.
+fn window_label(quiet_hours: &Value) -> Option<String> {
+  let start = quiet_hours.get("start")?.as_str()?;
+  let end = quiet_hours.get("end")?.as_str()?;
+  let overnight = parse_hhmm(start)? > parse_hhmm(end)?;
+
+  let mut label = format!("{}–{}", start, end);
+  if overnight {
+    label.push_str(" (次日)");
+  }
+  if let Some(tz) = quiet_hours.get("timezone").and_then(|v| v.as_str()) {
+    label.push_str(&format!(" {}", tz));
+  }
+  Some(label)
+}
+
+/// Add `quiet_hours_label` to push bots that have a window configured.
+/// Bots without one are left untouched.
+pub(crate) fn annotate_bots(bots: &mut Value) {
+  let Some(push) = bots.get_mut("push").and_then(|v| v.as_array_mut()) else {
+    return;
+  };
+  for bot in push.iter_mut() {
+    if let Some(label) = bot.get("quietHours").and_then(window_label) {
+      bot["quiet_hours_label"] = Value::String(label);
+    }
+  }
+}
+
+/// The `quietHours` object sent to the daemon. Its schema has `timezone`
+/// optional but not nullable, so an unset one is left out.
+pub(crate) fn quiet_hours_value(start: &str, end: &str, timezone: Option<String>) -> Value {
+  let mut quiet_hours = serde_json::json!({ "start": start.trim(), "end": end.trim() });
+  if let Some(tz) = timezone.map(|tz| tz.trim().to_string()).filter(|tz| !tz.is_empty()) {
+    quiet_hours["timezone"] = Value::String(tz);
+  }
+  quiet_hours
+}
+
+fn unsupported() -> Value {
+  serde_json::json!({
+    "ok": false,
+    "supported": false,
+    "error": "daemon does not support quiet hours",
+  })
+}
+
+/// Send a quiet-hours request, telling "daemon down" apart from "daemon too old".
+fn quiet_hours_request(req: &Value) -> Result<Value, Value> {
+  let Some(ipc_path) = get_ipc_path() else {
+    return Err(serde_json::json!({ "ok": false, "error": "daemon not running" }));
+  };
+  let request_type = req["type"].as_str().unwrap_or_default();
+  if crate::capabilities::supports(request_type) == Some(false) {
+    return Err(unsupported());
+  }
+  let req_str = serde_json::to_string(req).unwrap_or_default();
+  match ipc_payload(&ipc_path, &req_str) {
+    Ok(payload) => Ok(payload),
+    Err(DaemonError::NoResponse) if daemon_reachable(&ipc_path) => Err(unsupported()),
+    Err(e) => Err(e.to_json()),
+  }
+}
+
+#[tauri::command]
+pub(crate) fn set_bot_quiet_hours(
+  bot_type: String,
+  bot_id: String,
+  start: String,
+  end: String,
+  timezone: Option<String>,
+) -> Value {
+  if bot_type != "push" {
+    return serde_json::json!({ "ok": false, "error": "quiet hours only apply to push bots" });
+  }
+  let (Some(start_min), Some(end_min)) = (parse_hhmm(&start), parse_hhmm(&end)) else {
+    return serde_json::json!({ "ok": false, "error": "时间格式应为 HH:MM" });
+  };
+  if start_min == end_min {
+    return serde_json::json!({ "ok": false, "error": "开始与结束时间不能相同" });
+  }
+
+  let quiet_hours = quiet_hours_value(&start, &end, timezone);
+  let req = serde_json::json!({
+    "type": "set_quiet_hours_request",
+    "payload": { "botType": bot_type, "botId": bot_id, "quietHours": quiet_hours }
+  });
+
+  let _own_write = crate::file_watcher::own_config_write();
+  let result = quiet_hours_request(&req);
+  crate::config_cache::invalidate();
+  match result {
+    Ok(payload) => serde_json::json!({
+      "ok": payload["ok"].as_bool().unwrap_or(false),
+      "error": payload.get("error"),
+      "supported": true,
+      "overnight": start_min > end_min,
+      "label": window_label(&quiet_hours),
+    }),
+    Err(e) => e,
+  }
+}
+
+#[tauri::command]
+pub(crate) fn get_bot_quiet_hours(bot_type: String, bot_id: String) -> Value {
+  let req = serde_json::json!({
+    "type": "get_quiet_hours_request",
+    "payload": { "botType": bot_type, "botId": bot_id }
+  });
+
+  match quiet_hours_request(&req) {
+    Ok(payload) => {
+      let quiet_hours = payload.get("quietHours").cloned().unwrap_or(Value::Null);
+      serde_json::json!({
+        "ok": payload["ok"].as_bool().unwrap_or(false),
+        "error": payload.get("error"),
+        "supported": true,
+        "label": window_label(&quiet_hours),
+        "quiet_hours": quiet_hours,
+      })
+    }
+    Err(e) => e,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn unset_timezone_is_left_out() {
+    let value = quiet_hours_value("23:00", "08:00", None);
+    assert_eq!(value, serde_json::json!({ "start": "23:00", "end": "08:00" }));
+    let value = quiet_hours_value(" 23:00", "08:00 ", Some("  ".to_string()));
+    assert_eq!(value, serde_json::json!({ "start": "23:00", "end": "08:00" }));
+  }
+
+  #[test]
+  fn timezone_is_kept_trimmed() {
+    let value = quiet_hours_value("22:00", "07:30", Some(" Asia/Shanghai ".to_string()));
+    assert_eq!(value["timezone"], "Asia/Shanghai");
+    assert_eq!(window_label(&value).as_deref(), Some("22:00–07:30 (次日) Asia/Shanghai"));
+  }
+
+  #[test]
+  fn parse_hhmm_bounds() {
+    assert_eq!(parse_hhmm("00:00"), Some(0));
+    assert_eq!(parse_hhmm("23:59"), Some(23 * 60 + 59));
+    assert_eq!(parse_hhmm("24:00"), None);
+    assert_eq!(parse_hhmm("7:00"), None);
+    assert_eq!(parse_hhmm("07:60"), None);
+  }
+}
@@ -0,0 +1,36 @@
+//! The GUI's recent console output, kept in memory for the log bundle.
+//!
+//! The GUI logs with `gui_println!`, which prints as `println!` did and also
+//! keeps the line, timestamped, in a ring of the last `MAX_LINES`.
+//! `collect_logs` writes the ring out as `gui-recent.log`, so a bundle shows
+//! what the GUI was doing even when nothing of it reached a file (a release
+//! build on Windows has no console, and `gui.log` only traces IPC).
+
+use crate::config_backup::format_timestamp;
+use crate::unix_now;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+const MAX_LINES: usize = 1000;
+
+static LINES: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Print `line` and keep it. Used through `gui_println!`.
+pub(crate) fn record(line: String) {
+  println!("{}", line);
+  let mut lines = LINES.lock().unwrap_or_else(|e| e.into_inner());
+  if lines.len() >= MAX_LINES {
+    lines.pop_front();
+  }
+  lines.push_back(format!("{} {}", format_timestamp(unix_now()), line));
+}
+
+/// The kept lines, oldest first, one per line.
+pub(crate) fn contents() -> String {
+  let lines = LINES.lock().unwrap_or_else(|e| e.into_inner());
+  lines.iter().fold(String::new(), |mut text, line| {
+    text.push_str(line);
+    text.push('\n');
+    text
+  })
+}
@@ -0,0 +1,452 @@
+//! Changelogs of every release newer than a given version.
+//!
+//! `UpdateInfo.release_notes` is the latest release's body only, so someone
+//! several versions behind misses what came in between. The releases list
+//! is read `PAGE_SIZE` at a time, each page sent with the ETag it last came
+//! with; pages are kept in `~/.felay/release-notes-cache.json` so a 304
+//! (which doesn't count against the rate limit) can be answered. Paging
+//! stops at a page that reaches `current_version` or once `MAX_RELEASES`
+//! are found. Offline, the latest release's body from the update cache is
+//! all there is. `get_release_notes_since` and `get_changelog_since` are
+//! two shapes of the same list.
+
+use crate::{
+  ensure_update_check_enabled, get_home_dir, http, last_update_info, releases_list_endpoint,
+  tag_version, update_channel, update_endpoint, update_timeout, version_cmp, version_gt,
+  UpdateError, UpdateInfo,
+};
+use crate::gui_settings::UpdateChannel;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+const PAGE_SIZE: u32 = 30;
+const MAX_RELEASES: usize = 10;
+/// Pages read at most, should the list not reach `current_version`.
+const MAX_PAGES: u32 = 5;
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct ReleaseNote {
+  version: String,
+  /// None for the offline fallback, which has no date.
+  published_at: Option<String>,
+  notes: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct ChangelogEntry {
+  tag: String,
+  notes: String,
+}
+
+/// The fields kept of a listed release.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct CachedRelease {
+  tag: String,
+  #[serde(default)]
+  draft: bool,
+  #[serde(default)]
+  prerelease: bool,
+  #[serde(default)]
+  published_at: Option<String>,
+  #[serde(default)]
+  body: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedPage {
+  etag: String,
+  releases: Vec<CachedRelease>,
+}
+
+/// Pages by URL.
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct PageCache {
+  pages: BTreeMap<String, CachedPage>,
+}
+
+fn cache_path() -> Option<PathBuf> {
+  let home = get_home_dir()?;
+  Some(PathBuf::from(home).join(".felay").join("release-notes-cache.json"))
+}
+
+fn read_cache() -> PageCache {
+  cache_path()
+    .and_then(|path| fs::read_to_string(path).ok())
+    .and_then(|text| serde_json::from_str(&text).ok())
+    .unwrap_or_default()
+}
+
+fn write_cache(cache: &PageCache) {
+  let Some(path) = cache_path() else {
+    return;
+  };
+  if let Some(dir) = path.parent() {
+    let _ = fs::create_dir_all(dir);
+  }
+  if let Ok(text) = serde_json::to_string_pretty(cache) {
+    let tmp = path.with_extension("json.tmp");
+    if fs::write(&tmp, text).and_then(|_| fs::rename(&tmp, &path)).is_err() {
+      let _ = fs::remove_file(&tmp);
+    }
+  }
+}
+
+fn cached_release(release: &Value) -> Option<CachedRelease> {
+  Some(CachedRelease {
+    tag: release["tag_name"].as_str()?.to_string(),
+    draft: release["draft"] == true,
+    prerelease: release["prerelease"] == true,
+    published_at: release["published_at"].as_str().map(String::from),
+    body: release["body"].as_str().unwrap_or_default().to_string(),
+  })
+}
+
+/// GET `url`, conditional on `etag` when given.
+async fn send_page(
+  client: &reqwest::Client,
+  url: &str,
+  etag: Option<&str>,
+) -> Result<reqwest::Response, UpdateError> {
+  let mut req = http::get(client, url);
+  if let Some(etag) = etag {
+    req = req.header("If-None-Match", etag);
+  }
+  req.send().await.map_err(|e| UpdateError::from_reqwest(&e))
+}
+
+/// One page of the list, revalidated against its cached copy.
+async fn fetch_page(
+  client: &reqwest::Client,
+  url: &str,
+  cache: &mut PageCache,
+) -> Result<Vec<CachedRelease>, UpdateError> {
+  let etag = cache.pages.get(url).map(|page| page.etag.clone()).filter(|e| !e.is_empty());
+  let mut resp = send_page(client, url, etag.as_deref()).await?;
+
+  if resp.status() == reqwest::StatusCode::NOT_MODIFIED {
+    if let Some(page) = cache.pages.get(url) {
+      return Ok(page.releases.clone());
+    }
+    // Nothing cached to stand for (a caching proxy's doing): ask for the page
+    resp = send_page(client, url, None).await?;
+  }
+  if !resp.status().is_success() {
+    return Err(UpdateError::from_response(&resp, http::github_token_for(url).is_some()));
+  }
+  let etag = resp
+    .headers()
+    .get("etag")
+    .and_then(|v| v.to_str().ok())
+    .unwrap_or("")
+    .to_string();
+  let json: Vec<Value> = resp.json().await.map_err(|e| UpdateError::from_reqwest(&e))?;
+  let releases: Vec<CachedRelease> = json.iter().filter_map(cached_release).collect();
+  cache.pages.insert(url.to_string(), CachedPage { etag, releases: releases.clone() });
+  Ok(releases)
+}
+
+/// Listed releases newer than `current`, read page by page.
+async fn fetch_newer(current: &str) -> Result<Vec<CachedRelease>, UpdateError> {
+  ensure_update_check_enabled()?;
+  let client = http::http_client(update_timeout()).map_err(UpdateError::other)?;
+  let endpoint = update_endpoint().map_err(UpdateError::other)?;
+  let list = releases_list_endpoint(&endpoint, PAGE_SIZE)
+    .ok_or_else(|| UpdateError::other("更新地址没有发布列表，无法读取历史更新说明"))?;
+  let include_prereleases = update_channel() == UpdateChannel::Beta;
+
+  let mut cache = read_cache();
+  let newer = walk_pages(&client, &list, current, include_prereleases, &mut cache).await?;
+  write_cache(&cache);
+  Ok(newer)
+}
+
+/// Read the releases list at `list` page by page, keeping the releases
+/// newer than `current`, until a page reaches it, a page is short or
+/// `MAX_RELEASES` are found.
+async fn walk_pages(
+  client: &reqwest::Client,
+  list: &str,
+  current: &str,
+  include_prereleases: bool,
+  cache: &mut PageCache,
+) -> Result<Vec<CachedRelease>, UpdateError> {
+  let mut newer = Vec::new();
+  for page in 1..=MAX_PAGES {
+    let url = format!("{}&page={}", list, page);
+    let releases = match fetch_page(client, &url, cache).await {
+      Ok(releases) => releases,
+      // The first page is what matters; later ones only add older releases
+      Err(e) if page == 1 => return Err(e),
+      Err(_) => break,
+    };
+    let full = releases.len() as u32 == PAGE_SIZE;
+    // Newest first by date, so a page reaching `current` is the last needed
+    let reached = releases.iter().any(|r| !version_gt(tag_version(&r.tag), current));
+    newer.extend(releases.into_iter().filter(|r| {
+      !r.draft && (include_prereleases || !r.prerelease) && version_gt(tag_version(&r.tag), current)
+    }));
+    if reached || !full || newer.len() >= MAX_RELEASES {
+      break;
+    }
+  }
+  Ok(newer)
+}
+
+/// `notes` without HTML comments (release templates leave them in) and
+/// with the `@` of mentions dropped, so names show as plain text. An
+/// `@` inside a word, as in an email address, stays.
+fn sanitize_notes(notes: &str) -> String {
+  let mut without_comments = String::with_capacity(notes.len());
+  let mut rest = notes;
+  while let Some(start) = rest.find("<!--") {
+    without_comments.push_str(&rest[..start]);
+    rest = match rest[start..].find("-->") {
+      Some(end) => &rest[start + end + 3..],
+      None => "",
+    };
+  }
+  without_comments.push_str(rest);
+
+  let mut clean = String::with_capacity(without_comments.len());
+  let mut prev: Option<char> = None;
+  let mut chars = without_comments.chars().peekable();
+  while let Some(c) = chars.next() {
+    let word_start = !prev.is_some_and(|p| p.is_alphanumeric() || matches!(p, '.' | '_' | '-'));
+    let mention = c == '@' && word_start && chars.peek().is_some_and(|n| n.is_ascii_alphanumeric());
+    if !mention {
+      clean.push(c);
+    }
+    prev = Some(c);
+  }
+  clean.trim().to_string()
+}
+
+/// Releases newer than `version`, newest first and at most `MAX_RELEASES`.
+/// Pre-releases count on the beta channel only. When the list can't be
+/// read, the latest release from the update cache, if that one is newer.
+async fn releases_since(version: &str) -> Result<Vec<CachedRelease>, UpdateError> {
+  let current = tag_version(version.trim()).to_string();
+  let mut releases = match fetch_newer(&current).await {
+    Ok(releases) => releases,
+    Err(e) => return offline_fallback(last_update_info(), &current, e),
+  };
+  releases.sort_by(|a, b| version_cmp(tag_version(&b.tag), tag_version(&a.tag)));
+  releases.dedup_by(|a, b| a.tag == b.tag);
+  releases.truncate(MAX_RELEASES);
+  Ok(releases)
+}
+
+/// The latest release from the update cache `info`, if it is newer than
+/// `current`; `err` (why the list couldn't be read) without a cache.
+fn offline_fallback(
+  info: Option<UpdateInfo>,
+  current: &str,
+  err: UpdateError,
+) -> Result<Vec<CachedRelease>, UpdateError> {
+  let Some(info) = info else {
+    return Err(err);
+  };
+  gui_println!("[gui] release notes unavailable ({}), using the cached latest", err.message);
+  if !version_gt(tag_version(&info.latest_version), current) {
+    return Ok(Vec::new());
+  }
+  Ok(vec![CachedRelease {
+    tag: info.latest_version,
+    draft: false,
+    prerelease: info.prerelease,
+    published_at: None,
+    body: info.release_notes,
+  }])
+}
+
+/// Release notes of the releases newer than `current_version` (see
+/// `releases_since`), by version and publish date.
+#[tauri::command]
+pub(crate) async fn get_release_notes_since(
+  current_version: String,
+) -> Result<Vec<ReleaseNote>, UpdateError> {
+  let releases = releases_since(&current_version).await?;
+  Ok(
+    releases
+      .into_iter()
+      .map(|r| ReleaseNote {
+        version: tag_version(&r.tag).to_string(),
+        published_at: r.published_at,
+        notes: sanitize_notes(&r.body),
+      })
+      .collect(),
+  )
+}
+
+/// The changelog from `version` up to the latest release, by tag, newest
+/// first (see `releases_since`).
+#[tauri::command]
+pub(crate) async fn get_changelog_since(
+  version: String,
+) -> Result<Vec<ChangelogEntry>, UpdateError> {
+  let releases = releases_since(&version).await?;
+  Ok(
+    releases
+      .into_iter()
+      .map(|r| ChangelogEntry {
+        tag: r.tag,
+        notes: sanitize_notes(&r.body),
+      })
+      .collect(),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::tests::MockServer;
+  use std::sync::atomic::{AtomicUsize, Ordering};
+  use std::sync::Arc;
+
+  fn client() -> reqwest::Client {
+    reqwest::Client::builder().no_proxy().build().unwrap()
+  }
+
+  /// A release list page in GitHub's shape.
+  fn page_json(releases: &[(&str, bool)]) -> String {
+    let releases: Vec<Value> = releases
+      .iter()
+      .map(|(tag, prerelease)| {
+        serde_json::json!({ "tag_name": tag, "prerelease": prerelease, "body": tag })
+      })
+      .collect();
+    Value::from(releases).to_string()
+  }
+
+  /// Page `n` of a list that is `pages[n - 1]`, empty beyond.
+  fn list_server(pages: Vec<String>) -> MockServer {
+    MockServer::start_with(move |head| {
+      let path = head.split(' ').nth(1).unwrap_or("/");
+      let n: usize = path.rsplit("page=").next().and_then(|n| n.parse().ok()).unwrap_or(1);
+      let body = pages.get(n - 1).cloned().unwrap_or_else(|| "[]".to_string());
+      (200, String::new(), body)
+    })
+  }
+
+  fn list_of(server: &MockServer) -> String {
+    format!("{}/releases?per_page={}", server.url, PAGE_SIZE)
+  }
+
+  #[test]
+  fn notes_lose_comments_and_mentions_but_keep_emails() {
+    let notes = "<!-- template: fill in -->\n## Fixes\n- by @alice and @bob-2 (#12)\n\
+                 <!-- a\nmultiline comment -->- mail ops@felay.dev or a.b@c.io\n- @ alone";
+    assert_eq!(
+      sanitize_notes(notes),
+      "## Fixes\n- by alice and bob-2 (#12)\n- mail ops@felay.dev or a.b@c.io\n- @ alone"
+    );
+    // An unclosed comment runs to the end
+    assert_eq!(sanitize_notes("kept <!-- dropped"), "kept");
+    assert_eq!(sanitize_notes("  @felay  "), "felay");
+  }
+
+  #[tokio::test]
+  async fn etag_is_sent_back_and_304_reuses_the_cached_page() {
+    let server = MockServer::start_with(|head| {
+      if head.contains("if-none-match: \"v1\"") {
+        return (304, String::new(), String::new());
+      }
+      (200, "etag: \"v1\"\r\n".to_string(), page_json(&[("v1.1.0", false)]))
+    });
+    let url = format!("{}&page=1", list_of(&server));
+    let mut cache = PageCache::default();
+
+    let first = fetch_page(&client(), &url, &mut cache).await.unwrap();
+    assert_eq!(cache.pages[&url].etag, "\"v1\"");
+    let second = fetch_page(&client(), &url, &mut cache).await.unwrap();
+    assert_eq!(second.len(), 1);
+    assert_eq!(second[0].tag, first[0].tag);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(!requests[0].contains("if-none-match"));
+    assert!(requests[1].contains("if-none-match: \"v1\""));
+  }
+
+  #[tokio::test]
+  async fn a_304_without_a_cached_page_is_fetched_again() {
+    let answered = Arc::new(AtomicUsize::new(0));
+    let count = answered.clone();
+    let server = MockServer::start_with(move |_| {
+      if count.fetch_add(1, Ordering::SeqCst) == 0 {
+        return (304, String::new(), String::new());
+      }
+      (200, "etag: \"v2\"\r\n".to_string(), page_json(&[("v1.2.0", false)]))
+    });
+    let url = format!("{}&page=1", list_of(&server));
+    let mut cache = PageCache::default();
+
+    let releases = fetch_page(&client(), &url, &mut cache).await.unwrap();
+    assert_eq!(releases[0].tag, "v1.2.0");
+    assert_eq!(cache.pages[&url].etag, "\"v2\"");
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests.iter().all(|r| !r.contains("if-none-match")));
+  }
+
+  #[tokio::test]
+  async fn paging_stops_at_the_page_reaching_the_current_version() {
+    // A full page of newer pre-releases, then the page holding 1.0.0
+    let betas: Vec<String> = (0..PAGE_SIZE).map(|n| format!("v2.0.0-beta.{}", 30 - n)).collect();
+    let first: Vec<(&str, bool)> = betas.iter().map(|t| (t.as_str(), true)).collect();
+    let second = page_json(&[("v1.1.0", false), ("v1.0.0", false), ("v0.9.0", false)]);
+    let server = list_server(vec![page_json(&first), second, page_json(&[("v0.8.0", false)])]);
+
+    let mut cache = PageCache::default();
+    let stable = walk_pages(&client(), &list_of(&server), "1.0.0", false, &mut cache).await;
+    let tags: Vec<String> = stable.unwrap().into_iter().map(|r| r.tag).collect();
+    assert_eq!(tags, ["v1.1.0"]);
+    assert_eq!(server.requests().len(), 2);
+
+    // On the beta channel the first page alone holds enough
+    let beta = walk_pages(&client(), &list_of(&server), "1.0.0", true, &mut cache).await;
+    assert_eq!(beta.unwrap().len(), PAGE_SIZE as usize);
+    assert_eq!(server.requests().len(), 3);
+  }
+
+  #[tokio::test]
+  async fn paging_stops_at_a_short_page() {
+    let server = list_server(vec![page_json(&[("v3.0.0", false), ("v2.0.0", false)])]);
+    let mut cache = PageCache::default();
+    let newer = walk_pages(&client(), &list_of(&server), "1.0.0", false, &mut cache).await;
+    assert_eq!(newer.unwrap().len(), 2);
+    assert_eq!(server.requests().len(), 1);
+  }
+
+  fn cached_info(latest: &str) -> UpdateInfo {
+    serde_json::from_value(serde_json::json!({
+      "not_modified": false,
+      "etag": "",
+      "has_update": true,
+      "current_version": "1.0.0",
+      "latest_version": latest,
+      "release_url": "https://x/r",
+      "release_notes": "<!-- c -->latest notes",
+      "source": "cache",
+    }))
+    .unwrap()
+  }
+
+  #[test]
+  fn offline_falls_back_to_the_cached_latest_release() {
+    let offline = || UpdateError::other("offline");
+    let releases = offline_fallback(Some(cached_info("v1.2.0")), "1.0.0", offline()).unwrap();
+    assert_eq!(releases.len(), 1);
+    assert_eq!(releases[0].tag, "v1.2.0");
+    assert_eq!(releases[0].published_at, None);
+    assert_eq!(sanitize_notes(&releases[0].body), "latest notes");
+
+    let up_to_date = offline_fallback(Some(cached_info("v1.0.0")), "1.0.0", offline());
+    assert!(up_to_date.unwrap().is_empty());
+    let err = offline_fallback(None, "1.0.0", offline()).unwrap_err();
+    assert_eq!(err.message, "offline");
+  }
+}
@@ -0,0 +1,72 @@
+//! Session start/end notifications derived from the tray status poll.
+//!
+//! Each poll returns the full session list. Comparing it with the previous
+//! poll's active sessions gives the sessions that started and ended in
+//! between, which the UI gets as `session-started` / `session-ended`
+//! events carrying the session. The first poll only sets the baseline:
+//! sessions that were running before this GUI run are not reported.
+
+use crate::{launch_state, DaemonSession};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+pub(crate) const SESSION_STARTED_EVENT: &str = "session-started";
+pub(crate) const SESSION_ENDED_EVENT: &str = "session-ended";
+
+/// Active sessions as of the last poll, by id; None before the first poll.
+static ACTIVE: Mutex<Option<HashMap<String, DaemonSession>>> = Mutex::new(None);
+
+#[derive(Default)]
+pub(crate) struct SessionChanges {
+  pub(crate) started: Vec<DaemonSession>,
+  pub(crate) ended: Vec<DaemonSession>,
+}
+
+/// Record the latest session list and return what changed since the last
+/// call. A session ends when it is marked ended or disappears from the list
+/// (e.g. after a daemon restart); it is reported with its last known state.
+pub(crate) fn track(sessions: &[DaemonSession]) -> SessionChanges {
+  if !sessions.is_empty() {
+    launch_state::record_session_seen();
+  }
+  let current: HashMap<String, DaemonSession> = sessions
+    .iter()
+    .filter(|s| s.status != "ended")
+    .map(|s| (s.session_id.clone(), s.clone()))
+    .collect();
+
+  let mut active = ACTIVE.lock().unwrap_or_else(|e| e.into_inner());
+  let Some(previous) = active.as_ref() else {
+    *active = Some(current);
+    return SessionChanges::default();
+  };
+
+  let started = current
+    .values()
+    .filter(|s| !previous.contains_key(&s.session_id))
+    .cloned()
+    .collect();
+  let ended = previous
+    .values()
+    .filter(|s| !current.contains_key(&s.session_id))
+    .map(|s| {
+      // Prefer the final state if the daemon still lists it
+      let listed = sessions.iter().find(|n| n.session_id == s.session_id);
+      listed.unwrap_or(s).clone()
+    })
+    .collect();
+  *active = Some(current);
+  SessionChanges { started, ended }
+}
+
+pub(crate) fn emit(app: &AppHandle, changes: &SessionChanges) {
+  let events = [(SESSION_STARTED_EVENT, &changes.started), (SESSION_ENDED_EVENT, &changes.ended)];
+  for (event, sessions) in events {
+    for session in sessions {
+      if let Err(e) = app.emit(event, session) {
+        gui_println!("[gui] failed to emit {}: {}", event, e);
+      }
+    }
+  }
+}
@@ -0,0 +1,207 @@
+//! Picking the release asset that installs on this machine.
+//!
+//! Assets are classified by file name, the way tauri-action names them
+//! (`Felay_0.2.0_x64-setup.exe`, `Felay_0.2.0_aarch64.dmg`,
+//! `felay_0.2.0_amd64.AppImage`, ...). The recommendation needs both the OS
+//! and the architecture to match; an asset whose name has no architecture
+//! is listed but never recommended, except a universal macOS build.
+//! Signatures, checksums and updater manifests are listed without a
+//! package type.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct AssetInfo {
+  name: String,
+  url: String,
+  size: u64,
+  content_type: String,
+  /// "windows" / "macos" / "linux", as `std::env::consts::OS`.
+  os: Option<String>,
+  /// "x86_64" / "aarch64" / "x86" as `std::env::consts::ARCH`, or
+  /// "universal".
+  arch: Option<String>,
+  /// "nsis", "msi", "dmg", "app", "appimage", "deb" or "rpm".
+  package: Option<String>,
+}
+
+/// Installer packages per OS, most preferred first.
+const PREFERRED: &[(&str, &[&str])] = &[
+  ("windows", &["nsis", "msi"]),
+  ("macos", &["dmg", "app"]),
+  ("linux", &["appimage", "deb", "rpm"]),
+];
+
+/// `(os, package)` from the file name's extension.
+fn package_of(name: &str) -> Option<(&'static str, &'static str)> {
+  let name = name.to_lowercase();
+  let kinds = [
+    ("-setup.exe", "windows", "nsis"),
+    (".msi", "windows", "msi"),
+    (".dmg", "macos", "dmg"),
+    (".app.tar.gz", "macos", "app"),
+    (".appimage", "linux", "appimage"),
+    (".deb", "linux", "deb"),
+    (".rpm", "linux", "rpm"),
+  ];
+  kinds
+    .iter()
+    .find(|(suffix, _, _)| name.ends_with(suffix))
+    .map(|(_, os, package)| (*os, *package))
+}
+
+/// The architecture named in the file name, as `std::env::consts::ARCH`.
+fn arch_of(name: &str) -> Option<&'static str> {
+  // `x86_64` is the one name with a separator in it
+  let lower = name.to_lowercase().replace("x86_64", "x64");
+  let tokens: Vec<&str> = lower.split(|c: char| !c.is_ascii_alphanumeric()).collect();
+  let has = |names: &[&str]| tokens.iter().any(|t| names.contains(t));
+  if has(&["aarch64", "arm64"]) {
+    Some("aarch64")
+  } else if has(&["x64", "amd64"]) {
+    Some("x86_64")
+  } else if has(&["x86", "i386", "i686"]) {
+    Some("x86")
+  } else if has(&["universal"]) {
+    Some("universal")
+  } else {
+    None
+  }
+}
+
+/// Classify the `assets` of a GitHub release response.
+pub(crate) fn classify(release: &Value) -> Vec<AssetInfo> {
+  let assets = release["assets"].as_array().into_iter().flatten();
+  assets
+    .filter_map(|asset| {
+      let name = asset["name"].as_str()?.to_string();
+      let package = package_of(&name);
+      Some(AssetInfo {
+        url: asset["browser_download_url"].as_str()?.to_string(),
+        size: asset["size"].as_u64().unwrap_or(0),
+        content_type: asset["content_type"].as_str().unwrap_or_default().to_string(),
+        os: package.map(|(os, _)| os.to_string()),
+        arch: package.and(arch_of(&name)).map(String::from),
+        package: package.map(|(_, package)| package.to_string()),
+        name,
+      })
+    })
+    .collect()
+}
+
+/// The best asset for `os` / `arch`, or None if none clearly matches.
+fn recommend(assets: &[AssetInfo], os: &str, arch: &str) -> Option<AssetInfo> {
+  let (_, packages) = PREFERRED.iter().find(|(preferred_os, _)| *preferred_os == os)?;
+  let arch_matches = |asset: &AssetInfo| match asset.arch.as_deref() {
+    Some("universal") => os == "macos",
+    Some(asset_arch) => asset_arch == arch,
+    None => false,
+  };
+  packages.iter().find_map(|package| {
+    let candidates = assets
+      .iter()
+      .filter(|a| a.os.as_deref() == Some(os) && a.package.as_deref() == Some(package));
+    // An exact architecture beats a universal build
+    let mut matching: Vec<&AssetInfo> = candidates.filter(|a| arch_matches(a)).collect();
+    matching.sort_by_key(|a| a.arch.as_deref() == Some("universal"));
+    matching.first().map(|a| (*a).clone())
+  })
+}
+
+/// `recommend` for the running OS and architecture.
+pub(crate) fn recommend_for_host(assets: &[AssetInfo]) -> Option<AssetInfo> {
+  recommend(assets, std::env::consts::OS, std::env::consts::ARCH)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// A release response in GitHub's shape, with the asset names tauri-action
+  /// gives a Windows / macOS / Linux build plus the updater's extras.
+  const RELEASE: &str =
+    include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/release-v0.2.0.json"));
+
+  fn assets() -> Vec<AssetInfo> {
+    classify(&serde_json::from_str(RELEASE).unwrap())
+  }
+
+  fn find<'a>(assets: &'a [AssetInfo], name: &str) -> &'a AssetInfo {
+    assets.iter().find(|a| a.name == name).unwrap()
+  }
+
+  #[test]
+  fn every_asset_is_listed() {
+    let assets = assets();
+    assert_eq!(assets.len(), 17);
+    let setup = find(&assets, "Felay_0.2.0_x64-setup.exe");
+    assert_eq!(setup.size, 5242880);
+    assert_eq!(setup.content_type, "application/x-msdownload");
+    assert!(setup.url.ends_with("/v0.2.0/Felay_0.2.0_x64-setup.exe"));
+  }
+
+  #[test]
+  fn classification() {
+    let assets = assets();
+    let table = [
+      ("Felay_0.2.0_x64-setup.exe", Some("windows"), Some("x86_64"), Some("nsis")),
+      ("Felay_0.2.0_arm64-setup.exe", Some("windows"), Some("aarch64"), Some("nsis")),
+      ("Felay_0.2.0_x64_en-US.msi", Some("windows"), Some("x86_64"), Some("msi")),
+      ("Felay_0.2.0_aarch64.dmg", Some("macos"), Some("aarch64"), Some("dmg")),
+      ("Felay_0.2.0_x64.dmg", Some("macos"), Some("x86_64"), Some("dmg")),
+      ("Felay_0.2.0_universal.dmg", Some("macos"), Some("universal"), Some("dmg")),
+      ("Felay_aarch64.app.tar.gz", Some("macos"), Some("aarch64"), Some("app")),
+      ("Felay_universal.app.tar.gz", Some("macos"), Some("universal"), Some("app")),
+      ("felay_0.2.0_amd64.AppImage", Some("linux"), Some("x86_64"), Some("appimage")),
+      ("felay_0.2.0_amd64.deb", Some("linux"), Some("x86_64"), Some("deb")),
+      ("felay-0.2.0-1.x86_64.rpm", Some("linux"), Some("x86_64"), Some("rpm")),
+      // Signatures, manifests and checksums have no package type
+      ("Felay_0.2.0_x64-setup.exe.sig", None, None, None),
+      ("felay_0.2.0_amd64.AppImage.sig", None, None, None),
+      ("latest.json", None, None, None),
+      ("SHA256SUMS.txt", None, None, None),
+    ];
+    for (name, os, arch, package) in table {
+      let asset = find(&assets, name);
+      assert_eq!(asset.os.as_deref(), os, "{}", name);
+      assert_eq!(asset.arch.as_deref(), arch, "{}", name);
+      assert_eq!(asset.package.as_deref(), package, "{}", name);
+    }
+  }
+
+  #[test]
+  fn recommendation_per_os_and_arch() {
+    let assets = assets();
+    let table = [
+      ("windows", "x86_64", Some("Felay_0.2.0_x64-setup.exe")),
+      ("windows", "aarch64", Some("Felay_0.2.0_arm64-setup.exe")),
+      ("windows", "x86", None),
+      ("macos", "aarch64", Some("Felay_0.2.0_aarch64.dmg")),
+      ("macos", "x86_64", Some("Felay_0.2.0_x64.dmg")),
+      ("linux", "x86_64", Some("felay_0.2.0_amd64.AppImage")),
+      ("linux", "aarch64", None),
+      ("freebsd", "x86_64", None),
+    ];
+    for (os, arch, expected) in table {
+      let recommended = recommend(&assets, os, arch);
+      assert_eq!(recommended.map(|a| a.name).as_deref(), expected, "{}/{}", os, arch);
+    }
+  }
+
+  #[test]
+  fn universal_build_when_no_exact_match() {
+    let assets: Vec<AssetInfo> = assets()
+      .into_iter()
+      .filter(|a| !matches!(a.name.as_str(), "Felay_0.2.0_aarch64.dmg" | "Felay_0.2.0_x64.dmg"))
+      .collect();
+    let recommended = recommend(&assets, "macos", "x86_64").map(|a| a.name);
+    assert_eq!(recommended.as_deref(), Some("Felay_0.2.0_universal.dmg"));
+  }
+
+  #[test]
+  fn no_assets() {
+    assert!(classify(&serde_json::json!({ "tag_name": "v0.2.0" })).is_empty());
+    assert!(recommend(&[], "windows", "x86_64").is_none());
+  }
+}
@@ -0,0 +1,278 @@
+//! Downloading a release asset for the user to install.
+//!
+//! `check_update` only links the release page. `download_update` streams
+//! the chosen asset to `~/.felay/updates/<name>.partial`, emitting
+//! `update-download-progress` as it goes, and renames it once complete. A
+//! partial left by a failed or cancelled download is resumed with a Range
+//! request; partials untouched for `STALE_PARTIAL_AGE` are removed. The
+//! SHA-256 is checked against the expected value passed in, else a
+//! `<asset>.sha256` sibling, else a line naming the asset in the release
+//! notes; with none of those the result says `verified: false`. The
+//! download is an operation (see `operations`): its id comes with every
+//! progress event, the first sent before any data, and
+//! `cancel_operation` stops it, keeping the partial to resume.
+
+use crate::operations::{self, OperationHandle};
+use crate::gui_settings;
+use crate::http::{self, http_client};
+use crate::{get_home_dir, last_release_notes, update_timeout};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter};
+
+pub(crate) const UPDATE_DOWNLOAD_PROGRESS_EVENT: &str = "update-download-progress";
+
+/// Per request; the client's own timeout is sized for API calls.
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+const STALE_PARTIAL_AGE: Duration = Duration::from_secs(7 * 24 * 3600);
+/// Progress is emitted at most this often while the total is unknown.
+const PROGRESS_STEP_BYTES: u64 = 1024 * 1024;
+const CANCEL_POLL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Serialize, Clone)]
+struct DownloadProgress {
+  operation_id: String,
+  bytes: u64,
+  /// None when the server sent no length.
+  total: Option<u64>,
+  percent: Option<u8>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct DownloadResult {
+  operation_id: String,
+  path: String,
+  sha256: String,
+  /// A checksum was found and matched.
+  verified: bool,
+  /// Continued from an earlier partial download.
+  resumed: bool,
+}
+
+fn updates_dir() -> Option<PathBuf> {
+  Some(PathBuf::from(get_home_dir()?).join(".felay").join("updates"))
+}
+
+/// The asset's file name from its URL, reduced to safe characters.
+fn file_name(url: &reqwest::Url) -> Option<String> {
+  let last = url.path_segments()?.next_back()?;
+  let name: String = last
+    .chars()
+    .map(|c| if c.is_ascii_alphanumeric() || "._-".contains(c) { c } else { '_' })
+    .collect();
+  let name = name.trim_start_matches('.').to_string();
+  (!name.is_empty()).then_some(name)
+}
+
+fn remove_stale_partials(dir: &Path) {
+  let Ok(entries) = fs::read_dir(dir) else {
+    return;
+  };
+  for entry in entries.flatten() {
+    let path = entry.path();
+    if path.extension().is_none_or(|ext| ext != "partial") {
+      continue;
+    }
+    let age = entry
+      .metadata()
+      .and_then(|m| m.modified())
+      .ok()
+      .and_then(|modified| SystemTime::now().duration_since(modified).ok());
+    if age.is_some_and(|age| age > STALE_PARTIAL_AGE) {
+      let _ = fs::remove_file(path);
+    }
+  }
+}
+
+/// The first 64-hex-digit word in `text`, lowercased.
+fn sha256_in(text: &str) -> Option<String> {
+  text
+    .split(|c: char| !c.is_ascii_hexdigit())
+    .find(|word| word.len() == 64)
+    .map(str::to_lowercase)
+}
+
+/// The checksum published for the asset: a `<asset>.sha256` sibling, or a
+/// line of the release notes naming the asset.
+async fn published_sha256(
+  client: &reqwest::Client,
+  url: &reqwest::Url,
+  name: &str,
+) -> Option<String> {
+  let sibling = format!("{}.sha256", url);
+  if let Ok(resp) = client.get(sibling).send().await {
+    if resp.status().is_success() {
+      if let Some(sum) = resp.text().await.ok().as_deref().and_then(sha256_in) {
+        return Some(sum);
+      }
+    }
+  }
+  let notes = last_release_notes()?;
+  notes.lines().filter(|line| line.contains(name)).find_map(sha256_in)
+}
+
+fn file_sha256(path: &Path) -> io::Result<String> {
+  let mut hasher = Sha256::new();
+  io::copy(&mut File::open(path)?, &mut hasher)?;
+  Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+fn percent(bytes: u64, total: Option<u64>) -> Option<u8> {
+  total.filter(|t| *t > 0).map(|t| (bytes * 100 / t).min(100) as u8)
+}
+
+fn emit_progress(app: &AppHandle, op: &OperationHandle, bytes: u64, total: Option<u64>) {
+  let percent = percent(bytes, total);
+  let progress = DownloadProgress { operation_id: op.id().to_string(), bytes, total, percent };
+  if let Err(e) = app.emit(UPDATE_DOWNLOAD_PROGRESS_EVENT, progress) {
+    gui_println!("[gui] failed to emit {}: {}", UPDATE_DOWNLOAD_PROGRESS_EVENT, e);
+  }
+}
+
+/// Resolves once `op` is cancelled.
+async fn cancelled(op: &OperationHandle) {
+  while !op.is_cancelled() {
+    tokio::time::sleep(CANCEL_POLL).await;
+  }
+}
+
+/// `step`'s result, or an error as soon as `op` is cancelled, so a stalled
+/// connection never holds up a cancel.
+async fn unless_cancelled<T>(
+  op: &OperationHandle,
+  step: impl std::future::Future<Output = Result<T, String>>,
+) -> Result<T, String> {
+  tokio::select! {
+    result = step => result,
+    _ = cancelled(op) => Err("cancelled".to_string()),
+  }
+}
+
+/// Stream the asset into `partial`, resuming what is already there.
+/// Returns whether an earlier partial was continued.
+async fn fetch(
+  app: &AppHandle,
+  client: &reqwest::Client,
+  url: &reqwest::Url,
+  partial: &Path,
+  op: &OperationHandle,
+) -> Result<bool, String> {
+  let send = |from: u64| {
+    let mut req = client.get(url.clone()).timeout(DOWNLOAD_TIMEOUT);
+    if from > 0 {
+      req = req.header(reqwest::header::RANGE, format!("bytes={}-", from));
+    }
+    req.send()
+  };
+  let mut existing = fs::metadata(partial).map(|m| m.len()).unwrap_or(0);
+  let failed = |e: reqwest::Error| match http::hint(http::error_kind(&e)) {
+    Some(hint) => format!("下载失败: {} ({})", hint, e),
+    None => format!("下载失败: {}", e),
+  };
+  let mut resp = unless_cancelled(op, async { send(existing).await.map_err(failed) }).await?;
+  if existing > 0 && resp.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+    // The partial doesn't fit the asset (any more): start over
+    existing = 0;
+    resp = unless_cancelled(op, async { send(0).await.map_err(failed) }).await?;
+  }
+  if !resp.status().is_success() {
+    return Err(format!("下载失败: HTTP {}", resp.status()));
+  }
+
+  // 206 continues the partial; a plain 200 starts over
+  let resumed = existing > 0 && resp.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+  let mut file = if resumed {
+    OpenOptions::new().append(true).open(partial)
+  } else {
+    File::create(partial)
+  }
+  .map_err(|e| format!("无法写入 {}: {}", partial.display(), e))?;
+
+  let mut bytes = if resumed { existing } else { 0 };
+  let total = resp.content_length().map(|len| len + bytes);
+  let mut last_emitted: Option<(u64, Option<u8>)> = None;
+  loop {
+    let chunk = unless_cancelled(op, async {
+      resp.chunk().await.map_err(|e| format!("下载中断: {}", e))
+    })
+    .await?;
+    let Some(chunk) = chunk else {
+      break;
+    };
+    file.write_all(&chunk).map_err(|e| format!("无法写入 {}: {}", partial.display(), e))?;
+    bytes += chunk.len() as u64;
+
+    let percent = percent(bytes, total);
+    let due = match last_emitted {
+      None => true,
+      Some((_, last)) if percent.is_some() => percent != last,
+      Some((at, _)) => bytes - at >= PROGRESS_STEP_BYTES,
+    };
+    if due {
+      emit_progress(app, op, bytes, total);
+      last_emitted = Some((bytes, percent));
+    }
+  }
+  file.flush().map_err(|e| e.to_string())?;
+  emit_progress(app, op, bytes, total.map(|_| bytes));
+  Ok(resumed)
+}
+
+/// Download a release asset to `~/.felay/updates/` and return its path.
+#[tauri::command]
+pub(crate) async fn download_update(
+  app: AppHandle,
+  asset_url: String,
+  expected_sha256: Option<String>,
+) -> Result<DownloadResult, String> {
+  if gui_settings::with(|s| s.disable_update_check) {
+    return Err("更新已在 gui-settings.json 中关闭 (disable_update_check)".to_string());
+  }
+  let url = reqwest::Url::parse(&asset_url).map_err(|e| format!("invalid URL: {}", e))?;
+  if url.scheme() != "https" {
+    return Err("asset URL must start with https://".to_string());
+  }
+  let name = file_name(&url).ok_or("asset URL has no file name")?;
+  let expected = match expected_sha256 {
+    Some(sum) => Some(sha256_in(&sum).ok_or("expected_sha256 is not a SHA-256 hex digest")?),
+    None => None,
+  };
+  let dir = updates_dir().ok_or("cannot determine home directory")?;
+  fs::create_dir_all(&dir).map_err(|e| format!("无法创建 {}: {}", dir.display(), e))?;
+  remove_stale_partials(&dir);
+
+  let op = operations::begin_exclusive(&app, "download_update").ok_or("已有更新正在下载")?;
+  let partial = dir.join(format!("{}.partial", name));
+  // Before any data, so the UI can offer cancel right away
+  emit_progress(&app, &op, fs::metadata(&partial).map(|m| m.len()).unwrap_or(0), None);
+
+  let client = http_client(update_timeout())?;
+  let resumed = fetch(&app, &client, &url, &partial, &op).await?;
+
+  let sha256 = file_sha256(&partial).map_err(|e| e.to_string())?;
+  let expected = match expected {
+    Some(sum) => Some(sum),
+    None => published_sha256(&client, &url, &name).await,
+  };
+  if let Some(expected) = &expected {
+    if *expected != sha256 {
+      // Resuming a corrupt file would only fail again
+      let _ = fs::remove_file(&partial);
+      return Err(format!("校验失败: SHA-256 应为 {}，实际为 {}", expected, sha256));
+    }
+  }
+
+  let path = dir.join(&name);
+  fs::rename(&partial, &path).map_err(|e| format!("无法保存 {}: {}", path.display(), e))?;
+  Ok(DownloadResult {
+    operation_id: op.id().to_string(),
+    path: path.to_string_lossy().to_string(),
+    sha256,
+    verified: expected.is_some(),
+    resumed,
+  })
+}
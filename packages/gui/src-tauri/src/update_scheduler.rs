@@ -0,0 +1,140 @@
+//! Update checks in the background, so updates are found without the
+//! settings page open.
+//!
+//! `start` runs `check_update` `STARTUP_DELAY` after launch and then every
+//! `update_check_interval_hours`. A failed check (or one answered from the
+//! stale cache) is retried after `RETRY_BASE`, doubling per failure up to
+//! the interval. The first time a run sees a given version as available and
+//! not skipped or snoozed, it emits `update-available`, shows a native
+//! notification, and puts a "有新版本" item at the top of the tray menu that
+//! opens the release page. With `disable_update_check` set nothing is
+//! fetched; the setting is re-read every round.
+
+use crate::{
+  check_update, gui_settings, os_open, sleep_unless_shutdown, tag_version, UpdateInfo,
+};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::menu::{Menu, MenuItem};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_notification::NotificationExt;
+
+pub(crate) const UPDATE_AVAILABLE_EVENT: &str = "update-available";
+pub(crate) const UPDATE_MENU_ID: &str = "update";
+
+const STARTUP_DELAY: Duration = Duration::from_secs(20);
+const RETRY_BASE: Duration = Duration::from_secs(5 * 60);
+
+/// The last scheduled check's result.
+static LAST_RESULT: Mutex<Option<UpdateInfo>> = Mutex::new(None);
+
+/// The version announced this run and its tray item.
+static ANNOUNCED: Mutex<Option<(String, MenuItem)>> = Mutex::new(None);
+
+/// Release page of the announced version, for the tray item.
+static RELEASE_URL: Mutex<Option<String>> = Mutex::new(None);
+
+fn retry_delay(failures: u32, interval: Duration) -> Duration {
+  RETRY_BASE.saturating_mul(1 << failures.min(16)).min(interval)
+}
+
+fn check_once() -> Result<UpdateInfo, String> {
+  tauri::async_runtime::block_on(check_update(None)).map_err(|e| e.message)
+}
+
+/// Emit, notify and add the tray item for `info`, once per version.
+fn announce(app: &AppHandle, menu: &Menu, info: &UpdateInfo) {
+  let version = tag_version(&info.latest_version).to_string();
+  let mut announced = ANNOUNCED.lock().unwrap_or_else(|e| e.into_inner());
+  if announced.as_ref().is_some_and(|(v, _)| *v == version) {
+    return;
+  }
+  *RELEASE_URL.lock().unwrap_or_else(|e| e.into_inner()) = Some(info.release_url.clone());
+
+  if let Err(e) = app.emit(UPDATE_AVAILABLE_EVENT, info.clone()) {
+    gui_println!("[gui] failed to emit {}: {}", UPDATE_AVAILABLE_EVENT, e);
+  }
+  let body = format!("Felay v{} 已发布，点击托盘菜单查看", version);
+  if let Err(e) = app.notification().builder().title("有新版本").body(body).show() {
+    gui_println!("[gui] update notification failed: {}", e);
+  }
+
+  // An older announcement's item goes; the menu keeps one
+  if let Some((_, item)) = announced.take() {
+    let _ = menu.remove(&item);
+  }
+  let label = format!("有新版本 v{}", version);
+  match MenuItem::with_id(app, UPDATE_MENU_ID, label, true, None::<&str>) {
+    Ok(item) => {
+      if let Err(e) = menu.insert(&item, 0) {
+        gui_println!("[gui] failed to add update menu item: {}", e);
+      }
+      *announced = Some((version, item));
+    }
+    Err(e) => gui_println!("[gui] failed to create update menu item: {}", e),
+  }
+}
+
+/// Drop the tray item, e.g. once the update is skipped or installed.
+fn withdraw(menu: &Menu) {
+  if let Some((_, item)) = ANNOUNCED.lock().unwrap_or_else(|e| e.into_inner()).take() {
+    let _ = menu.remove(&item);
+  }
+}
+
+/// Spawn the scheduler on a background thread.
+pub(crate) fn start(app: AppHandle, menu: Menu) {
+  crate::spawn_background(move || {
+    let mut delay = STARTUP_DELAY;
+    let mut failures = 0u32;
+    loop {
+      if !sleep_unless_shutdown(delay) {
+        break;
+      }
+      let interval = gui_settings::with(|s| s.update_check_interval());
+      delay = interval;
+      if gui_settings::with(|s| s.disable_update_check) {
+        withdraw(&menu);
+        continue;
+      }
+
+      let info = match check_once() {
+        Ok(info) => info,
+        Err(e) => {
+          gui_println!("[gui] scheduled update check failed: {}", e);
+          failures += 1;
+          delay = retry_delay(failures, interval);
+          continue;
+        }
+      };
+      if info.stale {
+        failures += 1;
+        delay = retry_delay(failures, interval);
+      } else {
+        failures = 0;
+      }
+      if info.has_update && !info.suppressed {
+        announce(&app, &menu, &info);
+      } else {
+        withdraw(&menu);
+      }
+      *LAST_RESULT.lock().unwrap_or_else(|e| e.into_inner()) = Some(info);
+    }
+  });
+}
+
+/// Open the announced release's page; the tray item's action.
+pub(crate) fn open_release_page() {
+  let url = RELEASE_URL.lock().unwrap_or_else(|e| e.into_inner()).clone();
+  if let Some(url) = url.filter(|url| url.starts_with("https://")) {
+    if let Err(e) = os_open::open(std::ffi::OsStr::new(&url)) {
+      gui_println!("[gui] failed to open release page: {}", e);
+    }
+  }
+}
+
+/// The last background check's result; null before the first one.
+#[tauri::command]
+pub(crate) fn get_scheduled_update() -> Option<UpdateInfo> {
+  LAST_RESULT.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
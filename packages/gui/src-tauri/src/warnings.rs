@@ -0,0 +1,91 @@
+//! Bot warning dismissal.
+//!
+//! The daemon re-reports the same warnings on every status poll. Dismissals
+//! are stored in gui-settings keyed by a hash of `bot_id + message`, so a
+//! changed message for the same bot resurfaces, and they expire after a
+//! configurable period so recurring problems come back.
+
+use crate::{gui_settings, unix_now, BotWarning};
+use serde_json::Value;
+
+const DISMISSED_KEY: &str = "dismissed_warnings";
+
+/// Stable FNV-1a hash of a warning, hex-encoded. Persisted across runs, so
+/// it must not depend on the std hasher's per-release implementation.
+pub(crate) fn warning_hash(bot_id: &str, message: &str) -> String {
+  let mut hash: u64 = 0xcbf29ce484222325;
+  for byte in bot_id.bytes().chain([0u8]).chain(message.bytes()) {
+    hash ^= byte as u64;
+    hash = hash.wrapping_mul(0x100000001b3);
+  }
+  format!("{hash:016x}")
+}
+
+fn ttl_secs() -> u64 {
+  gui_settings::with(|s| s.warning_dismiss_hours) * 3600
+}
+
+/// Hashes of warnings dismissed within the TTL.
+fn active_dismissals(settings: &Value) -> Vec<String> {
+  let now = unix_now();
+  let ttl = ttl_secs();
+  settings
+    .get(DISMISSED_KEY)
+    .and_then(|v| v.as_object())
+    .map(|map| {
+      map
+        .iter()
+        .filter(|(_, at)| at.as_u64().is_some_and(|at| now.saturating_sub(at) < ttl))
+        .map(|(hash, _)| hash.clone())
+        .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// Fill in `hash` and `dismissed` on each warning.
+pub(crate) fn annotate(warnings: &mut [BotWarning]) {
+  let dismissed = active_dismissals(&gui_settings::load());
+  for w in warnings.iter_mut() {
+    w.hash = warning_hash(&w.bot_id, &w.message);
+    w.dismissed = dismissed.contains(&w.hash);
+  }
+}
+
+#[tauri::command]
+pub(crate) fn dismiss_warning(bot_id: String, message_hash: String) -> Value {
+  let mut settings = gui_settings::load();
+  let now = unix_now();
+  let ttl = ttl_secs();
+
+  // Keep only unexpired entries so the map doesn't grow forever
+  let mut dismissed = settings
+    .get(DISMISSED_KEY)
+    .and_then(|v| v.as_object())
+    .cloned()
+    .unwrap_or_default();
+  dismissed.retain(|_, at| at.as_u64().is_some_and(|at| now.saturating_sub(at) < ttl));
+  dismissed.insert(message_hash, Value::from(now));
+  settings[DISMISSED_KEY] = Value::Object(dismissed);
+
+  match gui_settings::save(&settings) {
+    Ok(_) => serde_json::json!({ "ok": true, "bot_id": bot_id }),
+    Err(e) => serde_json::json!({ "ok": false, "error": e }),
+  }
+}
+
+#[tauri::command]
+pub(crate) fn get_all_warnings(include_dismissed: bool) -> Vec<BotWarning> {
+  let Some(ipc_path) = crate::get_ipc_path() else {
+    return vec![];
+  };
+  let Some(status) = crate::request_daemon_status(&ipc_path) else {
+    return vec![];
+  };
+
+  let mut warnings = status.warnings.unwrap_or_default();
+  annotate(&mut warnings);
+  if !include_dismissed {
+    warnings.retain(|w| !w.dismissed);
+  }
+  warnings
+}
@@ -0,0 +1,312 @@
+//! Webhook URL pre-checks for push bots.
+//!
+//! A typo in a pasted webhook URL only shows up when the first real
+//! notification silently fails, so the settings form can ask for a cheap
+//! reachability probe before saving. `check_webhook` knows the providers
+//! and posts an empty message to tell a wrong token from a wrong URL;
+//! `validate_webhook` only sees whether any URL answers at all.
+
+use crate::http;
+use serde::Serialize;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+const VALIDATE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Known push-bot webhook providers and the hosts they are served from.
+const PROVIDERS: &[(&str, &[&str])] = &[
+  ("feishu", &["open.feishu.cn"]),
+  ("lark", &["open.larksuite.com"]),
+  ("dingtalk", &["oapi.dingtalk.com"]),
+  ("wecom", &["qyapi.weixin.qq.com"]),
+];
+
+/// Query parameters that carry the webhook token for query-style providers.
+const TOKEN_PARAMS: &[&str] = &["access_token", "key", "token"];
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum WebhookStatus {
+  Reachable,
+  InvalidUrl,
+  DnsFailure,
+  Timeout,
+  ConnectFailure,
+  ProviderError,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct WebhookCheck {
+  status: WebhookStatus,
+  provider: Option<String>,
+  masked_url: String,
+  http_status: Option<u16>,
+  detail: Option<String>,
+}
+
+impl WebhookCheck {
+  pub(crate) fn is_reachable(&self) -> bool {
+    self.status == WebhookStatus::Reachable
+  }
+}
+
+/// Mask the token part of a webhook URL so it can be logged or returned.
+/// Path-style tokens (`/bot/v2/hook/<token>`) and query tokens
+/// (`?access_token=<token>`) are both replaced with `***`.
+pub(crate) fn mask_webhook_url(raw: &str) -> String {
+  let Ok(mut url) = reqwest::Url::parse(raw) else {
+    return "***".to_string();
+  };
+
+  let segments: Vec<String> = url
+    .path_segments()
+    .map(|s| s.map(|p| p.to_string()).collect())
+    .unwrap_or_default();
+  if let Some(pos) = segments.iter().position(|s| s == "hook") {
+    let masked: Vec<&str> = segments
+      .iter()
+      .enumerate()
+      .map(|(i, s)| if i > pos { "***" } else { s.as_str() })
+      .collect();
+    url.set_path(&masked.join("/"));
+  }
+
+  let pairs: Vec<(String, String)> = url
+    .query_pairs()
+    .map(|(k, v)| {
+      let v = if TOKEN_PARAMS.contains(&k.as_ref()) {
+        "***".to_string()
+      } else {
+        v.into_owned()
+      };
+      (k.into_owned(), v)
+    })
+    .collect();
+  if !pairs.is_empty() {
+    url.query_pairs_mut().clear().extend_pairs(pairs);
+  }
+
+  url.to_string()
+}
+
+pub(crate) fn detect_provider(host: &str) -> Option<&'static str> {
+  PROVIDERS
+    .iter()
+    .find(|(_, hosts)| hosts.contains(&host))
+    .map(|(name, _)| *name)
+}
+
+fn body_snippet(body: &str) -> String {
+  body.chars().take(200).collect()
+}
+
+/// Provider codes that mean "the endpoint exists but this token is wrong".
+/// DingTalk's 310000 (keyword, signature or IP allowlist mismatch) is what a
+/// valid secured robot answers the empty probe with, so it is not one.
+fn is_token_rejected(provider: &str, body: &Value) -> bool {
+  let code = body
+    .get("code")
+    .or_else(|| body.get("errcode"))
+    .or_else(|| body.get("StatusCode"))
+    .and_then(|c| c.as_i64());
+  match (provider, code) {
+    ("feishu" | "lark", Some(c)) => c == 19001,
+    ("dingtalk", Some(c)) => c == 300001,
+    ("wecom", Some(c)) => c == 93000,
+    _ => false,
+  }
+}
+
+/// Classify a transport-level reqwest error.
+pub(crate) fn classify_reqwest_error(err: &reqwest::Error) -> WebhookStatus {
+  if err.is_timeout() {
+    return WebhookStatus::Timeout;
+  }
+  let mut source: Option<&dyn std::error::Error> = Some(err);
+  while let Some(e) = source {
+    let text = e.to_string().to_lowercase();
+    if text.contains("dns") || text.contains("lookup") || text.contains("resolve") {
+      return WebhookStatus::DnsFailure;
+    }
+    source = e.source();
+  }
+  WebhookStatus::ConnectFailure
+}
+
+/// Validate `url` against the known providers and probe it with an empty
+/// JSON POST, which every supported provider answers with a parameter error
+/// without delivering a message.
+pub(crate) async fn probe_webhook(url: &str) -> WebhookCheck {
+  let masked_url = mask_webhook_url(url);
+  let invalid = |detail: &str| WebhookCheck {
+    status: WebhookStatus::InvalidUrl,
+    provider: None,
+    masked_url: masked_url.clone(),
+    http_status: None,
+    detail: Some(detail.to_string()),
+  };
+
+  let Ok(parsed) = reqwest::Url::parse(url.trim()) else {
+    return invalid("URL 格式无效");
+  };
+  if parsed.scheme() != "https" {
+    return invalid("webhook 必须使用 https://");
+  }
+  let host = parsed.host_str().unwrap_or("").to_lowercase();
+  let Some(provider) = detect_provider(&host) else {
+    return invalid("未知的 webhook 服务商域名");
+  };
+
+  let client = match http::http_client(Duration::from_secs(5)) {
+    Ok(c) => c,
+    Err(e) => {
+      return WebhookCheck {
+        status: WebhookStatus::ConnectFailure,
+        provider: Some(provider.to_string()),
+        masked_url,
+        http_status: None,
+        detail: Some(e),
+      }
+    }
+  };
+
+  gui_println!("[gui] checking webhook {}", masked_url);
+
+  let resp = match client.post(parsed).json(&serde_json::json!({})).send().await {
+    Ok(r) => r,
+    Err(e) => {
+      return WebhookCheck {
+        status: classify_reqwest_error(&e),
+        provider: Some(provider.to_string()),
+        masked_url,
+        http_status: None,
+        detail: http::hint(http::error_kind(&e)).map(String::from),
+      }
+    }
+  };
+
+  let http_status = resp.status().as_u16();
+  let success = resp.status().is_success();
+  let text = resp.text().await.unwrap_or_default();
+  let json = serde_json::from_str::<Value>(&text).unwrap_or(Value::Null);
+
+  let status = if success && !is_token_rejected(provider, &json) {
+    WebhookStatus::Reachable
+  } else {
+    WebhookStatus::ProviderError
+  };
+
+  WebhookCheck {
+    status,
+    provider: Some(provider.to_string()),
+    masked_url,
+    http_status: Some(http_status),
+    detail: if status == WebhookStatus::Reachable {
+      None
+    } else {
+      Some(body_snippet(&text))
+    },
+  }
+}
+
+#[tauri::command]
+pub(crate) async fn check_webhook(url: String) -> WebhookCheck {
+  probe_webhook(&url).await
+}
+
+#[derive(Debug, Serialize, Default)]
+pub(crate) struct WebhookValidation {
+  /// Any HTTP answer counts; webhooks often refuse HEAD and GET.
+  reachable: bool,
+  status_code: Option<u16>,
+  latency_ms: Option<u64>,
+  error: Option<String>,
+  /// Set for plain http, which sends the token unencrypted.
+  warning: Option<String>,
+}
+
+/// `raw` as a webhook URL, or why it can't be one, before any request.
+fn parse_webhook_url(raw: &str) -> Result<reqwest::Url, String> {
+  if raw.trim() != raw || raw.chars().any(char::is_whitespace) {
+    return Err("URL 中不能有空格".to_string());
+  }
+  let url = reqwest::Url::parse(raw).map_err(|e| format!("URL 无效: {}", e))?;
+  if !matches!(url.scheme(), "https" | "http") {
+    return Err("URL 必须以 https:// 开头".to_string());
+  }
+  if url.host_str().is_none_or(|host| !host.contains('.') && host != "localhost") {
+    return Err("URL 缺少有效的主机名".to_string());
+  }
+  Ok(url)
+}
+
+/// Check that a push-bot webhook URL is well-formed and answers, with a
+/// HEAD request (GET if HEAD is refused) through the configured proxy.
+/// Nothing is posted, so no message is sent.
+#[tauri::command]
+pub(crate) async fn validate_webhook(url: String) -> WebhookValidation {
+  let mut result = WebhookValidation::default();
+  let parsed = match parse_webhook_url(&url) {
+    Ok(parsed) => parsed,
+    Err(e) => {
+      result.error = Some(e);
+      return result;
+    }
+  };
+  if parsed.scheme() == "http" {
+    result.warning = Some("建议使用 https，http 会明文发送 webhook 令牌".to_string());
+  }
+  let client = match http::http_client(VALIDATE_TIMEOUT) {
+    Ok(client) => client,
+    Err(e) => {
+      result.error = Some(e);
+      return result;
+    }
+  };
+
+  gui_println!("[gui] validating webhook {}", mask_webhook_url(&url));
+  let started = Instant::now();
+  let mut sent = client.head(parsed.clone()).send().await;
+  let refused = |status: reqwest::StatusCode| {
+    status == reqwest::StatusCode::METHOD_NOT_ALLOWED
+      || status == reqwest::StatusCode::NOT_IMPLEMENTED
+  };
+  if sent.as_ref().is_ok_and(|resp| refused(resp.status())) {
+    sent = client.get(parsed).send().await;
+  }
+  match sent {
+    Ok(resp) => {
+      result.reachable = true;
+      result.status_code = Some(resp.status().as_u16());
+      result.latency_ms = Some(started.elapsed().as_millis() as u64);
+    }
+    Err(e) => {
+      // reqwest puts the URL, token included, into its message
+      let e = e.without_url();
+      result.error = Some(match http::hint(http::error_kind(&e)) {
+        Some(hint) => hint.to_string(),
+        None => e.to_string(),
+      });
+    }
+  }
+  result
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn dingtalk_invalid_token_is_rejected() {
+    let body = json!({ "errcode": 300001, "errmsg": "token is not exist" });
+    assert!(is_token_rejected("dingtalk", &body));
+  }
+
+  #[test]
+  fn dingtalk_security_mismatch_means_reachable() {
+    // A secured robot refuses the empty probe's keyword/signature/IP check
+    let body = json!({ "errcode": 310000, "errmsg": "keywords not in content" });
+    assert!(!is_token_rejected("dingtalk", &body));
+  }
+}
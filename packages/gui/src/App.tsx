@@ -5,7 +5,7 @@ import SessionsView from "./SessionsView";
 import RobotsView from "./RobotsView";
 import SettingsView from "./SettingsView";
 import { LocaleProvider } from "./i18n";
-import type { TabKey, GuiStatus, BotsData } from "./types";
+import type { TabKey, GuiStatus, BotsData, StartDaemonResult } from "./types";
 
 const emptyStatus: GuiStatus = {
   running: false,
@@ -48,7 +48,10 @@ export function App() {
       if (cancelled) return;
       setDaemonStarting(true);
       try {
-        await invoke("start_daemon");
+        const result = await invoke<StartDaemonResult>("start_daemon");
+        if (!result.ok) {
+          console.warn("[gui] auto-start daemon failed:", result.error);
+        }
       } catch (e) {
         console.warn("[gui] auto-start daemon failed:", e);
       }
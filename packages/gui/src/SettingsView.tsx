@@ -52,7 +52,6 @@ export default function SettingsView({ daemonRunning }: { daemonRunning: boolean
   const [updateInfo, setUpdateInfo] = useState<{ currentVersion: string; latestVersion: string; releaseUrl: string; releaseNotes: string } | null>(null);
   const [updateError, setUpdateError] = useState("");
   const [appVersion] = useState(() => __APP_VERSION__);
-  const [cachedEtag, setCachedEtag] = useState("");
 
   // Log export state
   const [exporting, setExporting] = useState(false);
@@ -78,7 +77,6 @@ export default function SettingsView({ daemonRunning }: { daemonRunning: boolean
       const raw = localStorage.getItem("felay-update-cache");
       if (raw) {
         const cached = JSON.parse(raw) as { etag: string; hasUpdate: boolean; latestVersion: string; releaseUrl: string; releaseNotes: string };
-        setCachedEtag(cached.etag);
         setUpdateInfo({ currentVersion: appVersion, latestVersion: cached.latestVersion, releaseUrl: cached.releaseUrl, releaseNotes: cached.releaseNotes });
         setUpdateStatus(cached.hasUpdate ? "has_update" : "up_to_date");
       }
@@ -138,7 +136,7 @@ export default function SettingsView({ daemonRunning }: { daemonRunning: boolean
         not_modified: boolean; etag: string;
         has_update: boolean; current_version: string;
         latest_version: string; release_url: string; release_notes: string;
-      }>("check_update", { cachedEtag: cachedEtag || null });
+      }>("force_check_update");
 
       if (result.not_modified) {
         // 304 — cached data is still valid, just refresh status
@@ -152,8 +150,7 @@ export default function SettingsView({ daemonRunning }: { daemonRunning: boolean
         return;
       }
 
-      // 200 — new data, update cache
-      setCachedEtag(result.etag);
+      // Fresh result — update cache
       const info = { currentVersion: result.current_version, latestVersion: result.latest_version, releaseUrl: result.release_url, releaseNotes: result.release_notes };
       setUpdateInfo(info);
       setUpdateStatus(result.has_update ? "has_update" : "up_to_date");
@@ -167,7 +164,14 @@ export default function SettingsView({ daemonRunning }: { daemonRunning: boolean
         releaseNotes: result.release_notes,
       }));
     } catch (e) {
-      setUpdateError(String(e));
+      // Structured { kind, message, status } from the backend
+      const err = e as { kind?: string; message?: string; status?: number | null };
+      if (err && typeof err === "object" && err.kind && err.kind !== "other") {
+        const text = t(`settings.updateError.${err.kind}`);
+        setUpdateError(err.status ? `${text} (${err.status})` : text);
+      } else {
+        setUpdateError(err?.message ?? String(e));
+      }
       setUpdateStatus("error");
     }
   };
@@ -176,7 +180,7 @@ export default function SettingsView({ daemonRunning }: { daemonRunning: boolean
     setExporting(true);
     setExportMessage(null);
     try {
-      const savedPath = await invoke<string>("collect_logs");
+      const savedPath = await invoke<string>("collect_logs", { reveal: true });
       setExportMessage({ text: `${t("settings.exportDone")} ${savedPath}`, ok: true });
       setTimeout(() => setExportMessage(null), 5000);
     } catch (e) {
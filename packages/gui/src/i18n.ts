@@ -118,6 +118,15 @@ const dict: Record<string, Record<Locale, string>> = {
   "settings.newVersion": { zh: "发现新版本!", en: "New version available!" },
   "settings.download": { zh: "前往下载", en: "Download" },
   "settings.checkFailed": { zh: "检查失败", en: "Check failed" },
+  "settings.updateError.timeout": { zh: "连接超时，请检查网络", en: "Request timed out, check your connection" },
+  "settings.updateError.dns": { zh: "无法解析域名，请检查网络或代理", en: "Could not resolve host, check your network or proxy" },
+  "settings.updateError.connect": { zh: "无法连接到更新服务器", en: "Could not connect to the update server" },
+  "settings.updateError.http": { zh: "更新服务器返回错误", en: "Update server returned an error" },
+  "settings.updateError.proxy_auth": { zh: "代理需要认证，请在代理地址中填写用户名和密码", en: "The proxy requires authentication, add the user and password to the proxy URL" },
+  "settings.updateError.tls": { zh: "证书校验失败，如代理会重新签发证书请设置 extra_ca_cert_path", en: "Certificate verification failed, set extra_ca_cert_path if your proxy re-signs HTTPS" },
+  "settings.updateError.rate_limited": { zh: "GitHub 请求次数已达上限，请稍后再试", en: "GitHub rate limit reached, try again later" },
+  "settings.updateError.disabled": { zh: "更新检查已关闭", en: "Update checks are turned off" },
+  "settings.updateError.bad_token": { zh: "GitHub Token 无效或已过期，请检查 github_token 设置", en: "The GitHub token is invalid or expired, check the github_token setting" },
   "settings.exportLogs": { zh: "导出诊断日志", en: "Export Diagnostic Logs" },
   "settings.exportLogsHint": { zh: "遇到问题？导出日志帮助排查", en: "Having issues? Export logs for troubleshooting" },
   "settings.exporting": { zh: "导出中...", en: "Exporting..." },
@@ -11,11 +11,16 @@ export interface SessionItem {
   interactive_bot_connected?: boolean;
   push_bot_id?: string;
   push_enabled?: boolean;
+  /** Project `.claude/settings.json` in `cwd` lacks the Felay hook. */
+  claude_project_config_issue: boolean;
 }
 
 export interface BotWarning {
   botId: string;
   message: string;
+  /** Stable hash of botId + message, passed back to `dismiss_warning`. */
+  hash: string;
+  dismissed: boolean;
 }
 
 export interface GuiStatus {
@@ -26,6 +31,43 @@ export interface GuiStatus {
   warnings: BotWarning[];
 }
 
+/** Dashboard counters from `get_stats`; all counts are of active sessions. */
+export interface Stats {
+  running: boolean;
+  active_sessions: number;
+  interactive_connected: number;
+  push_enabled: number;
+  warnings: number;
+  by_cli: Record<string, number>;
+}
+
+export interface StartDaemonResult {
+  ok: boolean;
+  already_running: boolean;
+  error: string | null;
+  spawned_pid: number | null;
+}
+
+export type AutoStartOutcome =
+  | "already_running"
+  | "started"
+  | "timed_out"
+  | "failed"
+  | "in_progress"
+  | "cancelled";
+
+export interface AutoStartResult {
+  outcome: AutoStartOutcome;
+  error: string | null;
+  elapsed_ms: number;
+}
+
+export interface LaunchContext {
+  first_launch: boolean;
+  upgraded_from: string | null;
+  current_version: string;
+}
+
 export interface InteractiveBot {
   id: string;
   name: string;
@@ -39,6 +81,9 @@ export interface PushBot {
   name: string;
   webhook: string;
   secret?: string;
+  quietHours?: { start: string; end: string; timezone?: string };
+  /** Display form of `quietHours`, added by the GUI backend. */
+  quiet_hours_label?: string;
 }
 
 export interface BotsData {
@@ -46,9 +91,122 @@ export interface BotsData {
   push: PushBot[];
 }
 
+/** convert_bot: the new bot is saved under `bot_id`; `unmapped_fields`
+ *  lists the fields that were saved empty and still need filling in. */
+export interface BotConversion {
+  ok: boolean;
+  bot_id?: string;
+  unmapped_fields?: string[];
+  error?: string;
+}
+
+/** One field of a bot type, from get_bot_schema. */
+export interface BotSchemaField {
+  name: string;
+  type: "string" | "number" | "boolean" | "object";
+  required: boolean;
+  /** Render masked. */
+  sensitive: boolean;
+}
+
+export interface BotSchema {
+  ok: boolean;
+  botType?: "interactive" | "push";
+  fields?: BotSchemaField[];
+  /** "local" when the daemon couldn't be asked. */
+  source?: "daemon" | "local";
+  error?: string;
+}
+
 export interface AppConfig {
+  schemaVersion?: number;
+  /** Set by get_config when an older layout was upgraded in memory. */
+  migrated_from?: number;
+  /** Pass back to get_config as known_hash to skip an unchanged config. */
+  config_hash?: string;
   bots: { interactive: InteractiveBot[]; push: PushBot[] };
   reconnect: { maxRetries: number; initialInterval: number; backoffMultiplier: number };
   push: { mergeWindow: number; maxMessageBytes: number };
   input?: { enterRetryCount: number; enterRetryInterval: number };
 }
+
+/** get_effective_config; secrets are masked. `effective` is false when an
+ *  older daemon could only give its raw config. */
+export interface EffectiveConfig {
+  ok: true;
+  effective: boolean;
+  config: AppConfig;
+  config_path: string | null;
+}
+
+export type OnboardingStep =
+  | "install_daemon"
+  | "start_daemon"
+  | "add_bot"
+  | "setup_hooks"
+  | "start_session"
+  | "done";
+
+/** get_onboarding_state */
+export interface OnboardingState {
+  first_run: boolean;
+  felay_dir_exists: boolean;
+  felay_dir_error: string | null;
+  daemon_found: boolean;
+  daemon_running: boolean;
+  bots_configured: boolean;
+  hooks: { name: "codex" | "claude"; installed: boolean; configured: boolean }[];
+  session_seen: boolean;
+  next_step: OnboardingStep;
+}
+
+/** detect_installed_clis; `path` is null when only the config dir was found. */
+export interface InstalledCli {
+  name: "codex" | "claude" | "gemini";
+  installed: boolean;
+  path: string | null;
+  config_exists: boolean;
+}
+
+/** get_health; bot counts are null while the daemon is down. */
+export interface Health {
+  daemon: boolean;
+  bots_ok: number | null;
+  bots_failing: number | null;
+  network_ok: boolean;
+}
+
+/** download_update */
+export interface DownloadResult {
+  operation_id: string;
+  path: string;
+  sha256: string;
+  verified: boolean;
+  resumed: boolean;
+}
+
+/** Payload of the `update-download-progress` event. */
+export interface DownloadProgress {
+  operation_id: string;
+  bytes: number;
+  total: number | null;
+  percent: number | null;
+}
+
+/** Payload of the `operation-cancelled` event; see cancel_operation. */
+export interface OperationCancelled {
+  operation_id: string;
+  kind: "download_update" | "tail_daemon_log";
+}
+
+/** initialize_felay_home */
+export interface HomeInit {
+  created: string[];
+  settings_created: boolean;
+}
+
+/** list_profiles; `active` is null for the default profile (~/.felay). */
+export interface ProfileList {
+  active: string | null;
+  profiles: { name: string; has_config: boolean }[];
+}
@@ -10,11 +10,22 @@ export interface InteractiveBotConfig {
   encryptKey?: string;
 }
 
+/** Daily window during which pushes are held back and delivered afterwards. */
+export interface QuietHours {
+  /** Local start time, "HH:MM". */
+  start: string;
+  /** Local end time, "HH:MM"; earlier than `start` means the window wraps past midnight. */
+  end: string;
+  /** IANA time zone; defaults to the daemon's local zone. */
+  timezone?: string;
+}
+
 export interface PushBotConfig {
   id: string;
   name: string;
   webhook: string;
   secret?: string;
+  quietHours?: QuietHours;
 }
 
 export interface ReconnectSettings {
@@ -40,7 +51,11 @@ export interface DefaultBotSettings {
   defaultPushBotId?: string;
 }
 
+/** Current `config.json` layout; configs without `schemaVersion` are version 0. */
+export const CONFIG_SCHEMA_VERSION = 2;
+
 export interface AppConfig {
+  schemaVersion?: number;
   bots: {
     interactive: InteractiveBotConfig[];
     push: PushBotConfig[];
@@ -52,6 +67,7 @@ export interface AppConfig {
 }
 
 export const defaultAppConfig: AppConfig = {
+  schemaVersion: CONFIG_SCHEMA_VERSION,
   bots: { interactive: [], push: [] },
   reconnect: { maxRetries: 3, initialInterval: 5, backoffMultiplier: 2 },
   push: { mergeWindow: 2000, maxMessageBytes: 30000 },
@@ -199,6 +215,21 @@ export interface UnbindBotRequest {
   };
 }
 
+export interface UnbindAllBotsRequest {
+  type: "unbind_all_bots_request";
+  payload: { sessionId: string };
+}
+
+export interface UnbindAllBotsResponse {
+  type: "unbind_all_bots_response";
+  payload: {
+    ok: boolean;
+    error?: string;
+    /** Which bot types were bound (and are now detached). */
+    unbound?: { interactive: boolean; push: boolean };
+  };
+}
+
 export interface BindBotResponse {
   type: "bind_bot_response";
   payload: { ok: boolean; error?: string };
@@ -214,15 +245,119 @@ export interface TestBotRequest {
   };
 }
 
+export interface TestBotResult {
+  ok: boolean;
+  error?: string;
+  botName?: string;
+  /** HTTP status returned by the bot platform, when the test made an HTTP call. */
+  statusCode?: number;
+  /** Round-trip time of the test call in milliseconds. */
+  latencyMs?: number;
+  /** Raw message returned by the bot platform (e.g. Feishu `msg`). */
+  detail?: string;
+}
+
 export interface TestBotResponse {
   type: "test_bot_response";
+  payload: TestBotResult;
+}
+
+/** Test a draft bot config inline, before it has been saved. */
+export interface TestBotConfigRequest {
+  type: "test_bot_config_request";
   payload: {
-    ok: boolean;
-    error?: string;
-    botName?: string;
+    botType: BotType;
+    interactive?: InteractiveBotConfig;
+    push?: PushBotConfig;
+  };
+}
+
+export interface TestBotConfigResponse {
+  type: "test_bot_config_response";
+  payload: TestBotResult;
+}
+
+/* ── Bot secret read-back ── */
+
+/** Secret fields that may be read back individually. */
+export type BotSecretField = "appSecret" | "encryptKey" | "webhook" | "secret";
+
+export interface GetBotSecretRequest {
+  type: "get_bot_secret_request";
+  payload: {
+    botType: BotType;
+    botId: string;
+    field: BotSecretField;
+  };
+}
+
+export interface GetBotSecretResponse {
+  type: "get_bot_secret_response";
+  payload: { ok: boolean; value?: string; error?: string };
+}
+
+/* ── Bot field schema ── */
+
+export interface BotSchemaField {
+  name: string;
+  type: "string" | "number" | "boolean" | "object";
+  required: boolean;
+  /** Holds a secret; forms mask it and logs never show it. */
+  sensitive: boolean;
+}
+
+export interface BotSchemaRequest {
+  type: "bot_schema_request";
+  payload: { botType: BotType };
+}
+
+export interface BotSchemaResponse {
+  type: "bot_schema_response";
+  payload: { ok: boolean; botType?: BotType; fields?: BotSchemaField[]; error?: string };
+}
+
+/* ── Quiet hours ── */
+
+export interface SetQuietHoursRequest {
+  type: "set_quiet_hours_request";
+  payload: {
+    botType: BotType;
+    botId: string;
+    /** null clears the window. */
+    quietHours: QuietHours | null;
+  };
+}
+
+export interface SetQuietHoursResponse {
+  type: "set_quiet_hours_response";
+  payload: { ok: boolean; error?: string };
+}
+
+export interface GetQuietHoursRequest {
+  type: "get_quiet_hours_request";
+  payload: { botType: BotType; botId: string };
+}
+
+export interface GetQuietHoursResponse {
+  type: "get_quiet_hours_response";
+  payload: { ok: boolean; quietHours?: QuietHours | null; error?: string };
+}
+
+/** A window for one session's pushes, used in place of its push bot's. */
+export interface SetSessionQuietHoursRequest {
+  type: "set_session_quiet_hours_request";
+  payload: {
+    sessionId: string;
+    /** null goes back to the push bot's window. */
+    quietHours: QuietHours | null;
   };
 }
 
+export interface SetSessionQuietHoursResponse {
+  type: "set_session_quiet_hours_response";
+  payload: { ok: boolean; error?: string };
+}
+
 /* ── Config messages ── */
 
 export interface GetConfigRequest {
@@ -244,6 +379,26 @@ export interface SaveConfigResponse {
   payload: { ok: boolean; error?: string };
 }
 
+/** The config the daemon runs with: the file merged over `defaultAppConfig`. */
+export interface EffectiveConfigRequest {
+  type: "effective_config_request";
+}
+
+export interface EffectiveConfigResponse {
+  type: "effective_config_response";
+  payload: { config: AppConfig; configPath: string };
+}
+
+/** Replace the whole config with `defaultAppConfig`. */
+export interface ResetConfigRequest {
+  type: "reset_config_request";
+}
+
+export interface ResetConfigResponse {
+  type: "reset_config_response";
+  payload: { ok: boolean; error?: string };
+}
+
 /* ── Default bot messages ── */
 
 export interface SetDefaultBotRequest {
@@ -316,7 +471,12 @@ export interface ActivateBotRequest {
 
 export interface ActivateBotResponse {
   type: "activate_bot_response";
-  payload: { ok: boolean; error?: string };
+  payload: {
+    ok: boolean;
+    error?: string;
+    /** Bot that was temporarily active before this one, or null if none. */
+    previousBotId?: string | null;
+  };
 }
 
 /* ── Claude Code config check/setup messages ── */
@@ -339,11 +499,12 @@ export interface CheckClaudeConfigResponse {
 
 export interface SetupClaudeConfigRequest {
   type: "setup_claude_config_request";
+  payload?: { dryRun?: boolean };
 }
 
 export interface SetupClaudeConfigResponse {
   type: "setup_claude_config_response";
-  payload: { ok: boolean; error?: string };
+  payload: ConfigSetupResult;
 }
 
 /* ── Codex config check/setup messages ── */
@@ -366,17 +527,90 @@ export interface CheckCodexConfigResponse {
 
 export interface SetupCodexConfigRequest {
   type: "setup_codex_config_request";
+  payload?: { dryRun?: boolean };
 }
 
 export interface SetupCodexConfigResponse {
   type: "setup_codex_config_response";
-  payload: { ok: boolean; error?: string };
+  payload: ConfigSetupResult;
+}
+
+/** Result of a CLI config setup. Dry runs fill in the preview fields and write nothing. */
+export interface ConfigSetupResult {
+  ok: boolean;
+  error?: string;
+  dryRun?: boolean;
+  /** Whether applying would change the file. */
+  changed?: boolean;
+  currentContent?: string;
+  newContent?: string;
+  /** Line diff of currentContent → newContent ("- "/"+ "/"  " prefixes). */
+  diff?: string;
+}
+
+/* ── Felay hook removal (uninstall) ── */
+
+export interface RemoveCodexHooksRequest {
+  type: "remove_codex_hooks_request";
+}
+
+export interface RemoveCodexHooksResponse {
+  type: "remove_codex_hooks_response";
+  payload: HookRemovalResult;
+}
+
+export interface RemoveClaudeHooksRequest {
+  type: "remove_claude_hooks_request";
+}
+
+export interface RemoveClaudeHooksResponse {
+  type: "remove_claude_hooks_response";
+  payload: HookRemovalResult;
+}
+
+/** Result of removing Felay's hooks from a CLI config. */
+export interface HookRemovalResult {
+  ok: boolean;
+  error?: string;
+  /** The entries that were removed, as they appeared in the file. */
+  removed: string[];
+  /** Remaining mentions of felay that were not recognized as its hooks. */
+  leftover: string[];
+}
+
+/** Bumped when an existing request or response changes shape incompatibly. */
+export const IPC_PROTOCOL_VERSION = 1;
+
+/** Capability handshake: which request types this daemon understands. */
+export interface HelloRequest {
+  type: "hello";
+}
+
+export interface HelloResponse {
+  type: "hello_response";
+  payload: {
+    protocolVersion: number;
+    requests: string[];
+    /** Behaviors within requests, e.g. "setup_dry_run" for `dryRun` on setup. */
+    features?: string[];
+  };
+}
+
+/** Sent instead of the expected reply when handling a request throws. */
+export interface ErrorResponse {
+  type: "error";
+  payload: {
+    code: string;
+    message: string;
+    requestType?: string;
+  };
 }
 
 export type DaemonMessage =
   | SessionRegistration
   | PtyOutputEvent
   | FeishuInputEvent
+  | HelloRequest
   | StatusRequest
   | StopRequest
   | SessionEndedEvent
@@ -385,10 +619,19 @@ export type DaemonMessage =
   | DeleteBotRequest
   | BindBotRequest
   | UnbindBotRequest
+  | UnbindAllBotsRequest
   | TestBotRequest
+  | TestBotConfigRequest
+  | GetBotSecretRequest
+  | BotSchemaRequest
+  | SetQuietHoursRequest
+  | GetQuietHoursRequest
+  | SetSessionQuietHoursRequest
   | ActivateBotRequest
   | GetConfigRequest
+  | EffectiveConfigRequest
   | SaveConfigRequest
+  | ResetConfigRequest
   | SetDefaultBotRequest
   | GetDefaultsRequest
   | CodexNotifyEvent
@@ -397,25 +640,40 @@ export type DaemonMessage =
   | ClaudeNotifyEvent
   | CheckClaudeConfigRequest
   | SetupClaudeConfigRequest
+  | RemoveCodexHooksRequest
+  | RemoveClaudeHooksRequest
   | ApiProxyEvent;
 
 export type DaemonReply =
+  | ErrorResponse
+  | HelloResponse
   | StatusResponse
   | StopResponse
   | ListBotsResponse
   | SaveBotResponse
   | DeleteBotResponse
   | BindBotResponse
+  | UnbindAllBotsResponse
   | TestBotResponse
+  | TestBotConfigResponse
+  | GetBotSecretResponse
+  | BotSchemaResponse
+  | SetQuietHoursResponse
+  | GetQuietHoursResponse
+  | SetSessionQuietHoursResponse
   | ActivateBotResponse
   | GetConfigResponse
+  | EffectiveConfigResponse
   | SaveConfigResponse
+  | ResetConfigResponse
   | SetDefaultBotResponse
   | GetDefaultsResponse
   | CheckCodexConfigResponse
   | SetupCodexConfigResponse
   | CheckClaudeConfigResponse
-  | SetupClaudeConfigResponse;
+  | SetupClaudeConfigResponse
+  | RemoveCodexHooksResponse
+  | RemoveClaudeHooksResponse;
 
 export interface DaemonLockFile {
   pid: number;
@@ -8,6 +8,7 @@
  */
 import { build } from "esbuild";
 import { exec } from "@yao-pkg/pkg";
+import crypto from "node:crypto";
 import fs from "node:fs";
 import path from "node:path";
 import { fileURLToPath } from "node:url";
@@ -204,6 +205,11 @@ await exec([
   path.join(BUILD, "felay-daemon.exe"),
 ]);
 
+// Checksum checked by the GUI before spawning the daemon (daemon-integrity feature)
+const daemonExe = path.join(BUILD, "felay-daemon.exe");
+const daemonHash = crypto.createHash("sha256").update(fs.readFileSync(daemonExe)).digest("hex");
+fs.writeFileSync(`${daemonExe}.sha256`, `${daemonHash}  felay-daemon.exe\n`);
+
 // Step 5: Copy node-pty prebuilt native modules
 console.log("[build] Copying node-pty native modules...");
 const prebuildsDir = findNodePtyPrebuilds();
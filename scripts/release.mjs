@@ -13,6 +13,7 @@
  *   2. pnpm run build          — compile TypeScript (shared → daemon → cli → gui)
  *   3. build-binaries.mjs      — esbuild + pkg → felay.exe, felay-daemon.exe
  *   4. cargo tauri build       — Vite frontend + Rust GUI + NSIS installer
+ *                                (--features daemon-integrity: verifies the daemon checksum)
  *   5. (optional) gh release   — upload to GitHub Releases
  *
  * Prerequisites:
@@ -98,7 +99,7 @@ log("Step 2/4 — Building standalone binaries...");
 run("node scripts/build-binaries.mjs");
 
 // Verify build artifacts exist
-const requiredArtifacts = ["felay.exe", "felay-daemon.exe", "felay-notify.js", "felay-claude-hook.js"];
+const requiredArtifacts = ["felay.exe", "felay-daemon.exe", "felay-daemon.exe.sha256", "felay-notify.js", "felay-claude-hook.js"];
 for (const f of requiredArtifacts) {
   const p = path.join(ROOT, "build", f);
   if (!fs.existsSync(p)) {
@@ -111,7 +112,7 @@ log("All build artifacts verified.");
 // Step 3: Build NSIS installer via cargo tauri build
 log("Step 3/4 — Building NSIS installer...");
 ensureCargo();
-run("cargo tauri build", { cwd: TAURI_DIR });
+run("cargo tauri build --features daemon-integrity", { cwd: TAURI_DIR });
 
 // Locate installer
 const nsisDir = path.join(TAURI_DIR, "target", "release", "bundle", "nsis");